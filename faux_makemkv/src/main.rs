@@ -5,54 +5,106 @@
 //!
 //! This simulates running MakeMKV by playing back output. It is mainly used for development
 //! purposes to test without having to actually copy a disc.
+//!
+//! What each command does is scripted by a scenario file (see [`scenario`]) specified by the
+//! `FAUX_MAKEMKV_SCENARIO` environment variable. If the variable is a folder, the scenario file is
+//! `<name>.scenario` in the folder, where `<name>` is the file name of the device path passed to
+//! the command (e.g. `feature_film` for `dev:/dev/feature_film`). This allows each drive to run a
+//! different scenario.
+//!
+//! If a scenario is not specified, the files in `FAUX_MAKEMKV_INFO_PATH` and
+//! `FAUX_MAKEMKV_MKV_PATH` are played back for the "info" and "mkv" commands.
+//!
+//! Scenarios can be recorded from the real MakeMKV (see [`record`]) by setting
+//! `FAUX_MAKEMKV_RECORD` to the scenario file or folder to record to, using the same naming as
+//! `FAUX_MAKEMKV_SCENARIO`. The real MakeMKV is run using `FAUX_MAKEMKV_PROGRAM`, which defaults to
+//! `makemkvcon`.
+
+mod record;
+mod scenario;
 
 use std::env;
-use std::fs;
-use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::process;
-use std::thread;
 use std::time::Duration;
 
+use scenario::{Runner, Scenario, Step};
+
 fn main() {
     let args: Vec<String> = env::args().skip(1).collect();
 
-    for arg in &args {
-        if arg == "info" {
-            simulate_info_command();
-            return;
-        }
-        if arg == "mkv" {
-            simulate_mkv_command();
-            return;
+    let Some(command) = args.iter().find(|arg| *arg == "info" || *arg == "mkv") else {
+        eprintln!("Error: No valid command provided. Use 'info' or 'mkv'.");
+        process::exit(1);
+    };
+
+    let device = args.iter()
+        .find_map(|arg| arg.strip_prefix("dev:"))
+        .unwrap_or_default();
+
+    // The output folder is the last argument of the mkv command.
+    let output_dir = if command == "mkv" { args.last().map(PathBuf::from) } else { None };
+
+    if let Some(path) = env::var_os("FAUX_MAKEMKV_RECORD").map(PathBuf::from) {
+        let result = scenario_path(&path, device)
+            .and_then(|path| record::run(&args, command, device, output_dir.as_deref(), &path));
+        match result {
+            Ok(code) => process::exit(code),
+            Err(error) => {
+                eprintln!("Error: {}", error);
+                process::exit(1);
+            }
         }
     }
 
-    eprintln!("Error: No valid command provided. Use 'info' or 'mkv'.");
-    process::exit(1);
-}
-
-fn simulate_info_command() {
-    let path = match env::var("FAUX_MAKEMKV_INFO_PATH") {
-        Ok(p) => p,
-        Err(_) => {
-            eprintln!("Error: FAUX_MAKEMKV_INFO_PATH environment variable must be set");
+    let scenario = match load_scenario(command, device) {
+        Ok(scenario) => scenario,
+        Err(error) => {
+            eprintln!("Error: {}", error);
             process::exit(1);
         }
     };
 
-    echo_file(&path);
-}
-
-fn simulate_mkv_command() {
-    let path = match env::var("FAUX_MAKEMKV_MKV_PATH") {
-        Ok(p) => p,
-        Err(_) => {
-            eprintln!("Error: FAUX_MAKEMKV_MKV_PATH environment variable must be set");
+    let steps = if command == "info" { &scenario.info } else { &scenario.mkv };
+    let mut runner = Runner::new(&scenario, output_dir, get_delay());
+    match runner.run(steps) {
+        Ok(code) => process::exit(code),
+        Err(error) => {
+            eprintln!("Error: {}", error);
             process::exit(1);
         }
+    }
+}
+
+/// Loads the scenario for the device.
+fn load_scenario(command: &str, device: &str) -> Result<Scenario, String> {
+    if let Some(path) = env::var_os("FAUX_MAKEMKV_SCENARIO").map(PathBuf::from) {
+        return Scenario::load(&scenario_path(&path, device)?);
+    }
+
+    let var = if command == "info" { "FAUX_MAKEMKV_INFO_PATH" } else { "FAUX_MAKEMKV_MKV_PATH" };
+    let path = env::var(var).map_err(|_| format!("{} environment variable must be set", var))?;
+    let steps = vec![Step::Play(PathBuf::from(path))];
+
+    let scenario = if command == "info" {
+        Scenario { info: steps, ..Scenario::default() }
+    } else {
+        Scenario { mkv: steps, ..Scenario::default() }
     };
+    Ok(scenario)
+}
 
-    echo_file(&path);
+/// Returns the path of the scenario file for the device.
+///
+/// If `path` is a folder, the scenario file is named after the device in the folder. Otherwise,
+/// `path` is the scenario file.
+fn scenario_path(path: &Path, device: &str) -> Result<PathBuf, String> {
+    if !path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+    let name = Path::new(device).file_name()
+        .ok_or(format!("invalid device '{}'", device))?;
+    Ok(path.join(format!("{}.scenario", name.to_string_lossy())))
 }
 
 fn get_delay() -> Duration {
@@ -64,34 +116,3 @@ fn get_delay() -> Duration {
 
     Duration::from_millis(0)
 }
-
-fn echo_file(path: &str) {
-    let data = match fs::read_to_string(path) {
-        Ok(d) => d,
-        Err(err) => {
-            eprintln!("Error reading file: {}", err);
-            process::exit(1);
-        }
-    };
-
-    let delay = get_delay();
-    let sleep_time = Duration::from_millis(10);
-    let stdout = io::stdout();
-    let mut handle = stdout.lock();
-
-    for line in data.lines() {
-        if line.trim().is_empty() {
-            continue; // Skip empty lines
-        }
-
-        writeln!(handle, "{}", line).unwrap();
-        handle.flush().unwrap();
-
-        let mut time = Duration::default();
-        while time < delay {
-            thread::sleep(sleep_time);
-            time += sleep_time;
-        }
-    }
-}
-
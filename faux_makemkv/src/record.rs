@@ -0,0 +1,338 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Records MakeMKV runs as scenarios.
+//!
+//! The recorder runs the real MakeMKV with the same arguments and passes its output through
+//! unchanged, so the application works the same as when running MakeMKV directly. Each line of
+//! output is also written to a scenario file as an `out` or `err` step prefixed with the time it
+//! was written (e.g. `@1500 out MSG:...`), so replaying the scenario reproduces the timing of the
+//! recorded run.
+//!
+//! Running the "info" command starts a new scenario file and running the "mkv" command adds its
+//! section to the existing file. This matches a copy operation, which runs "info" and then "mkv"
+//! for the same disc. For the "mkv" command, a `title` step is added for each title copied to the
+//! output folder so the replay creates placeholder files for them. A non-zero exit code is recorded
+//! as an `exit` step and MakeMKV being killed as a `crash` step.
+//!
+//! The drive's serial number, the device path, the output folder, and the home folder are scrubbed
+//! from the recorded output so scenarios can be shared. The other drives listed by MakeMKV are
+//! recorded as empty slots since their names include their serial numbers and the labels of the
+//! discs in them.
+//!
+//! If the recorder is killed, such as when a copy operation is cancelled, MakeMKV exits the next
+//! time it writes output since its pipes are closed. The scenario contains the output up until
+//! then.
+
+use std::borrow::Cow;
+use std::env;
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::scenario::Scenario;
+
+/// Size of the placeholder files created for the copied titles when a scenario is replayed.
+///
+/// The size of the copied files is not recorded so that replaying a scenario is fast.
+const PLACEHOLDER_SIZE: u64 = 1048576;
+
+/// The standard stream a line of output was written to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Stream {
+    Out,
+    Err,
+}
+
+/// Runs MakeMKV and records its output to a scenario file.
+///
+/// Returns the exit code of MakeMKV.
+///
+/// # Args
+///
+/// `args`:  The arguments to run MakeMKV with.
+///
+/// `command`:  The MakeMKV command being run ("info" or "mkv").
+///
+/// `device`:  The device path of the optical drive.
+///
+/// `output_dir`:  The output folder of the "mkv" command.
+///
+/// `path`:  The scenario file to record to.
+pub fn run(
+    args: &[String],
+    command: &str,
+    device: &str,
+    output_dir: Option<&Path>,
+    path: &Path,
+) -> Result<i32, String> {
+    let started = Instant::now();
+    let scrubs = scrubs(device, output_dir);
+
+    let file = if command == "info" {
+        File::create(path)
+    } else {
+        OpenOptions::new().create(true).append(true).open(path)
+    };
+    let file = file.map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+
+    let mut recorder = Recorder { file, path };
+    if command != "info" {
+        recorder.write_line("")?;
+    }
+    recorder.write_line(&format!("[{}]", command))?;
+    // The delay from `FAUX_MAKEMKV_DELAY` would change the recorded timing.
+    recorder.write_line("delay 0")?;
+
+    let program = env::var_os("FAUX_MAKEMKV_PROGRAM").unwrap_or(OsString::from("makemkvcon"));
+    // The variable is removed so a `faux_makemkv` being recorded runs its scenario instead of
+    // recording itself.
+    let mut child = Command::new(&program)
+        .args(args)
+        .env_remove("FAUX_MAKEMKV_RECORD")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", program.to_string_lossy(), e))?;
+
+    // Both streams are read on their own thread so the lines are recorded in the order they are
+    // written.
+    let (tx, rx) = mpsc::channel();
+    let stdout = child.stdout.take().ok_or("failed to read standard output")?;
+    let stderr = child.stderr.take().ok_or("failed to read standard error")?;
+    let out_handle = spawn_reader(stdout, Stream::Out, started, tx.clone());
+    let err_handle = spawn_reader(stderr, Stream::Err, started, tx);
+
+    for (elapsed, stream, line) in rx {
+        let result = match stream {
+            Stream::Out => pass_through(&mut io::stdout(), &line),
+            Stream::Err => pass_through(&mut io::stderr(), &line),
+        };
+        result.map_err(|e| e.to_string())?;
+
+        let text = String::from_utf8_lossy(&line);
+        let text = scrub_drives(text.trim_end_matches(['\r', '\n']), device);
+        let text = scrub(&text, &scrubs);
+        recorder.write_line(&step_line(elapsed, stream, &text))?;
+    }
+
+    let _ = out_handle.join();
+    let _ = err_handle.join();
+    let status = child.wait()
+        .map_err(|e| format!("failed to wait for {}: {}", program.to_string_lossy(), e))?;
+
+    if let Some(output_dir) = output_dir {
+        for (index, name) in Scenario::load(path)?.output_file_names()? {
+            if output_dir.join(&name).is_file() {
+                recorder.write_line(&format!("title {} {}", index, PLACEHOLDER_SIZE))?;
+            }
+        }
+    }
+
+    match status.code() {
+        Some(0) => {},
+        Some(code) => recorder.write_line(&format!("exit {}", code))?,
+        None => recorder.write_line("crash")?,
+    }
+
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Writes the lines of a scenario file.
+struct Recorder<'a> {
+    /// The scenario file.
+    file: File,
+
+    /// The path of the scenario file.
+    path: &'a Path,
+}
+
+impl Recorder<'_> {
+    /// Writes a line to the scenario file.
+    ///
+    /// Each line is flushed so the scenario is complete up until the recorder exits.
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        writeln!(self.file, "{}", line)
+            .and_then(|_| self.file.flush())
+            .map_err(|e| format!("failed to write {}: {}", self.path.display(), e))
+    }
+}
+
+/// Spawns a thread that sends each line of a stream along with when it was read.
+fn spawn_reader<R: Read + Send + 'static>(
+    stream: R,
+    kind: Stream,
+    started: Instant,
+    tx: mpsc::Sender<(Duration, Stream, Vec<u8>)>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        loop {
+            let mut line = Vec::new();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if tx.send((started.elapsed(), kind, line)).is_err() {
+                        break;
+                    }
+                },
+            }
+        }
+    })
+}
+
+/// Writes the raw bytes of a line to a stream.
+fn pass_through<W: Write>(stream: &mut W, line: &[u8]) -> io::Result<()> {
+    stream.write_all(line)?;
+    stream.flush()
+}
+
+/// Returns the scenario step for a line of output.
+fn step_line(elapsed: Duration, stream: Stream, text: &str) -> String {
+    let step = match stream {
+        Stream::Out => "out",
+        Stream::Err => "err",
+    };
+    format!("@{} {} {}", elapsed.as_millis(), step, text)
+}
+
+/// Returns the text to scrub from the recorded output and what to replace it with.
+///
+/// The longest text is first so folders are replaced before any folders they are within.
+fn scrubs(device: &str, output_dir: Option<&Path>) -> Vec<(String, String)> {
+    let mut scrubs = Vec::new();
+
+    if let Some(serial_number) = serial_number(device) {
+        scrubs.push((serial_number, String::from("0000000000")));
+    }
+    scrubs.push((device.to_owned(), String::from("/dev/sr0")));
+    if let Some(output_dir) = output_dir {
+        scrubs.push((output_dir.display().to_string(), String::from("/tmp/output")));
+    }
+    if let Some(home) = env::var_os("HOME") {
+        scrubs.push((home.to_string_lossy().into_owned(), String::from("/home/user")));
+    }
+
+    scrubs.retain(|(text, _)| !text.is_empty() && text != "/");
+    scrubs.sort_by_key(|(text, _)| std::cmp::Reverse(text.len()));
+    scrubs
+}
+
+/// Returns the serial number of the optical drive.
+///
+/// The serial number is read using `lsblk` the same way the application does. Returns `None` if
+/// the serial number cannot be read.
+fn serial_number(device: &str) -> Option<String> {
+    let output = Command::new("lsblk")
+        .args(["--nodeps", "--noheadings", "--output", "SERIAL", device])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let serial_number = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    (output.status.success() && !serial_number.is_empty()).then_some(serial_number)
+}
+
+/// Replaces the entry of a drive other than the one being recorded with an empty slot.
+///
+/// MakeMKV lists every drive in a `DRV` line, such as
+/// `DRV:1,2,999,12,"BD-RE HL-DT-ST BD-RE WH16NS60 1.02 KLZK6LB0526","LABEL","/dev/sr1"`, where the
+/// last field is the drive's device path. The entry of the drive being recorded is kept so that it
+/// is scrubbed the same as the rest of the output. Other lines are returned unchanged.
+fn scrub_drives<'a>(text: &'a str, device: &str) -> Cow<'a, str> {
+    let Some(entry) = text.strip_prefix("DRV:") else {
+        return Cow::Borrowed(text);
+    };
+    let Some((index, _)) = entry.split_once(',') else {
+        return Cow::Borrowed(text);
+    };
+    let drive_device = entry.rsplit(',').next().unwrap_or_default();
+
+    if !device.is_empty() && drive_device.trim_matches('"') == device {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!(r#"DRV:{},256,999,0,"","","""#, index))
+    }
+}
+
+/// Replaces each occurrence of the scrubbed text.
+fn scrub(text: &str, scrubs: &[(String, String)]) -> String {
+    scrubs.iter().fold(text.to_owned(), |text, (from, to)| text.replace(from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::scenario::Step;
+
+    #[test]
+    fn test_scrub() {
+        let scrubs = vec![
+            (String::from("/home/kevin/Videos/inbox"), String::from("/tmp/output")),
+            (String::from("KLZK6LB0526"), String::from("0000000000")),
+            (String::from("/home/kevin"), String::from("/home/user")),
+        ];
+
+        let text = r#"MSG:5014,0,2,"Saving 2 titles into file:///home/kevin/Videos/inbox""#;
+        let expected = r#"MSG:5014,0,2,"Saving 2 titles into file:///tmp/output""#;
+        assert_eq!(scrub(text, &scrubs), expected);
+
+        let text = "Drive KLZK6LB0526 log written to /home/kevin/.MakeMKV/log.txt";
+        assert_eq!(
+            scrub(text, &scrubs),
+            "Drive 0000000000 log written to /home/user/.MakeMKV/log.txt",
+        );
+    }
+
+    #[test]
+    fn test_scrub_drives() {
+        let recorded = concat!(
+            r#"DRV:0,2,999,12,"BD-RE HL-DT-ST BD-RE WH16NS60 1.02","#,
+            r#""SHORT_FILMS","/dev/sr0""#,
+        );
+        assert_eq!(scrub_drives(recorded, "/dev/sr0"), recorded);
+
+        let other = concat!(
+            r#"DRV:1,2,999,1,"DVD+R-DL HL-DT-ST DVDRAM GP65NB60 PF00 KZ8J6HD1234","#,
+            r#""HOME","/dev/sr1""#,
+        );
+        assert_eq!(scrub_drives(other, "/dev/sr0"), r#"DRV:1,256,999,0,"","","""#);
+
+        let empty = r#"DRV:2,256,999,0,"","","""#;
+        assert_eq!(scrub_drives(empty, "/dev/sr0"), empty);
+        assert_eq!(scrub_drives(recorded, ""), r#"DRV:0,256,999,0,"","","""#);
+
+        let text = r#"MSG:1005,0,1,"MakeMKV v1.18.1 linux(x64-release) started""#;
+        assert_eq!(scrub_drives(text, "/dev/sr0"), text);
+    }
+
+    #[test]
+    fn test_step_line() {
+        let lines = [
+            step_line(Duration::from_millis(1500), Stream::Out, "PRGV:0,0,65536"),
+            step_line(Duration::from_micros(2750), Stream::Err, "Error reading disc"),
+        ];
+        assert_eq!(lines, ["@1500 out PRGV:0,0,65536", "@2 err Error reading disc"]);
+
+        // The recorded lines are replayed at the same time.
+        let text = format!("[mkv]\n{}\n", lines.join("\n"));
+        let scenario = Scenario::parse(&text, &PathBuf::new()).unwrap();
+        assert_eq!(scenario.mkv, [
+            Step::At(
+                Duration::from_millis(1500),
+                Box::new(Step::Out(String::from("PRGV:0,0,65536"))),
+            ),
+            Step::At(
+                Duration::from_millis(2),
+                Box::new(Step::Err(String::from("Error reading disc"))),
+            ),
+        ]);
+    }
+}
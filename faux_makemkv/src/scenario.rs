@@ -0,0 +1,393 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Scripted MakeMKV runs.
+//!
+//! A scenario file describes what each command does with one step per line. The steps for the
+//! "info" command follow an `[info]` line and the steps for the "mkv" command follow an `[mkv]`
+//! line. Blank lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # Copies the first title and then fails reading the disc.
+//! [info]
+//! delay 40
+//! play feature_film/info.txt
+//!
+//! [mkv]
+//! play feature_film/mkv.txt
+//! title 0 1048576
+//! err Error reading disc
+//! exit 1
+//! ```
+//!
+//! The following steps are supported:
+//!
+//! - `delay <ms>`:  Sets how long to wait after each line written by `out`, `err`, and `play`.
+//!   Defaults to the value of `FAUX_MAKEMKV_DELAY` or 0.
+//! - `out <text>`:  Writes a line to standard output.
+//! - `err <text>`:  Writes a line to standard error.
+//! - `play <file>`:  Writes each line of the file to standard output. The path is relative to the
+//!   scenario file.
+//! - `sleep <ms>`:  Waits before continuing.
+//! - `title <index> <size>`:  Creates a placeholder file of `size` bytes in the output folder of
+//!   the "mkv" command, named by the `OutputFileName` attribute of the title in the output of the
+//!   info steps.
+//! - `titles <size>`:  Creates a placeholder file for every title in the output of the info steps.
+//! - `crash`:  Aborts the process, like MakeMKV crashing.
+//! - `hang`:  Waits until the process is killed, such as when the command is cancelled.
+//! - `exit <code>`:  Exits with the code. The command exits with 0 after the last step.
+//!
+//! Any step can be prefixed with `@<ms>` to wait until that many milliseconds have passed since
+//! the command started before running it (e.g. `@1500 out MSG:...`), which replays the timing of
+//! recorded output.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The ID of the `OutputFileName` attribute in `TINFO` messages.
+const OUTPUT_FILE_NAME_ID: &str = "27";
+
+/// A step of a scripted command.
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    /// Runs the step once the time has passed since the command started.
+    At(Duration, Box<Step>),
+
+    /// Aborts the process.
+    Crash,
+
+    /// Sets how long to wait after each line of output.
+    Delay(Duration),
+
+    /// Writes a line to standard error.
+    Err(String),
+
+    /// Exits with the code.
+    Exit(i32),
+
+    /// Waits until the process is killed.
+    Hang,
+
+    /// Writes a line to standard output.
+    Out(String),
+
+    /// Writes each line of the file to standard output.
+    Play(PathBuf),
+
+    /// Waits before continuing.
+    Sleep(Duration),
+
+    /// Creates a placeholder file for a title.
+    Title { index: usize, size: u64 },
+
+    /// Creates a placeholder file for every title.
+    Titles { size: u64 },
+}
+
+/// The steps for each command.
+#[derive(Debug, Default, PartialEq)]
+pub struct Scenario {
+    /// The steps of the "info" command.
+    pub info: Vec<Step>,
+
+    /// The steps of the "mkv" command.
+    pub mkv: Vec<Step>,
+}
+
+impl Scenario {
+    /// Loads a scenario file.
+    pub fn load(path: &Path) -> Result<Scenario, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        Scenario::parse(&text, base_dir)
+    }
+
+    /// Parses the text of a scenario file.
+    ///
+    /// Paths of `play` steps are resolved relative to `base_dir`.
+    pub fn parse(text: &str, base_dir: &Path) -> Result<Scenario, String> {
+        let mut scenario = Scenario::default();
+        let mut steps = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line {
+                "[info]" => steps = Some(&mut scenario.info),
+                "[mkv]" => steps = Some(&mut scenario.mkv),
+                _ => {
+                    let step = parse_step(line, base_dir)
+                        .map_err(|e| format!("line {}: {}", index + 1, e))?;
+                    steps.as_mut()
+                        .ok_or(format!("line {}: step outside of a section", index + 1))?
+                        .push(step);
+                },
+            }
+        }
+
+        Ok(scenario)
+    }
+
+    /// Returns the output file name of each title in the output of the info steps.
+    pub fn output_file_names(&self) -> Result<Vec<(usize, String)>, String> {
+        let mut names = Vec::new();
+        for step in &self.info {
+            for line in step.output_lines()? {
+                if let Some(name) = parse_output_file_name(&line) {
+                    names.push(name);
+                }
+            }
+        }
+        Ok(names)
+    }
+}
+
+impl Step {
+    /// Returns the lines written to standard output by the step.
+    fn output_lines(&self) -> Result<Vec<String>, String> {
+        match self {
+            Step::At(_, step) => step.output_lines(),
+            Step::Out(text) => Ok(vec![text.clone()]),
+            Step::Play(path) => read_lines(path),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Parses a single step.
+fn parse_step(line: &str, base_dir: &Path) -> Result<Step, String> {
+    let (name, args) = line.split_once(' ').unwrap_or((line, ""));
+
+    if let Some(ms) = name.strip_prefix('@') {
+        let step = parse_step(args.trim_start(), base_dir)?;
+        return Ok(Step::At(parse_ms(ms)?, Box::new(step)));
+    }
+
+    let step = match name {
+        "crash" => Step::Crash,
+        "delay" => Step::Delay(parse_ms(args)?),
+        "err" => Step::Err(args.to_owned()),
+        "exit" => Step::Exit(args.parse().map_err(|_| format!("invalid exit code '{}'", args))?),
+        "hang" => Step::Hang,
+        "out" => Step::Out(args.to_owned()),
+        "play" => Step::Play(base_dir.join(args)),
+        "sleep" => Step::Sleep(parse_ms(args)?),
+        "title" => {
+            let (index, size) = args.split_once(' ').ok_or("expected title index and size")?;
+            let index = index.parse().map_err(|_| format!("invalid title index '{}'", index))?;
+            Step::Title { index, size: parse_size(size)? }
+        },
+        "titles" => Step::Titles { size: parse_size(args)? },
+        _ => return Err(format!("unknown step '{}'", name)),
+    };
+
+    Ok(step)
+}
+
+/// Waits until the process is killed.
+///
+/// The process also exits once its parent has exited, since the parent can't kill it anymore and
+/// it would otherwise never exit.
+fn hang() -> ! {
+    #[cfg(unix)]
+    let parent_id = std::os::unix::process::parent_id();
+    loop {
+        thread::sleep(Duration::from_millis(100));
+        #[cfg(unix)]
+        if std::os::unix::process::parent_id() != parent_id {
+            process::exit(1);
+        }
+    }
+}
+
+/// Parses a number of milliseconds.
+fn parse_ms(s: &str) -> Result<Duration, String> {
+    s.parse()
+        .map(Duration::from_millis)
+        .map_err(|_| format!("invalid milliseconds '{}'", s))
+}
+
+/// Parses a size in bytes.
+fn parse_size(s: &str) -> Result<u64, String> {
+    s.parse().map_err(|_| format!("invalid size '{}'", s))
+}
+
+/// Parses the title index and value of an `OutputFileName` attribute from a line of output.
+///
+/// Returns `None` if the line is not a `TINFO` message for the attribute.
+fn parse_output_file_name(line: &str) -> Option<(usize, String)> {
+    // TINFO:<title_index>,<id>,<code>,<value>
+    let data = line.strip_prefix("TINFO:")?;
+    let mut parts = data.splitn(4, ',');
+    let index = parts.next()?.parse().ok()?;
+    if parts.next()? != OUTPUT_FILE_NAME_ID {
+        return None;
+    }
+    let value = parts.nth(1)?.trim_matches('"');
+    Some((index, value.to_owned()))
+}
+
+/// Reads the non-empty lines of a file.
+fn read_lines(path: &Path) -> Result<Vec<String>, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let lines = text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.to_owned())
+        .collect();
+    Ok(lines)
+}
+
+/// Runs the steps of a command.
+pub struct Runner<'a> {
+    /// The scenario the steps are from.
+    scenario: &'a Scenario,
+
+    /// The output folder of the "mkv" command.
+    output_dir: Option<PathBuf>,
+
+    /// How long to wait after each line of output.
+    delay: Duration,
+
+    /// When the command started.
+    started: Instant,
+}
+
+impl<'a> Runner<'a> {
+    /// Creates a runner for a command that is starting now.
+    pub fn new(scenario: &'a Scenario, output_dir: Option<PathBuf>, delay: Duration) -> Self {
+        Self { scenario, output_dir, delay, started: Instant::now() }
+    }
+
+    /// Runs the steps, returning the exit code.
+    pub fn run(&mut self, steps: &[Step]) -> Result<i32, String> {
+        for step in steps {
+            if let Some(code) = self.run_step(step)? {
+                return Ok(code);
+            }
+        }
+        Ok(0)
+    }
+
+    /// Runs a step, returning the exit code if the step exits.
+    fn run_step(&mut self, step: &Step) -> Result<Option<i32>, String> {
+        match step {
+            Step::At(time, step) => {
+                thread::sleep(time.saturating_sub(self.started.elapsed()));
+                return self.run_step(step);
+            },
+            Step::Crash => {
+                let _ = io::stdout().flush();
+                process::abort();
+            },
+            Step::Delay(delay) => self.delay = *delay,
+            Step::Err(text) => {
+                let mut stderr = io::stderr().lock();
+                writeln!(stderr, "{}", text).map_err(|e| e.to_string())?;
+                thread::sleep(self.delay);
+            },
+            Step::Exit(code) => return Ok(Some(*code)),
+            Step::Hang => hang(),
+            Step::Out(text) => self.write_line(text)?,
+            Step::Play(path) => {
+                for line in read_lines(path)? {
+                    self.write_line(&line)?;
+                }
+            },
+            Step::Sleep(duration) => thread::sleep(*duration),
+            Step::Title { index, size } => {
+                let names = self.scenario.output_file_names()?;
+                let (_, name) = names.iter()
+                    .find(|(i, _)| i == index)
+                    .ok_or(format!("title {} has no OutputFileName", index))?;
+                self.create_file(name, *size)?;
+            },
+            Step::Titles { size } => {
+                for (_, name) in self.scenario.output_file_names()? {
+                    self.create_file(&name, *size)?;
+                }
+            },
+        }
+        Ok(None)
+    }
+
+    /// Writes a line to standard output.
+    fn write_line(&self, text: &str) -> Result<(), String> {
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", text).map_err(|e| e.to_string())?;
+        stdout.flush().map_err(|e| e.to_string())?;
+        thread::sleep(self.delay);
+        Ok(())
+    }
+
+    /// Creates a placeholder file in the output folder.
+    fn create_file(&self, name: &str, size: u64) -> Result<(), String> {
+        let output_dir = self.output_dir.as_ref().ok_or("no output folder")?;
+        let path = output_dir.join(name);
+        File::create(&path)
+            .and_then(|file| file.set_len(size))
+            .map_err(|e| format!("failed to create {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = "
+            # comment
+            [info]
+            delay 40
+            play disc/info.txt
+            @1500 out MSG:5011
+
+            [mkv]
+            err read error
+            title 1 1024
+            exit 3
+        ";
+        let scenario = Scenario::parse(text, Path::new("fixtures")).unwrap();
+        assert_eq!(scenario.info, vec![
+            Step::Delay(Duration::from_millis(40)),
+            Step::Play(PathBuf::from("fixtures/disc/info.txt")),
+            Step::At(Duration::from_millis(1500), Box::new(Step::Out(String::from("MSG:5011")))),
+        ]);
+        assert_eq!(scenario.mkv, vec![
+            Step::Err(String::from("read error")),
+            Step::Title { index: 1, size: 1024 },
+            Step::Exit(3),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Scenario::parse("out text", Path::new(".")).is_err());
+        assert!(Scenario::parse("[info]\nsleep soon", Path::new(".")).is_err());
+        assert!(Scenario::parse("[mkv]\nexplode", Path::new(".")).is_err());
+    }
+
+    #[test]
+    fn test_output_file_names() {
+        let text = "
+            [info]
+            out TINFO:0,2,0,\"Feature\"
+            out TINFO:0,27,0,\"Feature_t00.mkv\"
+            out TINFO:1,27,0,\"Feature_t01.mkv\"
+        ";
+        let scenario = Scenario::parse(text, Path::new(".")).unwrap();
+        assert_eq!(scenario.output_file_names().unwrap(), vec![
+            (0, String::from("Feature_t00.mkv")),
+            (1, String::from("Feature_t01.mkv")),
+        ]);
+    }
+}
@@ -0,0 +1,419 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! FFmpeg command.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// Arguments included with every command to keep FFmpeg from prompting for input and to limit its
+/// output to errors.
+const COMMON_ARGS: [&str; 4] = ["-nostdin", "-hide_banner", "-loglevel", "error"];
+
+/// Builder for configuring the arguments for creating a contact sheet.
+///
+/// The frames are evenly spaced across the video (excluding the very start and end) and arranged
+/// in a grid from left to right, top to bottom.
+pub struct ContactSheetOptions {
+    /// Path to the input video file.
+    src_path: PathBuf,
+
+    /// Path to where to save the image. The image format is determined by the extension.
+    dst_path: PathBuf,
+
+    /// The duration of the input video.
+    duration: Duration,
+
+    /// The number of frames in each row of the grid.
+    columns: u32,
+
+    /// The number of rows in the grid.
+    rows: u32,
+
+    /// The width of each frame in pixels.
+    width: u32,
+}
+
+impl ContactSheetOptions {
+    /// Constructs the options with the minimally required arguments.
+    ///
+    /// `src_path` and `dst_path` are the paths to the input video and where to save the image
+    /// respectively. The `duration` of the input video is used to determine where the frames are
+    /// taken from.
+    pub fn new(src_path: &Path, dst_path: &Path, duration: Duration) -> ContactSheetOptions {
+        ContactSheetOptions {
+            src_path: src_path.to_path_buf(),
+            dst_path: dst_path.to_path_buf(),
+            duration,
+            columns: 4,
+            rows: 3,
+            width: 320,
+        }
+    }
+
+    /// Sets the number of columns and rows of frames.
+    pub fn grid(&mut self, columns: u32, rows: u32) -> &mut ContactSheetOptions {
+        self.columns = columns;
+        self.rows = rows;
+        self
+    }
+
+    /// Sets the width of each frame in pixels. The height is scaled to keep the aspect ratio.
+    pub fn width(&mut self, width: u32) -> &mut ContactSheetOptions {
+        self.width = width;
+        self
+    }
+
+    /// Returns the offsets into the video of each of the frames.
+    fn frame_offsets(&self) -> Vec<Duration> {
+        let count = self.columns * self.rows;
+        (1..=count)
+            .map(|n| self.duration.mul_f64(n as f64 / (count + 1) as f64))
+            .collect()
+    }
+
+    /// Returns a list of command-line arguments based on the configured options.
+    pub(crate) fn get_options(&self) -> Result<Vec<String>> {
+        if self.columns == 0 || self.rows == 0 {
+            return Err(Error::InvalidOption {
+                option: String::from("grid"),
+                error: String::from("columns and rows must be greater than zero"),
+            });
+        }
+
+        if self.width == 0 {
+            return Err(Error::InvalidOption {
+                option: String::from("width"),
+                error: String::from("width must be greater than zero"),
+            });
+        }
+
+        if self.duration.is_zero() {
+            return Err(Error::InvalidOption {
+                option: String::from("duration"),
+                error: String::from("duration must be greater than zero"),
+            });
+        }
+
+        let (src_path, dst_path) = check_paths(&self.src_path, &self.dst_path)?;
+
+        let mut args: Vec<String> = COMMON_ARGS.iter().map(|a| String::from(*a)).collect();
+
+        // Seeking before each input only decodes the frames near the offset instead of the entire
+        // video which is important for titles that are several hours long.
+        let offsets = self.frame_offsets();
+        for offset in offsets.iter() {
+            args.push(String::from("-ss"));
+            args.push(format!("{:.3}", offset.as_secs_f64()));
+            args.push(String::from("-i"));
+            args.push(src_path.to_owned());
+        }
+
+        let mut filter = String::new();
+        for index in 0..offsets.len() {
+            filter.push_str(&format!(
+                "[{index}:v:0]trim=end_frame=1,scale={}:-2,setsar=1[f{index}];",
+                self.width,
+            ));
+        }
+        for index in 0..offsets.len() {
+            filter.push_str(&format!("[f{index}]"));
+        }
+        filter.push_str(&format!(
+            "concat=n={}:v=1:a=0,tile={}x{}[sheet]",
+            offsets.len(),
+            self.columns,
+            self.rows,
+        ));
+
+        args.push(String::from("-filter_complex"));
+        args.push(filter);
+        args.push(String::from("-map"));
+        args.push(String::from("[sheet]"));
+        args.push(String::from("-frames:v"));
+        args.push(String::from("1"));
+        args.push(dst_path.to_owned());
+
+        Ok(args)
+    }
+}
+
+/// Builder for configuring the arguments for creating a preview clip.
+///
+/// The clip is encoded as H.264 with AAC audio so that it can be played by most players.
+pub struct PreviewClipOptions {
+    /// Path to the input video file.
+    src_path: PathBuf,
+
+    /// Path to where to save the clip.
+    dst_path: PathBuf,
+
+    /// The offset in seconds from the start of the input video where the clip starts.
+    start_at: u32,
+
+    /// The length of the clip in seconds.
+    length: u32,
+
+    /// The height of the clip in pixels.
+    height: u32,
+}
+
+impl PreviewClipOptions {
+    /// Constructs the options with the minimally required arguments.
+    ///
+    /// `src_path` and `dst_path` are the paths to the input video and where to save the clip
+    /// respectively.
+    pub fn new(src_path: &Path, dst_path: &Path) -> PreviewClipOptions {
+        PreviewClipOptions {
+            src_path: src_path.to_path_buf(),
+            dst_path: dst_path.to_path_buf(),
+            start_at: 0,
+            length: 30,
+            height: 360,
+        }
+    }
+
+    /// Sets the offset in seconds from the start of the input video where the clip starts.
+    pub fn start_at(&mut self, seconds: u32) -> &mut PreviewClipOptions {
+        self.start_at = seconds;
+        self
+    }
+
+    /// Sets the length of the clip in seconds.
+    pub fn length(&mut self, seconds: u32) -> &mut PreviewClipOptions {
+        self.length = seconds;
+        self
+    }
+
+    /// Sets the height of the clip in pixels. The width is scaled to keep the aspect ratio.
+    pub fn height(&mut self, height: u32) -> &mut PreviewClipOptions {
+        self.height = height;
+        self
+    }
+
+    /// Returns a list of command-line arguments based on the configured options.
+    pub(crate) fn get_options(&self) -> Result<Vec<String>> {
+        if self.length == 0 {
+            return Err(Error::InvalidOption {
+                option: String::from("length"),
+                error: String::from("length must be greater than zero"),
+            });
+        }
+
+        if self.height == 0 {
+            return Err(Error::InvalidOption {
+                option: String::from("height"),
+                error: String::from("height must be greater than zero"),
+            });
+        }
+
+        let (src_path, dst_path) = check_paths(&self.src_path, &self.dst_path)?;
+
+        let mut args: Vec<String> = COMMON_ARGS.iter().map(|a| String::from(*a)).collect();
+        args.extend([
+            String::from("-ss"),
+            self.start_at.to_string(),
+            String::from("-t"),
+            self.length.to_string(),
+            String::from("-i"),
+            src_path.to_owned(),
+            String::from("-map"),
+            String::from("0:v:0"),
+            String::from("-map"),
+            String::from("0:a:0?"),
+            String::from("-vf"),
+            format!("scale=-2:{}", self.height),
+            String::from("-c:v"),
+            String::from("libx264"),
+            String::from("-preset"),
+            String::from("veryfast"),
+            String::from("-crf"),
+            String::from("30"),
+            String::from("-c:a"),
+            String::from("aac"),
+            String::from("-ac"),
+            String::from("2"),
+            String::from("-b:a"),
+            String::from("96k"),
+            String::from("-movflags"),
+            String::from("+faststart"),
+            dst_path.to_owned(),
+        ]);
+
+        Ok(args)
+    }
+}
+
+/// Runs FFmpeg with the provided arguments and waits for it to complete.
+pub(crate) fn run_ffmpeg(args: Vec<String>) -> Result<()> {
+    // TODO: Need to be able to specify the path to the executable. For now, assume it's in the
+    //       PATH like the other commands.
+    let output = Command::new("ffmpeg")
+        .args(&args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|error| Error::CommandIoError { error })?;
+
+    if !output.status.success() {
+        return Err(Error::CommandFailed {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that the input file exists and the output file does not, returning both paths as
+/// strings.
+fn check_paths<'a>(src_path: &'a Path, dst_path: &'a Path) -> Result<(&'a str, &'a str)> {
+    if !src_path.is_file() {
+        return Err(Error::InvalidOption {
+            option: String::from("src_path"),
+            error: String::from("input file does not exist"),
+        });
+    }
+
+    let Some(src_path) = src_path.to_str() else {
+        return Err(Error::InvalidOption {
+            option: String::from("src_path"),
+            error: String::from("failed to convert input path"),
+        });
+    };
+
+    if dst_path.exists() {
+        return Err(Error::InvalidOption {
+            option: String::from("dst_path"),
+            error: String::from("output file already exists"),
+        });
+    }
+
+    let Some(dst_path) = dst_path.to_str() else {
+        return Err(Error::InvalidOption {
+            option: String::from("dst_path"),
+            error: String::from("failed to convert output path"),
+        });
+    };
+
+    Ok((src_path, dst_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::{self, OpenOptions};
+    use std::thread;
+
+    use super::*;
+
+    pub struct TempFile(pub PathBuf);
+
+    impl TempFile {
+        fn new(file_name: &Path) -> TempFile {
+            TempFile(env::temp_dir().join(file_name))
+        }
+
+        fn path(&self) -> &Path {
+            let TempFile(ref p) = *self;
+            p
+        }
+
+        fn create(&self) {
+            OpenOptions::new()
+                .create(true)
+                .truncate(true)
+                .write(true)
+                .open(self.path())
+                .expect("Failed to create file");
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let TempFile(ref p) = *self;
+            if !p.exists() {
+                return
+            }
+            let result = fs::remove_file(p);
+            // Avoid panicking while panicking as this causes the process to immediately abort,
+            // without displaying test results.
+            if !thread::panicking() {
+                result.unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn contact_sheet_options() {
+        let src_file = TempFile::new(Path::new("artie.ffmpeg.test.contact_sheet_options.mkv"));
+        src_file.create();
+
+        let dst_file = TempFile::new(Path::new("artie.ffmpeg.test.contact_sheet_options.jpg"));
+
+        let mut opts = ContactSheetOptions::new(
+            src_file.path(),
+            dst_file.path(),
+            Duration::from_secs(300),
+        );
+        opts.grid(2, 2).width(160);
+
+        let args = opts.get_options().unwrap();
+        let src = src_file.path().to_str().unwrap();
+        assert_eq!(&args[0..4], &COMMON_ARGS);
+        assert_eq!(&args[4..8], &["-ss", "60.000", "-i", src]);
+        assert_eq!(&args[16..20], &["-ss", "240.000", "-i", src]);
+        assert_eq!(&args[20], "-filter_complex");
+        assert!(args[21].starts_with("[0:v:0]trim=end_frame=1,scale=160:-2,setsar=1[f0];"));
+        assert!(args[21].ends_with("[f0][f1][f2][f3]concat=n=4:v=1:a=0,tile=2x2[sheet]"));
+        assert_eq!(&args[22..26], &["-map", "[sheet]", "-frames:v", "1"]);
+        assert_eq!(&args[26], dst_file.path().to_str().unwrap());
+
+        let mut opts = ContactSheetOptions::new(
+            src_file.path(),
+            dst_file.path(),
+            Duration::from_secs(300),
+        );
+        opts.grid(0, 2);
+        opts.get_options().expect_err("Expected an error");
+
+        let opts = ContactSheetOptions::new(src_file.path(), dst_file.path(), Duration::ZERO);
+        opts.get_options().expect_err("Expected an error");
+
+        dst_file.create();
+        let opts = ContactSheetOptions::new(
+            src_file.path(),
+            dst_file.path(),
+            Duration::from_secs(300),
+        );
+        opts.get_options().expect_err("Expected an error");
+    }
+
+    #[test]
+    fn preview_clip_options() {
+        let src_file = TempFile::new(Path::new("artie.ffmpeg.test.preview_clip_options.mkv"));
+        src_file.create();
+
+        let dst_file = TempFile::new(Path::new("artie.ffmpeg.test.preview_clip_options.mp4"));
+
+        let mut opts = PreviewClipOptions::new(src_file.path(), dst_file.path());
+        opts.start_at(120).length(15).height(240);
+
+        let args = opts.get_options().unwrap();
+        assert_eq!(&args[0..4], &COMMON_ARGS);
+        assert_eq!(&args[4..8], &["-ss", "120", "-t", "15"]);
+        assert_eq!(&args[8..10], &["-i", src_file.path().to_str().unwrap()]);
+        assert!(args.contains(&String::from("scale=-2:240")));
+        assert_eq!(args.last().unwrap(), dst_file.path().to_str().unwrap());
+
+        let mut opts = PreviewClipOptions::new(src_file.path(), dst_file.path());
+        opts.length(0);
+        opts.get_options().expect_err("Expected an error");
+
+        let opts = PreviewClipOptions::new(src_file.path(), dst_file.path());
+        drop(src_file);
+        opts.get_options().expect_err("Expected an error");
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Result and error types.
+
+use std::process::ExitStatus;
+
+/// Result type for the `ffmpeg` crate functions.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Error type for `ffmpeg` crate functions.
+#[derive(Debug)]
+pub enum Error {
+    /// Error raised when FFmpeg exits with an unsuccessful status.
+    CommandFailed {
+        status: ExitStatus,
+        stderr: String,
+    },
+
+    /// Error raised when attempting to start or wait for the command fails because of an I/O
+    /// error.
+    CommandIoError {
+        error: std::io::Error,
+    },
+
+    /// Error raised when attempting to run FFmpeg with invalid options.
+    InvalidOption {
+        option: String,
+        error: String,
+    },
+}
@@ -0,0 +1,46 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Crate responsible for running the FFmpeg command.
+//!
+//! FFmpeg is used to generate the previews of copied titles which help identify which title is
+//! which:
+//!
+//! - [`contact_sheet`] - Creates a single image containing a grid of frames spread across a video.
+//! - [`preview_clip`] - Creates a short, low resolution clip of a video.
+
+mod command;
+mod error;
+
+pub use crate::error::{Error, Result};
+pub use crate::command::{ContactSheetOptions, PreviewClipOptions};
+
+/// Run FFmpeg to create a contact sheet for a video.
+///
+/// <div class="warning">
+///
+/// Calling this function will block until the command completes. FFmpeg only decodes the frames
+/// included in the sheet so this should be relatively quick, but that depends on the system.
+///
+/// </div>
+///
+/// `opts` are the configurable options for the contact sheet which at a minimum requires the path
+/// to the input video, where to save the image, and the video's duration.
+pub fn contact_sheet(opts: &ContactSheetOptions) -> Result<()> {
+    command::run_ffmpeg(opts.get_options()?)
+}
+
+/// Run FFmpeg to create a preview clip of a video.
+///
+/// <div class="warning">
+///
+/// Calling this function will block until the command completes. This requires transcoding the
+/// clip which can take a while depending on the options and the system its being run on.
+///
+/// </div>
+///
+/// `opts` are the configurable options for the clip which at a minimum requires the path to the
+/// input video and where to save the clip.
+pub fn preview_clip(opts: &PreviewClipOptions) -> Result<()> {
+    command::run_ffmpeg(opts.get_options()?)
+}
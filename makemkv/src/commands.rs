@@ -7,10 +7,13 @@
 //! their output. The primary commands are the "info" command which can be executed with the
 //! [`run_info_command`] function and "mkv" which can be run with the [`run_mkv_command`] function.
 
+use std::env;
+use std::ffi::OsString;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::{ExitStatus, Stdio};
+use std::sync::OnceLock;
 
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{ChildStderr, ChildStdout, Command};
@@ -24,6 +27,32 @@ use crate::data::DiscInfo;
 use crate::messages::{self, Message};
 use crate::{Error, Progress, Result};
 
+/// Environment variable specifying the path to the MakeMKV executable (e.g. to run
+/// `faux_makemkv` instead).
+pub const PROGRAM_ENV: &str = "MAKEMKVCON";
+
+/// The MakeMKV executable set using [`set_program`].
+static PROGRAM: OnceLock<Program> = OnceLock::new();
+
+/// A MakeMKV executable and the environment variables it is run with.
+struct Program {
+    path: PathBuf,
+    envs: Vec<(String, OsString)>,
+}
+
+/// Sets the MakeMKV executable to run instead of the one specified by [`PROGRAM_ENV`].
+///
+/// Unlike setting [`PROGRAM_ENV`], this does not change the environment of the process, so it is
+/// safe to call while other threads are running (e.g. from tests). The executable can only be set
+/// once. Returns `false` if it was already set, in which case it is not changed.
+///
+/// `path` is the path to the executable.
+///
+/// `envs` are the environment variables set for the executable when it is run.
+pub fn set_program(path: PathBuf, envs: Vec<(String, OsString)>) -> bool {
+    PROGRAM.set(Program { path, envs }).is_ok()
+}
+
 /// Represents the data sent through the channel used to relay output from a running command for
 /// processing.
 pub enum ChannelData {
@@ -155,9 +184,7 @@ impl Context
 /// information is written to the [`DiscInfo`] field in `ctx`.
 pub async fn run_info_command(ctx: &mut Context) -> Result<ExitStatus>
 {
-    // TODO: Need to be able to specify the path to the executable. For now, assume it's in
-    //       the PATH. Defining an environment variable should be simple enough and suffice.
-    let mut cmd = Command::new("makemkvcon");
+    let mut cmd = program();
     cmd.arg("--robot");
     cmd.arg("--cache=1");
     cmd.arg("--noscan");
@@ -173,9 +200,7 @@ pub async fn run_info_command(ctx: &mut Context) -> Result<ExitStatus>
 /// The "mkv" command copies titles from a DVD or Blu-ray disc and saves them as MKV files.
 pub async fn run_mkv_command(ctx: &mut Context, out_dir: &Path) -> Result<ExitStatus>
 {
-    // TODO: Need to be able to specify the path to the executable. For now, assume it's in
-    //       the PATH. Defining an environment variable should be simple enough and suffice.
-    let mut cmd = Command::new("makemkvcon");
+    let mut cmd = program();
     cmd.arg("--robot");
     cmd.arg("--noscan");
     cmd.arg("--progress=-same");
@@ -187,6 +212,20 @@ pub async fn run_mkv_command(ctx: &mut Context, out_dir: &Path) -> Result<ExitSt
     run_command(&mut cmd, ctx).await
 }
 
+/// Returns the command for the MakeMKV executable to run.
+///
+/// This is the executable set using [`set_program`] if any. Otherwise, it is the value of the
+/// [`PROGRAM_ENV`] environment variable if set, or `makemkvcon` which is expected to be in the
+/// PATH.
+fn program() -> Command {
+    if let Some(program) = PROGRAM.get() {
+        let mut cmd = Command::new(&program.path);
+        cmd.envs(program.envs.iter().map(|(key, value)| (key, value)));
+        return cmd;
+    }
+    Command::new(env::var_os(PROGRAM_ENV).unwrap_or_else(|| OsString::from("makemkvcon")))
+}
+
 /// `Path` and `File` object for the command log file.
 struct LogFile {
     path: PathBuf,
@@ -24,6 +24,11 @@ pub enum Error {
     /// Error raised when the MakeMKV command was cancelled.
     CommandCancelled,
 
+    /// Error raised when the MakeMKV command exits with a failure status.
+    CommandFailed {
+        status: std::process::ExitStatus,
+    },
+
     /// Error raised when attempting to stop or wait for a command that has not started.
     CommandNotStarted,
 
@@ -18,7 +18,7 @@ use tokio_util::sync::CancellationToken;
 
 pub use crate::data::{DiscInfo, StreamInfo, TitleInfo};
 pub use crate::error::{Error, Result};
-pub use crate::commands::CommandOutput;
+pub use crate::commands::{CommandOutput, PROGRAM_ENV, set_program};
 
 use std::fs;
 use std::path::Path;
@@ -99,12 +99,16 @@ pub async fn get_disc_info(
     let mut ctx = Context::new(device, observer, ct.clone());
     ctx.log_output(log_path)?;
 
-    let _exit_status = commands::run_info_command(&mut ctx).await?;
+    let status = commands::run_info_command(&mut ctx).await?;
 
     if ct.is_cancelled() {
         return Err(Error::CommandCancelled);
     }
 
+    if !status.success() {
+        return Err(Error::CommandFailed { status });
+    }
+
     let log = fs::read_to_string(log_path)
         .map_err(|error| Error::FileOpenError { path: log_path.to_owned(), error })?;
 
@@ -138,7 +142,7 @@ pub async fn get_disc_info(
 /// # Errors
 ///
 /// In addition to the errors that can occur during the command's execution, this will error out if
-/// there are any MKV files in the output directory.
+/// there are any MKV files in the output directory or if MakeMKV exits with a failure status.
 pub async fn copy_disc(
     device: &str,
     out_dir: &Path,
@@ -154,12 +158,16 @@ pub async fn copy_disc(
     let mut ctx = Context::new(device, observer, ct.clone());
     ctx.log_output(log_path)?;
 
-    let _exit_status = commands::run_mkv_command(&mut ctx, out_dir).await?;
+    let status = commands::run_mkv_command(&mut ctx, out_dir).await?;
 
     if ct.is_cancelled() {
         return Err(Error::CommandCancelled);
     }
 
+    if !status.success() {
+        return Err(Error::CommandFailed { status });
+    }
+
     let log = fs::read_to_string(log_path)
         .map_err(|error| Error::FileOpenError { path: log_path.to_owned(), error })?;
 
@@ -7,6 +7,7 @@ use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::oneshot;
 
 use crate::{Error, Result};
+use crate::metrics;
 use crate::task;
 
 /// Default number of requests that can be queued in an actor's channel.
@@ -134,6 +135,7 @@ where
     Processor: MessageProcessor<Message> + std::marker::Send + 'static
 {
     let (tx, rx) = mpsc::channel(BUFFER_SIZE);
+    metrics::track_queue(name, &tx);
 
     let actor = Actor::new(name, rx, msg_processor);
     task::spawn(run(actor));
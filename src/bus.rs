@@ -11,7 +11,10 @@ use crate::actor::{self, Actor};
 use crate::app;
 use crate::db;
 use crate::drive;
+use crate::metadata;
+use crate::metrics;
 use crate::net;
+use crate::notify;
 use crate::task;
 use crate::ui;
 
@@ -30,9 +33,15 @@ pub enum Message {
     /// Messages for sending requests to a drive actor.
     Drive(drive::Message),
 
+    /// Messages for sending requests to the metadata actor.
+    Metadata(metadata::Message),
+
     /// Messages for sending requests to a client or server actor.
     Net(net::Message),
 
+    /// Messages for sending requests to the notify actor.
+    Notify(notify::Message),
+
     /// Messages for sending requests to the UI.
     UI(ui::Message),
 }
@@ -49,18 +58,31 @@ impl From<drive::Message> for Message {
     }
 }
 
+impl From<metadata::Message> for Message {
+    fn from(value: metadata::Message) -> Self {
+        Message::Metadata(value)
+    }
+}
+
 impl From<net::Message> for Message {
     fn from(value: net::Message) -> Self {
         Message::Net(value)
     }
 }
 
+impl From<notify::Message> for Message {
+    fn from(value: notify::Message) -> Self {
+        Message::Notify(value)
+    }
+}
+
 /// Create's the channel used to send messages to the message bus.
 ///
 /// This will return both the transmission (as a handle) and receiving end of the channel. The
 /// receiving end should be passed to [`init_processor`] when the message processing is started.
 pub fn init_channel() -> (Handle, Receiver<Message>) {
     let (tx, rx) = mpsc::channel(actor::BUFFER_SIZE);
+    metrics::track_queue("message bus", &tx);
     (Handle::new(tx), rx)
 }
 
@@ -74,17 +96,24 @@ pub fn init_channel() -> (Handle, Receiver<Message>) {
 ///
 /// `drive_mgr`:  Handle used to send messages to the drive manager actor and drive actors.
 ///
+/// `metadata`:  Handle used to send messages to the metadata actor. Expected to be `Some` on the
+/// control node and `None` on the worker node.
+///
 /// `bus_send`:  The transmission end of the message bus communication channel.
 ///
 /// `net`:  Handle used to send messages to a client or server actor. Which depends on the mode the
 /// application is running in.
+///
+/// `notify`:  Handle used to send messages to the notify actor.
 pub fn init_processor(
     db: Option<db::Handle>,
     drive_mgr: drive::Handle,
+    metadata: Option<metadata::Handle>,
     net: net::Handle,
+    notify: notify::Handle,
     bus_recv: Receiver<Message>,
 ) -> JoinHandle<()> {
-    let msg_processor = MessageBus::new(db, drive_mgr, net);
+    let msg_processor = MessageBus::new(db, drive_mgr, metadata, net, notify);
     let actor = Actor::new("message bus", bus_recv, msg_processor);
 
     // Unlike other actors, return the JoinHandle so that headless mode (no GUI) has something to
@@ -92,6 +121,23 @@ pub fn init_processor(
     task::spawn(actor::run(actor))
 }
 
+/// Create's a message processor that only forwards messages to the database actor and start its
+/// processing task.
+///
+/// This is used by the command line operations (e.g. `--backup`) that only need the database, so
+/// they can run without starting the drive, network, and notify actors. Any other messages are
+/// dropped.
+///
+/// # Args
+///
+/// `db`:  Handle used to send messages to the database actor.
+///
+/// `bus_recv`:  The receiving end of the message bus communication channel.
+pub fn init_db_processor(db: db::Handle, bus_recv: Receiver<Message>) -> JoinHandle<()> {
+    let actor = Actor::new("message bus", bus_recv, DatabaseBus { db });
+    task::spawn(actor::run(actor))
+}
+
 /// Messaging bus used for inner-application communication.
 ///
 /// The application is made up of multiple actors, each with their own responsibility. To avoid
@@ -110,10 +156,20 @@ struct MessageBus {
     /// All [`Message::Drive`] messages will be forwarded to this handle.
     drive_mgr: drive::Handle,
 
+    /// Handle used to send messages to the metadata actor.
+    ///
+    /// All [`Message::Metadata`] messages will be forwarded to this handle.
+    metadata: Option<metadata::Handle>,
+
     /// Handle used to send messages to a client or server actor.
     ///
     /// All [`Message::Net`] messages will be forwarded to this handle.
     net: net::Handle,
+
+    /// Handle used to send messages to the notify actor.
+    ///
+    /// All [`Message::Notify`] messages will be forwarded to this handle.
+    notify: notify::Handle,
 }
 
 impl MessageBus {
@@ -126,9 +182,20 @@ impl MessageBus {
     ///
     /// `drive_mgr`:  Handle used to send messages to the drive manager actor.
     ///
+    /// `metadata`:  Handle used to send messages to the metadata actor. Expected to be `Some` on
+    /// the control node and `None` on the worker node.
+    ///
     /// `net`:  Handle used to send messages to a client or server actor.
-    fn new(db: Option<db::Handle>, drive_mgr: drive::Handle, net: net::Handle) -> Self {
-        Self { db, drive_mgr, net }
+    ///
+    /// `notify`:  Handle used to send messages to the notify actor.
+    fn new(
+        db: Option<db::Handle>,
+        drive_mgr: drive::Handle,
+        metadata: Option<metadata::Handle>,
+        net: net::Handle,
+        notify: notify::Handle,
+    ) -> Self {
+        Self { db, drive_mgr, metadata, net, notify }
     }
 }
 
@@ -148,14 +215,40 @@ impl actor::MessageProcessor<Message> for MessageBus {
             Message::Drive(msg) => {
                 self.drive_mgr.send(msg).await
             },
+            Message::Metadata(msg) => {
+                // Like the database, the metadata actor only exists on the control node.
+                self.metadata
+                    .as_mut()
+                    .expect("metadata handle should not be None")
+                    .send(msg)
+                    .await
+            },
             Message::Net(msg) => {
                 self.net.send(msg).await
             },
+            Message::Notify(msg) => {
+                self.notify.send(msg).await
+            },
             Message::UI(_) => Ok(()),
         }
     }
 }
 
+/// Messaging bus used when only the database actor is running.
+struct DatabaseBus {
+    /// Handle used to send messages to the database actor.
+    db: db::Handle,
+}
+
+impl actor::MessageProcessor<Message> for DatabaseBus {
+    async fn process(&mut self, msg: Message) -> Result<()> {
+        match msg {
+            Message::Database(msg) => self.db.send(msg).await,
+            _ => Ok(()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // TODO[TESTS]
@@ -6,6 +6,7 @@
 use std::io::prelude::*;
 
 use flate2::Compression;
+use flate2::read::ZlibDecoder;
 use flate2::write::ZlibEncoder;
 
 use crate::Result;
@@ -25,7 +26,73 @@ pub fn compress(s: &str) -> Result<Vec<u8>> {
     encoder.finish().map_err(|e| e.into())
 }
 
+/// Decompress a string compressed using [`compress`].
+///
+/// Empty data is treated as an empty string since some fields are stored uncompressed when they
+/// are initially empty.
+///
+/// # Args
+///
+/// `bytes`:  The compressed data.
+///
+/// # Errors
+///
+/// [`crate::Error::StdIo`] Raised if the data cannot be decompressed or is not valid UTF-8.
+pub fn decompress(bytes: &[u8]) -> Result<String> {
+    let mut s = String::new();
+    if !bytes.is_empty() {
+        ZlibDecoder::new(bytes).read_to_string(&mut s)?;
+    }
+    Ok(s)
+}
+
+/// Decompress a string compressed using [`compress`], failing if it is larger than `max` bytes.
+///
+/// Used for data received from the network so that a small payload cannot expand into a huge
+/// string.
+///
+/// # Args
+///
+/// `bytes`:  The compressed data.
+///
+/// `max`:  The maximum size of the decompressed string in bytes.
+///
+/// # Errors
+///
+/// [`crate::Error::StdIo`] Raised if the data cannot be decompressed, is not valid UTF-8 or is
+/// larger than `max` bytes.
+pub fn decompress_max(bytes: &[u8], max: usize) -> Result<String> {
+    let mut s = String::new();
+    let limit = u64::try_from(max).unwrap_or(u64::MAX).saturating_add(1);
+    ZlibDecoder::new(bytes).take(limit).read_to_string(&mut s)?;
+    if s.len() > max {
+        let error = format!("decompressed data is larger than {} bytes", max);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, error).into());
+    }
+    Ok(s)
+}
+
 #[cfg(test)]
 mod tests {
-    // TODO[TESTS]
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let text = "MSG:1005,0,1,\"MakeMKV v1.18.1 linux(x64-release) started\"";
+        let bytes = compress(text).unwrap();
+        assert_eq!(decompress(&bytes).unwrap(), text);
+    }
+
+    #[test]
+    fn test_decompress_empty() {
+        assert_eq!(decompress(&[]).unwrap(), "");
+    }
+
+    #[test]
+    fn test_decompress_max() {
+        let text = "a".repeat(1000);
+        let bytes = compress(&text).unwrap();
+        assert_eq!(decompress_max(&bytes, 1000).unwrap(), text);
+        assert!(decompress_max(&bytes, 999).is_err());
+    }
 }
@@ -0,0 +1,513 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Backs up and restores the application database.
+//!
+//! Backups are made while the application is running using SQLite's online backup API, which
+//! copies the database a few pages at a time so other connections are not blocked for the length
+//! of the backup. Each backup is written to a temporary file and passes an integrity check before
+//! it is given its final name (`artie-<UTC timestamp>.db`). Only the most recent backups are kept
+//! (see [`Settings::retain`]).
+//!
+//! Backups can be made on demand using [`backup`] or on a schedule by calling [`init`] during
+//! application startup.
+//!
+//! A backup is restored using [`restore`] before the database is initialized. The backup must pass
+//! an integrity check and have a schema version this version of the application can migrate. The
+//! database being replaced is backed up first in case the wrong backup is restored.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Utc;
+
+use rusqlite::{Connection, OpenFlags};
+use rusqlite::backup::Backup;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+use crate::bus;
+use crate::path;
+use crate::task;
+
+/// The name of the folder in the data directory where backups are stored if a folder is not
+/// configured.
+const FOLDER_NAME: &str = "backups";
+
+/// The prefix of the backup file names.
+const FILE_PREFIX: &str = "artie-";
+
+/// The extension of the backup file names.
+const FILE_EXTENSION: &str = "db";
+
+/// The format of the timestamp in the backup file names.
+///
+/// The timestamps sort in the order the backups were made.
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+
+/// The number of pages copied in each step of a backup.
+const PAGES_PER_STEP: i32 = 256;
+
+/// The length of time to wait between each step of a backup, allowing other connections to use
+/// the database.
+const STEP_PAUSE: Duration = Duration::from_millis(10);
+
+/// Database backup settings.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Settings {
+    /// The number of hours between scheduled backups.
+    ///
+    /// Scheduled backups are disabled if zero.
+    #[serde(default = "Settings::default_interval_hours")]
+    pub interval_hours: u32,
+
+    /// The number of backups to keep. The oldest backups are deleted after a new backup is made.
+    #[serde(default = "Settings::default_retain")]
+    pub retain: usize,
+
+    /// The folder the backups are stored in.
+    ///
+    /// The backups are stored in the `backups` folder of the data directory if not set.
+    #[serde(default)]
+    pub folder: Option<PathBuf>,
+}
+
+impl Settings {
+    /// A backup is made every day if not specified in the config.
+    fn default_interval_hours() -> u32 {
+        24
+    }
+
+    /// A week of daily backups is kept if not specified in the config.
+    fn default_retain() -> usize {
+        7
+    }
+
+    /// Returns the folder the backups are stored in.
+    fn folder(&self) -> PathBuf {
+        self.folder.clone()
+            .unwrap_or_else(|| path::data_path(FOLDER_NAME))
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            interval_hours: Self::default_interval_hours(),
+            retain: Self::default_retain(),
+            folder: None,
+        }
+    }
+}
+
+/// Starts the task that makes the scheduled backups.
+///
+/// Nothing is started if scheduled backups are disabled. The first backup is made once the
+/// interval has elapsed since the most recent backup.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the application actors.
+///
+/// `settings`:  The database backup settings.
+pub fn init(bus: &bus::Handle, settings: Settings) {
+    if settings.interval_hours == 0 {
+        tracing::info!("scheduled database backups disabled");
+        return;
+    }
+
+    let bus = bus.clone();
+    task::spawn(async move {
+        let interval = Duration::from_secs(u64::from(settings.interval_hours) * 60 * 60);
+        loop {
+            let delay = match next_backup_delay(settings.folder(), interval).await {
+                Ok(delay) => delay,
+                Err(error) => {
+                    tracing::error!(?error, "failed to find the last database backup");
+                    interval
+                },
+            };
+            tokio::time::sleep(delay).await;
+
+            if let Err(error) = backup(&bus, &settings).await {
+                tracing::error!(?error, "database backup failed");
+            }
+        }
+    });
+}
+
+/// Backs up the database and deletes the oldest backups.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the application actors.
+///
+/// `settings`:  The database backup settings.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the database cannot be copied.
+///
+/// [`Error::BackupIntegrity`] if the integrity check of the backup fails. The backup is deleted.
+///
+/// [`Error::StdIo`] if the backup folder cannot be created or the backup cannot be renamed.
+pub async fn backup(bus: &bus::Handle, settings: &Settings) -> Result<PathBuf> {
+    let folder = settings.folder();
+    let retain = settings.retain;
+
+    let backup_path = super::run(bus, move |conn| {
+        let backup_path = create(conn, &folder)?;
+        prune(&folder, retain)?;
+        Ok(backup_path)
+    }).await?;
+
+    tracing::info!(?backup_path, "database backup completed");
+    Ok(backup_path)
+}
+
+/// Replaces the database with a backup.
+///
+/// This must be called before the database is initialized. The existing database, if there is
+/// one, is backed up before it is replaced. Its backup is not counted towards the retained
+/// backups until the next backup is made.
+///
+/// Backups with an older schema version are migrated when the database is initialized.
+///
+/// # Args
+///
+/// `backup_path`:  The path to the backup to restore.
+///
+/// `settings`:  The database backup settings.
+///
+/// # Errors
+///
+/// [`Error::FileNotFound`] if the backup does not exist.
+///
+/// [`Error::BackupIntegrity`] if the integrity check of the backup fails.
+///
+/// [`Error::SchemaVersion`] if the backup was made by a newer version of the application or is not
+/// an application database.
+///
+/// [`Error::Database`] if a database operation fails.
+///
+/// [`Error::StdIo`] if the existing database cannot be backed up.
+pub fn restore(backup_path: &Path, settings: &Settings) -> Result<Option<PathBuf>> {
+    let db_path = path::data_path(super::DATABASE_NAME);
+    restore_into(backup_path, &db_path, &settings.folder())
+}
+
+/// Replaces the database at a path with a backup.
+///
+/// Returns the path to the backup of the replaced database if there was one.
+///
+/// # Args
+///
+/// `backup_path`:  The path to the backup to restore.
+///
+/// `db_path`:  The path to the database being replaced.
+///
+/// `folder`:  The folder the backup of the replaced database is stored in.
+///
+/// # Errors
+///
+/// See [`restore`].
+fn restore_into(backup_path: &Path, db_path: &Path, folder: &Path) -> Result<Option<PathBuf>> {
+    if !backup_path.is_file() {
+        return Err(Error::FileNotFound { path: backup_path.to_owned() });
+    }
+
+    let source = Connection::open_with_flags(backup_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    check_integrity(&source, backup_path)?;
+    check_schema_version(&source)?;
+
+    let exists = db_path.is_file();
+
+    let mut conn = Connection::open(db_path)?;
+    conn.busy_timeout(super::BUSY_TIMEOUT)?;
+
+    let previous = if exists {
+        Some(create(&conn, folder)?)
+    } else {
+        None
+    };
+
+    copy(&source, &mut conn)?;
+
+    tracing::info!(?backup_path, ?previous, "database restored");
+    Ok(previous)
+}
+
+/// Copies the database to a new backup file in a folder.
+///
+/// The backup is made using a temporary file name which is changed once the backup passes an
+/// integrity check. The backup uses a rollback journal instead of write-ahead logging so it is
+/// contained in a single file.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database being backed up.
+///
+/// `folder`:  The folder to create the backup in. It is created if it does not exist.
+///
+/// # Errors
+///
+/// See [`backup`].
+fn create(conn: &Connection, folder: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(folder)?;
+
+    let file_name = format!(
+        "{}{}.{}",
+        FILE_PREFIX,
+        Utc::now().format(TIMESTAMP_FORMAT),
+        FILE_EXTENSION,
+    );
+    let backup_path = folder.join(&file_name);
+    let partial_path = folder.join(format!("{}.partial", file_name));
+
+    let result = (|| {
+        let mut dest = Connection::open(&partial_path)?;
+        copy(conn, &mut dest)?;
+        dest.pragma_update_and_check(None, "journal_mode", "DELETE", |_| Ok(()))?;
+        check_integrity(&dest, &backup_path)
+    })();
+
+    if let Err(error) = result {
+        let _ = fs::remove_file(&partial_path)
+            .inspect_err(|e| tracing::warn!(?partial_path, error=?e, "failed to remove backup"));
+        return Err(error);
+    }
+
+    fs::rename(&partial_path, &backup_path)?;
+    Ok(backup_path)
+}
+
+/// Copies the contents of one database into another using the online backup API.
+///
+/// # Args
+///
+/// `source`:  The connection to the database being copied.
+///
+/// `dest`:  The connection to the database being replaced.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the database cannot be copied.
+fn copy(source: &Connection, dest: &mut Connection) -> Result<()> {
+    let backup = Backup::new(source, dest)?;
+    backup.run_to_completion(PAGES_PER_STEP, STEP_PAUSE, None)?;
+    Ok(())
+}
+
+/// Runs the SQLite integrity check on a database.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `db_path`:  The path to the database reported in the error.
+///
+/// # Errors
+///
+/// [`Error::BackupIntegrity`] if the integrity check reports any problems.
+///
+/// [`Error::Database`] if the integrity check cannot be run.
+fn check_integrity(conn: &Connection, db_path: &Path) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let problems = stmt.query_map((), |r| r.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+
+    if problems.len() == 1 && problems[0] == "ok" {
+        return Ok(());
+    }
+
+    Err(Error::BackupIntegrity { path: db_path.to_owned(), problems })
+}
+
+/// Verifies the schema version of a database can be migrated by this version of the application.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`Error::SchemaVersion`] if the version is newer than the latest migration or the database does
+/// not have a schema version.
+///
+/// [`Error::Database`] if the version cannot be read.
+fn check_schema_version(conn: &Connection) -> Result<()> {
+    let version = super::schema_version(conn)?;
+    let supported = super::MIGRATIONS.len() as u32;
+
+    if version == 0 || version > supported {
+        return Err(Error::SchemaVersion { version, supported });
+    }
+
+    Ok(())
+}
+
+/// Deletes the oldest backups in a folder so that only the most recent are kept.
+///
+/// # Args
+///
+/// `folder`:  The folder containing the backups.
+///
+/// `retain`:  The number of backups to keep.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] if the folder cannot be read or a backup cannot be deleted.
+fn prune(folder: &Path, retain: usize) -> Result<()> {
+    let backups = list(folder)?;
+    let count = backups.len().saturating_sub(retain);
+
+    for backup_path in backups.into_iter().take(count) {
+        fs::remove_file(&backup_path)?;
+        tracing::info!(?backup_path, "deleted database backup");
+    }
+
+    Ok(())
+}
+
+/// Returns the backups in a folder from oldest to newest.
+///
+/// # Args
+///
+/// `folder`:  The folder containing the backups.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] if the folder cannot be read.
+fn list(folder: &Path) -> Result<Vec<PathBuf>> {
+    if !folder.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(folder)? {
+        let entry_path = entry?.path();
+        let is_backup = entry_path.is_file()
+            && entry_path.extension().is_some_and(|e| e == FILE_EXTENSION)
+            && entry_path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(FILE_PREFIX));
+        if is_backup {
+            backups.push(entry_path);
+        }
+    }
+
+    backups.sort();
+    Ok(backups)
+}
+
+/// Returns how long to wait before making the next scheduled backup.
+///
+/// # Args
+///
+/// `folder`:  The folder containing the backups.
+///
+/// `interval`:  The time between backups.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] if the folder or the most recent backup cannot be read.
+///
+/// [`Error::JoinError`] if the task reading the folder panics.
+async fn next_backup_delay(folder: PathBuf, interval: Duration) -> Result<Duration> {
+    let last = task::spawn_blocking(move || -> Result<Option<Duration>> {
+        let Some(last) = list(&folder)?.pop() else {
+            return Ok(None);
+        };
+        let elapsed = fs::metadata(last)?
+            .modified()?
+            .elapsed()
+            .unwrap_or_default();
+        Ok(Some(elapsed))
+    }).await??;
+
+    Ok(last.map_or(Duration::ZERO, |elapsed| interval.saturating_sub(elapsed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::db;
+    use crate::test_utils::TempDir;
+
+    #[test]
+    fn test_create_and_restore() {
+        let temp = TempDir::new("artie.test.db.backup.restore");
+        let folder = temp.path().join("backups");
+        let db_path = temp.path().join("artie.db");
+
+        let conn = db::open_in_memory();
+        db::host::create(&conn, "ripper").unwrap();
+
+        let backup_path = create(&conn, &folder).unwrap();
+        assert_eq!(list(&folder).unwrap(), vec![backup_path.clone()]);
+
+        assert!(restore_into(&backup_path, &db_path, &folder).unwrap().is_none());
+
+        let restored = Connection::open(&db_path).unwrap();
+        assert_eq!(db::host::get_all(&restored).unwrap()[0].hostname, "ripper");
+        assert_eq!(db::schema_version(&restored).unwrap() as usize, db::MIGRATIONS.len());
+        drop(restored);
+
+        let previous = restore_into(&backup_path, &db_path, &folder).unwrap().unwrap();
+        assert!(previous.is_file());
+        assert_eq!(list(&folder).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_restore_rejects_newer_schema() {
+        let temp = TempDir::new("artie.test.db.backup.schema");
+        let folder = temp.path().join("backups");
+
+        let conn = db::open_in_memory();
+        conn.pragma_update(None, "user_version", db::MIGRATIONS.len() + 1).unwrap();
+        let backup_path = create(&conn, &folder).unwrap();
+
+        let result = restore_into(&backup_path, &temp.path().join("artie.db"), &folder);
+        assert!(matches!(result, Err(Error::SchemaVersion { .. })));
+        assert!(!temp.path().join("artie.db").exists());
+    }
+
+    #[test]
+    fn test_restore_missing_backup() {
+        let temp = TempDir::new("artie.test.db.backup.missing");
+        let result = restore_into(
+            &temp.path().join("missing.db"),
+            &temp.path().join("artie.db"),
+            temp.path(),
+        );
+        assert!(matches!(result, Err(Error::FileNotFound { .. })));
+    }
+
+    #[test]
+    fn test_prune() {
+        let temp = TempDir::new("artie.test.db.backup.prune");
+        let folder = temp.path();
+        fs::create_dir_all(folder).unwrap();
+
+        for name in [
+            "artie-20260103T000000000Z.db",
+            "artie-20260101T000000000Z.db",
+            "artie-20260102T000000000Z.db",
+            "artie-20260104T000000000Z.db.partial",
+            "notes.db",
+        ] {
+            fs::write(folder.join(name), b"").unwrap();
+        }
+
+        prune(folder, 2).unwrap();
+
+        assert_eq!(list(folder).unwrap(), vec![
+            folder.join("artie-20260102T000000000Z.db"),
+            folder.join("artie-20260103T000000000Z.db"),
+        ]);
+        assert!(folder.join("notes.db").exists());
+    }
+}
@@ -5,6 +5,7 @@
 
 use std::path::PathBuf;
 
+use crate::metadata::{ProviderId, ProviderKind};
 use crate::models::{
     ContainerType,
     MediaType,
@@ -22,6 +23,28 @@ pub fn container_type_to_sql(container_type: &ContainerType) -> u8 {
     }
 }
 
+/// Converts the integral database value to a container type.
+///
+/// Unknown values are treated as [`ContainerType::MKV`].
+pub fn container_type_from_sql(value: u8) -> ContainerType {
+    match value {
+        1 => ContainerType::MP4,
+        _ => ContainerType::MKV,
+    }
+}
+
+/// Converts the media location database values back to a media location.
+///
+/// Unknown areas are treated as [`MediaLocation::Deleted`].
+pub fn media_location_from_sql(area: u8, path: &str) -> MediaLocation {
+    match area {
+        1 => MediaLocation::Inbox(PathBuf::from(path)),
+        2 => MediaLocation::Library(PathBuf::from(path)),
+        3 => MediaLocation::Archive(PathBuf::from(path)),
+        _ => MediaLocation::Deleted,
+    }
+}
+
 /// Converts media location to its database values.
 ///
 /// The returned result will be a two value tuple where the first value is the numeric value
@@ -46,6 +69,29 @@ pub fn media_type_to_sql(media_type: &MediaType) -> u8 {
     }
 }
 
+/// Converts the integral database value to a media type.
+///
+/// Unknown values are treated as [`MediaType::Movie`].
+pub fn media_type_from_sql(value: u8) -> MediaType {
+    match value {
+        1 => MediaType::Show,
+        _ => MediaType::Movie,
+    }
+}
+
+/// Converts the operation state database values back to an operation state.
+///
+/// Unknown values are treated as [`OperationState::Failed`].
+pub fn operation_state_from_sql(state: u8, error: &str) -> OperationState {
+    match state {
+        0 => OperationState::Requested,
+        1 => OperationState::Running,
+        2 => OperationState::Completed,
+        3 => OperationState::Cancelled,
+        _ => OperationState::Failed { reason: error.to_owned() },
+    }
+}
+
 /// Converts operation state to its integral value for use in the database.
 ///
 /// The result will be a two value tuple where the first value is the numberic value for the
@@ -62,6 +108,31 @@ pub fn operation_state_to_sql(state: &OperationState) -> (u8, String) {
     }
 }
 
+/// Converts a metadata provider kind to its integral database value.
+pub fn provider_kind_to_sql(provider: &ProviderKind) -> u8 {
+    match provider {
+        ProviderKind::Tmdb => 0,
+        ProviderKind::Tvdb => 1,
+        ProviderKind::Mock => 2,
+    }
+}
+
+/// Converts a metadata provider identifier to its database value.
+///
+/// The identifier is stored as text in the form `provider:id` (e.g. `tmdb:1234`). If the provided
+/// identifier is `None`, the result will be an empty string since the database column is not
+/// nullable.
+pub fn provider_id_to_sql(id: &Option<ProviderId>) -> String {
+    id.as_ref().map(|id| id.to_string()).unwrap_or_default()
+}
+
+/// Converts a metadata provider identifier database value back to the identifier.
+///
+/// Returns `None` if the value is empty or cannot be parsed.
+pub fn provider_id_from_sql(value: &str) -> Option<ProviderId> {
+    ProviderId::parse(value)
+}
+
 /// Converts special feature to its database values.
 ///
 /// The returned result will be a two value tuple where the first value is the numeric value for
@@ -89,11 +160,31 @@ pub fn special_feature_to_sql(special_feature: &Option<SpecialFeature>) -> (u8,
     }
 }
 
+/// Converts the special feature database values back to a special feature.
+///
+/// Returns `None` if the kind indicates the title is not a special feature or is unknown.
+pub fn special_feature_from_sql(kind: u8, name: &str) -> Option<SpecialFeature> {
+    let kind = match kind {
+        1 => SpecialFeatureType::BehindTheScenes,
+        2 => SpecialFeatureType::DeletedScenes,
+        3 => SpecialFeatureType::Interviews,
+        4 => SpecialFeatureType::Scenes,
+        5 => SpecialFeatureType::Samples,
+        6 => SpecialFeatureType::Shorts,
+        7 => SpecialFeatureType::Featurettes,
+        8 => SpecialFeatureType::Clips,
+        9 => SpecialFeatureType::Extras,
+        10 => SpecialFeatureType::Trailers,
+        _ => return None,
+    };
+
+    Some(SpecialFeature { kind, name: name.to_owned() })
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use crate::models::{
         ContainerType,
         MediaLocation,
@@ -123,6 +214,28 @@ mod tests {
         assert_eq!(media_type_to_sql(&MediaType::Show), 1);
     }
 
+    #[test]
+    fn test_media_type_from_sql() {
+        assert_eq!(media_type_from_sql(0), MediaType::Movie);
+        assert_eq!(media_type_from_sql(1), MediaType::Show);
+    }
+
+    #[test]
+    fn test_media_location_from_sql() {
+        let location = media_location_from_sql(2, "shows/bar.mkv");
+        assert!(matches!(location, MediaLocation::Library(p) if p == Path::new("shows/bar.mkv")));
+        assert!(matches!(media_location_from_sql(4, ""), MediaLocation::Deleted));
+    }
+
+    #[test]
+    fn test_operation_state_from_sql() {
+        assert!(matches!(operation_state_from_sql(2, ""), OperationState::Completed));
+        assert!(matches!(
+            operation_state_from_sql(4, "disk full"),
+            OperationState::Failed { reason } if reason == "disk full"
+        ));
+    }
+
     #[test]
     fn test_media_location_to_sql_inbox() {
         let (area, path) = media_location_to_sql(
@@ -195,6 +308,20 @@ mod tests {
         assert_eq!(error, reason);
     }
 
+    #[test]
+    fn test_provider_id_to_sql() {
+        let id = Some(ProviderId { provider: ProviderKind::Tmdb, id: 1234 });
+        assert_eq!(provider_id_to_sql(&id), "tmdb:1234");
+        assert_eq!(provider_id_to_sql(&None), "");
+    }
+
+    #[test]
+    fn test_provider_id_from_sql() {
+        let id = ProviderId { provider: ProviderKind::Tvdb, id: 81189 };
+        assert_eq!(provider_id_from_sql("tvdb:81189"), Some(id));
+        assert_eq!(provider_id_from_sql(""), None);
+    }
+
     #[test]
     fn test_special_feature_to_sql_none_option() {
         let (kind, name) = special_feature_to_sql(&None);
@@ -232,4 +359,18 @@ mod tests {
             assert_eq!(name, "Test Feature");
         }
     }
+
+    #[test]
+    fn test_special_feature_from_sql() {
+        assert!(special_feature_from_sql(0, "").is_none());
+
+        let sf = special_feature_from_sql(10, "Trailer").unwrap();
+        assert!(matches!(sf.kind, SpecialFeatureType::Trailers));
+        assert_eq!(sf.name, "Trailer");
+
+        for kind in 1..=10 {
+            let sf = special_feature_from_sql(kind, "Test");
+            assert_eq!(special_feature_to_sql(&sf).0, kind);
+        }
+    }
 }
@@ -5,13 +5,13 @@
 
 use chrono::{DateTime, Utc};
 
-use rusqlite::Connection;
+use rusqlite::{Connection, Row};
 
 use makemkv::DiscInfo;
 
 use crate::Result;
 use crate::compress;
-use crate::models::{CopyOperation, OperationState};
+use crate::models::{CopyOperation, OperationState, Reference};
 
 use super::conv;
 
@@ -46,6 +46,8 @@ pub fn create(conn: &Connection, copy_operation: &mut CopyOperation) -> Result<(
                                    , copy_log
                                    , host_id
                                    , error
+                                   , metadata_id
+                                   , app_log
                                    )
              VALUES ( ?1 -- started
                     , ?2 -- completed
@@ -64,6 +66,8 @@ pub fn create(conn: &Connection, copy_operation: &mut CopyOperation) -> Result<(
                     , ?15 -- copy_log
                     , ?16 -- host_id
                     , ?17 -- error
+                    , ?18 -- metadata_id
+                    , ?19 -- app_log
                     )
           RETURNING id
     ";
@@ -90,6 +94,8 @@ pub fn create(conn: &Connection, copy_operation: &mut CopyOperation) -> Result<(
         copy_operation.copy_log.as_bytes(),
         copy_operation.host.id,
         error,
+        conv::provider_id_to_sql(&copy_operation.metadata_id),
+        copy_operation.app_log.as_bytes(),
     ];
 
     let id = stmt.query_row(params, |r| r.get::<_, u32>(0))?;
@@ -100,6 +106,202 @@ pub fn create(conn: &Connection, copy_operation: &mut CopyOperation) -> Result<(
     Ok(())
 }
 
+/// Gets all of the copy operation records ordered by id.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `include_logs`:  Indicates if the logs and disc metadata should be read. If `false`, those
+/// fields will be empty.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+///
+/// [`crate::Error::StdIo`] raised if the logs or disc metadata cannot be decompressed.
+pub fn get_all(conn: &Connection, include_logs: bool) -> Result<Vec<CopyOperation>> {
+    let sql = "
+        SELECT id
+             , started
+             , completed
+             , state
+             , error
+             , media_type
+             , title
+             , year
+             , disc
+             , disc_uuid
+             , season
+             , location
+             , memo
+             , drive_id
+             , host_id
+             , metadata_id
+             , metadata
+             , info_log
+             , copy_log
+             , app_log
+          FROM copy_operation
+      ORDER BY id
+    ";
+
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query(())?;
+
+    let mut copy_operations = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut copy_operation = from_row(row)?;
+        if include_logs {
+            copy_operation.metadata = compress::decompress(&row.get::<_, Vec<u8>>(16)?)?;
+            copy_operation.info_log = compress::decompress(&row.get::<_, Vec<u8>>(17)?)?;
+            copy_operation.copy_log = compress::decompress(&row.get::<_, Vec<u8>>(18)?)?;
+            copy_operation.app_log = compress::decompress(&row.get::<_, Vec<u8>>(19)?)?;
+        }
+        copy_operations.push(copy_operation);
+    }
+
+    Ok(copy_operations)
+}
+
+/// Gets the most recently started copy operations ordered from newest to oldest.
+///
+/// The logs and disc metadata are not read since they are large and rarely needed. Those fields
+/// will be empty.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `limit`:  The maximum number of copy operations to return.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn get_recent(conn: &Connection, limit: u32) -> Result<Vec<CopyOperation>> {
+    let sql = "
+        SELECT id
+             , started
+             , completed
+             , state
+             , error
+             , media_type
+             , title
+             , year
+             , disc
+             , disc_uuid
+             , season
+             , location
+             , memo
+             , drive_id
+             , host_id
+             , metadata_id
+          FROM copy_operation
+      ORDER BY started DESC
+             , id DESC
+         LIMIT ?1
+    ";
+
+    let mut stmt = conn.prepare(sql)?;
+    let copy_operations = stmt.query_map((limit,), from_row)?
+        .collect::<rusqlite::Result<Vec<CopyOperation>>>()?;
+
+    Ok(copy_operations)
+}
+
+/// Creates a copy operation record keeping the copy operation's existing id.
+///
+/// Used when importing a catalog (see [`crate::library::catalog`]). The logs and disc metadata are
+/// compressed the same as when they are set on a new copy operation.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `copy_operation`:  The copy operation data to create the record from.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+///
+/// [`crate::Error::StdIo`] raised if the logs or disc metadata cannot be compressed.
+pub fn import(conn: &Connection, copy_operation: &CopyOperation) -> Result<()> {
+    let sql = "
+        INSERT INTO copy_operation ( id
+                                   , started
+                                   , completed
+                                   , state
+                                   , media_type
+                                   , title
+                                   , year
+                                   , disc
+                                   , disc_uuid
+                                   , season
+                                   , location
+                                   , memo
+                                   , metadata
+                                   , drive_id
+                                   , info_log
+                                   , copy_log
+                                   , host_id
+                                   , error
+                                   , metadata_id
+                                   , app_log
+                                   )
+             VALUES ( ?1 -- id
+                    , ?2 -- started
+                    , ?3 -- completed
+                    , ?4 -- state
+                    , ?5 -- media_type
+                    , ?6 -- title
+                    , ?7 -- year
+                    , ?8 -- disc
+                    , ?9 -- disc_uuid
+                    , ?10 -- season
+                    , ?11 -- location
+                    , ?12 -- memo
+                    , ?13 -- metadata
+                    , ?14 -- drive_id
+                    , ?15 -- info_log
+                    , ?16 -- copy_log
+                    , ?17 -- host_id
+                    , ?18 -- error
+                    , ?19 -- metadata_id
+                    , ?20 -- app_log
+                    )
+    ";
+
+    let (state, error) = conv::operation_state_to_sql(&copy_operation.state);
+
+    let params = rusqlite::params![
+        copy_operation.id,
+        copy_operation.started.timestamp(),
+        copy_operation.completed.timestamp(),
+        state,
+        conv::media_type_to_sql(&copy_operation.media_type),
+        copy_operation.title,
+        copy_operation.year,
+        copy_operation.disc,
+        copy_operation.disc_uuid,
+        copy_operation.season,
+        copy_operation.location,
+        copy_operation.memo,
+        compress::compress(&copy_operation.metadata)?,
+        copy_operation.drive.id,
+        compress::compress(&copy_operation.info_log)?,
+        compress::compress(&copy_operation.copy_log)?,
+        copy_operation.host.id,
+        error,
+        conv::provider_id_to_sql(&copy_operation.metadata_id),
+        compress::compress(&copy_operation.app_log)?,
+    ];
+
+    let _ = conn.execute(sql, params)?;
+
+    tracing::trace!(id=copy_operation.id, "import copy_operation entry");
+    Ok(())
+}
+
 /// Update the copy log field of a copy operation record.
 ///
 /// # Args
@@ -137,6 +339,43 @@ pub fn set_copy_log(
     Ok(())
 }
 
+/// Update the application log field of a copy operation record.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `copy_operation`:  The copy operation being updated. If this operation is successful, its
+/// application log field will be updated.
+///
+/// `app_log`:  The events logged by the application while performing the copy operation.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+///
+/// [`crate::Error::StdIo`] raised if the provided log cannot be compressed.
+pub fn set_app_log(
+    conn: &Connection,
+    copy_operation: &mut CopyOperation,
+    app_log: &str,
+) -> Result<()> {
+    let sql = "
+        UPDATE copy_operation
+           SET app_log=?1
+         WHERE id=?2
+    ";
+
+    let bytes = compress::compress(app_log)?;
+
+    let _ = conn.execute(sql, (bytes, copy_operation.id))?;
+
+    copy_operation.app_log = app_log.to_owned();
+
+    tracing::trace!(id=copy_operation.id, "set copy_operation app log");
+    Ok(())
+}
+
 /// Update the info log field of a copy operation record.
 ///
 /// # Args
@@ -265,6 +504,48 @@ pub fn set_state(
     Ok(())
 }
 
+/// Adds the metadata provider identifier column to the copy operation table.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub(super) fn add_metadata_id_column(conn: &Connection) -> Result<()> {
+    let sql = "
+        ALTER TABLE copy_operation
+         ADD COLUMN metadata_id TEXT NOT NULL DEFAULT ''
+    ";
+
+    let _ = conn.execute(sql, ())?;
+
+    tracing::info!("add copy_operation metadata_id column");
+    Ok(())
+}
+
+/// Adds the application log column to the copy operation table.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub(super) fn add_app_log_column(conn: &Connection) -> Result<()> {
+    let sql = "
+        ALTER TABLE copy_operation
+         ADD COLUMN app_log BLOB NOT NULL DEFAULT X''
+    ";
+
+    let _ = conn.execute(sql, ())?;
+
+    tracing::info!("add copy_operation app_log column");
+    Ok(())
+}
+
 /// Creates the database table for storing copy operation data if it does not exist.
 ///
 /// # Args
@@ -306,6 +587,28 @@ pub(super) fn create_table(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Creates a copy operation from a row selected by [`get_recent`] or [`get_all`].
+fn from_row(row: &Row) -> rusqlite::Result<CopyOperation> {
+    Ok(CopyOperation {
+        id: row.get(0)?,
+        started: DateTime::from_timestamp(row.get(1)?, 0).unwrap_or_default(),
+        completed: DateTime::from_timestamp(row.get(2)?, 0).unwrap_or_default(),
+        state: conv::operation_state_from_sql(row.get(3)?, &row.get::<_, String>(4)?),
+        media_type: conv::media_type_from_sql(row.get(5)?),
+        title: row.get(6)?,
+        year: row.get(7)?,
+        disc: row.get(8)?,
+        disc_uuid: row.get(9)?,
+        season: row.get(10)?,
+        location: row.get(11)?,
+        memo: row.get(12)?,
+        drive: Reference { id: row.get(13)?, value: None },
+        host: Reference { id: row.get(14)?, value: None },
+        metadata_id: conv::provider_id_from_sql(&row.get::<_, String>(15)?),
+        ..CopyOperation::default()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -320,6 +623,8 @@ mod tests {
         super::super::optical_drive::create_table(&conn)
             .expect("Failed to create optical_drive table");
         create_table(&conn).expect("Failed to create copy_operation table");
+        add_metadata_id_column(&conn).expect("Failed to add metadata_id column");
+        add_app_log_column(&conn).expect("Failed to add app_log column");
         let host = super::super::host::create(&conn, "testhost")
             .expect("Failed to create host");
         let drive = super::super::optical_drive::create(&conn, "SN-TEST-001")
@@ -364,6 +669,26 @@ mod tests {
         assert_ne!(op1.id, op2.id);
     }
 
+    #[test]
+    fn test_get_recent() {
+        let (conn, host_id, drive_id) = setup_test_db();
+        for (started, title) in [(100, "First"), (300, "Third"), (200, "Second")] {
+            let mut op = make_copy_operation(host_id, drive_id);
+            op.started = DateTime::from_timestamp(started, 0).unwrap();
+            op.title = title.to_owned();
+            op.copy_log = "copy log output".to_owned();
+            create(&conn, &mut op).unwrap();
+        }
+
+        let ops = get_recent(&conn, 2).expect("Failed to get recent copy operations");
+
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[0].title, "Third");
+        assert_eq!(ops[1].title, "Second");
+        assert_eq!(ops[0].drive.id, drive_id);
+        assert!(ops[0].copy_log.is_empty());
+    }
+
     #[test]
     fn test_set_copy_log() {
         let (conn, host_id, drive_id) = setup_test_db();
@@ -375,6 +700,19 @@ mod tests {
         assert_eq!(op.copy_log, "copy log output");
     }
 
+    #[test]
+    fn test_set_app_log() {
+        let (conn, host_id, drive_id) = setup_test_db();
+        let mut op = make_copy_operation(host_id, drive_id);
+        create(&conn, &mut op).unwrap();
+
+        set_app_log(&conn, &mut op, "INFO artie: copy started").expect("Failed to set app log");
+        assert_eq!(op.app_log, "INFO artie: copy started");
+
+        let ops = get_all(&conn, true).unwrap();
+        assert_eq!(ops[0].app_log, "INFO artie: copy started");
+    }
+
     #[test]
     fn test_set_info_log() {
         let (conn, host_id, drive_id) = setup_test_db();
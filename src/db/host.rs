@@ -49,6 +49,55 @@ pub fn create(conn: &Connection, hostname: &str) -> Result<Host> {
     Ok(host)
 }
 
+/// Gets all of the host records ordered by id.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`Error::Database`] raised if the database operation fails.
+pub fn get_all(conn: &Connection) -> Result<Vec<Host>> {
+    let sql = "
+        SELECT id, hostname
+          FROM host
+      ORDER BY id
+    ";
+
+    let mut stmt = conn.prepare(sql)?;
+
+    let hosts = stmt.query_map((), |r| Ok(Host { id: r.get(0)?, hostname: r.get(1)? }))?
+        .collect::<rusqlite::Result<Vec<Host>>>()?;
+
+    Ok(hosts)
+}
+
+/// Creates a host record keeping the host's existing id.
+///
+/// Used when importing a catalog (see [`crate::library::catalog`]).
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `host`:  The host data to create the record from.
+///
+/// # Errors
+///
+/// [`Error::Database`] raised if the database operation fails.
+pub fn import(conn: &Connection, host: &Host) -> Result<()> {
+    let sql = "
+        INSERT INTO host (id, hostname)
+             VALUES (?1, ?2)
+    ";
+
+    let _ = conn.execute(sql, (host.id, &host.hostname))?;
+
+    tracing::trace!(?host, "import host entry");
+    Ok(())
+}
+
 /// Gets a host record from the database using its hostname if it exists or creates a new instance
 /// if it does not exist.
 ///
@@ -0,0 +1,214 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Database operations for [`IntegrityScan`] data.
+
+use chrono::{DateTime, Utc};
+
+use rusqlite::{Connection, OptionalExtension, Row};
+use rusqlite::types::Type;
+
+use crate::Result;
+use crate::models::IntegrityScan;
+
+/// Creates a new integrity scan record in the database.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `scan`:  The scan data to create the record from. If successful, the id field will be set.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn create(conn: &Connection, scan: &mut IntegrityScan) -> Result<()> {
+    let sql = "
+        INSERT INTO integrity_scan ( started
+                                   , completed
+                                   , verified
+                                   , findings
+                                   )
+             VALUES ( ?1        -- started
+                    , ?2        -- completed
+                    , ?3        -- verified
+                    , jsonb(?4) -- findings
+                    )
+          RETURNING id
+    ";
+
+    let params = rusqlite::params![
+        scan.started.timestamp(),
+        scan.completed.timestamp(),
+        scan.verified,
+        serde_json::to_string(&scan.findings)?,
+    ];
+
+    let id = conn.query_row(sql, params, |r| r.get::<_, u32>(0))?;
+
+    scan.id = id;
+
+    tracing::trace!(id, "create integrity_scan entry");
+    Ok(())
+}
+
+/// Updates the completed time, verified count, and findings of an integrity scan.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `scan`:  The scan being completed. If successful, the completed field will be set to the
+/// current UTC time.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn complete(conn: &Connection, scan: &mut IntegrityScan) -> Result<()> {
+    let sql = "
+        UPDATE integrity_scan
+           SET completed=?1,
+               verified=?2,
+               findings=jsonb(?3)
+         WHERE id=?4
+    ";
+
+    let completed = Utc::now();
+
+    let params = rusqlite::params![
+        completed.timestamp(),
+        scan.verified,
+        serde_json::to_string(&scan.findings)?,
+        scan.id,
+    ];
+
+    let _ = conn.execute(sql, params)?;
+
+    scan.completed = completed;
+
+    tracing::trace!(id=scan.id, "complete integrity_scan entry");
+    Ok(())
+}
+
+/// Gets the most recently started integrity scan.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn get_latest(conn: &Connection) -> Result<Option<IntegrityScan>> {
+    let sql = "
+        SELECT id
+             , started
+             , completed
+             , verified
+             , json(findings)
+          FROM integrity_scan
+      ORDER BY started DESC
+             , id DESC
+         LIMIT 1
+    ";
+
+    let scan = conn.query_row(sql, (), from_row)
+        .optional()?;
+
+    Ok(scan)
+}
+
+/// Creates the database table for storing integrity scan data if it does not exist.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub(super) fn create_table(conn: &Connection) -> Result<()> {
+    let sql = "
+        CREATE TABLE integrity_scan (
+            id         INTEGER  PRIMARY KEY AUTOINCREMENT,
+            started    INTEGER  NOT NULL,
+            completed  INTEGER  NOT NULL,
+            verified   INTEGER  NOT NULL,
+            findings   BLOB     NOT NULL
+        ) STRICT
+    ";
+
+    let _ = conn.execute(sql, ())?;
+
+    tracing::info!("create integrity_scan table");
+    Ok(())
+}
+
+/// Creates an integrity scan from a row.
+fn from_row(row: &Row) -> rusqlite::Result<IntegrityScan> {
+    let findings = serde_json::from_str(&row.get::<_, String>(4)?)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(4, Type::Text, Box::new(e)))?;
+
+    Ok(IntegrityScan {
+        id: row.get(0)?,
+        started: DateTime::from_timestamp(row.get(1)?, 0).unwrap_or_default(),
+        completed: DateTime::from_timestamp(row.get(2)?, 0).unwrap_or_default(),
+        verified: row.get(3)?,
+        findings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    use crate::models::{IntegrityFinding, MediaLocation};
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().expect("Failed to create in-memory database");
+        create_table(&conn).expect("Failed to create table");
+        conn
+    }
+
+    #[test]
+    fn test_get_latest_empty() {
+        let conn = setup_test_db();
+        assert!(get_latest(&conn).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_create_and_complete() {
+        let conn = setup_test_db();
+
+        let mut first = IntegrityScan {
+            started: DateTime::from_timestamp(1000, 0).unwrap(),
+            ..IntegrityScan::default()
+        };
+        create(&conn, &mut first).unwrap();
+
+        let mut scan = IntegrityScan {
+            started: DateTime::from_timestamp(2000, 0).unwrap(),
+            ..IntegrityScan::default()
+        };
+        create(&conn, &mut scan).unwrap();
+        assert!(scan.id > first.id);
+
+        scan.verified = 3;
+        scan.findings.push(IntegrityFinding::Missing {
+            video_id: 7,
+            location: MediaLocation::Library(PathBuf::from("movies/gone.mkv")),
+        });
+        complete(&conn, &mut scan).unwrap();
+
+        let latest = get_latest(&conn).unwrap().unwrap();
+        assert_eq!(latest.id, scan.id);
+        assert_eq!(latest.verified, 3);
+        assert_eq!(latest.completed.timestamp(), scan.completed.timestamp());
+        assert!(matches!(
+            &latest.findings[..],
+            [IntegrityFinding::Missing { video_id: 7, .. }]
+        ));
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Database operations for caching metadata provider responses.
+//!
+//! The responses are stored as JSON text and keyed by the provider, the type of lookup, and a
+//! lookup specific key (e.g. the search query). See [`crate::metadata`].
+
+use std::time::Duration;
+
+use chrono::Utc;
+
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::Result;
+use crate::metadata::ProviderKind;
+
+use super::conv;
+
+/// Specifies the types of cached lookups.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    /// Search results.
+    Search,
+
+    /// Movie or show details.
+    Details,
+}
+
+impl Kind {
+    /// Converts the kind to its integral database value.
+    fn to_sql(self) -> u8 {
+        match self {
+            Kind::Search => 0,
+            Kind::Details => 1,
+        }
+    }
+}
+
+/// Gets a cached response.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `provider`:  The provider the response was received from.
+///
+/// `kind`:  The type of lookup.
+///
+/// `key`:  The lookup key.
+///
+/// `max_age`:  The maximum age of the cache entry. Entries older than this are ignored.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn get(
+    conn: &Connection,
+    provider: ProviderKind,
+    kind: Kind,
+    key: &str,
+    max_age: Duration,
+) -> Result<Option<String>> {
+    let sql = "
+        SELECT data
+          FROM metadata_cache
+         WHERE provider=?1
+           AND kind=?2
+           AND key=?3
+           AND fetched>=?4
+    ";
+
+    let oldest = Utc::now().timestamp() - max_age.as_secs() as i64;
+
+    let params = (conv::provider_kind_to_sql(&provider), kind.to_sql(), key, oldest);
+
+    let data = conn.query_row(sql, params, |r| r.get::<_, String>(0))
+        .optional()?;
+
+    Ok(data)
+}
+
+/// Creates or replaces a cached response.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `provider`:  The provider the response was received from.
+///
+/// `kind`:  The type of lookup.
+///
+/// `key`:  The lookup key.
+///
+/// `data`:  The response as JSON text.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn set(
+    conn: &Connection,
+    provider: ProviderKind,
+    kind: Kind,
+    key: &str,
+    data: &str,
+) -> Result<()> {
+    let sql = "
+        INSERT INTO metadata_cache ( provider
+                                   , kind
+                                   , key
+                                   , fetched
+                                   , data
+                                   )
+             VALUES ( ?1 -- provider
+                    , ?2 -- kind
+                    , ?3 -- key
+                    , ?4 -- fetched
+                    , ?5 -- data
+                    )
+        ON CONFLICT(provider, kind, key)
+        DO UPDATE SET fetched=excluded.fetched
+                    , data=excluded.data
+    ";
+
+    let params = (
+        conv::provider_kind_to_sql(&provider),
+        kind.to_sql(),
+        key,
+        Utc::now().timestamp(),
+        data,
+    );
+
+    let _ = conn.execute(sql, params)?;
+
+    tracing::trace!(?provider, ?kind, key, "set metadata cache entry");
+    Ok(())
+}
+
+/// Creates the database table for caching metadata responses if it does not exist.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub(super) fn create_table(conn: &Connection) -> Result<()> {
+    let sql = "
+        CREATE TABLE metadata_cache (
+            id        INTEGER  PRIMARY KEY AUTOINCREMENT,
+            provider  INTEGER  NOT NULL,
+            kind      INTEGER  NOT NULL,
+            key       TEXT     NOT NULL,
+            fetched   INTEGER  NOT NULL,
+            data      TEXT     NOT NULL,
+            UNIQUE(provider, kind, key)
+        ) STRICT
+    ";
+
+    let _ = conn.execute(sql, ())?;
+
+    tracing::info!("create metadata_cache table");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().expect("Failed to create in-memory database");
+        create_table(&conn).expect("Failed to create table");
+        conn
+    }
+
+    #[test]
+    fn test_create_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        let result = create_table(&conn);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_get_missing() {
+        let conn = setup_test_db();
+        let data = get(&conn, ProviderKind::Tmdb, Kind::Search, "movie:0:alien", DAY).unwrap();
+        assert!(data.is_none());
+    }
+
+    #[test]
+    fn test_set_and_get() {
+        let conn = setup_test_db();
+        set(&conn, ProviderKind::Tmdb, Kind::Search, "movie:0:alien", "[1]").unwrap();
+
+        let data = get(&conn, ProviderKind::Tmdb, Kind::Search, "movie:0:alien", DAY).unwrap();
+        assert_eq!(data.as_deref(), Some("[1]"));
+
+        // Same key, but different provider or kind.
+        let data = get(&conn, ProviderKind::Tvdb, Kind::Search, "movie:0:alien", DAY).unwrap();
+        assert!(data.is_none());
+        let data = get(&conn, ProviderKind::Tmdb, Kind::Details, "movie:0:alien", DAY).unwrap();
+        assert!(data.is_none());
+    }
+
+    #[test]
+    fn test_set_replaces_existing() {
+        let conn = setup_test_db();
+        set(&conn, ProviderKind::Tmdb, Kind::Details, "movie:348", "{\"a\":1}").unwrap();
+        set(&conn, ProviderKind::Tmdb, Kind::Details, "movie:348", "{\"a\":2}").unwrap();
+
+        let data = get(&conn, ProviderKind::Tmdb, Kind::Details, "movie:348", DAY).unwrap();
+        assert_eq!(data.as_deref(), Some("{\"a\":2}"));
+    }
+
+    #[test]
+    fn test_get_expired() {
+        let conn = setup_test_db();
+        set(&conn, ProviderKind::Tmdb, Kind::Search, "movie:0:alien", "[]").unwrap();
+
+        conn.execute("UPDATE metadata_cache SET fetched=fetched-?1", (2 * DAY.as_secs(),))
+            .unwrap();
+
+        let data = get(&conn, ProviderKind::Tmdb, Kind::Search, "movie:0:alien", DAY).unwrap();
+        assert!(data.is_none());
+    }
+}
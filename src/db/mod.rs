@@ -3,23 +3,37 @@
 
 //! Handles interactions with the application database.
 //!
-//! To perform database operations, open a connection to the database using the [`connect`]
-//! function to request a connection from the database actor. The same connection cannot be used
-//! accross threads. Therefore, a new connection should be established within each task.
+//! To perform database operations, use the [`run`] function to run a closure with a connection on
+//! a thread where blocking is acceptable. A connection can also be borrowed directly using the
+//! [`connect`] function for tasks that perform several operations between other async calls.
+//!
+//! Connections are borrowed from a pool owned by the database actor and are returned to the pool
+//! when dropped. Each connection is configured to use write-ahead logging, wait on locks held by
+//! other connections (see [`BUSY_TIMEOUT`]), and enforce foreign key constraints.
 //!
 //! Before opening a connection, [`init`] must be called to perform initialization. This happens
 //! during application startup.
+//!
+//! The database can be backed up while the application is running and restored from a backup using
+//! the [`backup`] module.
 
+pub mod backup;
 mod conv;
 pub mod copy_operation;
 pub mod host;
+pub mod integrity_scan;
+pub mod metadata_cache;
 pub mod optical_drive;
 pub mod title;
 pub mod transaction;
 pub mod transcode_operation;
 pub mod video;
 
-use std::path::PathBuf;
+use std::path::Path;
+use std::time::Duration;
+
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
 
 use rusqlite::Connection;
 
@@ -29,10 +43,35 @@ use crate::{Error, Result};
 use crate::actor::{self, Response};
 use crate::bus;
 use crate::path;
+use crate::task;
 
 /// The name of the SQLite database file.
 const DATABASE_NAME: &str = "artie.db";
 
+/// The maximum number of connections kept open by the connection pool.
+const POOL_SIZE: u32 = 8;
+
+/// The length of time a connection waits for a lock held by another connection to be released
+/// before failing with a busy error.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A connection borrowed from the database connection pool.
+///
+/// The connection is returned to the pool when dropped.
+pub type PooledConnection = r2d2::PooledConnection<SqliteConnectionManager>;
+
+/// The database migrations in the order they must be applied.
+///
+/// The schema version stored in the database (`PRAGMA user_version`) is the number of migrations
+/// that have been applied.
+const MIGRATIONS: &[fn(&Connection) -> Result<()>] = &[
+    migration_0,
+    migration_1,
+    migration_2,
+    migration_3,
+    migration_4,
+    migration_5,
+];
 
 /// Handle used to communicate with the database actor.
 pub type Handle = actor::Handle<Message>;
@@ -42,29 +81,55 @@ pub type Handle = actor::Handle<Message>;
 pub enum Message {
     /// Open a connection to the database.
     Connect {
-        response: Response<Connection>,
+        response: Response<PooledConnection>,
     }
 }
 
-/// Open a connection to the database.
+/// Borrow a connection to the database from the connection pool.
+///
+/// The database operations are blocking. Prefer [`run`] unless the connection needs held between
+/// other async calls.
 ///
 /// `bus`:  Handle for sending messages to the database actor.
 ///
 /// # Errors
 ///
-/// [`Error::Database`] if the connection fails.
+/// [`Error::DatabasePool`] if a connection does not become available or a new connection cannot
+/// be opened.
 ///
 /// [`Error::ChannelSend`] if the request could not be sent to the database actor.
 ///
 /// [`Error::ResponseRecv`] if the there was an error while waiting for the response from the
 /// database actor to the request.
-pub async fn connect(bus: &bus::Handle) -> Result<Connection> {
+pub async fn connect(bus: &bus::Handle) -> Result<PooledConnection> {
     let (tx, rx) = oneshot::channel();
     let msg = Message::Connect { response: tx };
     bus.send(msg).await?;
     rx.await?
 }
 
+/// Runs a closure with a connection to the database on a thread where blocking is acceptable.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the database actor.
+///
+/// `func`:  The closure performing the database operations.
+///
+/// # Errors
+///
+/// Any of the errors returned by [`connect`] or `func`.
+///
+/// [`Error::JoinError`] if the closure panics.
+pub async fn run<F, T>(bus: &bus::Handle, func: F) -> Result<T>
+where
+    F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let mut conn = connect(bus).await?;
+    task::spawn_blocking(move || func(&mut conn)).await?
+}
+
 /// Initialize the database.
 ///
 /// This will create the actor, spawn the task to process requests, and perform any required
@@ -72,34 +137,62 @@ pub async fn connect(bus: &bus::Handle) -> Result<Connection> {
 ///
 /// # Errors
 ///
+/// [`Error::DatabasePool`] if the connection pool cannot be created.
+///
 /// [`Error::Database`] if a database operation fails while determining the need for and running
 /// migrations.
 pub fn init() -> Result<Handle> {
-    let msg_processor = MessageProcessor::new(path::data_path(DATABASE_NAME));
+    let db_path = path::data_path(DATABASE_NAME);
 
-    let mut run_migrations = true;
+    let exists = db_path.is_file();
 
-    // If the path already exists, assume the database is already initialized. This will only
-    // work during initial development. Beyond that, we'll need to track versions.
-    if msg_processor.db_path.is_file() {
-        run_migrations = false;
-    }
+    let pool = open_pool(&db_path)?;
 
-    let conn = msg_processor.connect()?;
-
-    if run_migrations {
-        migration_0(&conn)?;
-    }
+    migrate(&*pool.get()?, exists)?;
 
     tracing::info!("database initialized");
 
-    Ok(actor::create_and_run("database", msg_processor))
+    Ok(actor::create_and_run("database", MessageProcessor::new(pool)))
+}
+
+/// Creates the connection pool for a database file.
+///
+/// # Args
+///
+/// `db_path`:  The path to the SQLite file.
+///
+/// # Errors
+///
+/// [`Error::DatabasePool`] if the initial connections cannot be opened.
+fn open_pool(db_path: &Path) -> Result<Pool<SqliteConnectionManager>> {
+    let manager = SqliteConnectionManager::file(db_path)
+        .with_init(configure);
+    let pool = Pool::builder()
+        .max_size(POOL_SIZE)
+        .build(manager)?;
+    Ok(pool)
+}
+
+/// Configures a newly opened connection.
+///
+/// # Args
+///
+/// `conn`:  The connection to configure.
+///
+/// # Errors
+///
+/// [`rusqlite::Error`] if any of the pragmas cannot be set.
+fn configure(conn: &mut Connection) -> rusqlite::Result<()> {
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(())
 }
 
 /// Processes messages sent to the database actor.
 struct MessageProcessor {
-    /// The path to the SQLite file.
-    db_path: PathBuf,
+    /// The pool the connections are borrowed from.
+    pool: Pool<SqliteConnectionManager>,
 }
 
 impl MessageProcessor {
@@ -107,15 +200,25 @@ impl MessageProcessor {
     ///
     /// # Args
     ///
-    /// `db_path`:  The path to the SQLite file.
-    fn new(db_path: PathBuf) -> Self {
-        Self { db_path }
+    /// `pool`:  The pool the connections are borrowed from.
+    fn new(pool: Pool<SqliteConnectionManager>) -> Self {
+        Self { pool }
     }
 
-    /// Open a connection to the database.
-    fn connect(&self) -> Result<Connection> {
-        let conn = Connection::open(&self.db_path)?;
-        Ok(conn)
+    /// Borrow a connection from the pool.
+    ///
+    /// Waiting for a connection to become available blocks, so the connection is retrieved on a
+    /// blocking thread to avoid stalling the actor while the pool is exhausted.
+    ///
+    /// # Args
+    ///
+    /// `resp`:  The transmission end of the channel to send the response.
+    fn connect(&self, resp: Response<PooledConnection>) {
+        let pool = self.pool.clone();
+        task::spawn_blocking(move || {
+            let _ = resp.send(pool.get().map_err(Error::from))
+                .inspect_err(|_| send_error_trace("Connect"));
+        });
     }
 }
 
@@ -123,14 +226,61 @@ impl actor::MessageProcessor<Message> for MessageProcessor {
     async fn process(&mut self, msg: Message) -> Result<()> {
         match msg {
             Message::Connect { response } => {
-                response.send(self.connect())
-                    .inspect_err(|_| send_error_trace("Connect"))
-                    .map_err(|_| Error::ResponseSend)
+                self.connect(response);
+                Ok(())
             },
         }
     }
 }
 
+/// Applies any database migrations that have not yet been applied.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `existing`:  Indicates if the database file existed prior to opening the connection. Databases
+/// created before the schema version was tracked will have a version of zero even though the
+/// initial migration was applied. In that case, the initial migration will be skipped.
+///
+/// # Errors
+///
+/// [`Error::Database`] if any of the database operations fail.
+fn migrate(conn: &Connection, existing: bool) -> Result<()> {
+    let mut version = schema_version(conn)?;
+
+    if version == 0 && existing {
+        tracing::info!("found database without schema version");
+        version = 1;
+        conn.pragma_update(None, "user_version", version)?;
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let transaction = conn.unchecked_transaction()?;
+        migration(&transaction)?;
+        transaction.pragma_update(None, "user_version", index + 1)?;
+        transaction.commit()?;
+    }
+
+    Ok(())
+}
+
+/// Gets the schema version of the database.
+///
+/// This is the number of migrations that have been applied to the database.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the version cannot be read.
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    let version = conn.pragma_query_value(None, "user_version", |r| r.get::<_, u32>(0))?;
+    Ok(version)
+}
+
 /// Initializes the database schema.
 ///
 /// # Args
@@ -159,6 +309,99 @@ fn migration_0(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Adds metadata provider support.
+///
+/// This creates the metadata cache table and adds the metadata provider identifier to the copy
+/// operation and title tables.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`Error::Database`] if any of the database operations fail.
+fn migration_1(conn: &Connection) -> Result<()> {
+    metadata_cache::create_table(conn)?;
+    copy_operation::add_metadata_id_column(conn)?;
+    title::add_metadata_id_column(conn)?;
+
+    tracing::info!("completed migration 1");
+
+    Ok(())
+}
+
+/// Adds support for discarding titles when they are catalogued.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`Error::Database`] if any of the database operations fail.
+fn migration_2(conn: &Connection) -> Result<()> {
+    title::add_discarded_column(conn)?;
+
+    tracing::info!("completed migration 2");
+
+    Ok(())
+}
+
+/// Adds support for previewing the copied videos.
+///
+/// The locations of the contact sheet and preview clip generated for each video are stored with
+/// the video.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the migration fails.
+fn migration_3(conn: &Connection) -> Result<()> {
+    video::add_preview_columns(conn)?;
+
+    tracing::info!("completed migration 3");
+
+    Ok(())
+}
+
+/// Adds support for recording the results of integrity scans.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the migration fails.
+fn migration_4(conn: &Connection) -> Result<()> {
+    integrity_scan::create_table(conn)?;
+
+    tracing::info!("completed migration 4");
+
+    Ok(())
+}
+
+/// Adds support for storing the application's log events with each copy operation.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the migration fails.
+fn migration_5(conn: &Connection) -> Result<()> {
+    copy_operation::add_app_log_column(conn)?;
+
+    tracing::info!("completed migration 5");
+
+    Ok(())
+}
+
 /// Log an error due to failure to send a response.
 ///
 /// # Args
@@ -168,8 +411,65 @@ fn send_error_trace(request: &str) {
     tracing::error!("failed to send {} response", request);
 }
 
+/// Opens an in-memory database with all of the migrations applied.
+///
+/// Used by tests outside of the database module that need the complete schema.
+#[cfg(test)]
+pub(crate) fn open_in_memory() -> Connection {
+    let conn = Connection::open_in_memory().expect("Failed to create in-memory database");
+    migrate(&conn, false).expect("Failed to migrate in-memory database");
+    conn
+}
+
 #[cfg(test)]
 mod tests {
-    // TODO[TESTS]
+    use std::fs;
+
+    use super::*;
+
+    use crate::test_utils::TempDir;
+
+    #[test]
+    fn test_migrate_new_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn, false).unwrap();
+        assert_eq!(schema_version(&conn).unwrap() as usize, MIGRATIONS.len());
+    }
+
+    #[test]
+    fn test_migrate_unversioned_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        migration_0(&conn).unwrap();
+        migrate(&conn, true).unwrap();
+        assert_eq!(schema_version(&conn).unwrap() as usize, MIGRATIONS.len());
+    }
+
+    #[test]
+    fn test_pool_configures_connections() {
+        let temp = TempDir::new("artie.test.db.pool");
+        fs::create_dir_all(temp.path()).unwrap();
+
+        let pool = open_pool(&temp.path().join(DATABASE_NAME)).unwrap();
+        let conn = pool.get().unwrap();
+
+        let journal_mode: String = conn.pragma_query_value(None, "journal_mode", |r| r.get(0))
+            .unwrap();
+        let foreign_keys: bool = conn.pragma_query_value(None, "foreign_keys", |r| r.get(0))
+            .unwrap();
+        let busy_timeout: u64 = conn.pragma_query_value(None, "busy_timeout", |r| r.get(0))
+            .unwrap();
+
+        assert_eq!(journal_mode, "wal");
+        assert!(foreign_keys);
+        assert_eq!(busy_timeout, BUSY_TIMEOUT.as_millis() as u64);
+    }
+
+    #[test]
+    fn test_migrate_up_to_date() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn, false).unwrap();
+        migrate(&conn, true).unwrap();
+        assert_eq!(schema_version(&conn).unwrap() as usize, MIGRATIONS.len());
+    }
 }
 
@@ -80,6 +80,56 @@ pub fn get_by_serial_number(
     Ok(drive)
 }
 
+/// Gets all of the optical drive records ordered by id.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`Error::Database`] raised if the database operation fails.
+pub fn get_all(conn: &Connection) -> Result<Vec<OpticalDrive>> {
+    let sql = "
+        SELECT id, serial_number
+          FROM optical_drive
+      ORDER BY id
+    ";
+
+    let mut stmt = conn.prepare(sql)?;
+
+    let drives = stmt
+        .query_map((), |r| Ok(OpticalDrive { id: r.get(0)?, serial_number: r.get(1)? }))?
+        .collect::<rusqlite::Result<Vec<OpticalDrive>>>()?;
+
+    Ok(drives)
+}
+
+/// Creates an optical drive record keeping the drive's existing id.
+///
+/// Used when importing a catalog (see [`crate::library::catalog`]).
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `drive`:  The drive data to create the record from.
+///
+/// # Errors
+///
+/// [`Error::Database`] raised if the database operation fails.
+pub fn import(conn: &Connection, drive: &OpticalDrive) -> Result<()> {
+    let sql = "
+        INSERT INTO optical_drive (id, serial_number)
+             VALUES (?1, ?2)
+    ";
+
+    let _ = conn.execute(sql, (drive.id, &drive.serial_number))?;
+
+    tracing::trace!(?drive, "import optical_drive entry");
+    Ok(())
+}
+
 /// Gets an optical drive record from the database using its serial number if it exists or creates
 /// a new instance if it does not exist.
 ///
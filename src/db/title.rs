@@ -3,10 +3,10 @@
 
 //! Database operations for [`Title`] data.
 
-use rusqlite::Connection;
+use rusqlite::{Connection, Row};
 
 use crate::Result;
-use crate::models::Title;
+use crate::models::{MediaType, Title};
 
 use super::conv;
 
@@ -36,6 +36,8 @@ pub fn create(conn: &Connection, title: &mut Title) -> Result<()> {
                           , disc
                           , location
                           , memo
+                          , metadata_id
+                          , discarded
                           )
              VALUES ( ?1 -- title_index
                     , ?2 -- media_type
@@ -50,6 +52,8 @@ pub fn create(conn: &Connection, title: &mut Title) -> Result<()> {
                     , ?11 -- disc
                     , ?12 -- location
                     , ?13 -- memo
+                    , ?14 -- metadata_id
+                    , ?15 -- discarded
                     )
           RETURNING id
     ";
@@ -72,6 +76,8 @@ pub fn create(conn: &Connection, title: &mut Title) -> Result<()> {
         title.disc,
         title.location,
         title.memo,
+        conv::provider_id_to_sql(&title.metadata_id),
+        title.discarded,
     ];
 
     let id = stmt.query_row(params, |r| r.get::<_, u32>(0))?;
@@ -82,6 +88,278 @@ pub fn create(conn: &Connection, title: &mut Title) -> Result<()> {
     Ok(())
 }
 
+/// Gets the titles created by a copy operation ordered by their index on the disc.
+///
+/// The titles' videos are not included (`videos` will be `None`).
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `copy_operation_id`:  The id of the copy operation.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn get_by_copy_operation(conn: &Connection, copy_operation_id: u32) -> Result<Vec<Title>> {
+    let sql = format!("
+        SELECT {SELECT_COLUMNS}
+          FROM title
+         WHERE id IN (SELECT title_id
+                        FROM video
+                       WHERE copy_operation_id=?1)
+      ORDER BY title_index
+    ");
+
+    let mut stmt = conn.prepare(&sql)?;
+    let titles = stmt.query_map((copy_operation_id,), from_row)?
+        .collect::<rusqlite::Result<Vec<Title>>>()?;
+
+    Ok(titles)
+}
+
+/// Gets the titles for a season of a show ordered by disc and index.
+///
+/// The titles' videos are not included (`videos` will be `None`).
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `title`:  The name of the show.
+///
+/// `year`:  The release year of the show.
+///
+/// `season`:  The season number.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn get_by_season(conn: &Connection, title: &str, year: u16, season: u16) -> Result<Vec<Title>> {
+    let sql = format!("
+        SELECT {SELECT_COLUMNS}
+          FROM title
+         WHERE media_type=?1
+           AND title=?2
+           AND year=?3
+           AND season=?4
+      ORDER BY disc
+             , title_index
+    ");
+
+    let params = (conv::media_type_to_sql(&MediaType::Show), title, year, season);
+
+    let mut stmt = conn.prepare(&sql)?;
+    let titles = stmt.query_map(params, from_row)?
+        .collect::<rusqlite::Result<Vec<Title>>>()?;
+
+    Ok(titles)
+}
+
+/// Gets all of the title records ordered by id.
+///
+/// The titles' videos are not included (`videos` will be `None`).
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn get_all(conn: &Connection) -> Result<Vec<Title>> {
+    let sql = format!("
+        SELECT {SELECT_COLUMNS}
+          FROM title
+      ORDER BY id
+    ");
+
+    let mut stmt = conn.prepare(&sql)?;
+    let titles = stmt.query_map((), from_row)?
+        .collect::<rusqlite::Result<Vec<Title>>>()?;
+
+    Ok(titles)
+}
+
+/// Creates a title record keeping the title's existing id.
+///
+/// Used when importing a catalog (see [`crate::library::catalog`]).
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `title`:  The title data to create the record from.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn import(conn: &Connection, title: &Title) -> Result<()> {
+    let sql = "
+        INSERT INTO title ( id
+                          , title_index
+                          , media_type
+                          , title
+                          , year
+                          , season
+                          , episode_number
+                          , episode_count
+                          , special_feature_kind
+                          , special_feature_name
+                          , version
+                          , disc
+                          , location
+                          , memo
+                          , metadata_id
+                          , discarded
+                          )
+             VALUES ( ?1 -- id
+                    , ?2 -- title_index
+                    , ?3 -- media_type
+                    , ?4 -- title
+                    , ?5 -- year
+                    , ?6 -- season
+                    , ?7 -- episode_number
+                    , ?8 -- episode_count
+                    , ?9 -- special_feature_kind
+                    , ?10 -- special_feature_name
+                    , ?11 -- version
+                    , ?12 -- disc
+                    , ?13 -- location
+                    , ?14 -- memo
+                    , ?15 -- metadata_id
+                    , ?16 -- discarded
+                    )
+    ";
+
+    let (sf_kind, sf_name) = conv::special_feature_to_sql(&title.special_feature);
+
+    let params = rusqlite::params![
+        title.id,
+        title.index,
+        conv::media_type_to_sql(&title.media_type),
+        title.title,
+        title.year,
+        title.season,
+        title.episode_number,
+        title.episode_count,
+        sf_kind,
+        sf_name,
+        title.version,
+        title.disc,
+        title.location,
+        title.memo,
+        conv::provider_id_to_sql(&title.metadata_id),
+        title.discarded,
+    ];
+
+    let _ = conn.execute(sql, params)?;
+
+    tracing::trace!(?title, "import title entry");
+    Ok(())
+}
+
+/// Updates a title record with the title's editable fields.
+///
+/// The index of the title is not updated since it is fixed by the copy operation.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `title`:  The title data to update the record with.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn update(conn: &Connection, title: &Title) -> Result<()> {
+    let sql = "
+        UPDATE title
+           SET media_type=?1,
+               title=?2,
+               year=?3,
+               season=?4,
+               episode_number=?5,
+               episode_count=?6,
+               special_feature_kind=?7,
+               special_feature_name=?8,
+               version=?9,
+               disc=?10,
+               location=?11,
+               memo=?12,
+               metadata_id=?13,
+               discarded=?14
+         WHERE id=?15
+    ";
+
+    let (sf_kind, sf_name) = conv::special_feature_to_sql(&title.special_feature);
+
+    let params = rusqlite::params![
+        conv::media_type_to_sql(&title.media_type),
+        title.title,
+        title.year,
+        title.season,
+        title.episode_number,
+        title.episode_count,
+        sf_kind,
+        sf_name,
+        title.version,
+        title.disc,
+        title.location,
+        title.memo,
+        conv::provider_id_to_sql(&title.metadata_id),
+        title.discarded,
+        title.id,
+    ];
+
+    let _ = conn.execute(sql, params)?;
+
+    tracing::trace!(?title, "update title entry");
+    Ok(())
+}
+
+/// Adds the discarded flag column to the title table.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub(super) fn add_discarded_column(conn: &Connection) -> Result<()> {
+    let sql = "
+        ALTER TABLE title
+         ADD COLUMN discarded INTEGER NOT NULL DEFAULT 0
+    ";
+
+    let _ = conn.execute(sql, ())?;
+
+    tracing::info!("add title discarded column");
+    Ok(())
+}
+
+/// Adds the metadata provider identifier column to the title table.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub(super) fn add_metadata_id_column(conn: &Connection) -> Result<()> {
+    let sql = "
+        ALTER TABLE title
+         ADD COLUMN metadata_id TEXT NOT NULL DEFAULT ''
+    ";
+
+    let _ = conn.execute(sql, ())?;
+
+    tracing::info!("add title metadata_id column");
+    Ok(())
+}
+
 /// Creates the database table for storing title data if it does not exist.
 ///
 /// # Args
@@ -117,15 +395,60 @@ pub fn create_table(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// The columns selected when reading title records. See [`from_row`].
+const SELECT_COLUMNS: &str = "
+    id
+  , title_index
+  , media_type
+  , title
+  , year
+  , season
+  , episode_number
+  , episode_count
+  , special_feature_kind
+  , special_feature_name
+  , version
+  , disc
+  , location
+  , memo
+  , metadata_id
+  , discarded
+";
+
+/// Creates a title from a row selected using [`SELECT_COLUMNS`].
+fn from_row(row: &Row) -> rusqlite::Result<Title> {
+    Ok(Title {
+        id: row.get(0)?,
+        index: row.get(1)?,
+        media_type: conv::media_type_from_sql(row.get(2)?),
+        title: row.get(3)?,
+        year: row.get(4)?,
+        season: row.get(5)?,
+        episode_number: row.get(6)?,
+        episode_count: row.get(7)?,
+        special_feature: conv::special_feature_from_sql(row.get(8)?, &row.get::<_, String>(9)?),
+        version: row.get(10)?,
+        disc: row.get(11)?,
+        location: row.get(12)?,
+        memo: row.get(13)?,
+        metadata_id: conv::provider_id_from_sql(&row.get::<_, String>(14)?),
+        discarded: row.get(15)?,
+        videos: None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rusqlite::Connection;
-    use crate::models::MediaType;
+    use crate::metadata::{ProviderId, ProviderKind};
+    use crate::models::{MediaType, SpecialFeature, SpecialFeatureType};
 
     fn setup_test_db() -> Connection {
         let conn = Connection::open_in_memory().expect("Failed to create in-memory database");
         create_table(&conn).expect("Failed to create table");
+        add_metadata_id_column(&conn).expect("Failed to add metadata_id column");
+        add_discarded_column(&conn).expect("Failed to add discarded column");
         conn
     }
 
@@ -144,6 +467,8 @@ mod tests {
             disc: 1,
             location: "shelf-a".to_owned(),
             memo: String::new(),
+            metadata_id: None,
+            discarded: false,
             videos: None,
         }
     }
@@ -196,6 +521,8 @@ mod tests {
             disc: 1,
             location: "shelf-b".to_owned(),
             memo: "double episode".to_owned(),
+            metadata_id: None,
+            discarded: false,
             videos: None,
         };
 
@@ -204,6 +531,22 @@ mod tests {
         assert!(title.id > 0);
     }
 
+    #[test]
+    fn test_create_title_with_metadata_id() {
+        let conn = setup_test_db();
+        let mut title = Title {
+            metadata_id: Some(ProviderId { provider: ProviderKind::Tmdb, id: 603 }),
+            ..make_title("Test Movie")
+        };
+
+        create(&conn, &mut title).expect("Failed to create title");
+
+        let metadata_id: String = conn
+            .query_row("SELECT metadata_id FROM title WHERE id=?1", (title.id,), |r| r.get(0))
+            .unwrap();
+        assert_eq!(metadata_id, "tmdb:603");
+    }
+
     #[test]
     fn test_create_title_with_version() {
         let conn = setup_test_db();
@@ -221,6 +564,8 @@ mod tests {
             disc: 1,
             location: "shelf-a".to_owned(),
             memo: String::new(),
+            metadata_id: None,
+            discarded: false,
             videos: None,
         };
 
@@ -228,4 +573,59 @@ mod tests {
 
         assert!(title.id > 0);
     }
+
+    #[test]
+    fn test_get_by_season() {
+        let conn = setup_test_db();
+
+        let show = |disc: u16, index: u8, season: u16| Title {
+            media_type: MediaType::Show,
+            season,
+            disc,
+            index,
+            ..make_title("Test Show")
+        };
+
+        let mut titles = [show(2, 0, 1), show(1, 1, 1), show(1, 0, 1), show(1, 0, 2)];
+        for title in titles.iter_mut() {
+            create(&conn, title).unwrap();
+        }
+        create(&conn, &mut make_title("Test Show")).unwrap();
+
+        let season = get_by_season(&conn, "Test Show", 2024, 1).unwrap();
+        let order: Vec<_> = season.iter().map(|t| (t.disc, t.index)).collect();
+        assert_eq!(order, vec![(1, 0), (1, 1), (2, 0)]);
+        assert!(season.iter().all(|t| t.media_type == MediaType::Show && t.season == 1));
+    }
+
+    #[test]
+    fn test_update() {
+        let conn = setup_test_db();
+        let mut title = Title {
+            media_type: MediaType::Show,
+            season: 1,
+            ..make_title("Test Show")
+        };
+        create(&conn, &mut title).unwrap();
+
+        title.episode_number = 5;
+        title.episode_count = 2;
+        title.special_feature = Some(SpecialFeature {
+            kind: SpecialFeatureType::Featurettes,
+            name: "Making Of".to_owned(),
+        });
+        title.version = "Extended".to_owned();
+        title.memo = "updated".to_owned();
+        title.discarded = true;
+        update(&conn, &title).expect("Failed to update title");
+
+        let loaded = get_by_season(&conn, "Test Show", 2024, 1).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].episode_number, 5);
+        assert_eq!(loaded[0].episode_count, 2);
+        assert_eq!(loaded[0].special_feature, title.special_feature);
+        assert_eq!(loaded[0].version, "Extended");
+        assert_eq!(loaded[0].memo, "updated");
+        assert!(loaded[0].discarded);
+    }
 }
@@ -3,9 +3,143 @@
 
 //! Database operations for [`TranscodeOperation`] data.
 
-use rusqlite::Connection;
+use chrono::DateTime;
+
+use rusqlite::{Connection, Row};
 
 use crate::Result;
+use crate::compress;
+use crate::models::{Reference, TranscodeOperation};
+
+use super::{conv, video};
+
+/// Gets all of the transcode operation records ordered by id.
+///
+/// The source video is not stored with the operation, so its reference will have an id of zero.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `include_logs`:  Indicates if the command log should be read. If `false`, it will be empty.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+///
+/// [`crate::Error::StdIo`] raised if the command log cannot be decompressed.
+pub fn get_all(conn: &Connection, include_logs: bool) -> Result<Vec<TranscodeOperation>> {
+    let sql = "
+        SELECT id
+             , started
+             , completed
+             , state
+             , episode_number
+             , episode_count
+             , special_feature_kind
+             , special_feature_name
+             , version
+             , json(audio_tracks)
+             , json(subtitle_tracks)
+             , host_id
+             , title_id
+             , command_log
+          FROM transcode_operation
+      ORDER BY id
+    ";
+
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query(())?;
+
+    let mut transcode_operations = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut transcode_operation = from_row(row)?;
+        if include_logs {
+            transcode_operation.command_log = compress::decompress(&row.get::<_, Vec<u8>>(13)?)?;
+        }
+        transcode_operations.push(transcode_operation);
+    }
+
+    Ok(transcode_operations)
+}
+
+/// Creates a transcode operation record keeping the transcode operation's existing id.
+///
+/// Used when importing a catalog (see [`crate::library::catalog`]). The table does not have an
+/// error column, so the reason of a failed operation is not kept.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `transcode_operation`:  The transcode operation data to create the record from.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+///
+/// [`crate::Error::SerdeJson`] raised if the tracks cannot be serialized.
+///
+/// [`crate::Error::StdIo`] raised if the command log cannot be compressed.
+pub fn import(conn: &Connection, transcode_operation: &TranscodeOperation) -> Result<()> {
+    let sql = "
+        INSERT INTO transcode_operation ( id
+                                        , started
+                                        , completed
+                                        , state
+                                        , episode_number
+                                        , episode_count
+                                        , special_feature_kind
+                                        , special_feature_name
+                                        , version
+                                        , audio_tracks
+                                        , subtitle_tracks
+                                        , command_log
+                                        , host_id
+                                        , title_id
+                                        )
+             VALUES ( ?1         -- id
+                    , ?2         -- started
+                    , ?3         -- completed
+                    , ?4         -- state
+                    , ?5         -- episode_number
+                    , ?6         -- episode_count
+                    , ?7         -- special_feature_kind
+                    , ?8         -- special_feature_name
+                    , ?9         -- version
+                    , jsonb(?10) -- audio_tracks
+                    , jsonb(?11) -- subtitle_tracks
+                    , ?12        -- command_log
+                    , ?13        -- host_id
+                    , ?14        -- title_id
+                    )
+    ";
+
+    let (state, _) = conv::operation_state_to_sql(&transcode_operation.state);
+    let (sf_kind, sf_name) = conv::special_feature_to_sql(&transcode_operation.special_feature);
+
+    let params = rusqlite::params![
+        transcode_operation.id,
+        transcode_operation.started.timestamp(),
+        transcode_operation.completed.timestamp(),
+        state,
+        transcode_operation.episode_number,
+        transcode_operation.episode_count,
+        sf_kind,
+        sf_name,
+        transcode_operation.version,
+        serde_json::to_string(&transcode_operation.audio_tracks)?,
+        serde_json::to_string(&transcode_operation.subtitle_tracks)?,
+        compress::compress(&transcode_operation.command_log)?,
+        transcode_operation.host.id,
+        transcode_operation.title.id,
+    ];
+
+    let _ = conn.execute(sql, params)?;
+
+    tracing::trace!(id=transcode_operation.id, "import transcode_operation entry");
+    Ok(())
+}
 
 /// Creates the database table for storing transcode operation data if it does not exist.
 ///
@@ -44,15 +178,94 @@ pub(crate) fn create_table(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Creates a transcode operation from a row selected by [`get_all`].
+fn from_row(row: &Row) -> rusqlite::Result<TranscodeOperation> {
+    Ok(TranscodeOperation {
+        id: row.get(0)?,
+        started: DateTime::from_timestamp(row.get(1)?, 0).unwrap_or_default(),
+        completed: DateTime::from_timestamp(row.get(2)?, 0).unwrap_or_default(),
+        state: conv::operation_state_from_sql(row.get(3)?, ""),
+        episode_number: row.get(4)?,
+        episode_count: row.get(5)?,
+        special_feature: conv::special_feature_from_sql(row.get(6)?, &row.get::<_, String>(7)?),
+        version: row.get(8)?,
+        audio_tracks: video::json_column(row, 9)?,
+        subtitle_tracks: video::json_column(row, 10)?,
+        command_log: String::new(),
+        host: Reference { id: row.get(11)?, value: None },
+        title: Reference { id: row.get(12)?, value: None },
+        source_video: Reference { id: 0, value: None },
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rusqlite::Connection;
 
+    use crate::db;
+    use crate::models::{MediaType, OperationState, SpecialFeature, SpecialFeatureType, Title};
+
     #[test]
     fn test_create_table() {
         let conn = Connection::open_in_memory().unwrap();
         let result = create_table(&conn);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_import_get_all() {
+        let conn = db::open_in_memory();
+        let host = db::host::create(&conn, "transcoder").unwrap();
+        let mut title = Title {
+            id: 0,
+            index: 1,
+            media_type: MediaType::Movie,
+            title: String::from("Test Movie"),
+            year: 2024,
+            season: 0,
+            episode_number: 0,
+            episode_count: 0,
+            special_feature: None,
+            version: String::new(),
+            disc: 1,
+            location: String::from("shelf-a"),
+            memo: String::new(),
+            metadata_id: None,
+            discarded: false,
+            videos: None,
+        };
+        db::title::create(&conn, &mut title).unwrap();
+
+        let transcode_operation = TranscodeOperation {
+            id: 7,
+            started: DateTime::from_timestamp(100, 0).unwrap(),
+            completed: DateTime::from_timestamp(200, 0).unwrap(),
+            state: OperationState::Completed,
+            episode_number: 0,
+            episode_count: 0,
+            special_feature: Some(SpecialFeature {
+                kind: SpecialFeatureType::Featurettes,
+                name: String::from("Making Of"),
+            }),
+            version: String::from("1080p"),
+            audio_tracks: Vec::new(),
+            subtitle_tracks: Vec::new(),
+            command_log: String::from("command log"),
+            host: Reference { id: host.id, value: None },
+            title: Reference { id: title.id, value: None },
+            source_video: Reference { id: 0, value: None },
+        };
+        import(&conn, &transcode_operation).unwrap();
+
+        let ops = get_all(&conn, false).unwrap();
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].id, 7);
+        assert_eq!(ops[0].version, "1080p");
+        assert_eq!(ops[0].title.id, title.id);
+        assert!(ops[0].command_log.is_empty());
+
+        let ops = get_all(&conn, true).unwrap();
+        assert_eq!(ops[0].command_log, "command log");
+    }
 }
@@ -3,10 +3,15 @@
 
 //! Database operations for [`Video`] data.
 
-use rusqlite::Connection;
+use std::time::Duration;
+
+use blake3::Hash;
+
+use rusqlite::{Connection, Row};
+use rusqlite::types::Type;
 
 use crate::Result;
-use crate::models::{Video, VideoSource};
+use crate::models::{MediaLocation, Reference, Video, VideoSource};
 
 use super::conv;
 
@@ -83,6 +88,255 @@ pub fn create(conn: &Connection, video: &mut Video) -> Result<()> {
     Ok(())
 }
 
+/// Gets the videos created by a copy operation.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `copy_operation_id`:  The id of the copy operation.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn get_by_copy_operation(conn: &Connection, copy_operation_id: u32) -> Result<Vec<Video>> {
+    let sql = format!("
+        SELECT {SELECT_COLUMNS}
+          FROM video
+         WHERE copy_operation_id=?1
+      ORDER BY id
+    ");
+
+    let mut stmt = conn.prepare(&sql)?;
+    let videos = stmt.query_map((copy_operation_id,), from_row)?
+        .collect::<rusqlite::Result<Vec<Video>>>()?;
+
+    Ok(videos)
+}
+
+/// Gets all of the videos whose files have not been deleted.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn get_existing(conn: &Connection) -> Result<Vec<Video>> {
+    let sql = format!("
+        SELECT {SELECT_COLUMNS}
+          FROM video
+         WHERE location_area!=?1
+      ORDER BY id
+    ");
+
+    let (deleted_area, _) = conv::media_location_to_sql(&MediaLocation::Deleted);
+
+    let mut stmt = conn.prepare(&sql)?;
+    let videos = stmt.query_map((deleted_area,), from_row)?
+        .collect::<rusqlite::Result<Vec<Video>>>()?;
+
+    Ok(videos)
+}
+
+/// Gets the videos associated with a title.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `title_id`:  The id of the title.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn get_by_title(conn: &Connection, title_id: u32) -> Result<Vec<Video>> {
+    let sql = format!("
+        SELECT {SELECT_COLUMNS}
+          FROM video
+         WHERE title_id=?1
+      ORDER BY id
+    ");
+
+    let mut stmt = conn.prepare(&sql)?;
+    let videos = stmt.query_map((title_id,), from_row)?
+        .collect::<rusqlite::Result<Vec<Video>>>()?;
+
+    Ok(videos)
+}
+
+/// Gets all of the video records ordered by id.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn get_all(conn: &Connection) -> Result<Vec<Video>> {
+    let sql = format!("
+        SELECT {SELECT_COLUMNS}
+          FROM video
+      ORDER BY id
+    ");
+
+    let mut stmt = conn.prepare(&sql)?;
+    let videos = stmt.query_map((), from_row)?
+        .collect::<rusqlite::Result<Vec<Video>>>()?;
+
+    Ok(videos)
+}
+
+/// Creates a video record keeping the video's existing id.
+///
+/// Used when importing a catalog (see [`crate::library::catalog`]).
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `video`:  The video data to create the record from.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn import(conn: &Connection, video: &Video) -> Result<()> {
+    let sql = "
+        INSERT INTO video ( id
+                          , location_area
+                          , location_path
+                          , checksum
+                          , container
+                          , video_tracks
+                          , audio_tracks
+                          , subtitle_tracks
+                          , copy_operation_id
+                          , transcode_operation_id
+                          , title_id
+                          , duration
+                          )
+             VALUES ( ?1        -- id
+                    , ?2        -- location_area
+                    , ?3        -- location_path
+                    , ?4        -- checksum
+                    , ?5        -- container
+                    , jsonb(?6) -- video_tracks
+                    , jsonb(?7) -- audio_tracks
+                    , jsonb(?8) -- subtitle_tracks
+                    , ?9        -- copy_operation_id
+                    , ?10       -- transcode_operation_id
+                    , ?11       -- title_id
+                    , ?12       -- duration
+                    )
+    ";
+
+    let (loc_area, loc_path) = conv::media_location_to_sql(&video.location);
+
+    let (copy_operation, transcode_operation) = match &video.source {
+        VideoSource::CopyOperation(reference) => (Some(reference.id), None),
+        VideoSource::TranscodeOperation(reference) => (None, Some(reference.id)),
+    };
+
+    let checksum = video.checksum.to_hex();
+
+    let params = rusqlite::params![
+        video.id,
+        loc_area,
+        loc_path,
+        checksum.as_str(),
+        conv::container_type_to_sql(&video.container),
+        serde_json::to_string(&video.video_tracks)?,
+        serde_json::to_string(&video.audio_tracks)?,
+        serde_json::to_string(&video.subtitle_tracks)?,
+        copy_operation,
+        transcode_operation,
+        video.title.id,
+        video.duration.as_secs(),
+    ];
+
+    let _ = conn.execute(sql, params)?;
+
+    set_previews(conn, video)?;
+
+    tracing::trace!(?video, "import video entry");
+    Ok(())
+}
+
+/// Updates the location of a video's file.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `video`:  The video being updated. If successful, its location will be set.
+///
+/// `location`:  The new location of the video's file.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn set_location(conn: &Connection, video: &mut Video, location: MediaLocation) -> Result<()> {
+    let sql = "
+        UPDATE video
+           SET location_area=?1,
+               location_path=?2
+         WHERE id=?3
+    ";
+
+    let (area, path) = conv::media_location_to_sql(&location);
+
+    let _ = conn.execute(sql, (area, path, video.id))?;
+
+    video.location = location;
+
+    tracing::trace!(id=video.id, ?video.location, "set video location");
+    Ok(())
+}
+
+/// Updates the locations of the video's previews.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `video`:  The video with the contact sheet and preview clip locations to save.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub fn set_previews(conn: &Connection, video: &Video) -> Result<()> {
+    let sql = "
+        UPDATE video
+           SET contact_sheet_area=?1,
+               contact_sheet_path=?2,
+               preview_clip_area=?3,
+               preview_clip_path=?4
+         WHERE id=?5
+    ";
+
+    let (sheet_area, sheet_path) = video.contact_sheet.as_ref()
+        .map(conv::media_location_to_sql)
+        .unzip();
+    let (clip_area, clip_path) = video.preview_clip.as_ref()
+        .map(conv::media_location_to_sql)
+        .unzip();
+
+    let params = rusqlite::params![
+        sheet_area,
+        sheet_path,
+        clip_area,
+        clip_path,
+        video.id,
+    ];
+
+    let _ = conn.execute(sql, params)?;
+
+    tracing::trace!(id=video.id, "set video previews");
+    Ok(())
+}
+
 /// Creates the database table for storing video data if it does not exist.
 ///
 /// # Args
@@ -119,6 +373,92 @@ pub fn create_table(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Adds the contact sheet and preview clip location columns to the video table.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if the database operation fails.
+pub(super) fn add_preview_columns(conn: &Connection) -> Result<()> {
+    let sql = "
+        ALTER TABLE video ADD COLUMN contact_sheet_area INTEGER;
+        ALTER TABLE video ADD COLUMN contact_sheet_path TEXT;
+        ALTER TABLE video ADD COLUMN preview_clip_area  INTEGER;
+        ALTER TABLE video ADD COLUMN preview_clip_path  TEXT;
+    ";
+
+    conn.execute_batch(sql)?;
+
+    tracing::info!("add video preview columns");
+    Ok(())
+}
+
+/// The columns selected when reading video records. See [`from_row`].
+const SELECT_COLUMNS: &str = "
+    id
+  , location_area
+  , location_path
+  , checksum
+  , container
+  , json(video_tracks)
+  , json(audio_tracks)
+  , json(subtitle_tracks)
+  , copy_operation_id
+  , transcode_operation_id
+  , title_id
+  , duration
+  , contact_sheet_area
+  , contact_sheet_path
+  , preview_clip_area
+  , preview_clip_path
+";
+
+/// Creates a video from a row selected using [`SELECT_COLUMNS`].
+fn from_row(row: &Row) -> rusqlite::Result<Video> {
+    let checksum = Hash::from_hex(row.get::<_, String>(3)?)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(3, Type::Text, Box::new(e)))?;
+
+    let source = match row.get::<_, Option<u32>>(8)? {
+        Some(id) => VideoSource::CopyOperation(Reference { id, value: None }),
+        None => VideoSource::TranscodeOperation(Reference { id: row.get(9)?, value: None }),
+    };
+
+    Ok(Video {
+        id: row.get(0)?,
+        location: conv::media_location_from_sql(row.get(1)?, &row.get::<_, String>(2)?),
+        checksum,
+        container: conv::container_type_from_sql(row.get(4)?),
+        video_tracks: json_column(row, 5)?,
+        audio_tracks: json_column(row, 6)?,
+        subtitle_tracks: json_column(row, 7)?,
+        source,
+        title: Reference { id: row.get(10)?, value: None },
+        duration: Duration::from_secs(row.get(11)?),
+        contact_sheet: optional_location(row, 12)?,
+        preview_clip: optional_location(row, 14)?,
+    })
+}
+
+/// Reads a media location stored in an optional area and path column pair.
+fn optional_location(row: &Row, index: usize) -> rusqlite::Result<Option<MediaLocation>> {
+    let area = row.get::<_, Option<u8>>(index)?;
+    let path = row.get::<_, Option<String>>(index + 1)?;
+    Ok(area.zip(path).map(|(area, path)| conv::media_location_from_sql(area, &path)))
+}
+
+/// Deserializes a JSON column.
+pub(super) fn json_column<T>(row: &Row, index: usize) -> rusqlite::Result<T>
+where
+    T: for<'de> serde::Deserialize<'de>,
+{
+    let json = row.get::<_, String>(index)?;
+    serde_json::from_str(&json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -133,6 +473,8 @@ mod tests {
     use crate::db::title;
     use crate::db::transcode_operation;
     use crate::models::{
+        AudioCodec,
+        AudioTrack,
         ContainerType,
         CopyOperation,
         MediaLocation,
@@ -151,11 +493,20 @@ mod tests {
             .expect("Failed to create optical_drive table");
         title::create_table(&conn)
             .expect("Failed to create title table");
+        title::add_metadata_id_column(&conn)
+            .expect("Failed to add title metadata_id column");
+        title::add_discarded_column(&conn)
+            .expect("Failed to add title discarded column");
         copy_operation::create_table(&conn)
             .expect("Failed to create copy_operation table");
+        copy_operation::add_metadata_id_column(&conn)
+            .expect("Failed to add copy_operation metadata_id column");
+        copy_operation::add_app_log_column(&conn)
+            .expect("Failed to add copy_operation app_log column");
         transcode_operation::create_table(&conn)
             .expect("Failed to create transcode_operation table");
         create_table(&conn).expect("Failed to create video table");
+        add_preview_columns(&conn).expect("Failed to add video preview columns");
 
         let host = host::create(&conn, "testhost").expect("Failed to create host");
         let drive = optical_drive::create(&conn, "SN-TEST-001").expect("Failed to create drive");
@@ -174,6 +525,8 @@ mod tests {
             disc: 1,
             location: "shelf-a".to_owned(),
             memo: String::new(),
+            metadata_id: None,
+            discarded: false,
             videos: None,
         };
         title::create(&conn, &mut title).expect("Failed to create title");
@@ -200,6 +553,8 @@ mod tests {
             source: VideoSource::CopyOperation(Reference { id: copy_op_id, value: None }),
             title: Reference { id: title_id, value: None },
             duration: Duration::from_secs(7200),
+            contact_sheet: None,
+            preview_clip: None,
         }
     }
 
@@ -244,4 +599,96 @@ mod tests {
 
         assert!(video.id > 0);
     }
+
+    #[test]
+    fn test_get_by_copy_operation() {
+        let (conn, copy_op_id, title_id) = setup_test_db();
+        let mut video = Video {
+            audio_tracks: vec![AudioTrack {
+                index: 1,
+                name: String::from("Surround 5.1"),
+                codec: AudioCodec::AC3,
+                encode_method: None,
+                language: String::from("English"),
+                channel_count: 6,
+                channel_layout: String::from("5.1(side)"),
+            }],
+            ..make_video(copy_op_id, title_id)
+        };
+        create(&conn, &mut video).unwrap();
+
+        let videos = get_by_copy_operation(&conn, copy_op_id).unwrap();
+        assert_eq!(videos.len(), 1);
+
+        let loaded = &videos[0];
+        assert_eq!(loaded.id, video.id);
+        assert_eq!(loaded.checksum, video.checksum);
+        assert_eq!(loaded.duration, Duration::from_secs(7200));
+        assert_eq!(loaded.title.id, title_id);
+        assert_eq!(loaded.audio_tracks.len(), 1);
+        assert_eq!(loaded.audio_tracks[0].language, "English");
+        assert!(matches!(loaded.location, MediaLocation::Inbox(_)));
+        assert!(matches!(&loaded.source, VideoSource::CopyOperation(r) if r.id == copy_op_id));
+
+        assert!(get_by_copy_operation(&conn, copy_op_id + 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_by_title() {
+        let (conn, copy_op_id, title_id) = setup_test_db();
+        create(&conn, &mut make_video(copy_op_id, title_id)).unwrap();
+        create(&conn, &mut make_video(copy_op_id, title_id)).unwrap();
+
+        assert_eq!(get_by_title(&conn, title_id).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_get_existing_and_set_location() {
+        let (conn, copy_op_id, title_id) = setup_test_db();
+        let mut video = make_video(copy_op_id, title_id);
+        create(&conn, &mut video).unwrap();
+        let mut deleted = make_video(copy_op_id, title_id);
+        create(&conn, &mut deleted).unwrap();
+        set_location(&conn, &mut deleted, MediaLocation::Deleted).unwrap();
+
+        let videos = get_existing(&conn).unwrap();
+        assert_eq!(videos.len(), 1);
+        assert_eq!(videos[0].id, video.id);
+
+        let location = MediaLocation::Library(std::path::PathBuf::from("movies/moved.mkv"));
+        set_location(&conn, &mut video, location.clone()).unwrap();
+        assert_eq!(get_existing(&conn).unwrap()[0].location, location);
+    }
+
+    #[test]
+    fn test_set_previews() {
+        let (conn, copy_op_id, title_id) = setup_test_db();
+        let mut video = make_video(copy_op_id, title_id);
+        create(&conn, &mut video).unwrap();
+
+        let loaded = &get_by_title(&conn, title_id).unwrap()[0];
+        assert!(loaded.contact_sheet.is_none());
+        assert!(loaded.preview_clip.is_none());
+
+        video.contact_sheet = Some(MediaLocation::Inbox("movies/previews/test.jpg".into()));
+        set_previews(&conn, &video).unwrap();
+
+        let loaded = &get_by_title(&conn, title_id).unwrap()[0];
+        assert!(matches!(
+            &loaded.contact_sheet,
+            Some(MediaLocation::Inbox(p)) if p.ends_with("previews/test.jpg")
+        ));
+        assert!(loaded.preview_clip.is_none());
+    }
+
+    #[test]
+    fn test_title_get_by_copy_operation() {
+        let (conn, copy_op_id, title_id) = setup_test_db();
+        create(&conn, &mut make_video(copy_op_id, title_id)).unwrap();
+
+        let titles = title::get_by_copy_operation(&conn, copy_op_id).unwrap();
+        assert_eq!(titles.len(), 1);
+        assert_eq!(titles[0].id, title_id);
+        assert_eq!(titles[0].title, "Test Movie");
+    }
 }
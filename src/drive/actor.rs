@@ -22,6 +22,8 @@ use std::time::{Duration, Instant};
 
 use tokio_util::sync::CancellationToken;
 
+use tracing::Instrument;
+
 use makemkv::{CopyCommandOutput, InfoCommandOutput};
 
 use crate::{Error, Result};
@@ -39,8 +41,9 @@ use crate::drive::{
 };
 use crate::drive::copy;
 use crate::drive::data;
+use crate::metrics::{self, Outcome};
 use crate::models::{CopyParamaters, MediaLocation};
-use crate::net;
+use crate::net::{self, sentinel};
 use crate::task;
 
 /// Amount of time with getting a status update when the drive should be considered disconnected.
@@ -83,6 +86,13 @@ pub enum DriveRequest {
         response: Response<OpticalDrive>,
     },
 
+    /// Progress information about the ingest of the copied titles.
+    IngestProgress {
+        completed: usize,
+        total: usize,
+        response: Response<()>,
+    },
+
     /// Notify the drive actor that the MakeMKV copy command completed successfully.
     MakeMkvCopyComplete {
         output: CopyCommandOutput,
@@ -140,7 +150,7 @@ pub enum DriveRequest {
 
     /// Update the copy parameters stored in the drive's persistent data.
     SaveFormData {
-        data: FormDataUpdate,
+        data: Box<FormDataUpdate>,
         response: Response<()>,
     },
 
@@ -242,9 +252,11 @@ impl MessageProcessor {
                 tracing::warn!(sn=serial_number, ?error, "failed to lookup drive name");
             })
             .unwrap_or(serial_number.to_owned());
+        let drive = OpticalDrive::disconnected(&name, serial_number);
+        metrics::drive_updated(&drive);
         Self {
             bus,
-            drive: OpticalDrive::disconnected(&name, serial_number),
+            drive,
             worker: None,
             copy_ct: None,
             copy_started: None,
@@ -266,7 +278,16 @@ impl MessageProcessor {
     ///
     /// [`Error::ResponseSend`] if the response cannot be sent.
     fn begin_copy_disc(&mut self, params: CopyParamaters, resp: Response<()>) -> Result<()> {
-        let reply = if self.drive.state == OpticalDriveState::Idle {
+        // Titles copied by a worker node that does not see the same media folders would be written
+        // where this node cannot find them.
+        let unverified = self.worker.as_ref()
+            .map(|worker| (worker.clone(), sentinel::status(worker)))
+            .filter(|(_, check)| !check.allows_copy());
+
+        let reply = if let Some((worker, check)) = unverified {
+            tracing::warn!(sn=self.drive.serial_number, worker, ?check, "storage not verified");
+            Err(Error::StorageNotVerified { worker, reason: check.to_string() })
+        } else if self.drive.state == OpticalDriveState::Idle {
             self.drive.state = OpticalDriveState::Copying {
                 stage: "",
                 task: String::from(""),
@@ -282,6 +303,14 @@ impl MessageProcessor {
             let bus = self.bus.clone();
             let drive = self.drive.os_drive();
             let ct = self.copy_ct.as_ref().unwrap().clone();
+
+            // The events logged while copying are captured by the span and saved with the copy
+            // operation. The id is recorded once the database record is created.
+            let span = tracing::info_span!(
+                "copy_operation",
+                sn = %self.drive.serial_number,
+                id = tracing::field::Empty,
+            );
             task::spawn(async move {
                 copy::copy_disc(
                     bus,
@@ -289,7 +318,7 @@ impl MessageProcessor {
                     params,
                     ct,
                 ).await
-            });
+            }.instrument(span));
 
             Ok(())
         } else {
@@ -355,7 +384,8 @@ impl MessageProcessor {
     /// Check the status of the drive for stale data.
     ///
     /// If the drive has not received a status update from the drive monitor task or a running copy
-    /// operation in a while, the status will be set to disconnected.
+    /// operation in a while, the status will be set to disconnected. If the drive is copying, the
+    /// running MakeMKV command is failed instead so that the copy operation fails.
     ///
     /// # Args
     ///
@@ -373,6 +403,14 @@ impl MessageProcessor {
                 }
                 Ok(())
             },
+            OpticalDriveState::Copying { .. } if self.last_update.elapsed() >= DRIVE_TIMEOUT => {
+                // Updates stop when the worker node managing the drive disconnects, so the running
+                // MakeMKV command will never report back. Failing the command allows the copy
+                // operation to fail instead of waiting forever.
+                tracing::info!(sn=self.drive.serial_number, "drive timeout reached while copying");
+                self.fail_makemkv_command("drive stopped responding");
+                Ok(())
+            },
             OpticalDriveState::Copying {
                 stage,
                 task,
@@ -381,19 +419,14 @@ impl MessageProcessor {
                 subtask_progress,
                 elapsed_time: _,
             } => {
-                if self.last_update.elapsed() >= DRIVE_TIMEOUT {
-                    tracing::info!(sn=self.drive.serial_number, "drive timeout reached");
-                    todo!()
-                } else {
-                    self.drive.state = OpticalDriveState::Copying {
-                        stage,
-                        task: task.clone(),
-                        task_progress: *task_progress,
-                        subtask: subtask.clone(),
-                        subtask_progress: *subtask_progress,
-                        elapsed_time: self.compute_elapsed_time(),
-                    };
-                }
+                self.drive.state = OpticalDriveState::Copying {
+                    stage,
+                    task: task.clone(),
+                    task_progress: *task_progress,
+                    subtask: subtask.clone(),
+                    subtask_progress: *subtask_progress,
+                    elapsed_time: self.compute_elapsed_time(),
+                };
                 Ok(())
             },
             _ => Ok(()),
@@ -414,6 +447,7 @@ impl MessageProcessor {
     ///
     /// [`Error::ResponseSend`] if the response cannot be sent.
     fn copy_completed(&mut self, resp: Response<()>) -> Result<()> {
+        metrics::copy_finished(Outcome::Completed, self.compute_elapsed_time());
         self.copy_started = None;
         self.copy_ct = None;
 
@@ -447,6 +481,9 @@ impl MessageProcessor {
     ///
     /// [`Error::ResponseSend`] if the response cannot be sent.
     fn copy_failed(&mut self, error: String, resp: Response<()>) -> Result<()> {
+        // The cancellation token is taken when the copy is cancelled.
+        let outcome = if self.copy_ct.is_none() { Outcome::Cancelled } else { Outcome::Failed };
+        metrics::copy_finished(outcome, self.compute_elapsed_time());
         self.copy_started = None;
         self.copy_ct = None;
 
@@ -489,11 +526,58 @@ impl MessageProcessor {
     ///
     /// [`Error::ResponseSend`] if the response cannot be sent.
     fn get_status(&self, resp: Response<OpticalDrive>) -> Result<()> {
-        resp.send(Ok(self.drive.clone()))
+        let mut drive = self.drive.clone();
+        drive.storage = self.worker.as_deref().map(sentinel::status);
+        resp.send(Ok(drive))
             .inspect_err(|_| send_error_trace(&self.drive.serial_number, "GetStatus"))
             .map_err(|_| Error::ResponseSend)
     }
 
+    /// Update copy operation progress based on the number of copied titles that have been hashed.
+    ///
+    /// # Args
+    ///
+    /// `completed`:  The number of titles that have been hashed.
+    ///
+    /// `total`:  The total number of titles being hashed.
+    ///
+    /// `resp`:  The transmission end of the channel to send the response.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ResponseSend`] if the response cannot be sent.
+    fn ingest_progress(
+        &mut self,
+        completed: usize,
+        total: usize,
+        resp: Response<()>
+    ) -> Result<()> {
+        let OpticalDriveState::Copying { .. } = &self.drive.state else {
+            let error = Error::InvalidDriveState { state: self.drive.state.name().to_owned() };
+            return resp.send(Err(error))
+                .inspect_err(|_| send_error_trace(&self.drive.serial_number, "IngestProgress"))
+                .map_err(|_| Error::ResponseSend);
+        };
+
+        let task_progress = match total {
+            0 => 1.0,
+            total => completed as f32 / total as f32,
+        };
+
+        self.drive.state = OpticalDriveState::Copying {
+            stage: "Ingesting",
+            task: format!("Computing checksums ({completed} of {total} titles)"),
+            task_progress,
+            subtask: String::default(),
+            subtask_progress: 0.0,
+            elapsed_time: self.compute_elapsed_time(),
+        };
+
+        resp.send(Ok(()))
+            .inspect_err(|_| send_error_trace(&self.drive.serial_number, "IngestProgress"))
+            .map_err(|_| Error::ResponseSend)
+    }
+
     /// Update copy operation progress based on the process of the current MakeMKV command.
     ///
     /// # Args
@@ -632,7 +716,7 @@ impl MessageProcessor {
 
         self.makemkv_copy_resp = Some(resp);
 
-        match &self.worker {
+        let started = match &self.worker {
             Some(worker) => {
                 net::send_run_makemkv_copy(
                     &self.bus,
@@ -652,7 +736,19 @@ impl MessageProcessor {
                     ct,
                 )
             }
+        };
+
+        // The copy is waiting for the response, so it fails if the command cannot be started
+        // (e.g. the worker node rejected the request).
+        if let Err(error) = started
+            && let Some(copy_resp) = self.makemkv_copy_resp.take()
+        {
+            tracing::error!(sn=self.drive.serial_number, ?error, "failed to start makemkv copy");
+            return copy_resp.send(Err(error))
+                .inspect_err(|_| send_error_trace(&self.drive.serial_number, "RunMakeMkvCopy"))
+                .map_err(|_| Error::ResponseSend);
         }
+        Ok(())
     }
 
     /// Notify the actor the MakeMKV copy command was completed successfully.
@@ -734,7 +830,7 @@ impl MessageProcessor {
 
         self.makemkv_info_resp = Some(resp);
 
-        match &self.worker {
+        let started = match &self.worker {
             Some(worker) => {
                 net::send_run_makemkv_info(
                     &self.bus,
@@ -752,7 +848,19 @@ impl MessageProcessor {
                     ct,
                 )
             },
+        };
+
+        // The copy is waiting for the response, so it fails if the command cannot be started
+        // (e.g. the worker node rejected the request).
+        if let Err(error) = started
+            && let Some(info_resp) = self.makemkv_info_resp.take()
+        {
+            tracing::error!(sn=self.drive.serial_number, ?error, "failed to start makemkv info");
+            return info_resp.send(Err(error))
+                .inspect_err(|_| send_error_trace(&self.drive.serial_number, "RunMakeMkvInfo"))
+                .map_err(|_| Error::ResponseSend);
         }
+        Ok(())
     }
 
     /// Notify the actor the MakeMKV info command was completed successfully.
@@ -803,10 +911,32 @@ impl MessageProcessor {
     ///
     /// [`Error::ResponseSend`] if the response cannot be sent.
     fn makemkv_failed(&mut self, error: String, resp: Response<()>) -> Result<()> {
+        let running = self.fail_makemkv_command(&error);
+
+        let reply = if running || self.copy_ct.is_none() {
+            Ok(())
+        } else {
+            tracing::error!(sn=self.drive.serial_number, "command not running");
+            Err(Error::NotRunning)
+        };
+
+        resp.send(reply)
+            .inspect_err(|_| send_error_trace(&self.drive.serial_number, "MakemkvFailed"))
+            .map_err(|_| Error::ResponseSend)
+    }
+
+    /// Sends an error to the copy operation waiting on the running MakeMKV command.
+    ///
+    /// Returns `true` if a command was running.
+    ///
+    /// # Args
+    ///
+    /// `error`:  Why the command failed.
+    fn fail_makemkv_command(&mut self, error: &str) -> bool {
         let mut running = false;
 
         if let Some(info_resp) = self.makemkv_info_resp.take() {
-            let error = Error::MakeMkvCommandFailed { error: error.clone() };
+            let error = Error::MakeMkvCommandFailed { error: error.to_owned() };
             let _ = info_resp.send(Err(error))
                 .inspect_err(|_| {
                     tracing::error!(sn=self.drive.serial_number, "failed to send info failure");
@@ -814,8 +944,8 @@ impl MessageProcessor {
             running = true;
         }
 
-        if let Some(copy_resp) = self.makemkv_info_resp.take() {
-            let error = Error::MakeMkvCommandFailed { error: error.clone() };
+        if let Some(copy_resp) = self.makemkv_copy_resp.take() {
+            let error = Error::MakeMkvCommandFailed { error: error.to_owned() };
             let _ = copy_resp.send(Err(error))
                 .inspect_err(|_| {
                     tracing::error!(sn=self.drive.serial_number, "failed to send copy failure");
@@ -823,16 +953,7 @@ impl MessageProcessor {
             running = true;
         }
 
-        let reply = if running || self.copy_ct.is_none() {
-            Ok(())
-        } else {
-            tracing::error!(sn=self.drive.serial_number, "command not running");
-            Err(Error::NotRunning)
-        };
-
-        resp.send(reply)
-            .inspect_err(|_| send_error_trace(&self.drive.serial_number, "MakemkvFailed"))
-            .map_err(|_| Error::ResponseSend)
+        running
     }
 
     /// Saves the copy parameters for the drive.
@@ -896,6 +1017,11 @@ impl MessageProcessor {
             should_save = true;
         };
 
+        if let Some(metadata_id) = updated_data.metadata_id {
+            data.form.metadata_id = metadata_id;
+            should_save = true;
+        };
+
         let reply = if should_save {
             data::save_data(&self.drive.serial_number, &data)
         } else {
@@ -985,7 +1111,7 @@ impl actor::MessageProcessor<Message> for MessageProcessor {
     async fn process(&mut self, msg: Message) -> Result<()> {
         let request = msg.drive_request(&self.drive.serial_number)?;
 
-        match request {
+        let result = match request {
             DriveRequest::BeginCopyDisc { params, response } => {
                 self.begin_copy_disc(params, response)
             },
@@ -1004,6 +1130,9 @@ impl actor::MessageProcessor<Message> for MessageProcessor {
             DriveRequest::GetStatus { response } => {
                 self.get_status(response)
             },
+            DriveRequest::IngestProgress { completed, total, response } => {
+                self.ingest_progress(completed, total, response)
+            },
             DriveRequest::MakeMkvCopyComplete { output, response } => {
                 self.makemkv_copy_complete(output, response)
             },
@@ -1038,7 +1167,7 @@ impl actor::MessageProcessor<Message> for MessageProcessor {
                 self.run_makemkv_info(log_file, cancellation_token, response).await
             },
             DriveRequest::SaveFormData { data, response } => {
-                self.save_form_data(data, response)
+                self.save_form_data(*data, response)
             },
             DriveRequest::UpdateFromOs { drive, response, worker } => {
                 self.update_from_os(drive, worker, response)
@@ -1052,7 +1181,10 @@ impl actor::MessageProcessor<Message> for MessageProcessor {
             DriveRequest::WorkerRunMakeMkvInfo { log_file: _, response } => {
                 self.unsupported_request("WorkerRunMakeMkvInfo", response)
             },
-        }
+        };
+
+        metrics::drive_updated(&self.drive);
+        result
     }
 }
 
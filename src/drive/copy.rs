@@ -6,22 +6,31 @@
 //! The copy operation can be performed by calling [`copy_disc`].
 
 use std::fs;
+use std::path::Path;
+use std::sync::Arc;
 
 use chrono::Utc;
 
 use rusqlite::Connection;
 
 use tokio::sync::oneshot;
+use tokio::task::JoinSet;
 
 use tokio_util::sync::CancellationToken;
 
+use tracing::Span;
+
 use crate::{Error, Result};
 use crate::bus;
 use crate::db;
 use crate::drive::{DiscState, DriveRequest, Message, OsOpticalDrive};
 use crate::path;
 use crate::library;
+use crate::logging::capture;
+use crate::metrics;
 use crate::models::{CopyOperation, CopyParamaters, OperationState, Reference};
+use crate::notify::{self, Event};
+use crate::task;
 
 /// Copies the disc in the optical drive.
 ///
@@ -43,20 +52,6 @@ pub async fn copy_disc(
 ) {
     tracing::info!(sn=drive.serial_number, "starting copy operation");
 
-    let mut conn = match db::connect(&bus).await {
-        Ok(conn) => conn,
-        Err(error) => {
-            tracing::error!(sn=drive.serial_number, ?error, "database connection failed");
-            operation_failed(
-                &bus,
-                &drive.serial_number,
-                None,
-                ErrorMessage::ConnectFailed(error),
-            ).await;
-            return;
-        }
-    };
-
     let DiscState::Inserted { label: _disc_label, uuid: disc_uuid } = drive.disc else {
         tracing::error!(sn=drive.serial_number,"cannot copy from empty drive");
         operation_failed(
@@ -68,7 +63,10 @@ pub async fn copy_disc(
         return;
     };
 
-    let db_drive = match db::optical_drive::get_or_create(&conn, &drive.serial_number) {
+    let serial_number = drive.serial_number.clone();
+    let db_drive = match db::run(&bus, move |conn| {
+        db::optical_drive::get_or_create(conn, &serial_number)
+    }).await {
         Ok(drive) => drive,
         Err(error) => {
             tracing::error!(
@@ -88,7 +86,8 @@ pub async fn copy_disc(
 
     tracing::info!(sn=drive.serial_number, id=db_drive.id, "got drive record");
 
-    let host = match db::host::get_or_create(&conn, &drive.hostname) {
+    let hostname = drive.hostname.clone();
+    let host = match db::run(&bus, move |conn| db::host::get_or_create(conn, &hostname)).await {
         Ok(host) => host,
         Err(error) => {
             tracing::error!(sn=drive.serial_number, ?error, "failed to get/create host db record");
@@ -104,7 +103,7 @@ pub async fn copy_disc(
 
     tracing::info!(sn=drive.serial_number, id=host.id, host=host.hostname, "got host record");
 
-    let mut copy_operation = CopyOperation {
+    let copy_operation = CopyOperation {
         started: Utc::now(),
         media_type: copy_parameters.media_type,
         title: copy_parameters.title,
@@ -114,6 +113,7 @@ pub async fn copy_disc(
         season: copy_parameters.season_number,
         location: copy_parameters.location,
         memo: copy_parameters.memo,
+        metadata_id: copy_parameters.metadata_id,
         drive: Reference {
             id: db_drive.id,
             value: None
@@ -125,40 +125,51 @@ pub async fn copy_disc(
         ..CopyOperation::default()
     };
 
-    if let Err(error) = db::copy_operation::create(&conn, &mut copy_operation) {
-        tracing::error!(sn=drive.serial_number, ?error, "failed to get/create host db record");
-        operation_failed(
-            &bus,
-            &drive.serial_number,
-            None,
-            ErrorMessage::DbOpCopyOperationCreateFailed(error),
-        ).await;
-        return;
+    let copy_operation = match update(&bus, copy_operation, |conn, copy_operation| {
+        db::copy_operation::create(conn, copy_operation)
+    }).await {
+        Ok(copy_operation) => copy_operation,
+        Err((_, error)) => {
+            tracing::error!(
+                sn=drive.serial_number,
+                ?error,
+                "failed to create copy operation db record"
+            );
+            operation_failed(
+                &bus,
+                &drive.serial_number,
+                None,
+                ErrorMessage::DbOpCopyOperationCreateFailed(error),
+            ).await;
+            return;
+        },
     };
 
+    Span::current().record("id", copy_operation.id);
     tracing::info!(sn=drive.serial_number, id=copy_operation.id, "created copy operation record");
 
     // Don't check for cancellation until now because we want there to be a database entry.
     if cancellation_token.is_cancelled() {
         tracing::info!(sn=drive.serial_number, "copy operation cancelled");
-        operation_canceled(&bus, &drive.serial_number, conn, copy_operation).await;
+        operation_canceled(&bus, &drive.serial_number, copy_operation).await;
         return;
     }
 
-    if let Err(error) = db::copy_operation::set_state(
-        &conn,
-        &mut copy_operation,
-        OperationState::Running
-    ) {
-        tracing::error!(sn=drive.serial_number, ?error, "failed to set running state in db");
-        operation_failed(
-            &bus,
-            &drive.serial_number,
-            None,
-            ErrorMessage::DbOpSetStateRunning(error),
-        ).await;
-        return;
-    }
+    let copy_operation = match update(&bus, copy_operation, |conn, copy_operation| {
+        db::copy_operation::set_state(conn, copy_operation, OperationState::Running)
+    }).await {
+        Ok(copy_operation) => copy_operation,
+        Err((copy_operation, error)) => {
+            tracing::error!(sn=drive.serial_number, ?error, "failed to set running state in db");
+            operation_failed(
+                &bus,
+                &drive.serial_number,
+                copy_operation,
+                ErrorMessage::DbOpSetStateRunning(error),
+            ).await;
+            return;
+        },
+    };
 
     let output_location = path::inbox_location(&copy_operation, None);
     let Some(output_path) = path::location_path(&output_location) else {
@@ -180,7 +191,7 @@ pub async fn copy_disc(
         operation_failed(
             &bus,
             &drive.serial_number,
-            Some((conn, copy_operation)),
+            Some(copy_operation),
             ErrorMessage::OutputDirExists,
         ).await;
         return;
@@ -196,7 +207,7 @@ pub async fn copy_disc(
         operation_failed(
             &bus,
             &drive.serial_number,
-            Some((conn, copy_operation)),
+            Some(copy_operation),
             ErrorMessage::OutputDirCreateFailed(error.into()),
         ).await;
         return;
@@ -224,7 +235,7 @@ pub async fn copy_disc(
         operation_failed(
             &bus,
             &drive.serial_number,
-            Some((conn, copy_operation)),
+            Some(copy_operation),
             ErrorMessage::InfoCommandSendError(error),
         ).await;
         return;
@@ -236,7 +247,7 @@ pub async fn copy_disc(
 
     if cancellation_token.is_cancelled() {
         tracing::info!(sn=drive.serial_number, "copy operation cancelled");
-        operation_canceled(&bus, &drive.serial_number, conn, copy_operation).await;
+        operation_canceled(&bus, &drive.serial_number, copy_operation).await;
         return;
     }
 
@@ -248,7 +259,7 @@ pub async fn copy_disc(
             operation_failed(
                 &bus,
                 &drive.serial_number,
-                Some((conn, copy_operation)),
+                Some(copy_operation),
                 ErrorMessage::InfoCommandResponseError(error),
             ).await;
             return;
@@ -262,7 +273,7 @@ pub async fn copy_disc(
             operation_failed(
                 &bus,
                 &drive.serial_number,
-                Some((conn, copy_operation)),
+                Some(copy_operation),
                 ErrorMessage::MkvInfoCommandFailed(error),
             ).await;
             return;
@@ -276,7 +287,7 @@ pub async fn copy_disc(
         operation_failed(
             &bus,
             &drive.serial_number,
-            Some((conn, copy_operation)),
+            Some(copy_operation),
             ErrorMessage::DiscInfoSaveFailed(error),
         ).await;
         return;
@@ -284,29 +295,42 @@ pub async fn copy_disc(
 
     tracing::info!(sn=drive.serial_number, "saved disc info to file system");
 
-    if let Err(error) = db::copy_operation::set_metadata(&conn, &mut copy_operation, &disc_info) {
-        tracing::error!(sn=drive.serial_number, ?error, "failed to write disc info to db");
-        operation_failed(
-            &bus,
-            &drive.serial_number,
-            Some((conn, copy_operation)),
-            ErrorMessage::DbOpSetMetadataFailed(error),
-        ).await;
-        return;
-    }
+    // The disc info is shared with the database operations since they run on a blocking thread.
+    let disc_info = Arc::new(disc_info);
+    let info = disc_info.clone();
+    let copy_operation = match update(&bus, copy_operation, move |conn, copy_operation| {
+        db::copy_operation::set_metadata(conn, copy_operation, &info)
+    }).await {
+        Ok(copy_operation) => copy_operation,
+        Err((copy_operation, error)) => {
+            tracing::error!(sn=drive.serial_number, ?error, "failed to write disc info to db");
+            operation_failed(
+                &bus,
+                &drive.serial_number,
+                copy_operation,
+                ErrorMessage::DbOpSetMetadataFailed(error),
+            ).await;
+            return;
+        },
+    };
 
     tracing::info!(sn=drive.serial_number, "saved disc info to db");
 
-    if let Err(error) = db::copy_operation::set_info_log(&conn, &mut copy_operation, &log_text) {
-        tracing::error!(sn=drive.serial_number, ?error, "failed to write info log to db");
-        operation_failed(
-            &bus,
-            &drive.serial_number,
-            Some((conn, copy_operation)),
-            ErrorMessage::DbOpSetInfoLogFailed(error),
-        ).await;
-        return;
-    }
+    let copy_operation = match update(&bus, copy_operation, move |conn, copy_operation| {
+        db::copy_operation::set_info_log(conn, copy_operation, &log_text)
+    }).await {
+        Ok(copy_operation) => copy_operation,
+        Err((copy_operation, error)) => {
+            tracing::error!(sn=drive.serial_number, ?error, "failed to write info log to db");
+            operation_failed(
+                &bus,
+                &drive.serial_number,
+                copy_operation,
+                ErrorMessage::DbOpSetInfoLogFailed(error),
+            ).await;
+            return;
+        },
+    };
 
     tracing::info!(sn=drive.serial_number, "saved makemkv info log to db");
 
@@ -331,7 +355,7 @@ pub async fn copy_disc(
         operation_failed(
             &bus,
             &drive.serial_number,
-            Some((conn, copy_operation)),
+            Some(copy_operation),
             ErrorMessage::CopyCommandSendError(error),
         ).await;
         return;
@@ -341,7 +365,7 @@ pub async fn copy_disc(
 
     if cancellation_token.is_cancelled() {
         tracing::info!(sn=drive.serial_number, "copy operation cancelled");
-        operation_canceled(&bus, &drive.serial_number, conn, copy_operation).await;
+        operation_canceled(&bus, &drive.serial_number, copy_operation).await;
         return;
     }
 
@@ -355,7 +379,7 @@ pub async fn copy_disc(
             operation_failed(
                 &bus,
                 &drive.serial_number,
-                Some((conn, copy_operation)),
+                Some(copy_operation),
                 ErrorMessage::CopyCommandResponseError(error),
             ).await;
             return;
@@ -369,71 +393,118 @@ pub async fn copy_disc(
             operation_failed(
                 &bus,
                 &drive.serial_number,
-                Some((conn, copy_operation)),
+                Some(copy_operation),
                 ErrorMessage::MkvCopyCommandFailed(error),
             ).await;
             return;
         },
     };
 
-    if let Err(error) = db::copy_operation::set_copy_log(&conn, &mut copy_operation, &log_text) {
-        tracing::error!(sn=drive.serial_number, ?error, "failed to write copy log to db");
-        operation_failed(
-            &bus,
-            &drive.serial_number,
-            Some((conn, copy_operation)),
-            ErrorMessage::DbOpSetCopyLogFailed(error),
-        ).await;
-        return;
-    }
+    metrics::bytes_copied(copied_bytes(&output_path));
+
+    let copy_operation = match update(&bus, copy_operation, move |conn, copy_operation| {
+        db::copy_operation::set_copy_log(conn, copy_operation, &log_text)
+    }).await {
+        Ok(copy_operation) => copy_operation,
+        Err((copy_operation, error)) => {
+            tracing::error!(sn=drive.serial_number, ?error, "failed to write copy log to db");
+            operation_failed(
+                &bus,
+                &drive.serial_number,
+                copy_operation,
+                ErrorMessage::DbOpSetCopyLogFailed(error),
+            ).await;
+            return;
+        },
+    };
 
     tracing::info!(sn=drive.serial_number, "saved makemkv copy log to db");
 
-    if let Err(error) = library::process_copy_operation(
+    let checksums = match library::checksum_copy_operation(
+        &bus,
         &copy_operation,
         &drive.serial_number,
         &disc_info,
-        &mut conn,
-    ) {
-        tracing::error!(sn=drive.serial_number, ?error, "failed to generate videos and titles");
-        operation_failed(
-            &bus,
-            &drive.serial_number,
-            Some((conn, copy_operation)),
-            ErrorMessage::CreateVideosAndTitlesFailed(error),
-        ).await;
-        return;
-    }
+    ).await {
+        Ok(checksums) => checksums,
+        Err(error) => {
+            tracing::error!(sn=drive.serial_number, ?error, "failed to compute checksums");
+            operation_failed(
+                &bus,
+                &drive.serial_number,
+                Some(copy_operation),
+                ErrorMessage::CreateVideosAndTitlesFailed(error),
+            ).await;
+            return;
+        },
+    };
+
+    let serial_number = drive.serial_number.clone();
+    let copy_operation = match update(&bus, copy_operation, move |conn, copy_operation| {
+        library::process_copy_operation(
+            conn,
+            copy_operation,
+            &serial_number,
+            &disc_info,
+            &checksums,
+        )
+    }).await {
+        Ok(copy_operation) => copy_operation,
+        Err((copy_operation, error)) => {
+            tracing::error!(sn=drive.serial_number, ?error, "failed to generate videos and titles");
+            operation_failed(
+                &bus,
+                &drive.serial_number,
+                copy_operation,
+                ErrorMessage::CreateVideosAndTitlesFailed(error),
+            ).await;
+            return;
+        },
+    };
 
     tracing::info!(sn=drive.serial_number, "created title and video db records");
 
-    if let Err(error) = db::copy_operation::set_state(
-        &conn,
-        &mut copy_operation,
-        OperationState::Completed
-    ) {
-        tracing::error!(sn=drive.serial_number, ?error, "failed to set state to completed");
-        operation_failed(
-            &bus,
-            &drive.serial_number,
-            Some((conn, copy_operation)),
-            ErrorMessage::DbOpSetCopyLogFailed(error),
-        ).await;
-        return;
-    }
+    let copy_operation = match update(&bus, copy_operation, |conn, copy_operation| {
+        db::copy_operation::set_state(conn, copy_operation, OperationState::Completed)
+    }).await {
+        Ok(copy_operation) => copy_operation,
+        Err((copy_operation, error)) => {
+            tracing::error!(sn=drive.serial_number, ?error, "failed to set state to completed");
+            operation_failed(
+                &bus,
+                &drive.serial_number,
+                copy_operation,
+                ErrorMessage::DbOpSetCopyLogFailed(error),
+            ).await;
+            return;
+        },
+    };
 
     let (tx, rx) = oneshot::channel();
     let request = DriveRequest::CopyCompleted { response: tx };
     send_copy_result(&bus, &drive.serial_number, request, rx).await;
 
+    let event = Event::CopyCompleted {
+        drive: drive.serial_number.clone(),
+        title: copy_operation.title.clone(),
+    };
+    notify::publish(&bus, event).await;
+
     tracing::info!(sn=drive.serial_number, "copy operation completed successfully");
+    if let Some(copy_operation) = save_app_log(&bus, &drive.serial_number, copy_operation).await {
+        task::spawn(create_previews(bus, drive.serial_number, copy_operation));
+    }
 }
 
+/// The result of updating the copy operation record (see [`update`]).
+///
+/// On failure, the copy operation is returned with the error if it was not lost.
+type Update = std::result::Result<CopyOperation, (Option<CopyOperation>, Error)>;
+
 /// Specifies the various error messages that can occur during a copy operation.
 #[allow(dead_code)]
 #[derive(Debug)]
 enum ErrorMessage {
-    ConnectFailed(Error),
     CopyCommandResponseError(Error),
     CopyCommandSendError(Error),
     CreateVideosAndTitlesFailed(Error),
@@ -460,9 +531,6 @@ impl ErrorMessage {
     /// Creates the error message for the user.
     fn user_message(&self) -> String {
         match self {
-            ErrorMessage::ConnectFailed(_) => {
-                String::from("Database connection failed.")
-            },
             ErrorMessage::CopyCommandResponseError(_) => {
                 String::from("System Error (copy-response).")
             },
@@ -532,21 +600,141 @@ impl ErrorMessage {
     }
 }
 
+/// Creates the previews for the videos copied by the copy operation.
+///
+/// This is run in the background once the copy operation is completed so the drive can be used
+/// again without waiting for FFmpeg. The videos are processed in parallel, but the number of
+/// videos previews are created for at the same time is limited by
+/// [`library::preview::create_previews`].
+///
+/// Previews are only an aid for identifying the titles so failing to create them is only logged.
+async fn create_previews(bus: bus::Handle, serial_number: String, copy_operation: CopyOperation) {
+    let id = copy_operation.id;
+    let videos = match db::run(&bus, move |conn| db::video::get_by_copy_operation(conn, id)).await {
+        Ok(videos) => videos,
+        Err(error) => {
+            tracing::warn!(sn=serial_number, ?error, "failed to read videos for previews");
+            return;
+        },
+    };
+
+    let copy_operation = Arc::new(copy_operation);
+    let mut tasks = JoinSet::new();
+    for mut video in videos {
+        let bus = bus.clone();
+        let serial_number = serial_number.clone();
+        let copy_operation = copy_operation.clone();
+        task::spawn_in(&mut tasks, async move {
+            let id = video.id;
+            let result = library::preview::create_previews(&copy_operation, &mut video).await;
+            if let Err(error) = result {
+                tracing::warn!(sn=serial_number, id, ?error, "failed to create previews");
+            }
+
+            let result = db::run(&bus, move |conn| db::video::set_previews(conn, &video)).await;
+            if let Err(error) = result {
+                tracing::warn!(sn=serial_number, id, ?error, "failed to save previews");
+            }
+        });
+    }
+
+    while let Some(result) = tasks.join_next().await {
+        if let Err(error) = result {
+            tracing::warn!(sn=serial_number, ?error, "preview task failed");
+        }
+    }
+
+    tracing::info!(sn=serial_number, "created video previews");
+}
+
+/// Returns the total size of the files copied to the output folder.
+///
+/// Files that cannot be read are skipped since the size is only used for metrics.
+///
+/// # Args
+///
+/// `output_path`:  The folder the titles were copied to.
+fn copied_bytes(output_path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(output_path) else {
+        return 0;
+    };
+
+    entries.flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Saves the events captured by the current copy operation span with the copy operation.
+///
+/// Failing to save the events is logged but otherwise ignored, since the copy operation itself is
+/// unaffected. Returns the copy operation unless it was lost (see [`update`]).
+async fn save_app_log(
+    bus: &bus::Handle,
+    serial_number: &str,
+    copy_operation: CopyOperation,
+) -> Option<CopyOperation> {
+    let app_log = capture::take(&Span::current());
+    match update(bus, copy_operation, move |conn, copy_operation| {
+        db::copy_operation::set_app_log(conn, copy_operation, &app_log)
+    }).await {
+        Ok(copy_operation) => Some(copy_operation),
+        Err((copy_operation, error)) => {
+            tracing::warn!(sn=serial_number, ?error, "failed to save application log in database");
+            copy_operation
+        },
+    }
+}
+
+/// Updates the copy operation record using [`db::run`].
+///
+/// The copy operation is moved to the blocking thread running `func` and returned when it is done.
+/// If `func` fails, the copy operation is returned along with the error so the failure can still
+/// be recorded. It is only lost if a database connection cannot be made or `func` panics.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the database actor.
+///
+/// `copy_operation`:  The copy operation to update.
+///
+/// `func`:  The database operation updating the copy operation.
+async fn update<F>(bus: &bus::Handle, copy_operation: CopyOperation, func: F) -> Update
+where
+    F: FnOnce(&mut Connection, &mut CopyOperation) -> Result<()> + Send + 'static,
+{
+    let result = db::run(bus, move |conn| {
+        let mut copy_operation = copy_operation;
+        let result = func(conn, &mut copy_operation);
+        Ok((copy_operation, result))
+    }).await;
+
+    match result {
+        Ok((copy_operation, Ok(()))) => Ok(copy_operation),
+        Ok((copy_operation, Err(error))) => Err((Some(copy_operation), error)),
+        Err(error) => Err((None, error)),
+    }
+}
+
 /// Updates the drive actor state to failed with a message indicating operation was cancelled.
 async fn operation_canceled(
     bus: &bus::Handle,
     serial_number: &str,
-    conn: Connection,
     copy_operation: CopyOperation,
 ) {
-    let mut copy_operation = copy_operation;
-
-    if let Err(error) = db::copy_operation::set_state(
-        &conn,
-        &mut copy_operation,
-        OperationState::Cancelled
-    ) {
-        tracing::info!(sn=serial_number, ?error, "failed to set cancelled state in database");
+    let copy_operation = match update(bus, copy_operation, |conn, copy_operation| {
+        db::copy_operation::set_state(conn, copy_operation, OperationState::Cancelled)
+    }).await {
+        Ok(copy_operation) => Some(copy_operation),
+        Err((copy_operation, error)) => {
+            tracing::info!(sn=serial_number, ?error, "failed to set cancelled state in database");
+            copy_operation
+        },
+    };
+
+    if let Some(copy_operation) = copy_operation {
+        save_app_log(bus, serial_number, copy_operation).await;
     }
 
     let (tx, rx) = oneshot::channel();
@@ -559,24 +747,35 @@ async fn operation_canceled(
 }
 
 /// Updates the drive actor state to failed with the provided message.
+///
+/// A [`Event::CopyFailed`] notification is published with the user message.
 async fn operation_failed(
     bus: &bus::Handle,
     serial_number: &str,
-    data: Option<(Connection, CopyOperation)>,
+    copy_operation: Option<CopyOperation>,
     msg: ErrorMessage
 ) {
     let operation_state = OperationState::Failed {
         reason: msg.database_message(),
     };
+    let title = copy_operation.as_ref().map(|copy_operation| copy_operation.title.clone());
+
+    let copy_operation = match copy_operation {
+        Some(copy_operation) => match update(bus, copy_operation, |conn, copy_operation| {
+            db::copy_operation::set_state(conn, copy_operation, operation_state)
+        }).await {
+            Ok(copy_operation) => Some(copy_operation),
+            Err((copy_operation, error)) => {
+                tracing::info!(sn=serial_number, ?error, "failed to set failed state in database");
+                copy_operation
+            },
+        },
+        None => None,
+    };
 
-    if let Some((conn, mut copy_operation)) = data
-        && let Err(error) = db::copy_operation::set_state(
-            &conn,
-            &mut copy_operation,
-            operation_state
-        ) {
-            tracing::info!(sn=serial_number, ?error, "failed to set failed state in database");
-        }
+    if let Some(copy_operation) = copy_operation {
+        save_app_log(bus, serial_number, copy_operation).await;
+    }
 
     let (tx, rx) = oneshot::channel();
     let request = DriveRequest::CopyFailed {
@@ -584,7 +783,14 @@ async fn operation_failed(
         response: tx,
     };
 
-    send_copy_result(bus, serial_number, request, rx).await
+    send_copy_result(bus, serial_number, request, rx).await;
+
+    let event = Event::CopyFailed {
+        drive: serial_number.to_owned(),
+        title,
+        error: msg.user_message(),
+    };
+    notify::publish(bus, event).await;
 }
 
 /// Send the result of the copy operation to the drive actor.
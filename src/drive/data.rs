@@ -98,6 +98,13 @@ pub struct FormData {
 
     /// Additional information provided by the user.
     pub memo: String,
+
+    /// The canonical identifier of the movie or show selected from the metadata search results.
+    ///
+    /// Empty if a search result was not selected. See [`crate::metadata::ProviderId`] for the
+    /// format.
+    #[serde(default)]
+    pub metadata_id: String,
 }
 
 /// Data used to update the form data in the drive's persisten data.
@@ -128,7 +135,10 @@ pub struct FormDataUpdate {
     pub storage_location: Option<String>,
 
     /// Additional information provided by the user.
-    pub memo: Option<String>
+    pub memo: Option<String>,
+
+    /// The canonical identifier selected from the metadata search results.
+    pub metadata_id: Option<String>,
 }
 
 impl FormDataUpdate {
@@ -187,6 +197,14 @@ impl FormDataUpdate {
             ..FormDataUpdate::default()
         }
     }
+
+    /// Create a form data update instance for updating the metadata identifier.
+    pub fn metadata_id(value: String) -> Self {
+        Self {
+            metadata_id: Some(value),
+            ..FormDataUpdate::default()
+        }
+    }
 }
 
 /// Load the a drive's persistent data.
@@ -300,6 +318,7 @@ mod tests {
 
         let data = Data {
             name: String::from("Test Drive"),
+            rank: 1,
             form: FormData {
                 media_type: String::from("Test Type"),
                 title: String::from("Test Title"),
@@ -308,6 +327,7 @@ mod tests {
                 season_number: String::from(""),
                 storage_location: String::from("Test Location"),
                 memo: String::from("Test Memo"),
+                metadata_id: String::from("tmdb:1234"),
             },
         };
 
@@ -323,6 +343,7 @@ mod tests {
         assert_eq!(data.form.season_number, loaded_data.form.season_number);
         assert_eq!(data.form.storage_location, loaded_data.form.storage_location);
         assert_eq!(data.form.memo, loaded_data.form.memo);
+        assert_eq!(data.form.metadata_id, loaded_data.form.metadata_id);
     }
 }
 
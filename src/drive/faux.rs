@@ -4,7 +4,7 @@
 //! Faux implementation for development and testing purposes.
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
@@ -18,7 +18,25 @@ use super::{DiscState, OsOpticalDrive};
 /// system may not have optical drives or when it might not be desireable to use actual drives
 /// such as automated tests.
 pub fn get_optical_drives() -> Result<Vec<OsOpticalDrive>> {
-    let drives = get_faux_optical_drives()?.into_iter()
+    get_optical_drives_in(Path::new(FAUX_DRIVES_DIR))
+}
+
+/// Gets the optical drive information for the faux drives defined in a directory.
+///
+/// Each drive is defined by a JSON file in the directory. This allows multiple nodes running in
+/// the same process, such as in the integration tests, to each have their own drives.
+///
+/// # Args
+///
+/// `drives_dir`:  The directory containing the drive definitions.
+///
+/// # Errors
+///
+/// [`crate::Error::StdIo`] if the directory or one of the files cannot be read.
+///
+/// [`crate::Error::SerdeJson`] if one of the files is not a valid drive definition.
+pub fn get_optical_drives_in(drives_dir: &Path) -> Result<Vec<OsOpticalDrive>> {
+    let drives = get_faux_optical_drives(drives_dir)?.into_iter()
         .map(|fd| fd.into_os_drive())
         .collect();
     Ok(drives)
@@ -72,22 +90,19 @@ impl FauxDrive {
     }
 }
 
-fn get_faux_optical_drives() -> Result<Vec<FauxDrive>> {
-    let drives_dir = PathBuf::from(FAUX_DRIVES_DIR);
-
+fn get_faux_optical_drives(drives_dir: &Path) -> Result<Vec<FauxDrive>> {
     if !drives_dir.exists() {
         return Ok(Vec::new());
     }
 
     let mut drives = Vec::new();
 
-    for entry in fs::read_dir(&drives_dir).unwrap() {
-        let entry = entry.unwrap();
-        let path = entry.path();
+    for entry in fs::read_dir(drives_dir)? {
+        let path = entry?.path();
 
         if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let contents = fs::read_to_string(&path).unwrap();
-            let drive: FauxDrive = serde_json::from_str(&contents).unwrap();
+            let contents = fs::read_to_string(&path)?;
+            let drive: FauxDrive = serde_json::from_str(&contents)?;
             drives.push(drive);
         }
     }
@@ -96,7 +111,7 @@ fn get_faux_optical_drives() -> Result<Vec<FauxDrive>> {
 }
 
 fn get_faux_optical_drive(serial_number: &str) -> Result<Option<FauxDrive>> {
-    let drives = get_faux_optical_drives()?;
+    let drives = get_faux_optical_drives(Path::new(FAUX_DRIVES_DIR))?;
     Ok(drives.into_iter().find(|drive| drive.serial_number == serial_number))
 }
 
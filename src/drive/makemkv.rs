@@ -3,6 +3,9 @@
 
 //! Handles running MakeMKV operations for a drive.
 
+use std::fs;
+use std::path::Path;
+
 use tokio::sync::mpsc;
 
 use tokio_util::future::FutureExt;
@@ -38,6 +41,8 @@ use crate::task;
 /// # Errors
 ///
 /// [`Error::InvalidMediaLocation`] if one of the provided media locations are invalid.
+///
+/// [`Error::StdIo`] if the output or log folder cannot be created.
 pub fn run_makemkv_copy(
     bus: &bus::Handle,
     serial_number: &str,
@@ -50,6 +55,8 @@ pub fn run_makemkv_copy(
         .ok_or(Error::InvalidMediaLocation { location: output_dir })?;
     let log_path = path::location_path(&log_file)
         .ok_or(Error::InvalidMediaLocation { location: log_file })?;
+    create_parent_dir(&log_path)?;
+    fs::create_dir_all(&output_path)?;
 
     let (tx, rx) = mpsc::unbounded_channel::<CommandOutput>();
     let ct = cancellation_token.clone();
@@ -100,6 +107,8 @@ pub fn run_makemkv_copy(
 /// # Errors
 ///
 /// [`Error::InvalidMediaLocation`] if the provided log file location isn't valid
+///
+/// [`Error::StdIo`] if the log folder cannot be created.
 pub fn run_makemkv_info(
     bus: &bus::Handle,
     serial_number: &str,
@@ -109,6 +118,7 @@ pub fn run_makemkv_info(
 ) -> Result<()> {
     let log_path = path::location_path(&log_file)
         .ok_or(Error::InvalidMediaLocation { location: log_file })?;
+    create_parent_dir(&log_path)?;
 
     let (tx, rx) = mpsc::unbounded_channel::<CommandOutput>();
     let ct = cancellation_token.clone();
@@ -140,6 +150,21 @@ pub fn run_makemkv_info(
     Ok(())
 }
 
+/// Create the folder a file is written to if it does not exist.
+///
+/// The control node creates the inbox folder for a copy operation, but a worker node that does not
+/// share its media folders with the control node needs to create its own.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] if the folder cannot be created.
+fn create_parent_dir(file_path: &Path) -> Result<()> {
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
 /// Processes the output of a running MakeMKV command.
 ///
 /// # Args
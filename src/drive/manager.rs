@@ -13,7 +13,7 @@ use tokio::sync::oneshot;
 use crate::{Error, Mode, Result};
 use crate::actor::{self, Response};
 use crate::bus;
-use crate::drive::{self, DriveRequest, Handle, Message};
+use crate::drive::{self, DriveRequest, Handle, Message, OsOpticalDrive};
 use crate::drive::monitor;
 use crate::task;
 
@@ -53,7 +53,26 @@ pub enum ManagerRequest {
 /// Will return errors if the command to get optical drive data from the OS fails. This will vary
 /// based on OS type.
 pub fn init(bus: &bus::Handle, mode: Mode) -> Result<Handle> {
-    task::spawn(monitor::monitor_drives(bus.clone(), mode));
+    init_impl(bus, mode, drive::get_optical_drives)
+}
+
+/// Internal implementation of [`init`].
+///
+/// Allows the drives to be read from somewhere other than the OS, such as when multiple nodes are
+/// run in the same process by the integration tests.
+///
+/// # Args
+///
+/// `bus`:  Handle used to send messages to other actors via the message bus.
+///
+/// `mode`:  The mode the application is running as.
+///
+/// `get_drives`:  Gets the status of the optical drives connected to the node.
+pub(crate) fn init_impl<F>(bus: &bus::Handle, mode: Mode, get_drives: F) -> Result<Handle>
+where
+    F: Fn() -> Result<Vec<OsOpticalDrive>> + Send + 'static,
+{
+    task::spawn(monitor::monitor_drives(bus.clone(), mode, get_drives));
 
     let msg_processor = MessageProcessor::new(bus.clone(), mode);
     Ok(actor::create_and_run("drive manager", msg_processor))
@@ -54,10 +54,17 @@ use ::makemkv::{CopyCommandOutput, InfoCommandOutput};
 use crate::{Error, Result};
 use crate::bus;
 use crate::models::{CopyParamaters, MediaLocation};
+use crate::net::sentinel::StorageCheck;
 
 pub use data::{FormData, FormDataUpdate};
 pub use manager::init;
 
+// Used by the integration tests to give each node in the process its own faux drives.
+#[cfg(all(test, feature = "faux_drives"))]
+pub(crate) use faux::get_optical_drives_in;
+#[cfg(all(test, feature = "faux_drives"))]
+pub(crate) use manager::init_impl;
+
 use actor::DriveRequest;
 use manager::ManagerRequest;
 
@@ -226,6 +233,11 @@ pub struct OpticalDrive {
     /// This is the state within the context of this application which is mainly if its idle,
     /// copying, etc., not the state of the drive hardware itself.
     pub state: OpticalDriveState,
+
+    /// The result of checking the media folders of the worker node the drive is installed in.
+    ///
+    /// `None` if the drive is installed in the control node.
+    pub storage: Option<StorageCheck>,
 }
 
 impl OpticalDrive {
@@ -241,6 +253,7 @@ impl OpticalDrive {
             hostname: String::default(),
             disc: DiscState::None,
             state: OpticalDriveState::Disconnected,
+            storage: None,
         }
     }
 
@@ -292,6 +305,9 @@ pub struct OsOpticalDrive {
 ///
 /// [`Error::ResponseRecv`] if the response to the request could not be processed.
 ///
+/// [`Error::StorageNotVerified`] if the drive is installed in a worker node whose media folders
+/// have not been verified as shared with the control node.
+///
 /// [`Error::UnsupportedRequest`] if request is sent on the worker node.
 pub async fn begin_copy(
     bus: &bus::Handle,
@@ -383,6 +399,42 @@ pub async fn get(bus: &bus::Handle, serial_number: &str) -> Result<OpticalDrive>
     rx.await?
 }
 
+/// Update the progress of ingesting the titles created by a copy operation.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the drive actor.
+///
+/// `serial_number`:  Serial number of the optical drive.
+///
+/// `completed`:  The number of titles that have been hashed.
+///
+/// `total`:  The total number of titles being hashed.
+///
+/// # Errors
+///
+/// [`Error::ChannelSend`] if the request could not be sent to the drive actor.
+///
+/// [`Error::ResponseRecv`] if the response to the request could not be processed.
+///
+/// [`Error::InvalidDriveState`] if the drive is not copying a disc.
+///
+/// [`Error::UnsupportedRequest`] if the request is made on the worker node.
+pub async fn ingest_progress(
+    bus: &bus::Handle,
+    serial_number: &str,
+    completed: usize,
+    total: usize,
+) -> Result<()> {
+    let (tx, rx) = oneshot::channel();
+    let msg = Message::Drive {
+        serial_number: serial_number.to_owned(),
+        request: DriveRequest::IngestProgress { completed, total, response: tx },
+    };
+    bus.send(msg).await?;
+    rx.await?
+}
+
 /// Notify the drive actor the MakeMKV copy command has completed successfully.
 ///
 /// # Args
@@ -622,7 +674,7 @@ pub async fn save_form_data(
     let (tx, rx) = oneshot::channel();
     let msg = Message::Drive {
         serial_number: serial_number.to_owned(),
-        request: DriveRequest::SaveFormData { data, response: tx },
+        request: DriveRequest::SaveFormData { data: Box::new(data), response: tx },
     };
     bus.send(msg).await?;
     rx.await?
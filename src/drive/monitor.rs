@@ -11,9 +11,9 @@ use std::time::Duration;
 use tokio::sync::oneshot;
 use tokio::time;
 
-use crate::Mode;
+use crate::{Mode, Result};
 use crate::bus;
-use crate::drive::{self, DriveRequest, ManagerRequest, Message};
+use crate::drive::{DriveRequest, ManagerRequest, Message, OsOpticalDrive};
 
 /// Task for periodically checking the status of the drive.
 ///
@@ -22,9 +22,15 @@ use crate::drive::{self, DriveRequest, ManagerRequest, Message};
 /// `bus`:  Handle used to send requests to actors via the message bus.
 ///
 /// `mode`:  The mode the application is running as.
-pub async fn monitor_drives(bus: bus::Handle, mode: Mode) {
+///
+/// `get_drives`:  Gets the status of the optical drives from the OS (see
+/// [`crate::drive::get_optical_drives`]).
+pub async fn monitor_drives<F>(bus: bus::Handle, mode: Mode, get_drives: F)
+where
+    F: Fn() -> Result<Vec<OsOpticalDrive>>,
+{
     loop {
-        let drives = match drive::get_optical_drives() {
+        let drives = match get_drives() {
             Ok(drives) => drives,
             Err(error) => {
                 tracing::error!(?error, "failed to get drive info from OS");
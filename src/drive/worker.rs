@@ -13,6 +13,13 @@
 //!
 //! Requests made to the drive actor are typically done using the helper methods provided by the
 //! [`crate::drive`] module.
+//!
+//! If the worker node does not share its media folders with the control node, the copied titles
+//! are transferred to the control node's inbox before the copy is reported as complete (see
+//! [`crate::net::transfer`]). The progress of the transfer is reported to the control node like
+//! the progress of the MakeMKV command.
+
+use tokio::sync::mpsc;
 
 use tokio_util::sync::CancellationToken;
 
@@ -31,6 +38,8 @@ use crate::drive::{
 };
 use crate::models::MediaLocation;
 use crate::net;
+use crate::net::transfer::Progress;
+use crate::task;
 
 /// Create the worker actor instance for the provided drive.
 ///
@@ -54,6 +63,9 @@ struct MessageProcessor {
 
     /// Cancellation token used to cancel a running MakeMKV command.
     cancellation_token: Option<CancellationToken>,
+
+    /// The location of the folder the running MakeMKV copy command is writing to.
+    output_dir: Option<MediaLocation>,
 }
 
 impl MessageProcessor {
@@ -75,6 +87,7 @@ impl MessageProcessor {
                 hostname: String::default(),
             },
             cancellation_token: None,
+            output_dir: None,
         }
     }
 
@@ -145,6 +158,10 @@ impl MessageProcessor {
 
     /// Send the output of a completed MakeMKV copy command to the control node.
     ///
+    /// The copied titles are transferred to the control node first, which can take a while, so
+    /// this is done in a separate task and the response is sent right away. The copy is reported
+    /// as failed if the titles cannot be transferred.
+    ///
     /// # Args
     ///
     /// `output`:  The output of the copy command.
@@ -154,24 +171,39 @@ impl MessageProcessor {
     /// # Errors
     ///
     /// [`Error::ResponseSend`] if the response cannot be sent.
-    async fn makemkv_copy_complete(
+    fn makemkv_copy_complete(
         &mut self,
         output: CopyCommandOutput,
         resp: Response<()>,
     ) -> Result<()> {
         self.cancellation_token = None;
 
-        let reply = ignore_disconnected(
-            net::send_makemkv_copy_complete(
-                &self.bus,
-                &self.drive.serial_number,
-                output
-            ).await
-        );
-
         tracing::info!(sn=self.drive.serial_number, "makemkv copy complete");
 
-        resp.send(reply)
+        let bus = self.bus.clone();
+        let serial_number = self.drive.serial_number.clone();
+        let output_dir = self.output_dir.take();
+        task::spawn(async move {
+            let transferred = match output_dir {
+                Some(output_dir) => push_titles(&bus, &serial_number, output_dir).await,
+                None => Ok(()),
+            };
+
+            let result = match transferred {
+                Ok(()) => net::send_makemkv_copy_complete(&bus, &serial_number, output).await,
+                Err(error) => {
+                    tracing::error!(sn=serial_number, ?error, "failed to transfer titles");
+                    let error = format!("failed to transfer titles: {:?}", error);
+                    net::send_makemkv_failed(&bus, &serial_number, error).await
+                },
+            };
+
+            if let Err(error) = ignore_disconnected(result) {
+                tracing::error!(sn=serial_number, ?error, "failed to send copy result");
+            }
+        });
+
+        resp.send(Ok(()))
             .inspect_err(|_| send_error_trace(&self.drive.serial_number, "MakeMkvCopyComplete"))
             .map_err(|_| Error::ResponseSend)
     }
@@ -254,6 +286,8 @@ impl MessageProcessor {
     ///
     /// # Errors
     ///
+    /// [`Error::AlreadyRunning`] if a MakeMKV command is already running on the drive.
+    ///
     /// [`Error::InvalidMediaLocation`] if one of the provided media locations is invalid.
     ///
     /// [`Error::MakeMkv`] if an error occures while running the MakeMKV command.
@@ -265,10 +299,14 @@ impl MessageProcessor {
     ) -> Result<()> {
         if self.cancellation_token.is_some() {
             tracing::error!(sn=self.drive.serial_number, "MakeMKV command already running");
-            return Err(Error::AlreadyRunning);
+            let sn = &self.drive.serial_number;
+            return resp.send(Err(Error::AlreadyRunning))
+                .inspect_err(|_| send_error_trace(sn, "WorkerRunMakeMkvCopy"))
+                .map_err(|_| Error::ResponseSend);
         }
 
         self.cancellation_token = Some(CancellationToken::new());
+        self.output_dir = Some(output_dir.clone());
 
         let reply = drive::makemkv::run_makemkv_copy(
             &self.bus,
@@ -279,7 +317,12 @@ impl MessageProcessor {
             self.cancellation_token.as_ref().unwrap().clone(),
         );
 
-        tracing::info!(sn=self.drive.serial_number, "makemkv copy started");
+        if reply.is_ok() {
+            tracing::info!(sn=self.drive.serial_number, "makemkv copy started");
+        } else {
+            self.cancellation_token = None;
+            self.output_dir = None;
+        }
 
         resp.send(reply)
             .inspect_err(|_| send_error_trace(&self.drive.serial_number, "WorkerRunMakeMkvCopy"))
@@ -297,13 +340,18 @@ impl MessageProcessor {
     ///
     /// # Errors
     ///
+    /// [`Error::AlreadyRunning`] if a MakeMKV command is already running on the drive.
+    ///
     /// [`Error::InvalidMediaLocation`] if the provided log file location isn't valid
     ///
     /// [`Error::MakeMkv`] if an error occures while running the MakeMKV command.
     fn run_makemkv_info(&mut self, log_file: MediaLocation, resp: Response<()>) -> Result<()> {
         if self.cancellation_token.is_some() {
             tracing::error!(sn=self.drive.serial_number, "MakeMKV command already running");
-            return Err(Error::AlreadyRunning);
+            let sn = &self.drive.serial_number;
+            return resp.send(Err(Error::AlreadyRunning))
+                .inspect_err(|_| send_error_trace(sn, "WorkerRunMakeMkvInfo"))
+                .map_err(|_| Error::ResponseSend);
         }
 
         self.cancellation_token = Some(CancellationToken::new());
@@ -316,10 +364,14 @@ impl MessageProcessor {
             self.cancellation_token.as_ref().unwrap().clone(),
         );
 
-        tracing::info!(sn=self.drive.serial_number, "makemkv info started");
+        if reply.is_ok() {
+            tracing::info!(sn=self.drive.serial_number, "makemkv info started");
+        } else {
+            self.cancellation_token = None;
+        }
 
         resp.send(reply)
-            .inspect_err(|_| send_error_trace(&self.drive.serial_number, "WorkerRunMakeMkvCopy"))
+            .inspect_err(|_| send_error_trace(&self.drive.serial_number, "WorkerRunMakeMkvInfo"))
             .map_err(|_| Error::ResponseSend)
     }
 
@@ -381,8 +433,11 @@ impl actor::MessageProcessor<Message> for MessageProcessor {
             DriveRequest::GetStatus { response } => {
                 self.unsupported_request("GetStatus", response)
             },
+            DriveRequest::IngestProgress { completed: _, total: _, response } => {
+                self.unsupported_request("IngestProgress", response)
+            },
             DriveRequest::MakeMkvCopyComplete { output, response } => {
-                self.makemkv_copy_complete(output, response).await
+                self.makemkv_copy_complete(output, response)
             },
             DriveRequest::MakeMkvFailed { error, response } => {
                 self.makemkv_failed(error, response).await
@@ -446,6 +501,53 @@ fn ignore_disconnected(result: Result<()>) -> Result<()> {
 }
 
 
+/// Transfer the copied titles to the control node, reporting the progress to it.
+///
+/// # Args
+///
+/// `bus`:  Handle used to send messages to other actors via the message bus.
+///
+/// `serial_number`:  The serial number of the drive the titles were copied from.
+///
+/// `output_dir`:  The location of the folder the titles were copied to.
+///
+/// # Errors
+///
+/// See [`net::push_files`].
+async fn push_titles(
+    bus: &bus::Handle,
+    serial_number: &str,
+    output_dir: MediaLocation,
+) -> Result<()> {
+    let (progress_tx, mut progress_rx) = mpsc::channel::<Progress>(1);
+    let report = async {
+        let mut last = None;
+        while let Some(progress) = progress_rx.recv().await {
+            let (percent, file_percent) = (progress.percent(), progress.file_percent());
+            if last == Some((percent, file_percent)) {
+                continue;
+            }
+            last = Some((percent, file_percent));
+
+            let result = net::send_makemkv_progress(
+                bus,
+                serial_number,
+                String::from("Transferring titles"),
+                percent,
+                progress.file,
+                file_percent,
+            ).await;
+            if let Err(error) = ignore_disconnected(result) {
+                tracing::warn!(sn=serial_number, ?error, "failed to send transfer progress");
+            }
+        }
+    };
+
+    let push = net::push_files(bus, None, output_dir, Some(progress_tx));
+    let (result, ()) = tokio::join!(push, report);
+    result
+}
+
 /// Log an error due to failure to send a response.
 ///
 /// # Args
@@ -11,20 +11,40 @@ use tokio::sync::oneshot;
 use crate::bus;
 use crate::db;
 use crate::drive;
+use crate::metadata;
 use crate::models::MediaLocation;
 use crate::net;
+use crate::notify;
+use crate::settings;
 
 /// Specifies the errors that can occur throughout the application.
 #[derive(Debug)]
 pub enum Error {
+    /// Raised when the other node does not acknowledge a request in time.
+    AckTimeout,
+
     /// Raised when requesting an operation when another operation is already running preventing
     /// the requsted operation.
     AlreadyRunning,
 
+    /// Raised when the integrity check of a database backup reports problems.
+    BackupIntegrity {
+        path: PathBuf,
+        problems: Vec<String>,
+    },
+
     /// Raised when attempting to cancel an operation fails because the cancellation token is not
     /// available.
     CancelTokenNone,
 
+    /// Raised when importing a catalog into a database that already contains records.
+    CatalogNotEmpty,
+
+    /// Raised when importing a catalog exported using an unsupported version of the format.
+    CatalogVersion {
+        version: u32,
+    },
+
     /// Raised when attempting to send a message to an actor fails.
     ChannelSend(Box<ChannelSendError>),
 
@@ -43,6 +63,9 @@ pub enum Error {
     /// Raised when database operations fail.
     Database(rusqlite::Error),
 
+    /// Raised when a connection cannot be retrieved from the database connection pool.
+    DatabasePool(r2d2::Error),
+
     /// Raised when attempting to send a message to the control or worker node when they are not
     /// connected.
     Disconnected,
@@ -53,6 +76,9 @@ pub enum Error {
         serial_number: String,
     },
 
+    /// Raised when an FFmpeg command fails.
+    Ffmpeg(ffmpeg::Error),
+
     /// Raised when a file cannot be found.
     ///
     /// This may also be raised if the path is not a file or if the user does not have the required
@@ -61,6 +87,16 @@ pub enum Error {
         path: PathBuf,
     },
 
+    /// Raised when a message sent or received from another node is larger than the maximum frame
+    /// size (see [`net::frame`]).
+    FrameTooLarge {
+        size: usize,
+        max: usize,
+    },
+
+    /// Raised when an HTTP request fails or returns an unsuccessful status code.
+    Http(reqwest::Error),
+
     /// Raised when a drive actor gets a request meant for the manager or the request serial number
     /// does not match its associated drive serial number.
     InvalidDriveRequest,
@@ -70,6 +106,17 @@ pub enum Error {
         state: String,
     },
 
+    /// Raised when an email address in the notification settings cannot be parsed.
+    InvalidEmail {
+        address: String,
+        error: String,
+    },
+
+    /// Raised when a frame received from another node is not valid (see [`net::frame`]).
+    InvalidFrame {
+        reason: String,
+    },
+
     /// Error raised when attempting to use an invalid media location.
     ///
     /// This will typically be raised if attempting to use [`MediaLocation::Deleted`] when a valid
@@ -78,9 +125,25 @@ pub enum Error {
         location: MediaLocation,
     },
 
+    /// Raised when a client or server actor gets a request meant for the client manager.
+    InvalidNetRequest,
+
+    /// Raised when the settings fail validation.
+    ///
+    /// Contains every problem found, each naming the offending config key.
+    InvalidSettings {
+        problems: Vec<settings::Problem>,
+    },
+
     /// Error raised when a task cannot be joined.
     JoinError(tokio::task::JoinError),
 
+    /// Raised when the log file folder cannot be created.
+    LogFile(tracing_appender::rolling::InitError),
+
+    /// Raised when the logging filter directives cannot be parsed.
+    LogFilter(tracing_subscriber::filter::ParseError),
+
     /// Raised when a MakeMKV command fails.
     MakeMkv(makemkv::Error),
 
@@ -92,6 +155,22 @@ pub enum Error {
         error: String,
     },
 
+    /// Raised when notifying the media server when a media server is not configured.
+    MediaServerDisabled,
+
+    /// Raised when a video does not appear in the media server's library after it was refreshed.
+    MediaServerItemNotFound {
+        path: PathBuf,
+    },
+
+    /// Raised when requesting a metadata lookup when a metadata provider is not configured.
+    MetadataDisabled,
+
+    /// Raised when the metadata provider does not have a movie or show with the requested ID.
+    MetadataNotFound {
+        id: String,
+    },
+
     /// Raised when an audio codec mapping cannot be found.
     ///
     /// Will be raised when looking up the MakeMKV audio codec and there is not a mapping for the
@@ -130,24 +209,68 @@ pub enum Error {
     /// command is not actually running.
     NotRunning,
 
+    /// Raised when the metrics cannot be encoded.
+    Prometheus(prometheus::Error),
+
+    /// Raised when the other node rejects a request.
+    RequestRejected {
+        code: net::protocol::NackCode,
+        error: String,
+    },
+
     /// Raised when attempting to receive a response to a message.
     ResponseRecv(oneshot::error::RecvError),
 
     /// Raised when attempting to send a response fails.
     ResponseSend,
 
+    /// Raised when restoring a database backup with a schema version that is not supported.
+    ///
+    /// Backups made by a newer version of the application cannot be restored.
+    SchemaVersion {
+        version: u32,
+        supported: u32,
+    },
+
     /// Raised when serializing or deserializing JSON fails.
     SerdeJson(serde_json::Error),
 
+    /// Raised when the config file cannot be read or created.
+    SettingsRead {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+
+    /// Raised when sending an email using an SMTP server fails.
+    Smtp(lettre::transport::smtp::Error),
+
     /// Raised when an error occurs while performing I/O operations.
     StdIo(std::io::Error),
 
+    /// Raised when copying a disc on a worker node whose media folders have not been verified as
+    /// shared with the control node.
+    StorageNotVerified {
+        worker: String,
+        reason: String,
+    },
+
     /// Raised when deserializing TOML.
     TomlDeserialize(toml::de::Error),
 
+    /// Raised when parsing a TOML file to edit it fails.
+    TomlEdit(toml_edit::TomlError),
+
     /// Raised when serializing TOML.
     TomlSerialize(toml::ser::Error),
 
+    /// Raised when a file transfer between nodes fails.
+    TransferFailed {
+        error: String,
+    },
+
+    /// Raised when the other node stops responding during a file transfer.
+    TransferTimeout,
+
     /// Error raised when an unexpected stream type is encountered.
     UnexpectedStreamType {
         stream_type: Option<String>,
@@ -179,6 +302,11 @@ pub enum Error {
         arg: String,
     },
 
+    /// Raised when adding a worker node that was already added.
+    WorkerExists {
+        addr: String,
+    },
+
     /// Raised when a worker Option field is `None` when it was expected to be `Some`.
     WorkerNone,
 
@@ -195,12 +323,36 @@ impl From<gtk::glib::Error> for Error {
     }
 }
 
+impl From<ffmpeg::Error> for Error {
+    fn from(value: ffmpeg::Error) -> Self {
+        Error::Ffmpeg(value)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        Error::Http(value)
+    }
+}
+
 impl From<tokio::task::JoinError> for Error {
     fn from(value: tokio::task::JoinError) -> Self {
         Error::JoinError(value)
     }
 }
 
+impl From<tracing_appender::rolling::InitError> for Error {
+    fn from(value: tracing_appender::rolling::InitError) -> Self {
+        Error::LogFile(value)
+    }
+}
+
+impl From<tracing_subscriber::filter::ParseError> for Error {
+    fn from(value: tracing_subscriber::filter::ParseError) -> Self {
+        Error::LogFilter(value)
+    }
+}
+
 impl From<makemkv::Error> for Error {
     fn from(value: makemkv::Error) -> Self {
         Error::MakeMkv(value)
@@ -225,6 +377,12 @@ impl From<mpsc::error::SendError<drive::Message>> for Error {
     }
 }
 
+impl From<mpsc::error::SendError<metadata::Message>> for Error {
+    fn from(value: mpsc::error::SendError<metadata::Message>) -> Self {
+        Error::ChannelSend(Box::new(ChannelSendError::Metadata(value)))
+    }
+}
+
 impl From<mpsc::error::SendError<net::Message>> for Error {
     fn from(value: mpsc::error::SendError<net::Message>) -> Self {
         Error::ChannelSend(Box::new(ChannelSendError::Net(value)))
@@ -243,24 +401,48 @@ impl From<mpsc::error::SendError<net::OutgoingMessage>> for Error {
     }
 }
 
+impl From<mpsc::error::SendError<notify::Message>> for Error {
+    fn from(value: mpsc::error::SendError<notify::Message>) -> Self {
+        Error::ChannelSend(Box::new(ChannelSendError::Notify(value)))
+    }
+}
+
 impl From<oneshot::error::RecvError> for Error {
     fn from(value: oneshot::error::RecvError) -> Self {
         Error::ResponseRecv(value)
     }
 }
 
+impl From<prometheus::Error> for Error {
+    fn from(value: prometheus::Error) -> Self {
+        Error::Prometheus(value)
+    }
+}
+
 impl From<rusqlite::Error> for Error {
     fn from(value: rusqlite::Error) -> Self {
         Error::Database(value)
     }
 }
 
+impl From<r2d2::Error> for Error {
+    fn from(value: r2d2::Error) -> Self {
+        Error::DatabasePool(value)
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(value: serde_json::Error) -> Self {
         Error::SerdeJson(value)
     }
 }
 
+impl From<lettre::transport::smtp::Error> for Error {
+    fn from(value: lettre::transport::smtp::Error) -> Self {
+        Error::Smtp(value)
+    }
+}
+
 impl From<std::io::Error> for Error {
     fn from(value: std::io::Error) -> Self {
         Error::StdIo(value)
@@ -279,6 +461,12 @@ impl From<toml::de::Error> for Error {
     }
 }
 
+impl From<toml_edit::TomlError> for Error {
+    fn from(value: toml_edit::TomlError) -> Self {
+        Error::TomlEdit(value)
+    }
+}
+
 impl From<toml::ser::Error> for Error {
     fn from(value: toml::ser::Error) -> Self {
         Error::TomlSerialize(value)
@@ -297,8 +485,14 @@ pub enum ChannelSendError {
     /// Error raised when sending a message to the message bus fails.
     MessageBus(mpsc::error::SendError<bus::Message>),
 
+    /// Error raised when sending a message to the metadata actor fails.
+    Metadata(mpsc::error::SendError<metadata::Message>),
+
     /// Error raised when sending a message to the client or server fails.
     Net(mpsc::error::SendError<net::Message>),
+
+    /// Error raised when sending a message to the notify actor fails.
+    Notify(mpsc::error::SendError<notify::Message>),
 }
 
 /// Specifies the errors that can occur when attempting to send a message message to or from the
@@ -0,0 +1,628 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Integration test harness running a control node and worker nodes in the same process.
+//!
+//! Each node is started the same way as the application does at startup, but connected over the
+//! loopback interface. The worker nodes use faux drives (see the `faux_drives` feature) defined in
+//! a temporary folder per worker, and MakeMKV is replaced by `faux_makemkv` running the scenarios
+//! in `faux_makemkv/fixtures`. The scenario run for a drive is selected by its device path, so
+//! each drive can simulate a different disc or failure.
+//!
+//! The harness requires `faux_makemkv` to be built first:
+//!
+//! ```text
+//! cargo build --workspace
+//! cargo test --features faux_drives
+//! ```
+//!
+//! The path settings, preview settings and MakeMKV executable are global, so they are initialized
+//! once per process and shared by all of the nodes. The worker nodes see the same
+//! media folders as the control node, like workers using [`StorageMode::Shared`]. The tests use a
+//! different drive serial number each so they can run in parallel against the same database.
+//!
+//! A worker node can also be started behind a proxy that drops its hello (see
+//! [`Worker::start_legacy`]), so the control node sees it as a worker node that only knows the
+//! original line protocol.
+//!
+//! [`StorageMode::Shared`]: crate::net::transfer::StorageMode::Shared
+
+use std::env;
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::Mode;
+use crate::bus;
+use crate::db;
+use crate::drive::{self, OpticalDrive};
+use crate::library::preview;
+use crate::metadata;
+use crate::models::CopyOperation;
+use crate::net::{self, client, server};
+use crate::notify;
+use crate::path;
+use crate::task;
+use crate::test_utils::TempDir;
+
+/// How long to wait for a node to reach an expected state before failing the test.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// How often the state is checked while waiting.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// State shared by all of the nodes in the process.
+struct Shared {
+    /// The database used by every control node.
+    db: db::Handle,
+}
+
+/// Initializes the global state shared by all of the nodes, the first time it is called.
+fn shared() -> &'static Shared {
+    static SHARED: OnceLock<Shared> = OnceLock::new();
+    SHARED.get_or_init(|| {
+        // The folder is left behind since statics are never dropped. It is named after the
+        // process so runs don't interfere with each other.
+        let root = env::temp_dir().join(format!("artie_harness_{}", process::id()));
+        let settings = path::Settings {
+            inbox: root.join("inbox"),
+            library: root.join("library"),
+            archive: root.join("archive"),
+            data: root.join("data"),
+        };
+        path::init(settings).expect("failed to create harness folders");
+
+        preview::init(preview::Settings {
+            contact_sheet: false,
+            preview_clip: false,
+            ..preview::Settings::default()
+        });
+
+        // The scenarios are passed to `faux_makemkv` when it is run instead of setting the
+        // variables for the process, since other tests are running on other threads.
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("faux_makemkv/fixtures");
+        let envs = vec![(String::from("FAUX_MAKEMKV_SCENARIO"), fixtures.into_os_string())];
+        assert!(
+            makemkv::set_program(faux_makemkv_path(), envs),
+            "MakeMKV executable already set",
+        );
+
+        Shared { db: db::init().expect("failed to initialize harness database") }
+    })
+}
+
+/// Returns the path to the `faux_makemkv` binary built alongside the test binary.
+///
+/// # Panics
+///
+/// Panics if the binary has not been built.
+fn faux_makemkv_path() -> PathBuf {
+    // The test binary is in `target/<profile>/deps`.
+    let path = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent()?.parent().map(Path::to_path_buf))
+        .expect("failed to find target folder")
+        .join(format!("faux_makemkv{}", env::consts::EXE_SUFFIX));
+    assert!(path.is_file(), "{} not found, run `cargo build --workspace`", path.display());
+    path
+}
+
+/// Returns a port on the loopback interface that is not in use.
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("failed to find free port")
+        .port()
+}
+
+/// Stops the message bus of a node when dropped.
+///
+/// The other actors of the node stop once they can no longer send messages to the bus.
+struct Node {
+    /// Handle used to send messages to the node's actors via the message bus.
+    bus: bus::Handle,
+
+    /// The node's message bus task.
+    join_handle: JoinHandle<()>,
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        self.join_handle.abort();
+    }
+}
+
+/// A worker node with faux drives.
+pub struct Worker {
+    /// The running node.
+    node: Node,
+
+    /// The address and port the worker is listening on.
+    addr: String,
+
+    /// The folder containing the faux drive definitions.
+    drives: TempDir,
+
+    /// Stops the worker's server when cancelled.
+    server_ct: CancellationToken,
+
+    /// The proxy dropping the worker's hello, if started as a legacy worker.
+    proxy: Option<JoinHandle<()>>,
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        if let Some(proxy) = &self.proxy {
+            proxy.abort();
+        }
+    }
+}
+
+impl Worker {
+    /// Starts a worker node listening on a free port.
+    pub fn start() -> Self {
+        shared();
+
+        let port = free_port();
+        let settings = net::Settings {
+            listen_addr: String::from("127.0.0.1"),
+            listen_port: port,
+            ..net::Settings::default()
+        };
+
+        let drives = TempDir::new(format!("artie_harness_drives_{}", port));
+        fs::create_dir_all(drives.path()).expect("failed to create faux drives folder");
+
+        let (bus, bus_recv) = bus::init_channel();
+        let drives_dir = drives.path().to_path_buf();
+        let drive_mgr = drive::init_impl(&bus, Mode::Worker, move || {
+            drive::get_optical_drives_in(&drives_dir)
+        }).expect("failed to initialize drives");
+        let server_ct = CancellationToken::new();
+        let net = server::init(&bus, &settings, server_ct.clone());
+        let notify = notify::init(&notify::Settings::default())
+            .expect("failed to initialize notify");
+        let join_handle = bus::init_processor(None, drive_mgr, None, net, notify, bus_recv);
+
+        Self {
+            node: Node { bus, join_handle },
+            addr: format!("127.0.0.1:{}", port),
+            drives,
+            server_ct,
+            proxy: None,
+        }
+    }
+
+    /// Starts a worker node that the control node sees as only knowing the line protocol.
+    ///
+    /// The control node connects through a proxy that drops the worker's hello, so both nodes fall
+    /// back to the line protocol once the worker stops waiting for the reply.
+    pub fn start_legacy() -> Self {
+        let mut worker = Self::start();
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.set_nonblocking(true).map(|()| listener))
+            .expect("failed to start proxy");
+        let proxy_addr = listener.local_addr().expect("failed to start proxy").to_string();
+        let worker_addr = std::mem::replace(&mut worker.addr, proxy_addr);
+        worker.proxy = Some(task::spawn(drop_hello(listener, worker_addr)));
+        worker
+    }
+
+    /// Adds or updates a faux drive.
+    ///
+    /// # Args
+    ///
+    /// `serial_number`:  The serial number of the drive.
+    ///
+    /// `scenario`:  The name of the `faux_makemkv` scenario run for the drive's disc.
+    ///
+    /// `disc`:  The label of the inserted disc or `None` if the drive is empty.
+    pub fn set_drive(&self, serial_number: &str, scenario: &str, disc: Option<&str>) {
+        let disc = disc.map(|label| {
+            serde_json::json!({ "label": label, "uuid": format!("{}-{}", serial_number, label) })
+        });
+        let drive = serde_json::json!({
+            "name": serial_number,
+            "path": format!("/dev/{}", scenario),
+            "serial_number": serial_number,
+            "disc": disc,
+        });
+
+        // The drive monitor reads the folder while the file is written, so it is written to a
+        // temporary file and renamed.
+        let path = self.drives.path().join(format!("{}.json", serial_number));
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, drive.to_string()).expect("failed to write faux drive");
+        fs::rename(&tmp_path, &path).expect("failed to write faux drive");
+    }
+
+    /// Stops the worker's server, closing the connection to the control node.
+    ///
+    /// The rest of the worker keeps running, like a worker whose network connection was lost.
+    pub fn disconnect(&self) {
+        self.server_ct.cancel();
+    }
+}
+
+/// Forwards connections to a worker node, dropping the hello the worker node sends first.
+///
+/// # Args
+///
+/// `listener`:  The listener the control node connects to.
+///
+/// `worker_addr`:  The address and port of the worker node.
+async fn drop_hello(listener: TcpListener, worker_addr: String) {
+    let listener = tokio::net::TcpListener::from_std(listener).expect("failed to start proxy");
+    while let Ok((control, _)) = listener.accept().await {
+        let Ok(worker) = TcpStream::connect(&worker_addr).await else {
+            continue;
+        };
+
+        let (mut control_reader, mut control_writer) = control.into_split();
+        let (worker_reader, mut worker_writer) = worker.into_split();
+        let mut worker_reader = BufReader::new(worker_reader);
+        let mut hello = String::new();
+        if worker_reader.read_line(&mut hello).await.is_err() {
+            continue;
+        }
+        if !hello.contains(r#""type":"Hello""#)
+            && control_writer.write_all(hello.as_bytes()).await.is_err()
+        {
+            continue;
+        }
+
+        tokio::select! {
+            _ = io::copy(&mut worker_reader, &mut control_writer) => {},
+            _ = io::copy(&mut control_reader, &mut worker_writer) => {},
+        }
+    }
+}
+
+/// A control node connected to worker nodes.
+pub struct Control {
+    /// The running node.
+    node: Node,
+}
+
+impl Control {
+    /// Starts a control node with no drives of its own.
+    ///
+    /// # Args
+    ///
+    /// `workers`:  The worker nodes to connect to.
+    pub fn start(workers: &[&Worker]) -> Self {
+        let shared = shared();
+
+        let settings = net::Settings {
+            workers: workers.iter().map(|worker| worker.addr.clone()).collect(),
+            ..net::Settings::default()
+        };
+
+        let (bus, bus_recv) = bus::init_channel();
+        let drive_mgr = drive::init_impl(&bus, Mode::Control, || Ok(Vec::new()))
+            .expect("failed to initialize drives");
+        let metadata = metadata::init(&bus, &metadata::Settings::default())
+            .expect("failed to initialize metadata");
+        let net = client::manager::init(&bus, &settings, None);
+        let notify = notify::init(&notify::Settings::default())
+            .expect("failed to initialize notify");
+        let join_handle = bus::init_processor(
+            Some(shared.db.clone()),
+            drive_mgr,
+            Some(metadata),
+            net,
+            notify,
+            bus_recv,
+        );
+
+        Self { node: Node { bus, join_handle } }
+    }
+
+    /// Handle used to send messages to the control node's actors.
+    pub fn bus(&self) -> &bus::Handle {
+        &self.node.bus
+    }
+
+    /// Waits for a drive to reach an expected state.
+    ///
+    /// # Args
+    ///
+    /// `serial_number`:  The serial number of the drive.
+    ///
+    /// `expected`:  Returns `true` once the drive is in the expected state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the drive does not reach the expected state within [`WAIT_TIMEOUT`].
+    pub async fn wait_for_drive<F>(&self, serial_number: &str, expected: F) -> OpticalDrive
+    where
+        F: Fn(&OpticalDrive) -> bool,
+    {
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+        loop {
+            let drive = drive::get(self.bus(), serial_number).await;
+            match drive {
+                Ok(drive) if expected(&drive) => return drive,
+                _ if Instant::now() >= deadline => {
+                    panic!("drive {} did not reach expected state: {:?}", serial_number, drive);
+                },
+                _ => time::sleep(POLL_INTERVAL).await,
+            }
+        }
+    }
+
+    /// Returns the copy operations performed on a drive, including their logs.
+    ///
+    /// # Args
+    ///
+    /// `serial_number`:  The serial number of the drive.
+    pub async fn copy_operations(&self, serial_number: &str) -> Vec<CopyOperation> {
+        let conn = db::connect(self.bus()).await.expect("failed to connect to database");
+        let Some(drive) = db::optical_drive::get_by_serial_number(&conn, serial_number)
+            .expect("failed to read drive")
+        else {
+            return Vec::new();
+        };
+        db::copy_operation::get_all(&conn, true)
+            .expect("failed to read copy operations")
+            .into_iter()
+            .filter(|copy_operation| copy_operation.drive.id == drive.id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::drive::{DiscState, OpticalDriveState};
+    use crate::models::{CopyParamaters, MediaType, OperationState};
+    use crate::net::sentinel::StorageCheck;
+
+    fn copy_parameters(title: &str) -> CopyParamaters {
+        CopyParamaters {
+            media_type: MediaType::Movie,
+            title: title.to_owned(),
+            release_year: 2024,
+            season_number: 0,
+            disc_number: 1,
+            location: String::from("Shelf A"),
+            memo: String::new(),
+            metadata_id: None,
+        }
+    }
+
+    /// Waits until the drive's disc can be copied.
+    async fn wait_for_ready(control: &Control, serial_number: &str) -> OpticalDrive {
+        control.wait_for_drive(serial_number, |drive| {
+            drive.state == OpticalDriveState::Idle
+                && matches!(drive.disc, DiscState::Inserted { .. })
+                && drive.storage.as_ref().is_some_and(|check| check.allows_copy())
+        }).await
+    }
+
+    /// Waits until the copy operation running on the drive has finished.
+    async fn wait_for_copy(control: &Control, serial_number: &str) -> OpticalDrive {
+        control.wait_for_drive(serial_number, |drive| {
+            matches!(drive.state, OpticalDriveState::Success | OpticalDriveState::Failed { .. })
+        }).await
+    }
+
+    #[test]
+    fn test_insert_disc() {
+        let worker = Worker::start();
+        worker.set_drive("HARNESS01", "short_films", None);
+        let control = Control::start(&[&worker]);
+
+        task::block_on(async {
+            let drive = control.wait_for_drive("HARNESS01", |drive| {
+                drive.state == OpticalDriveState::Idle
+            }).await;
+            assert_eq!(drive.disc, DiscState::None);
+
+            worker.set_drive("HARNESS01", "short_films", Some("SHORT_FILMS"));
+            let drive = control.wait_for_drive("HARNESS01", |drive| {
+                drive.disc != DiscState::None
+            }).await;
+            assert_eq!(drive.disc, DiscState::Inserted {
+                label: String::from("SHORT_FILMS"),
+                uuid: String::from("HARNESS01-SHORT_FILMS"),
+            });
+            assert_eq!(drive.state, OpticalDriveState::Idle);
+
+            assert!(control.copy_operations("HARNESS01").await.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_copy() {
+        let worker = Worker::start();
+        worker.set_drive("HARNESS02", "short_films", Some("SHORT_FILMS"));
+        let control = Control::start(&[&worker]);
+
+        task::block_on(async {
+            wait_for_ready(&control, "HARNESS02").await;
+            drive::begin_copy(control.bus(), "HARNESS02", copy_parameters("Copy")).await.unwrap();
+
+            let drive = wait_for_copy(&control, "HARNESS02").await;
+            assert_eq!(drive.state, OpticalDriveState::Success);
+
+            let copy_operations = control.copy_operations("HARNESS02").await;
+            assert_eq!(copy_operations.len(), 1);
+            let copy_operation = &copy_operations[0];
+            assert!(matches!(copy_operation.state, OperationState::Completed));
+            assert_eq!(copy_operation.title, "Copy");
+            assert_eq!(copy_operation.disc_uuid, "HARNESS02-SHORT_FILMS");
+            assert!(copy_operation.info_log.contains("Operation successfully completed"));
+            assert!(copy_operation.copy_log.contains("0 titles saved"));
+            assert!(path::disc_info_path(copy_operation).is_file());
+
+            drive::reset(control.bus(), "HARNESS02").await.unwrap();
+            wait_for_ready(&control, "HARNESS02").await;
+        });
+    }
+
+    #[test]
+    fn test_cancel_copy() {
+        let worker = Worker::start();
+        worker.set_drive("HARNESS03", "hang", Some("FEATURE_FILM"));
+        let control = Control::start(&[&worker]);
+
+        task::block_on(async {
+            wait_for_ready(&control, "HARNESS03").await;
+            drive::begin_copy(control.bus(), "HARNESS03", copy_parameters("Cancel")).await.unwrap();
+            control.wait_for_drive("HARNESS03", |drive| drive.state.is_copying()).await;
+            drive::cancel_copy(control.bus(), "HARNESS03").await.unwrap();
+
+            let drive = wait_for_copy(&control, "HARNESS03").await;
+            assert_eq!(drive.state, OpticalDriveState::Failed {
+                error: String::from("Copy operation was cancelled."),
+            });
+
+            let copy_operations = control.copy_operations("HARNESS03").await;
+            assert_eq!(copy_operations.len(), 1);
+            assert!(matches!(copy_operations[0].state, OperationState::Cancelled));
+
+            // The worker is still able to copy a disc after the cancelled command.
+            worker.set_drive("HARNESS03", "short_films", Some("SHORT_FILMS"));
+            drive::reset(control.bus(), "HARNESS03").await.unwrap();
+            control.wait_for_drive("HARNESS03", |drive| drive.path == "/dev/short_films").await;
+            wait_for_ready(&control, "HARNESS03").await;
+            drive::begin_copy(control.bus(), "HARNESS03", copy_parameters("Retry")).await.unwrap();
+            let drive = wait_for_copy(&control, "HARNESS03").await;
+            assert_eq!(drive.state, OpticalDriveState::Success);
+        });
+    }
+
+    #[test]
+    fn test_worker_disconnect() {
+        let worker = Worker::start();
+        worker.set_drive("HARNESS04", "hang", Some("FEATURE_FILM"));
+        let control = Control::start(&[&worker]);
+
+        task::block_on(async {
+            wait_for_ready(&control, "HARNESS04").await;
+            drive::begin_copy(control.bus(), "HARNESS04", copy_parameters("Drop")).await.unwrap();
+            control.wait_for_drive("HARNESS04", |drive| drive.state.is_copying()).await;
+            worker.disconnect();
+
+            let drive = wait_for_copy(&control, "HARNESS04").await;
+            assert!(matches!(drive.state, OpticalDriveState::Failed { .. }));
+
+            let copy_operations = control.copy_operations("HARNESS04").await;
+            assert_eq!(copy_operations.len(), 1);
+            assert!(matches!(copy_operations[0].state, OperationState::Failed { .. }));
+
+            drive::reset(control.bus(), "HARNESS04").await.unwrap();
+            control.wait_for_drive("HARNESS04", |drive| {
+                drive.state == OpticalDriveState::Disconnected
+            }).await;
+        });
+    }
+
+    #[test]
+    fn test_copy_titles() {
+        let worker = Worker::start();
+        worker.set_drive("HARNESS05", "feature_film", Some("FEATURE_FILM"));
+        let control = Control::start(&[&worker]);
+
+        task::block_on(async {
+            wait_for_ready(&control, "HARNESS05").await;
+            drive::begin_copy(control.bus(), "HARNESS05", copy_parameters("Titles")).await.unwrap();
+
+            let drive = wait_for_copy(&control, "HARNESS05").await;
+            assert_eq!(drive.state, OpticalDriveState::Success);
+
+            let copy_operations = control.copy_operations("HARNESS05").await;
+            assert_eq!(copy_operations.len(), 1);
+            let copy_operation = &copy_operations[0];
+            assert!(matches!(copy_operation.state, OperationState::Completed));
+
+            let conn = db::connect(control.bus()).await.unwrap();
+            let titles = db::title::get_by_copy_operation(&conn, copy_operation.id).unwrap();
+            assert_eq!(titles.len(), 2);
+
+            let mut videos = db::video::get_by_copy_operation(&conn, copy_operation.id).unwrap();
+            videos.sort_by_key(|video| video.id);
+            let sizes: Vec<u64> = videos.iter()
+                .map(|video| path::location_path(&video.location).unwrap())
+                .map(|path| fs::metadata(path).unwrap().len())
+                .collect();
+            assert_eq!(sizes, [1048576, 262144]);
+            assert!(videos.iter().all(|video| video.video_tracks.len() == 1));
+            assert!(videos.iter().all(|video| video.audio_tracks.len() == 1));
+        });
+    }
+
+    #[test]
+    fn test_copy_read_error() {
+        let worker = Worker::start();
+        worker.set_drive("HARNESS06", "read_error", Some("FEATURE_FILM"));
+        let control = Control::start(&[&worker]);
+
+        task::block_on(async {
+            wait_for_ready(&control, "HARNESS06").await;
+            drive::begin_copy(control.bus(), "HARNESS06", copy_parameters("Read")).await.unwrap();
+
+            let drive = wait_for_copy(&control, "HARNESS06").await;
+            assert_eq!(drive.state, OpticalDriveState::Failed {
+                error: String::from("Copying disc failed."),
+            });
+
+            let copy_operations = control.copy_operations("HARNESS06").await;
+            assert_eq!(copy_operations.len(), 1);
+            assert!(matches!(copy_operations[0].state, OperationState::Failed { .. }));
+        });
+    }
+
+    #[test]
+    fn test_copy_crash() {
+        let worker = Worker::start();
+        worker.set_drive("HARNESS07", "crash", Some("FEATURE_FILM"));
+        let control = Control::start(&[&worker]);
+
+        task::block_on(async {
+            wait_for_ready(&control, "HARNESS07").await;
+            drive::begin_copy(control.bus(), "HARNESS07", copy_parameters("Crash")).await.unwrap();
+
+            let drive = wait_for_copy(&control, "HARNESS07").await;
+            assert_eq!(drive.state, OpticalDriveState::Failed {
+                error: String::from("Copying disc failed."),
+            });
+
+            let copy_operations = control.copy_operations("HARNESS07").await;
+            assert_eq!(copy_operations.len(), 1);
+            assert!(matches!(copy_operations[0].state, OperationState::Failed { .. }));
+        });
+    }
+
+    #[test]
+    fn test_legacy_worker_copy() {
+        let worker = Worker::start_legacy();
+        worker.set_drive("HARNESS08", "short_films", Some("SHORT_FILMS"));
+        let control = Control::start(&[&worker]);
+
+        task::block_on(async {
+            let drive = wait_for_ready(&control, "HARNESS08").await;
+            assert_eq!(drive.storage, Some(StorageCheck::Unverified));
+            drive::begin_copy(control.bus(), "HARNESS08", copy_parameters("Legacy")).await.unwrap();
+
+            let drive = wait_for_copy(&control, "HARNESS08").await;
+            assert_eq!(drive.state, OpticalDriveState::Success);
+
+            let copy_operations = control.copy_operations("HARNESS08").await;
+            assert_eq!(copy_operations.len(), 1);
+            assert!(matches!(copy_operations[0].state, OperationState::Completed));
+        });
+    }
+}
@@ -0,0 +1,1024 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Exports and imports the catalog of copied discs and videos.
+//!
+//! # JSON Format
+//!
+//! The JSON export contains every host, optical drive, copy operation, title, transcode operation,
+//! and video record in the database (see [`Catalog`]). Records keep their ids so that the
+//! references between them, and the inbox folders named after the copy operations, remain valid
+//! after an import.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "exported": "2026-03-01T18:30:00+00:00",
+//!   "hosts": [{ "id": 1, "hostname": "ripper" }],
+//!   "drives": [{ "id": 1, "serial_number": "KZ8J6HD1234" }],
+//!   "copy_operations": [{ "id": 1, "state": "Completed", "drive_id": 1, "host_id": 1, ... }],
+//!   "titles": [{ "id": 1, "index": 0, "title": "Blade Runner", ... }],
+//!   "transcode_operations": [{ "id": 1, "host_id": 1, "title_id": 1, ... }],
+//!   "videos": [{ "id": 1, "title_id": 1, "location": { "Inbox": "..." }, ... }]
+//! }
+//! ```
+//!
+//! Dates are RFC 3339 strings, durations are a number of seconds, and checksums are hex strings.
+//! The MakeMKV logs, HandBrake logs, and disc metadata are stored compressed in the database. They
+//! are only exported (decompressed) when requested since they make up most of the size of the
+//! export.
+//!
+//! Catalogs exported before transcode operations were included can still be imported as long as
+//! none of their videos were transcoded.
+//!
+//! # CSV Format
+//!
+//! The CSV export is a flat listing of the library with one row per video that includes the
+//! details of the video's title (see [`CSV_HEADER`]). It is intended for spreadsheets and cannot be
+//! imported.
+//!
+//! # Import
+//!
+//! A JSON export can be imported using [`import_json`] to rebuild the database on a fresh install.
+//! The import is refused if the database already contains any records.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use blake3::Hash;
+
+use chrono::{DateTime, Utc};
+
+use rusqlite::Connection;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+use crate::bus;
+use crate::db;
+use crate::metadata::ProviderId;
+use crate::models::{
+    AudioTrack,
+    ContainerType,
+    CopyOperation,
+    Host,
+    MediaLocation,
+    MediaType,
+    OperationState,
+    OpticalDrive,
+    Reference,
+    SpecialFeature,
+    SubtitleTrack,
+    Title,
+    TranscodeOperation,
+    Video,
+    VideoSource,
+    VideoTrack,
+};
+
+/// The version of the JSON format written by [`export_json`].
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The columns of the CSV export.
+pub const CSV_HEADER: &[&str] = &[
+    "video_id",
+    "title_id",
+    "copy_operation_id",
+    "copied",
+    "media_type",
+    "title",
+    "year",
+    "season",
+    "episode_number",
+    "episode_count",
+    "special_feature",
+    "version",
+    "disc",
+    "disc_location",
+    "memo",
+    "discarded",
+    "location_area",
+    "location_path",
+    "container",
+    "duration_seconds",
+    "video",
+    "audio_languages",
+    "subtitle_languages",
+    "checksum",
+];
+
+/// The contents of a JSON export.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Catalog {
+    /// The version of the format (see [`FORMAT_VERSION`]).
+    pub version: u32,
+
+    /// When the catalog was exported.
+    #[serde(with = "rfc3339")]
+    pub exported: DateTime<Utc>,
+
+    /// The computers copy operations were performed on.
+    pub hosts: Vec<HostRecord>,
+
+    /// The optical drives discs were copied with.
+    pub drives: Vec<DriveRecord>,
+
+    /// The discs that were copied.
+    pub copy_operations: Vec<CopyOperationRecord>,
+
+    /// The titles copied from the discs.
+    pub titles: Vec<TitleRecord>,
+
+    /// The titles that were transcoded.
+    #[serde(default)]
+    pub transcode_operations: Vec<TranscodeOperationRecord>,
+
+    /// The video files created for the titles.
+    pub videos: Vec<VideoRecord>,
+}
+
+impl Catalog {
+    /// Summarizes the number of records in the catalog.
+    pub fn summary(&self) -> String {
+        format!(
+            concat!(
+                "{} hosts, {} drives, {} copy operations, {} titles, {} transcode operations, ",
+                "{} videos",
+            ),
+            self.hosts.len(),
+            self.drives.len(),
+            self.copy_operations.len(),
+            self.titles.len(),
+            self.transcode_operations.len(),
+            self.videos.len(),
+        )
+    }
+}
+
+/// A host record in a JSON export (see [`Host`]).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HostRecord {
+    pub id: u32,
+    pub hostname: String,
+}
+
+/// An optical drive record in a JSON export (see [`OpticalDrive`]).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DriveRecord {
+    pub id: u32,
+    pub serial_number: String,
+}
+
+/// A copy operation record in a JSON export (see [`CopyOperation`]).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CopyOperationRecord {
+    pub id: u32,
+    #[serde(with = "rfc3339")]
+    pub started: DateTime<Utc>,
+    #[serde(with = "rfc3339")]
+    pub completed: DateTime<Utc>,
+    pub state: OperationState,
+    pub media_type: MediaType,
+    pub title: String,
+    pub year: u16,
+    pub disc: u16,
+    pub disc_uuid: String,
+    pub season: u16,
+    pub location: String,
+    pub memo: String,
+    pub metadata_id: Option<ProviderId>,
+    pub drive_id: u32,
+    pub host_id: u32,
+
+    /// The disc metadata as JSON. Only included when the logs are exported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<String>,
+
+    /// The MakeMKV info command log. Only included when the logs are exported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info_log: Option<String>,
+
+    /// The MakeMKV copy command log. Only included when the logs are exported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copy_log: Option<String>,
+
+    /// The application's log events. Only included when the logs are exported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_log: Option<String>,
+}
+
+/// A title record in a JSON export (see [`Title`]).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TitleRecord {
+    pub id: u32,
+    pub index: u8,
+    pub media_type: MediaType,
+    pub title: String,
+    pub year: u16,
+    pub season: u16,
+    pub episode_number: u16,
+    pub episode_count: u16,
+    pub special_feature: Option<SpecialFeature>,
+    pub version: String,
+    pub disc: u16,
+    pub location: String,
+    pub memo: String,
+    pub metadata_id: Option<ProviderId>,
+    pub discarded: bool,
+}
+
+/// A transcode operation record in a JSON export (see [`TranscodeOperation`]).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TranscodeOperationRecord {
+    pub id: u32,
+    #[serde(with = "rfc3339")]
+    pub started: DateTime<Utc>,
+    #[serde(with = "rfc3339")]
+    pub completed: DateTime<Utc>,
+    pub state: OperationState,
+    pub episode_number: u16,
+    pub episode_count: u16,
+    pub special_feature: Option<SpecialFeature>,
+    pub version: String,
+    pub audio_tracks: Vec<AudioTrack>,
+    pub subtitle_tracks: Vec<SubtitleTrack>,
+    pub host_id: u32,
+    pub title_id: u32,
+
+    /// The HandBrake command log. Only included when the logs are exported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_log: Option<String>,
+}
+
+/// A video record in a JSON export (see [`Video`]).
+///
+/// Exactly one of `copy_operation_id` and `transcode_operation_id` is set.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct VideoRecord {
+    pub id: u32,
+    pub title_id: u32,
+    pub copy_operation_id: Option<u32>,
+    pub transcode_operation_id: Option<u32>,
+    pub location: MediaLocation,
+    #[serde(with = "checksum_hex")]
+    pub checksum: Hash,
+    pub container: ContainerType,
+    pub duration: u64,
+    pub video_tracks: Vec<VideoTrack>,
+    pub audio_tracks: Vec<AudioTrack>,
+    pub subtitle_tracks: Vec<SubtitleTrack>,
+    pub contact_sheet: Option<MediaLocation>,
+    pub preview_clip: Option<MediaLocation>,
+}
+
+/// Exports the catalog to a JSON file.
+///
+/// Returns a summary of the number of records that were exported.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the database actor.
+///
+/// `file_path`:  The path of the file to create.
+///
+/// `include_logs`:  Indicates if the MakeMKV logs and disc metadata should be included.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the records cannot be read.
+///
+/// [`Error::StdIo`] if the file cannot be written or the logs cannot be decompressed.
+///
+/// [`Error::SerdeJson`] if the catalog cannot be serialized.
+pub async fn export_json(
+    bus: &bus::Handle,
+    file_path: &Path,
+    include_logs: bool,
+) -> Result<String> {
+    let file_path = file_path.to_owned();
+    db::run(bus, move |conn| {
+        let catalog = read(conn, include_logs)?;
+        let mut writer = BufWriter::new(File::create(&file_path)?);
+        serde_json::to_writer_pretty(&mut writer, &catalog)?;
+        writer.flush()?;
+        tracing::info!(?file_path, "exported catalog");
+        Ok(catalog.summary())
+    }).await
+}
+
+/// Exports the library to a CSV file.
+///
+/// Returns a summary of the number of records that were exported.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the database actor.
+///
+/// `file_path`:  The path of the file to create.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the records cannot be read.
+///
+/// [`Error::StdIo`] if the file cannot be written.
+pub async fn export_csv(bus: &bus::Handle, file_path: &Path) -> Result<String> {
+    let file_path = file_path.to_owned();
+    db::run(bus, move |conn| {
+        let catalog = read(conn, false)?;
+        let mut writer = BufWriter::new(File::create(&file_path)?);
+        write_csv(&catalog, &mut writer)?;
+        writer.flush()?;
+        tracing::info!(?file_path, "exported library csv");
+        Ok(catalog.summary())
+    }).await
+}
+
+/// Imports a catalog from a JSON file created by [`export_json`].
+///
+/// Returns a summary of the number of records that were imported.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the database actor.
+///
+/// `file_path`:  The path of the exported file.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] if the file cannot be read.
+///
+/// [`Error::SerdeJson`] if the file is not a valid catalog.
+///
+/// [`Error::CatalogVersion`] if the catalog was exported using an unsupported format version.
+///
+/// [`Error::CatalogNotEmpty`] if the database already contains records.
+///
+/// [`Error::Database`] if any of the records cannot be created. None of the records will be
+/// created in that case.
+pub async fn import_json(bus: &bus::Handle, file_path: &Path) -> Result<String> {
+    let file_path = file_path.to_owned();
+    db::run(bus, move |conn| {
+        let reader = BufReader::new(File::open(&file_path)?);
+        let catalog: Catalog = serde_json::from_reader(reader)?;
+        let summary = catalog.summary();
+        import(conn, catalog)?;
+        tracing::info!(?file_path, "imported catalog");
+        Ok(summary)
+    }).await
+}
+
+/// Reads all of the catalog records from the database.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `include_logs`:  Indicates if the MakeMKV logs and disc metadata should be included.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the records cannot be read.
+///
+/// [`Error::StdIo`] if the logs cannot be decompressed.
+fn read(conn: &Connection, include_logs: bool) -> Result<Catalog> {
+    let hosts = db::host::get_all(conn)?
+        .into_iter()
+        .map(|h| HostRecord { id: h.id, hostname: h.hostname })
+        .collect();
+
+    let drives = db::optical_drive::get_all(conn)?
+        .into_iter()
+        .map(|d| DriveRecord { id: d.id, serial_number: d.serial_number })
+        .collect();
+
+    let copy_operations = db::copy_operation::get_all(conn, include_logs)?
+        .into_iter()
+        .map(|c| CopyOperationRecord::new(c, include_logs))
+        .collect();
+
+    let titles = db::title::get_all(conn)?
+        .into_iter()
+        .map(TitleRecord::from)
+        .collect();
+
+    let transcode_operations = db::transcode_operation::get_all(conn, include_logs)?
+        .into_iter()
+        .map(|t| TranscodeOperationRecord::new(t, include_logs))
+        .collect();
+
+    let videos = db::video::get_all(conn)?
+        .into_iter()
+        .map(VideoRecord::from)
+        .collect();
+
+    Ok(Catalog {
+        version: FORMAT_VERSION,
+        exported: Utc::now(),
+        hosts,
+        drives,
+        copy_operations,
+        titles,
+        transcode_operations,
+        videos,
+    })
+}
+
+/// Creates the records in a catalog keeping their ids.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `catalog`:  The catalog to import.
+///
+/// # Errors
+///
+/// [`Error::CatalogVersion`] if the catalog was exported using an unsupported format version.
+///
+/// [`Error::CatalogNotEmpty`] if the database already contains records.
+///
+/// [`Error::Database`] if any of the records cannot be created. None of the records will be
+/// created in that case.
+///
+/// [`Error::StdIo`] if the logs cannot be compressed.
+fn import(conn: &mut Connection, catalog: Catalog) -> Result<()> {
+    if catalog.version != FORMAT_VERSION {
+        return Err(Error::CatalogVersion { version: catalog.version });
+    }
+
+    let empty = db::host::get_all(conn)?.is_empty()
+        && db::optical_drive::get_all(conn)?.is_empty()
+        && db::copy_operation::get_all(conn, false)?.is_empty()
+        && db::title::get_all(conn)?.is_empty()
+        && db::transcode_operation::get_all(conn, false)?.is_empty();
+    if !empty {
+        return Err(Error::CatalogNotEmpty);
+    }
+
+    let transaction = db::transaction::start(conn)?;
+
+    for host in catalog.hosts {
+        db::host::import(&transaction, &Host { id: host.id, hostname: host.hostname })?;
+    }
+
+    for drive in catalog.drives {
+        let drive = OpticalDrive { id: drive.id, serial_number: drive.serial_number };
+        db::optical_drive::import(&transaction, &drive)?;
+    }
+
+    for copy_operation in catalog.copy_operations {
+        db::copy_operation::import(&transaction, &copy_operation.into())?;
+    }
+
+    for title in catalog.titles {
+        db::title::import(&transaction, &title.into())?;
+    }
+
+    for transcode_operation in catalog.transcode_operations {
+        db::transcode_operation::import(&transaction, &transcode_operation.into())?;
+    }
+
+    for video in catalog.videos {
+        db::video::import(&transaction, &video.into())?;
+    }
+
+    db::transaction::commit(transaction)
+}
+
+/// Writes the CSV listing of the videos in a catalog.
+///
+/// # Args
+///
+/// `catalog`:  The catalog to list.
+///
+/// `writer`:  Where the CSV data is written.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] if the data cannot be written.
+fn write_csv<W: Write>(catalog: &Catalog, writer: &mut W) -> Result<()> {
+    let titles: HashMap<u32, &TitleRecord> = catalog.titles.iter()
+        .map(|t| (t.id, t))
+        .collect();
+    let copy_operations: HashMap<u32, &CopyOperationRecord> = catalog.copy_operations.iter()
+        .map(|c| (c.id, c))
+        .collect();
+
+    write_csv_row(writer, CSV_HEADER.iter().map(|h| Cow::Borrowed(*h)))?;
+
+    for video in &catalog.videos {
+        let title = titles.get(&video.title_id);
+        let copy_operation = video.copy_operation_id.and_then(|id| copy_operations.get(&id));
+
+        let (area, path) = location_columns(&video.location);
+
+        let row = [
+            video.id.to_string(),
+            video.title_id.to_string(),
+            video.copy_operation_id.map(|id| id.to_string()).unwrap_or_default(),
+            copy_operation.map(|c| c.started.to_rfc3339()).unwrap_or_default(),
+            title.map(|t| t.media_type.to_string()).unwrap_or_default(),
+            title.map(|t| t.title.clone()).unwrap_or_default(),
+            title.map(|t| t.year.to_string()).unwrap_or_default(),
+            title.map(|t| t.season.to_string()).unwrap_or_default(),
+            title.map(|t| t.episode_number.to_string()).unwrap_or_default(),
+            title.map(|t| t.episode_count.to_string()).unwrap_or_default(),
+            title.and_then(|t| t.special_feature.as_ref())
+                .map(|s| format!("{}: {}", s.kind.as_str(), s.name))
+                .unwrap_or_default(),
+            title.map(|t| t.version.clone()).unwrap_or_default(),
+            title.map(|t| t.disc.to_string()).unwrap_or_default(),
+            title.map(|t| t.location.clone()).unwrap_or_default(),
+            title.map(|t| t.memo.clone()).unwrap_or_default(),
+            title.map(|t| t.discarded.to_string()).unwrap_or_default(),
+            area.to_owned(),
+            path,
+            format!("{:?}", video.container),
+            video.duration.to_string(),
+            video.video_tracks.first()
+                .map(|t| format!("{:?} {}", t.codec, t.size))
+                .unwrap_or_default(),
+            join_languages(video.audio_tracks.iter().map(|t| t.language.as_str())),
+            join_languages(video.subtitle_tracks.iter().map(|t| t.language.as_str())),
+            video.checksum.to_hex().to_string(),
+        ];
+
+        write_csv_row(writer, row.into_iter().map(Cow::Owned))?;
+    }
+
+    Ok(())
+}
+
+/// Writes a row of CSV fields, quoting the fields as needed.
+fn write_csv_row<'a, W, I>(writer: &mut W, fields: I) -> Result<()>
+where
+    W: Write,
+    I: Iterator<Item = Cow<'a, str>>,
+{
+    let line = fields.map(|f| csv_field(&f).into_owned())
+        .collect::<Vec<String>>()
+        .join(",");
+    writeln!(writer, "{}", line)?;
+    Ok(())
+}
+
+/// Quotes a CSV field if it contains a delimiter, quote, or line break.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Joins the languages of a video's tracks for the CSV export.
+fn join_languages<'a, I: Iterator<Item = &'a str>>(languages: I) -> String {
+    languages.collect::<Vec<&str>>().join(" ")
+}
+
+/// Splits a media location into the area and path columns of the CSV export.
+fn location_columns(location: &MediaLocation) -> (&'static str, String) {
+    match location {
+        MediaLocation::Inbox(path) => ("Inbox", path.display().to_string()),
+        MediaLocation::Library(path) => ("Library", path.display().to_string()),
+        MediaLocation::Archive(path) => ("Archive", path.display().to_string()),
+        MediaLocation::Deleted => ("Deleted", String::new()),
+    }
+}
+
+impl CopyOperationRecord {
+    /// Creates the record for a copy operation.
+    ///
+    /// The logs and disc metadata are only included if `include_logs` is `true`.
+    fn new(copy_operation: CopyOperation, include_logs: bool) -> Self {
+        let logs = |value: String| include_logs.then_some(value);
+
+        Self {
+            id: copy_operation.id,
+            started: copy_operation.started,
+            completed: copy_operation.completed,
+            state: copy_operation.state,
+            media_type: copy_operation.media_type,
+            title: copy_operation.title,
+            year: copy_operation.year,
+            disc: copy_operation.disc,
+            disc_uuid: copy_operation.disc_uuid,
+            season: copy_operation.season,
+            location: copy_operation.location,
+            memo: copy_operation.memo,
+            metadata_id: copy_operation.metadata_id,
+            drive_id: copy_operation.drive.id,
+            host_id: copy_operation.host.id,
+            metadata: logs(copy_operation.metadata),
+            info_log: logs(copy_operation.info_log),
+            copy_log: logs(copy_operation.copy_log),
+            app_log: logs(copy_operation.app_log),
+        }
+    }
+}
+
+impl From<CopyOperationRecord> for CopyOperation {
+    fn from(record: CopyOperationRecord) -> Self {
+        Self {
+            id: record.id,
+            started: record.started,
+            completed: record.completed,
+            state: record.state,
+            media_type: record.media_type,
+            title: record.title,
+            year: record.year,
+            disc: record.disc,
+            disc_uuid: record.disc_uuid,
+            season: record.season,
+            location: record.location,
+            memo: record.memo,
+            metadata: record.metadata.unwrap_or_default(),
+            drive: Reference { id: record.drive_id, value: None },
+            info_log: record.info_log.unwrap_or_default(),
+            copy_log: record.copy_log.unwrap_or_default(),
+            app_log: record.app_log.unwrap_or_default(),
+            host: Reference { id: record.host_id, value: None },
+            metadata_id: record.metadata_id,
+            ..CopyOperation::default()
+        }
+    }
+}
+
+impl From<Title> for TitleRecord {
+    fn from(title: Title) -> Self {
+        Self {
+            id: title.id,
+            index: title.index,
+            media_type: title.media_type,
+            title: title.title,
+            year: title.year,
+            season: title.season,
+            episode_number: title.episode_number,
+            episode_count: title.episode_count,
+            special_feature: title.special_feature,
+            version: title.version,
+            disc: title.disc,
+            location: title.location,
+            memo: title.memo,
+            metadata_id: title.metadata_id,
+            discarded: title.discarded,
+        }
+    }
+}
+
+impl From<TitleRecord> for Title {
+    fn from(record: TitleRecord) -> Self {
+        Self {
+            id: record.id,
+            index: record.index,
+            media_type: record.media_type,
+            title: record.title,
+            year: record.year,
+            season: record.season,
+            episode_number: record.episode_number,
+            episode_count: record.episode_count,
+            special_feature: record.special_feature,
+            version: record.version,
+            disc: record.disc,
+            location: record.location,
+            memo: record.memo,
+            metadata_id: record.metadata_id,
+            discarded: record.discarded,
+            videos: None,
+        }
+    }
+}
+
+impl TranscodeOperationRecord {
+    /// Creates the record for a transcode operation.
+    ///
+    /// The command log is only included if `include_logs` is `true`.
+    fn new(transcode_operation: TranscodeOperation, include_logs: bool) -> Self {
+        Self {
+            id: transcode_operation.id,
+            started: transcode_operation.started,
+            completed: transcode_operation.completed,
+            state: transcode_operation.state,
+            episode_number: transcode_operation.episode_number,
+            episode_count: transcode_operation.episode_count,
+            special_feature: transcode_operation.special_feature,
+            version: transcode_operation.version,
+            audio_tracks: transcode_operation.audio_tracks,
+            subtitle_tracks: transcode_operation.subtitle_tracks,
+            host_id: transcode_operation.host.id,
+            title_id: transcode_operation.title.id,
+            command_log: include_logs.then_some(transcode_operation.command_log),
+        }
+    }
+}
+
+impl From<TranscodeOperationRecord> for TranscodeOperation {
+    fn from(record: TranscodeOperationRecord) -> Self {
+        Self {
+            id: record.id,
+            started: record.started,
+            completed: record.completed,
+            state: record.state,
+            episode_number: record.episode_number,
+            episode_count: record.episode_count,
+            special_feature: record.special_feature,
+            version: record.version,
+            audio_tracks: record.audio_tracks,
+            subtitle_tracks: record.subtitle_tracks,
+            command_log: record.command_log.unwrap_or_default(),
+            host: Reference { id: record.host_id, value: None },
+            title: Reference { id: record.title_id, value: None },
+            source_video: Reference { id: 0, value: None },
+        }
+    }
+}
+
+impl From<Video> for VideoRecord {
+    fn from(video: Video) -> Self {
+        let (copy_operation_id, transcode_operation_id) = match video.source {
+            VideoSource::CopyOperation(reference) => (Some(reference.id), None),
+            VideoSource::TranscodeOperation(reference) => (None, Some(reference.id)),
+        };
+
+        Self {
+            id: video.id,
+            title_id: video.title.id,
+            copy_operation_id,
+            transcode_operation_id,
+            location: video.location,
+            checksum: video.checksum,
+            container: video.container,
+            duration: video.duration.as_secs(),
+            video_tracks: video.video_tracks,
+            audio_tracks: video.audio_tracks,
+            subtitle_tracks: video.subtitle_tracks,
+            contact_sheet: video.contact_sheet,
+            preview_clip: video.preview_clip,
+        }
+    }
+}
+
+impl From<VideoRecord> for Video {
+    fn from(record: VideoRecord) -> Self {
+        let source = match (record.copy_operation_id, record.transcode_operation_id) {
+            (Some(id), _) => VideoSource::CopyOperation(Reference { id, value: None }),
+            (None, id) => VideoSource::TranscodeOperation(Reference {
+                id: id.unwrap_or_default(),
+                value: None,
+            }),
+        };
+
+        Self {
+            id: record.id,
+            location: record.location,
+            checksum: record.checksum,
+            container: record.container,
+            video_tracks: record.video_tracks,
+            audio_tracks: record.audio_tracks,
+            subtitle_tracks: record.subtitle_tracks,
+            source,
+            title: Reference { id: record.title_id, value: None },
+            duration: Duration::from_secs(record.duration),
+            contact_sheet: record.contact_sheet,
+            preview_clip: record.preview_clip,
+        }
+    }
+}
+
+/// Serializes a date/time as an RFC 3339 string.
+mod rfc3339 {
+    use chrono::{DateTime, Utc};
+
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+
+    pub fn serialize<S: Serializer>(value: &DateTime<Utc>, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&value.to_rfc3339())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<DateTime<Utc>, D::Error> {
+        let value = String::deserialize(d)?;
+        DateTime::parse_from_rfc3339(&value)
+            .map(|v| v.with_timezone(&Utc))
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Serializes a checksum as a hex string.
+mod checksum_hex {
+    use blake3::Hash;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+
+    pub fn serialize<S: Serializer>(value: &Hash, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(value.to_hex().as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Hash, D::Error> {
+        let value = String::deserialize(d)?;
+        Hash::from_hex(value).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    use crate::models::{AudioCodec, SpecialFeatureType};
+
+    /// Creates a database containing a single copied title.
+    fn setup_test_db() -> Connection {
+        let conn = db::open_in_memory();
+
+        let host = db::host::create(&conn, "ripper").unwrap();
+        let drive = db::optical_drive::create(&conn, "SN-TEST-001").unwrap();
+
+        let mut copy_operation = CopyOperation {
+            state: OperationState::Failed { reason: String::from("disc, \"scratched\"") },
+            title: String::from("Test Movie"),
+            year: 2024,
+            host: Reference { id: host.id, value: None },
+            drive: Reference { id: drive.id, value: None },
+            ..CopyOperation::default()
+        };
+        db::copy_operation::create(&conn, &mut copy_operation).unwrap();
+        db::copy_operation::set_info_log(&conn, &mut copy_operation, "info log").unwrap();
+
+        let mut title = Title {
+            id: 0,
+            index: 3,
+            media_type: MediaType::Movie,
+            title: String::from("Test Movie"),
+            year: 2024,
+            season: 0,
+            episode_number: 0,
+            episode_count: 0,
+            special_feature: Some(SpecialFeature {
+                kind: SpecialFeatureType::Trailers,
+                name: String::from("Teaser"),
+            }),
+            version: String::new(),
+            disc: 1,
+            location: String::from("shelf-a"),
+            memo: String::from("line one\nline two"),
+            metadata_id: None,
+            discarded: false,
+            videos: None,
+        };
+        db::title::create(&conn, &mut title).unwrap();
+
+        let mut video = Video {
+            id: 0,
+            location: MediaLocation::Inbox(PathBuf::from("movie/1/title_t03.mkv")),
+            checksum: blake3::hash(b"video"),
+            container: ContainerType::MKV,
+            video_tracks: Vec::new(),
+            audio_tracks: vec![AudioTrack {
+                index: 1,
+                name: String::from("Surround 5.1"),
+                codec: AudioCodec::AC3,
+                encode_method: None,
+                language: String::from("eng"),
+                channel_count: 6,
+                channel_layout: String::from("5.1(side)"),
+            }],
+            subtitle_tracks: Vec::new(),
+            source: VideoSource::CopyOperation(Reference { id: copy_operation.id, value: None }),
+            title: Reference { id: title.id, value: None },
+            duration: Duration::from_secs(5400),
+            contact_sheet: None,
+            preview_clip: None,
+        };
+        db::video::create(&conn, &mut video).unwrap();
+
+        conn
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let conn = setup_test_db();
+        let catalog = read(&conn, true).unwrap();
+        assert_eq!(catalog.copy_operations[0].info_log.as_deref(), Some("info log"));
+
+        let json = serde_json::to_string(&catalog).unwrap();
+
+        let mut imported = db::open_in_memory();
+        import(&mut imported, serde_json::from_str(&json).unwrap()).unwrap();
+
+        let mut reexported = read(&imported, true).unwrap();
+        reexported.exported = catalog.exported;
+        assert_eq!(serde_json::to_string(&reexported).unwrap(), json);
+    }
+
+    #[test]
+    fn test_round_trip_transcoded_video() {
+        let conn = setup_test_db();
+        let title = &db::title::get_all(&conn).unwrap()[0];
+        let host = &db::host::get_all(&conn).unwrap()[0];
+
+        let transcode_operation = TranscodeOperation {
+            id: 1,
+            started: DateTime::from_timestamp(100, 0).unwrap(),
+            completed: DateTime::from_timestamp(200, 0).unwrap(),
+            state: OperationState::Completed,
+            episode_number: 0,
+            episode_count: 0,
+            special_feature: None,
+            version: String::new(),
+            audio_tracks: Vec::new(),
+            subtitle_tracks: Vec::new(),
+            command_log: String::from("command log"),
+            host: Reference { id: host.id, value: None },
+            title: Reference { id: title.id, value: None },
+            source_video: Reference { id: 1, value: None },
+        };
+        db::transcode_operation::import(&conn, &transcode_operation).unwrap();
+
+        let mut video = Video {
+            id: 0,
+            location: MediaLocation::Library(PathBuf::from("Test Movie (2024)/Test Movie.mkv")),
+            checksum: blake3::hash(b"transcoded"),
+            container: ContainerType::MKV,
+            video_tracks: Vec::new(),
+            audio_tracks: Vec::new(),
+            subtitle_tracks: Vec::new(),
+            source: VideoSource::TranscodeOperation(Reference { id: 1, value: None }),
+            title: Reference { id: title.id, value: None },
+            duration: Duration::from_secs(5400),
+            contact_sheet: None,
+            preview_clip: None,
+        };
+        db::video::create(&conn, &mut video).unwrap();
+
+        let catalog = read(&conn, true).unwrap();
+        assert_eq!(catalog.transcode_operations[0].command_log.as_deref(), Some("command log"));
+        assert_eq!(catalog.videos[1].transcode_operation_id, Some(1));
+
+        let json = serde_json::to_string(&catalog).unwrap();
+
+        let mut imported = db::open_in_memory();
+        imported.pragma_update(None, "foreign_keys", true).unwrap();
+        import(&mut imported, serde_json::from_str(&json).unwrap()).unwrap();
+
+        let mut reexported = read(&imported, true).unwrap();
+        reexported.exported = catalog.exported;
+        assert_eq!(serde_json::to_string(&reexported).unwrap(), json);
+    }
+
+    #[test]
+    fn test_export_without_logs() {
+        let conn = setup_test_db();
+        let catalog = read(&conn, false).unwrap();
+
+        let json = serde_json::to_string(&catalog).unwrap();
+        assert!(!json.contains("info_log"));
+    }
+
+    #[test]
+    fn test_import_rejects_existing_records() {
+        let mut conn = setup_test_db();
+        let catalog = read(&conn, false).unwrap();
+        assert!(matches!(import(&mut conn, catalog), Err(Error::CatalogNotEmpty)));
+    }
+
+    #[test]
+    fn test_import_rejects_unknown_version() {
+        let conn = setup_test_db();
+        let mut catalog = read(&conn, false).unwrap();
+        catalog.version = FORMAT_VERSION + 1;
+
+        let mut imported = db::open_in_memory();
+        assert!(matches!(import(&mut imported, catalog), Err(Error::CatalogVersion { .. })));
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let conn = setup_test_db();
+        let catalog = read(&conn, false).unwrap();
+
+        let mut bytes = Vec::new();
+        write_csv(&catalog, &mut bytes).unwrap();
+        let csv = String::from_utf8(bytes).unwrap();
+
+        let (header, row) = csv.split_once('\n').unwrap();
+        assert_eq!(header, CSV_HEADER.join(","));
+        assert!(row.contains(",Movie,Test Movie,2024,"));
+        assert!(row.contains(",Trailers: Teaser,"));
+        assert!(row.contains(",\"line one\nline two\","));
+        assert!(row.contains(",Inbox,movie/1/title_t03.mkv,"));
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,539 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Proposes episode numbers for the titles copied from a television show disc.
+//!
+//! The titles on a show disc are usually a run of episodes of a similar length, possibly a "play
+//! all" title that concatenates those episodes, and a number of short special features. Using the
+//! order of the titles on the disc and their durations, [`propose`] will classify each title and
+//! number the episodes sequentially starting from the episode following the last episode of the
+//! previous disc in the season.
+//!
+//! The result is only a proposal. It is expected to be reviewed (and corrected) by the user before
+//! the titles are classified using [`Proposal::classification`].
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use rusqlite::Connection;
+
+use crate::Result;
+use crate::db;
+use crate::metadata::MediaDetails;
+use crate::models::{Classification, SpecialFeature, SpecialFeatureType, Title};
+
+/// Titles shorter than this fraction of the typical episode runtime are special features.
+const SPECIAL_RATIO: f64 = 0.6;
+
+/// Titles longer than this fraction of the typical episode runtime span multiple episodes.
+const EPISODE_RATIO: f64 = 1.4;
+
+/// The maximum number of episodes a single title is assumed to cover.
+///
+/// Anything longer that does not match a run of episodes is assumed to be a "play all" title.
+const MAX_EPISODE_COUNT: u16 = 3;
+
+/// Titles shorter than this are ignored when estimating the typical episode runtime from the titles
+/// themselves.
+const MIN_EPISODE_DURATION: Duration = Duration::from_secs(5 * 60);
+
+/// A title that can be assigned an episode.
+#[derive(Clone, Debug)]
+pub struct Candidate {
+    /// The id of the title.
+    pub title_id: u32,
+
+    /// The index of the title on the disc.
+    pub index: u8,
+
+    /// The duration of the title.
+    pub duration: Duration,
+}
+
+/// What a title was determined to contain.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Assignment {
+    /// One or more consecutive episodes.
+    Episode {
+        /// The number of the first episode.
+        number: u16,
+
+        /// The number of episodes.
+        count: u16,
+    },
+
+    /// A title that plays several of the episodes on the disc back to back.
+    PlayAll,
+
+    /// A special feature (extras, trailers, etc.).
+    Special,
+}
+
+/// The proposed assignment for a title.
+#[derive(Clone, Debug)]
+pub struct Proposal {
+    /// The id of the title.
+    pub title_id: u32,
+
+    /// The index of the title on the disc.
+    pub index: u8,
+
+    /// The duration of the title.
+    pub duration: Duration,
+
+    /// What the title is proposed to contain.
+    pub assignment: Assignment,
+
+    /// The combined runtime of the assigned episodes if known.
+    pub expected: Option<Duration>,
+
+    /// Whether the duration of the title is a good match for the assignment.
+    ///
+    /// Proposals that are not confident should be given a closer look when reviewing.
+    pub confident: bool,
+}
+
+impl Proposal {
+    /// Converts the proposed assignment into the classification of the title.
+    ///
+    /// "Play all" titles are discarded since their content is already covered by the episode
+    /// titles. Special features are classified as extras.
+    pub fn classification(&self) -> Classification {
+        match self.assignment {
+            Assignment::Episode { number, count } => Classification::Episodes { number, count },
+            Assignment::PlayAll => Classification::Discard,
+            Assignment::Special => Classification::Special(SpecialFeature {
+                kind: SpecialFeatureType::Extras,
+                name: String::default(),
+            }),
+        }
+    }
+}
+
+/// Information known about the season the titles belong to.
+#[derive(Clone, Debug)]
+pub struct Context {
+    /// The number of the first episode on the disc.
+    pub first_episode: u16,
+
+    /// Known episode runtimes keyed by episode number.
+    pub runtimes: BTreeMap<u16, Duration>,
+
+    /// The typical runtime of an episode if known.
+    ///
+    /// When `None`, the median of the known runtimes will be used. If no runtimes are known, it
+    /// will be estimated from the durations of the titles.
+    pub typical: Option<Duration>,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self { first_episode: 1, runtimes: BTreeMap::new(), typical: None }
+    }
+}
+
+/// Proposes an assignment for each of the titles.
+///
+/// # Args
+///
+/// `candidates`:  The titles ordered by their index on the disc.
+///
+/// `context`:  Information known about the season.
+pub fn propose(candidates: &[Candidate], context: &Context) -> Vec<Proposal> {
+    let typical = context.typical
+        .or_else(|| median(context.runtimes.values().copied().collect()))
+        .or_else(|| median(candidates.iter()
+            .map(|c| c.duration)
+            .filter(|d| *d >= MIN_EPISODE_DURATION)
+            .collect()));
+
+    let Some(typical) = typical else {
+        return candidates.iter()
+            .map(|c| make_proposal(c, Assignment::Special, None, false))
+            .collect();
+    };
+
+    let ratios: Vec<f64> = candidates.iter()
+        .map(|c| c.duration.as_secs_f64() / typical.as_secs_f64())
+        .collect();
+    let single: Vec<bool> = ratios.iter()
+        .map(|r| (SPECIAL_RATIO..=EPISODE_RATIO).contains(r))
+        .collect();
+
+    let mut proposals = Vec::with_capacity(candidates.len());
+    let mut next_episode = context.first_episode;
+
+    for (i, candidate) in candidates.iter().enumerate() {
+        let ratio = ratios[i];
+        let count = if ratio < SPECIAL_RATIO {
+            None
+        } else if single[i] {
+            Some(1)
+        } else if is_play_all(candidates, &single, i) {
+            None
+        } else {
+            let count = ratio.round() as u16;
+            (count <= MAX_EPISODE_COUNT && (ratio - ratio.round()).abs() <= 0.3).then_some(count)
+        };
+
+        let proposal = match count {
+            Some(count) => {
+                let assignment = Assignment::Episode { number: next_episode, count };
+                let expected = (next_episode..next_episode + count)
+                    .map(|n| context.runtimes.get(&n).copied())
+                    .sum::<Option<Duration>>();
+                let confident = match expected {
+                    Some(expected) => within(candidate.duration, expected, 0.1),
+                    None => within(candidate.duration, typical * u32::from(count), 0.15),
+                };
+                next_episode += count;
+                make_proposal(candidate, assignment, expected, confident)
+            },
+            None if ratio < SPECIAL_RATIO => {
+                make_proposal(candidate, Assignment::Special, None, ratio < SPECIAL_RATIO / 2.0)
+            },
+            None => make_proposal(candidate, Assignment::PlayAll, None, true),
+        };
+
+        proposals.push(proposal);
+    }
+
+    proposals
+}
+
+/// Builds the proposals for the titles created by a copy operation.
+///
+/// The first episode is the episode following the last episode assigned on a previous disc of the
+/// same season. Episode runtimes are taken from the metadata when it is provided. The typical
+/// runtime is taken from the episodes of the previous discs.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `copy_operation_id`:  The id of the copy operation.
+///
+/// `details`:  The metadata for the show if available.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] raised if a database operation fails.
+pub fn load_proposals(
+    conn: &Connection,
+    copy_operation_id: u32,
+    details: Option<&MediaDetails>,
+) -> Result<Vec<Proposal>> {
+    let titles = db::title::get_by_copy_operation(conn, copy_operation_id)?;
+    let Some(first) = titles.first() else {
+        return Ok(Vec::new());
+    };
+
+    let videos = db::video::get_by_copy_operation(conn, copy_operation_id)?;
+    let candidates: Vec<Candidate> = titles.iter()
+        .filter_map(|t| {
+            let video = videos.iter().find(|v| v.title.id == t.id)?;
+            Some(Candidate { title_id: t.id, index: t.index, duration: video.duration })
+        })
+        .collect();
+
+    let mut context = Context::default();
+
+    let season = db::title::get_by_season(conn, &first.title, first.year, first.season)?;
+    let previous: Vec<Title> = season.into_iter()
+        .filter(|t| t.disc < first.disc && t.episode_number > 0)
+        .collect();
+    if let Some(last) = previous.iter().map(|t| t.episode_number + t.episode_count.max(1)).max() {
+        context.first_episode = last;
+    }
+
+    let mut durations = Vec::new();
+    for title in previous.iter().filter(|t| t.episode_count <= 1) {
+        durations.extend(db::video::get_by_title(conn, title.id)?.into_iter().map(|v| v.duration));
+    }
+    context.typical = median(durations);
+
+    if let Some(season) = details.and_then(|d| d.season(first.season)) {
+        context.runtimes = season.episodes.iter()
+            .filter_map(|e| Some((e.number, e.runtime?)))
+            .collect();
+    }
+
+    Ok(propose(&candidates, &context))
+}
+
+/// Creates a proposal for a candidate.
+fn make_proposal(
+    candidate: &Candidate,
+    assignment: Assignment,
+    expected: Option<Duration>,
+    confident: bool,
+) -> Proposal {
+    Proposal {
+        title_id: candidate.title_id,
+        index: candidate.index,
+        duration: candidate.duration,
+        assignment,
+        expected,
+        confident,
+    }
+}
+
+/// Determines if a title plays a run of two or more consecutive single episode titles.
+///
+/// # Args
+///
+/// `candidates`:  All of the titles.
+///
+/// `single`:  Whether each of the titles looks like a single episode.
+///
+/// `index`:  The index (in `candidates`) of the title being checked.
+fn is_play_all(candidates: &[Candidate], single: &[bool], index: usize) -> bool {
+    let duration = candidates[index].duration;
+
+    for start in 0..candidates.len() {
+        let mut total = Duration::ZERO;
+        for end in start..candidates.len() {
+            if end == index || !single[end] {
+                break;
+            }
+            total += candidates[end].duration;
+            if end > start && within(duration, total, 0.02) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Determines if a duration is within a relative tolerance of the expected duration.
+///
+/// A minimum tolerance of one minute is always allowed since runtimes are often only known to the
+/// minute.
+fn within(duration: Duration, expected: Duration, tolerance: f64) -> bool {
+    let tolerance = expected.mul_f64(tolerance).max(Duration::from_secs(60));
+    duration.abs_diff(expected) <= tolerance
+}
+
+/// Calculates the median of the durations returning `None` if there are none.
+fn median(mut durations: Vec<Duration>) -> Option<Duration> {
+    if durations.is_empty() {
+        return None;
+    }
+    durations.sort();
+    let middle = durations.len() / 2;
+    if durations.len().is_multiple_of(2) {
+        Some((durations[middle - 1] + durations[middle]) / 2)
+    } else {
+        Some(durations[middle])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::metadata::{Episode, ProviderId, ProviderKind, Season};
+    use crate::models::{
+        ContainerType,
+        CopyOperation,
+        MediaLocation,
+        MediaType,
+        Reference,
+        Video,
+        VideoSource,
+    };
+
+    fn minutes(values: &[u64]) -> Vec<Candidate> {
+        values.iter()
+            .enumerate()
+            .map(|(i, m)| Candidate {
+                title_id: i as u32 + 1,
+                index: i as u8,
+                duration: Duration::from_secs(m * 60),
+            })
+            .collect()
+    }
+
+    fn assignments(proposals: &[Proposal]) -> Vec<Assignment> {
+        proposals.iter().map(|p| p.assignment).collect()
+    }
+
+    fn episode(number: u16, count: u16) -> Assignment {
+        Assignment::Episode { number, count }
+    }
+
+    #[test]
+    fn test_median() {
+        let d = Duration::from_secs;
+        assert_eq!(median(Vec::new()), None);
+        assert_eq!(median(vec![d(3), d(1), d(2)]), Some(d(2)));
+        assert_eq!(median(vec![d(4), d(1), d(2), d(3)]), Some(d(2) + Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_propose_standard_disc() {
+        let candidates = minutes(&[88, 22, 22, 23, 21, 3, 8]);
+        let proposals = propose(&candidates, &Context::default());
+
+        assert_eq!(assignments(&proposals), vec![
+            Assignment::PlayAll,
+            episode(1, 1),
+            episode(2, 1),
+            episode(3, 1),
+            episode(4, 1),
+            Assignment::Special,
+            Assignment::Special,
+        ]);
+        assert!(proposals[1].confident);
+        assert!(proposals[1].expected.is_none());
+    }
+
+    #[test]
+    fn test_propose_double_episode() {
+        let candidates = minutes(&[44, 88, 47, 45]);
+        let proposals = propose(&candidates, &Context::default());
+
+        assert_eq!(assignments(&proposals), vec![
+            episode(1, 1),
+            episode(2, 2),
+            episode(4, 1),
+            episode(5, 1),
+        ]);
+    }
+
+    #[test]
+    fn test_propose_continues_previous_disc() {
+        let candidates = minutes(&[44, 45, 44]);
+        let context = Context { first_episode: 5, ..Context::default() };
+        let proposals = propose(&candidates, &context);
+
+        assert_eq!(assignments(&proposals), vec![episode(5, 1), episode(6, 1), episode(7, 1)]);
+    }
+
+    #[test]
+    fn test_propose_known_runtimes() {
+        let candidates = minutes(&[42, 58, 43]);
+        let runtimes = [(1, 42), (2, 43), (3, 43)].into_iter()
+            .map(|(n, m)| (n, Duration::from_secs(m * 60)))
+            .collect();
+        let context = Context { runtimes, ..Context::default() };
+        let proposals = propose(&candidates, &context);
+
+        assert_eq!(assignments(&proposals), vec![episode(1, 1), episode(2, 1), episode(3, 1)]);
+        assert_eq!(proposals[0].expected, Some(Duration::from_secs(42 * 60)));
+        assert!(proposals[0].confident);
+        assert!(!proposals[1].confident);
+        assert!(proposals[2].confident);
+    }
+
+    #[test]
+    fn test_propose_no_episodes() {
+        let candidates = minutes(&[2, 3]);
+        let proposals = propose(&candidates, &Context::default());
+
+        assert_eq!(assignments(&proposals), vec![Assignment::Special, Assignment::Special]);
+    }
+
+    fn make_disc(conn: &Connection, disc: u16, durations: &[u64]) -> u32 {
+        let host = db::host::get_or_create(conn, "testhost").unwrap();
+        let drive = db::optical_drive::create(conn, &format!("SN-{disc}")).unwrap();
+        let mut op = CopyOperation {
+            media_type: MediaType::Show,
+            title: "Test Show".to_owned(),
+            year: 2004,
+            season: 1,
+            disc,
+            host: Reference { id: host.id, value: None },
+            drive: Reference { id: drive.id, value: None },
+            ..CopyOperation::default()
+        };
+        db::copy_operation::create(conn, &mut op).unwrap();
+
+        for (index, minutes) in durations.iter().enumerate() {
+            let mut title = Title {
+                id: 0,
+                index: index as u8,
+                media_type: MediaType::Show,
+                title: "Test Show".to_owned(),
+                year: 2004,
+                season: 1,
+                episode_number: 0,
+                episode_count: 0,
+                special_feature: None,
+                version: String::default(),
+                disc,
+                location: String::default(),
+                memo: String::default(),
+                metadata_id: None,
+                discarded: false,
+                videos: None,
+            };
+            db::title::create(conn, &mut title).unwrap();
+
+            let mut video = Video {
+                id: 0,
+                location: MediaLocation::Inbox(format!("disc{disc}/title_{index:02}.mkv").into()),
+                checksum: blake3::hash(format!("{disc}-{index}").as_bytes()),
+                container: ContainerType::MKV,
+                video_tracks: Vec::new(),
+                audio_tracks: Vec::new(),
+                subtitle_tracks: Vec::new(),
+                source: VideoSource::CopyOperation(Reference { id: op.id, value: None }),
+                title: Reference { id: title.id, value: None },
+                duration: Duration::from_secs(minutes * 60),
+                contact_sheet: None,
+                preview_clip: None,
+            };
+            db::video::create(conn, &mut video).unwrap();
+        }
+
+        op.id
+    }
+
+    #[test]
+    fn test_load_proposals() {
+        let conn = db::open_in_memory();
+        let disc1 = make_disc(&conn, 1, &[45, 44, 46, 3]);
+        let disc2 = make_disc(&conn, 2, &[44, 44, 88, 2]);
+
+        let proposals = load_proposals(&conn, disc1, None).unwrap();
+        let mut titles = db::title::get_by_copy_operation(&conn, disc1).unwrap();
+        for (title, proposal) in titles.iter_mut().zip(&proposals) {
+            title.classify(proposal.classification());
+            db::title::update(&conn, title).unwrap();
+        }
+
+        let titles = db::title::get_by_copy_operation(&conn, disc1).unwrap();
+        let numbers: Vec<_> = titles.iter().map(|t| (t.episode_number, t.episode_count)).collect();
+        assert_eq!(numbers, vec![(1, 1), (2, 1), (3, 1), (0, 0)]);
+        assert!(titles[3].special_feature.is_some());
+
+        let details = MediaDetails {
+            id: ProviderId { provider: ProviderKind::Mock, id: 1 },
+            media_type: MediaType::Show,
+            name: "Test Show".to_owned(),
+            year: 2004,
+            runtime: None,
+            seasons: vec![Season {
+                number: 1,
+                episodes: (1..=6)
+                    .map(|number| Episode {
+                        number,
+                        name: String::default(),
+                        runtime: Some(Duration::from_secs(44 * 60)),
+                    })
+                    .collect(),
+            }],
+        };
+        let proposals = load_proposals(&conn, disc2, Some(&details)).unwrap();
+
+        assert_eq!(assignments(&proposals), vec![
+            episode(4, 1),
+            episode(5, 1),
+            Assignment::PlayAll,
+            Assignment::Special,
+        ]);
+        assert_eq!(proposals[0].expected, Some(Duration::from_secs(44 * 60)));
+    }
+}
@@ -3,25 +3,32 @@
 
 //! Utilities to generate title and video records.
 //!
-//! The [`process_copy_operation`] function is used to process the title data extracted from a disc
-//! which was copied.
+//! The [`checksum_copy_operation`] function computes the checksums of the titles copied from a disc
+//! and the [`process_copy_operation`] function then uses them to process the title data extracted
+//! from the disc.
 
-use std::io::{self, BufReader};
-use std::fs::File;
+use std::path::PathBuf;
+use std::sync::Arc;
 
-use blake3::Hasher;
+use blake3::Hash;
 
 use rusqlite::{Connection, Transaction};
 
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
 use makemkv::{DiscInfo, StreamInfo, TitleInfo};
 
 use crate::{Error, Result};
+use crate::bus;
 use crate::db;
+use crate::drive;
 use crate::models::{
     AudioCodec,
     AudioTrack,
     ContainerType,
     CopyOperation,
+    MediaLocation,
     Reference,
     SubtitleCodec,
     SubtitleTrack,
@@ -32,19 +39,88 @@ use crate::models::{
     VideoTrack,
 };
 use crate::path;
+use crate::task;
+
+use super::integrity;
+
+/// The maximum number of copied files hashed at the same time.
+///
+/// Hashing is mostly limited by the disks so this is kept low, the same as the default for
+/// integrity scans.
+const CHECKSUM_CONCURRENCY: usize = 2;
+
+/// Computes the checksums of the files copied during a copy operation.
+///
+/// The checksums are computed in parallel on blocking threads before any changes are made to the
+/// database (see [`process_copy_operation`]) so that the database is not locked while the files
+/// are read. The progress is reported to the drive actor as the `Ingesting` stage of the copy
+/// operation.
+///
+/// The checksums are returned in the same order as the titles in `disc_info`. Titles that are
+/// missing their information do not have a checksum.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending progress updates to the drive actor.
+///
+/// `copy_operation`:  The copy operation.
+///
+/// `serial_number`:  The serial number of the drive the copy operation was performed on.
+///
+/// `disc_info`:  The information that was extracted from the disc about the titles.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] if one of the copied files cannot be read.
+///
+/// [`Error::JoinError`] if one of the tasks computing a checksum panics.
+///
+/// [`Error::MakeMkv`] if the output file name cannot be extracted from the title data.
+///
+/// [`Error::UnexpectedFileExtension`] if a copy operation generated a video file with a video
+/// extension other than `.mkv`.
+pub async fn checksum_copy_operation(
+    bus: &bus::Handle,
+    copy_operation: &CopyOperation,
+    serial_number: &str,
+    disc_info: &DiscInfo,
+) -> Result<Vec<Option<Hash>>> {
+    let mut paths = Vec::with_capacity(disc_info.titles.len());
+
+    for (index, title_info) in disc_info.titles.iter().enumerate() {
+        match title_info {
+            Some(title_info) => {
+                // path::location_path will only return `None` if the location is the `Deleted`
+                // location which we know it isn't (it should be the `Inbox` location).
+                let location = output_location(copy_operation, title_info)?;
+                paths.push(Some(path::location_path(&location).unwrap()));
+            },
+            None => {
+                tracing::warn!(sn=serial_number, index, "missing title information");
+                paths.push(None);
+            }
+        }
+    }
+
+    checksum_all(bus, serial_number, paths).await
+}
 
 /// Generates the title and video records in the database for each title that was copied during a
 /// copy operation.
 ///
+/// The records are created in a single transaction, so this should be run using [`db::run`].
+///
 /// # Args
 ///
+/// `conn`:  The database connection that should be used to generate the records.
+///
 /// `copy_operation`:  The copy operation.
 ///
 /// `serial_number`:  The serial number of the drive the copy operation was performed on.
 ///
 /// `disc_info`:  The information that was extracted from the disc about the titles.
 ///
-/// `conn`:  The database connection that should be used to generate the records.
+/// `checksums`:  The checksums of the copied files (see [`checksum_copy_operation`]).
 ///
 /// # Errors
 ///
@@ -61,38 +137,140 @@ use crate::path;
 /// extension other than `.mkv`. The check associated with this error is more of a sanity check
 /// to verify an assumption then a true error.
 pub fn process_copy_operation(
+    conn: &mut Connection,
     copy_operation: &CopyOperation,
     serial_number: &str,
     disc_info: &DiscInfo,
-    conn: &mut Connection,
+    checksums: &[Option<Hash>],
 ) -> Result<()> {
     let transaction = db::transaction::start(conn)?;
 
-    for (index, title_info) in disc_info.titles.iter().enumerate() {
-        match title_info {
-            Some(title_info) => {
-                tracing::trace!(sn=serial_number, index, "processing title");
-
-                // NOTE: The MakeMKV library has a hard limit on the number of titles of 100 titles
-                //       which means index should always be a value u8.
-                process_title_info(
-                    copy_operation,
-                    serial_number,
-                    index as u8,
-                    title_info,
-                    &transaction,
-                )?;
-            },
-            None => {
-                tracing::warn!(sn=serial_number, index, "missing title information");
-            }
-        }
+    for ((index, title_info), checksum) in disc_info.titles.iter().enumerate().zip(checksums) {
+        let (Some(title_info), Some(checksum)) = (title_info, checksum) else {
+            continue;
+        };
+
+        tracing::trace!(sn=serial_number, index, "processing title");
+
+        // NOTE: The MakeMKV library has a hard limit on the number of titles of 100 titles which
+        //       means index should always be a value u8.
+        let location = output_location(copy_operation, title_info)?;
+        process_title_info(
+            copy_operation,
+            serial_number,
+            index as u8,
+            title_info,
+            location,
+            *checksum,
+            &transaction,
+        )?;
     }
 
     db::transaction::commit(transaction)?;
     Ok(())
 }
 
+/// Computes the checksums of the copied files in parallel.
+///
+/// Like integrity scans, at most [`CHECKSUM_CONCURRENCY`] files are hashed at the same time. The
+/// checksums are returned in the same order as the paths, with `None` where there is no path. The
+/// drive actor is notified each time a checksum is completed. Failing to update the progress is
+/// logged, but is not an error.
+///
+/// If a checksum fails, the checksums that have not started are cancelled. Ones that are already
+/// running finish in the background, but their results are ignored.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending progress updates to the drive actor.
+///
+/// `serial_number`:  The serial number of the drive the copy operation was performed on.
+///
+/// `paths`:  The paths of the copied files. `None` for titles that were not copied.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] if one of the files cannot be read.
+///
+/// [`Error::JoinError`] if one of the tasks computing a checksum panics.
+async fn checksum_all(
+    bus: &bus::Handle,
+    serial_number: &str,
+    paths: Vec<Option<PathBuf>>,
+) -> Result<Vec<Option<Hash>>> {
+    let mut checksums = vec![None; paths.len()];
+    let total = paths.iter().flatten().count();
+    let semaphore = Arc::new(Semaphore::new(CHECKSUM_CONCURRENCY));
+
+    let mut tasks = JoinSet::new();
+    for (index, file_path) in paths.into_iter().enumerate() {
+        let Some(file_path) = file_path else {
+            continue;
+        };
+        let semaphore = semaphore.clone();
+        task::spawn_in(&mut tasks, async move {
+            let _permit = semaphore.acquire_owned()
+                .await
+                .expect("semaphore should not be closed");
+            let checksum = task::spawn_blocking(move || integrity::checksum(&file_path)).await?;
+            Ok::<_, Error>((index, checksum?))
+        });
+    }
+
+    report_progress(bus, serial_number, 0, total).await;
+
+    let mut completed = 0;
+    while let Some(result) = tasks.join_next().await {
+        let (index, checksum) = result??;
+        checksums[index] = Some(checksum);
+        completed += 1;
+        tracing::debug!(sn=serial_number, index, completed, total, "computed checksum");
+        report_progress(bus, serial_number, completed, total).await;
+    }
+
+    Ok(checksums)
+}
+
+/// Sends the ingest progress to the drive actor logging any failures.
+async fn report_progress(bus: &bus::Handle, serial_number: &str, completed: usize, total: usize) {
+    if let Err(error) = drive::ingest_progress(bus, serial_number, completed, total).await {
+        tracing::warn!(sn=serial_number, ?error, "failed to update ingest progress");
+    }
+}
+
+/// Returns the location of the file created for a title during a copy operation.
+///
+/// # Args
+///
+/// `copy_operation`:  The copy operation.
+///
+/// `title_info`:  The title information extracted from the disc during the copy operation.
+///
+/// # Errors
+///
+/// [`Error::MakeMkv`] if the output file name is missing from the title data.
+///
+/// [`Error::UnexpectedFileExtension`] if a copy operation generated a video file with a video
+/// extension other than `.mkv`. The check associated with this error is more of a sanity check
+/// to verify an assumption then a true error.
+fn output_location(
+    copy_operation: &CopyOperation,
+    title_info: &TitleInfo,
+) -> Result<MediaLocation> {
+    let file_name = title_info.output_file_name()?;
+
+    if !file_name.ends_with(".mkv") {
+        // The files created by the copy operation should always be an MKV file. This is a sanity
+        // check to help ensure that remains the case.
+        return Err(Error::UnexpectedFileExtension {
+            expected: String::from("*.mkv"),
+            actual: file_name.to_owned()
+        });
+    };
+
+    Ok(path::inbox_location(copy_operation, Some(&file_name)))
+}
+
 /// Generate the title and video records in the database for a title generated from a copy
 /// operation.
 ///
@@ -106,6 +284,10 @@ pub fn process_copy_operation(
 ///
 /// `title_info`:  The title information extracted from the disc during the copy operation.
 ///
+/// `location`:  The location of the file created for the title.
+///
+/// `checksum`:  The checksum of the file created for the title.
+///
 /// `transaction`:  The database connection used to create the new records.  The changes to the
 /// database will not be applied if there are any errors while processing all title data from the
 /// copy operation.
@@ -120,15 +302,13 @@ pub fn process_copy_operation(
 /// [`Error::MissingAudioCodecMapping`], [`Error::MissingSubtitleCodecMapping`], or
 /// [`Error::MissingVideoCodecMapping`] if the codecs specified in the title data cannot be mapped
 /// to one of the application codecs.
-///
-/// [`Error::UnexpectedFileExtension`] if a copy operation generated a video file with a video
-/// extension other than `.mkv`. The check associated with this error is more of a sanity check
-/// to verify an assumption then a true error.
 fn process_title_info(
     copy_operation: &CopyOperation,
     serial_number: &str,
     index: u8,
     title_info: &TitleInfo,
+    location: MediaLocation,
+    checksum: Hash,
     transaction: &Transaction,
 ) -> Result<()> {
     let mut title = Title {
@@ -145,33 +325,13 @@ fn process_title_info(
         disc: copy_operation.disc,
         location: copy_operation.location.clone(),
         memo: copy_operation.memo.clone(),
+        metadata_id: copy_operation.metadata_id.clone(),
+        discarded: false,
         videos: None,
     };
 
     db::title::create(transaction, &mut title)?;
 
-    let file_name = title_info.output_file_name()?;
-
-    if !file_name.ends_with(".mkv") {
-        // The files created by the copy operation should always be an MKV file. This is a sanity
-        // check to help ensure that remains the case.
-        return Err(Error::UnexpectedFileExtension {
-            expected: String::from("*.mkv"),
-            actual: file_name.to_owned()
-        });
-    };
-
-    let location = path::inbox_location(copy_operation, Some(&file_name));
-
-    // path::location_path will only return `None` if the location is the `Deleted` location which
-    // we know it isn't (it should be the `Inbox` location).
-    let file = File::open(path::location_path(&location).unwrap())?;
-
-    let mut reader = BufReader::new(file);
-    let mut hasher = Hasher::new();
-    io::copy(&mut reader, &mut hasher)?;
-    let checksum = hasher.finalize();
-
     let container = ContainerType::MKV;
 
     let mut video_tracks: Vec<VideoTrack> = vec![];
@@ -212,6 +372,8 @@ fn process_title_info(
         source,
         title: Reference { id: title.id, value: None },
         duration,
+        contact_sheet: None,
+        preview_clip: None,
     };
 
     db::video::create(transaction, &mut video)?;
@@ -314,5 +476,145 @@ fn process_video_stream(index: usize, stream: &StreamInfo) -> Result<VideoTrack>
 
 #[cfg(test)]
 mod tests {
-    // TODO[TESTS]
+    use std::fs;
+
+    use super::*;
+
+    use crate::test_utils::TempDir;
+
+    /// Creates a database containing a copy operation.
+    fn setup_test_db() -> (Connection, CopyOperation) {
+        let conn = db::open_in_memory();
+        let host = db::host::create(&conn, "ripper").unwrap();
+        let drive = db::optical_drive::create(&conn, "SN-TEST-001").unwrap();
+
+        let mut copy_operation = CopyOperation {
+            title: String::from("Test Movie"),
+            year: 2024,
+            host: Reference { id: host.id, value: None },
+            drive: Reference { id: drive.id, value: None },
+            ..CopyOperation::default()
+        };
+        db::copy_operation::create(&conn, &mut copy_operation).unwrap();
+
+        (conn, copy_operation)
+    }
+
+    /// Creates the title information for a copied title without any streams.
+    fn title_info(file_name: &str) -> TitleInfo {
+        let json = format!(
+            r#"{{"attributes":{{"OutputFileName":"{}","Duration":"1:30:00"}},"streams":[]}}"#,
+            file_name,
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    /// Creates disc information containing the titles.
+    fn disc_info(titles: Vec<Option<TitleInfo>>) -> DiscInfo {
+        let mut disc_info = DiscInfo::new();
+        disc_info.titles = titles;
+        disc_info
+    }
+
+    #[test]
+    fn test_checksum_all() {
+        let temp = TempDir::new("artie.test.ingest.checksum_all");
+        fs::create_dir_all(temp.path()).unwrap();
+
+        // The first file is the largest so its checksum completes after the others.
+        let contents: Vec<Vec<u8>> = (0..6u8)
+            .map(|i| if i == 0 { vec![0; 8 * 1024 * 1024] } else { vec![i; 16] })
+            .collect();
+        let mut paths = Vec::new();
+        for (index, content) in contents.iter().enumerate() {
+            let file_path = temp.path().join(format!("title_t{:02}.mkv", index));
+            fs::write(&file_path, content).unwrap();
+            // The title at index 2 was not copied.
+            paths.push((index != 2).then_some(file_path));
+        }
+        assert!(paths.len() > CHECKSUM_CONCURRENCY);
+
+        let (bus, bus_rx) = bus::init_channel();
+        drop(bus_rx);
+        let checksums = task::block_on(checksum_all(&bus, "SN-TEST-001", paths)).unwrap();
+
+        assert_eq!(checksums.len(), contents.len());
+        for (index, content) in contents.iter().enumerate() {
+            let expected = (index != 2).then(|| blake3::hash(content));
+            assert_eq!(checksums[index], expected, "checksum of title {}", index);
+        }
+    }
+
+    #[test]
+    fn test_checksum_all_missing_file() {
+        let temp = TempDir::new("artie.test.ingest.checksum_all_missing_file");
+        let paths = vec![Some(temp.path().join("missing.mkv"))];
+
+        let (bus, bus_rx) = bus::init_channel();
+        drop(bus_rx);
+        let result = task::block_on(checksum_all(&bus, "SN-TEST-001", paths));
+
+        assert!(matches!(result, Err(Error::StdIo(_))));
+    }
+
+    #[test]
+    fn test_process_copy_operation() {
+        let (mut conn, copy_operation) = setup_test_db();
+        let disc_info = disc_info(vec![
+            Some(title_info("title_t00.mkv")),
+            None,
+            Some(title_info("title_t02.mkv")),
+            Some(title_info("title_t03.mkv")),
+        ]);
+        // The title at index 1 is missing its information and the file of the title at index 3
+        // was not checksummed, so both are skipped.
+        let checksums = [Some(blake3::hash(b"t00")), None, Some(blake3::hash(b"t02")), None];
+
+        process_copy_operation(&mut conn, &copy_operation, "SN-TEST-001", &disc_info, &checksums)
+            .unwrap();
+
+        let titles = db::title::get_all(&conn).unwrap();
+        assert_eq!(titles.iter().map(|t| t.index).collect::<Vec<u8>>(), vec![0, 2]);
+        assert!(titles.iter().all(|t| !t.discarded && t.title == "Test Movie"));
+
+        let videos = db::video::get_all(&conn).unwrap();
+        assert_eq!(videos.len(), 2);
+        for (title, video) in titles.iter().zip(&videos) {
+            let file_name = format!("title_t{:02}.mkv", title.index);
+            assert_eq!(video.title.id, title.id);
+            assert_eq!(video.location, path::inbox_location(&copy_operation, Some(&file_name)));
+            assert_eq!(video.checksum, checksums[title.index as usize].unwrap());
+            assert_eq!(video.duration.as_secs(), 5400);
+        }
+    }
+
+    #[test]
+    fn test_process_copy_operation_rollback() {
+        let (mut conn, copy_operation) = setup_test_db();
+        conn.execute_batch("
+            CREATE TRIGGER fail_video BEFORE INSERT ON video
+             WHEN NEW.location_path LIKE '%title_t01.mkv'
+            BEGIN
+                SELECT RAISE(ABORT, 'insert failed');
+            END;
+        ").unwrap();
+
+        let disc_info = disc_info(vec![
+            Some(title_info("title_t00.mkv")),
+            Some(title_info("title_t01.mkv")),
+        ]);
+        let checksums = [Some(blake3::hash(b"t00")), Some(blake3::hash(b"t01"))];
+
+        let result = process_copy_operation(
+            &mut conn,
+            &copy_operation,
+            "SN-TEST-001",
+            &disc_info,
+            &checksums,
+        );
+
+        assert!(matches!(result, Err(Error::Database(_))));
+        assert!(db::title::get_all(&conn).unwrap().is_empty());
+        assert!(db::video::get_all(&conn).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,556 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Verifies the video files have not been moved, modified, or deleted.
+//!
+//! The checksum computed when a video is created (see [`crate::library::checksum_copy_operation`])
+//! is recomputed for every video whose file has not been deleted. The inbox, library, and archive
+//! folders are also searched for video files that are not referenced by any video. The results of
+//! each scan are recorded in the database (see [`IntegrityScan`]).
+//!
+//! Copy and transcode operations create their video files before the videos are created, so the
+//! folders they write to are not searched while they are running. The inbox folder of a running
+//! copy operation is skipped, and the library is skipped entirely while a transcode is running
+//! since the location of its file is not known until its video is created.
+//!
+//! When repairs are enabled, a missing video whose file is found elsewhere (matched using the
+//! checksum) has its location updated to where the file was found.
+//!
+//! Scans can be run on demand using [`scan`] or on a schedule by calling [`init`] during
+//! application startup.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use blake3::{Hash, Hasher};
+
+use chrono::Utc;
+
+use rusqlite::Connection;
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::Semaphore;
+
+use crate::Result;
+use crate::bus;
+use crate::db;
+use crate::models::{IntegrityFinding, IntegrityScan, MediaLocation, OperationState, Video};
+use crate::path;
+use crate::task;
+
+/// The extensions of the files considered to be videos when searching for unexpected files.
+const VIDEO_EXTENSIONS: [&str; 3] = ["mkv", "mp4", "m4v"];
+
+/// Integrity scan settings.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Settings {
+    /// The number of hours between scheduled scans.
+    ///
+    /// Scheduled scans are disabled if zero.
+    #[serde(default)]
+    pub interval_hours: u32,
+
+    /// The maximum number of files hashed at the same time.
+    #[serde(default = "Settings::default_concurrency")]
+    pub concurrency: usize,
+
+    /// Update the location of missing videos whose files are found elsewhere.
+    #[serde(default = "Settings::default_repair")]
+    pub repair: bool,
+}
+
+impl Settings {
+    /// The number of files hashed at the same time if not specified in the config.
+    ///
+    /// Hashing is mostly limited by the disks so this is kept low.
+    fn default_concurrency() -> usize {
+        2
+    }
+
+    /// Repairs are made if not specified in the config.
+    fn default_repair() -> bool {
+        true
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            interval_hours: 0,
+            concurrency: Self::default_concurrency(),
+            repair: Self::default_repair(),
+        }
+    }
+}
+
+/// Starts the task that runs the scheduled scans.
+///
+/// Nothing is started if scheduled scans are disabled. The first scan runs once the interval has
+/// elapsed since the last recorded scan.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the application actors.
+///
+/// `settings`:  The integrity scan settings.
+pub fn init(bus: &bus::Handle, settings: Settings) {
+    if settings.interval_hours == 0 {
+        tracing::info!("scheduled integrity scans disabled");
+        return;
+    }
+
+    let bus = bus.clone();
+    task::spawn(async move {
+        let interval = Duration::from_secs(u64::from(settings.interval_hours) * 60 * 60);
+        loop {
+            let delay = match next_scan_delay(&bus, interval).await {
+                Ok(delay) => delay,
+                Err(error) => {
+                    tracing::error!(?error, "failed to read the last integrity scan");
+                    interval
+                },
+            };
+            tokio::time::sleep(delay).await;
+
+            match scan(&bus, &settings).await {
+                Ok(scan) => tracing::info!(id=scan.id, summary=scan.summary(), "integrity scan"),
+                Err(error) => tracing::error!(?error, "integrity scan failed"),
+            }
+        }
+    });
+}
+
+/// Verifies the video files and records the results.
+///
+/// This must be run from a task on the application runtime since the files are hashed using
+/// blocking tasks.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the application actors.
+///
+/// `settings`:  The integrity scan settings.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] if the videos cannot be read, a video's location cannot be updated,
+/// or the results cannot be recorded.
+///
+/// [`crate::Error::StdIo`] if the media folders cannot be searched.
+///
+/// [`crate::Error::JoinError`] if a hashing task panics.
+pub async fn scan(bus: &bus::Handle, settings: &Settings) -> Result<IntegrityScan> {
+    let (mut scan, videos, running) = db::run(bus, |conn| {
+        let mut scan = IntegrityScan {
+            started: Utc::now(),
+            ..IntegrityScan::default()
+        };
+        db::integrity_scan::create(conn, &mut scan)?;
+        let videos = db::video::get_existing(conn)?;
+        let running = running_folders(conn)?;
+        Ok((scan, videos, running))
+    }).await?;
+
+    tracing::info!(id=scan.id, "integrity scan started");
+
+    let known: HashSet<PathBuf> = videos.iter()
+        .filter_map(|v| path::location_path(&v.location))
+        .collect();
+
+    let mut missing: Vec<Video> = Vec::new();
+    let mut present: Vec<(Video, PathBuf)> = Vec::new();
+    for video in videos {
+        match path::location_path(&video.location) {
+            Some(file_path) if file_path.is_file() => present.push((video, file_path)),
+            _ => missing.push(video),
+        }
+    }
+
+    let paths = present.iter().map(|(_, p)| p.clone()).collect();
+    let checksums = checksum_all(paths, settings.concurrency).await?;
+    for ((video, _), checksum) in present.into_iter().zip(checksums) {
+        match checksum {
+            Ok(checksum) if checksum == video.checksum => scan.verified += 1,
+            Ok(checksum) => scan.findings.push(IntegrityFinding::Modified {
+                video_id: video.id,
+                location: video.location,
+                checksum: checksum.to_hex().to_string(),
+            }),
+            Err(error) => scan.findings.push(IntegrityFinding::Unreadable {
+                video_id: video.id,
+                location: video.location,
+                error: format!("{:?}", error),
+            }),
+        }
+    }
+
+    let mut unexpected = task::spawn_blocking(move || find_unexpected(&known, &running)).await??;
+
+    if settings.repair && !missing.is_empty() && !unexpected.is_empty() {
+        let paths = unexpected.iter().map(|(_, p)| p.clone()).collect();
+        let checksums = checksum_all(paths, settings.concurrency).await?;
+
+        let mut relocations = Vec::new();
+        for ((location, _), checksum) in unexpected.iter_mut().zip(checksums) {
+            let Ok(checksum) = checksum else {
+                continue;
+            };
+            let Some(index) = missing.iter().position(|v| v.checksum == checksum) else {
+                continue;
+            };
+
+            let video = missing.swap_remove(index);
+            let to = std::mem::replace(location, MediaLocation::Deleted);
+            relocations.push((video, to));
+        }
+
+        let findings = db::run(bus, move |conn| repair(conn, relocations)).await?;
+        scan.findings.extend(findings);
+
+        unexpected.retain(|(location, _)| *location != MediaLocation::Deleted);
+    }
+
+    for video in missing {
+        scan.findings.push(IntegrityFinding::Missing {
+            video_id: video.id,
+            location: video.location,
+        });
+    }
+
+    for (location, _) in unexpected {
+        scan.findings.push(IntegrityFinding::Unexpected { location });
+    }
+
+    let scan = db::run(bus, move |conn| {
+        db::integrity_scan::complete(conn, &mut scan)?;
+        Ok(scan)
+    }).await?;
+
+    tracing::info!(id=scan.id, "integrity scan completed");
+    Ok(scan)
+}
+
+/// Updates the locations of missing videos to where their files were found.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// `relocations`:  The missing videos paired with the location their file was found.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] if any of the locations cannot be updated. None of the locations
+/// will be updated in that case.
+fn repair(
+    conn: &mut Connection,
+    relocations: Vec<(Video, MediaLocation)>,
+) -> Result<Vec<IntegrityFinding>> {
+    let mut findings = Vec::with_capacity(relocations.len());
+
+    let transaction = db::transaction::start(conn)?;
+    for (mut video, to) in relocations {
+        let from = std::mem::replace(&mut video.location, MediaLocation::Deleted);
+        db::video::set_location(&transaction, &mut video, to.clone())?;
+
+        tracing::info!(id=video.id, ?from, ?to, "repaired video location");
+        findings.push(IntegrityFinding::Relocated { video_id: video.id, from, to });
+    }
+    db::transaction::commit(transaction)?;
+
+    Ok(findings)
+}
+
+/// Computes the checksum of a file.
+///
+/// The file is memory mapped and hashed using multiple threads. This blocks until the checksum is
+/// computed so it should be called from a blocking thread.
+///
+/// # Errors
+///
+/// [`crate::Error::StdIo`] if the file cannot be read.
+pub(crate) fn checksum(file_path: &Path) -> Result<Hash> {
+    let mut hasher = Hasher::new();
+    hasher.update_mmap_rayon(file_path)?;
+    Ok(hasher.finalize())
+}
+
+/// Computes the checksums of files in parallel.
+///
+/// The checksums are returned in the same order as the paths. A failure to read a file is returned
+/// as the result for that file.
+///
+/// # Args
+///
+/// `paths`:  The paths of the files.
+///
+/// `concurrency`:  The maximum number of files hashed at the same time.
+///
+/// # Errors
+///
+/// [`crate::Error::JoinError`] if a hashing task panics.
+async fn checksum_all(paths: Vec<PathBuf>, concurrency: usize) -> Result<Vec<Result<Hash>>> {
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+
+    let mut handles = Vec::with_capacity(paths.len());
+    for file_path in paths {
+        let permit = semaphore.clone()
+            .acquire_owned()
+            .await
+            .expect("semaphore should not be closed");
+        handles.push(task::spawn_blocking(move || {
+            let _permit = permit;
+            checksum(&file_path)
+        }));
+    }
+
+    let mut checksums = Vec::with_capacity(handles.len());
+    for handle in handles {
+        checksums.push(handle.await?);
+    }
+    Ok(checksums)
+}
+
+/// Returns the folders written to by the copy and transcode operations that are running.
+///
+/// Operations that were requested but have not started are included since they may start while
+/// the scan is running.
+///
+/// # Args
+///
+/// `conn`:  The connection to the database.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] if the operations cannot be read.
+fn running_folders(conn: &Connection) -> Result<Vec<PathBuf>> {
+    let is_running = |state: &OperationState| {
+        matches!(state, OperationState::Requested | OperationState::Running)
+    };
+
+    let mut folders: Vec<PathBuf> = db::copy_operation::get_all(conn, false)?
+        .iter()
+        .filter(|c| is_running(&c.state))
+        .map(path::inbox_path)
+        .collect();
+
+    let transcoding = db::transcode_operation::get_all(conn, false)?
+        .iter()
+        .any(|t| is_running(&t.state));
+    if transcoding {
+        folders.extend(path::location_path(&MediaLocation::Library(PathBuf::new())));
+    }
+
+    Ok(folders)
+}
+
+/// Returns the video files in the media folders which are not one of the known files.
+///
+/// The preview folders in the inbox are skipped since they contain preview clips and not videos.
+///
+/// # Args
+///
+/// `known`:  The paths of the video files referenced by videos.
+///
+/// `running`:  The folders written to by running operations (see [`running_folders`]). The files
+/// in these folders are not reported.
+///
+/// # Errors
+///
+/// [`crate::Error::StdIo`] if a folder cannot be read.
+fn find_unexpected(
+    known: &HashSet<PathBuf>,
+    running: &[PathBuf],
+) -> Result<Vec<(MediaLocation, PathBuf)>> {
+    let areas: [fn(PathBuf) -> MediaLocation; 3] = [
+        MediaLocation::Inbox,
+        MediaLocation::Library,
+        MediaLocation::Archive,
+    ];
+
+    let mut unexpected = Vec::new();
+    for area in areas {
+        let root = path::location_path(&area(PathBuf::new()))
+            .expect("media area should have a path");
+        find_unexpected_in(&root, area, known, running, &mut unexpected)?;
+    }
+
+    Ok(unexpected)
+}
+
+/// Searches one of the media folders for video files which are not one of the known files.
+///
+/// # Args
+///
+/// `root`:  The path of the media folder.
+///
+/// `area`:  Creates the media location of a file from its path relative to `root`.
+///
+/// `known`:  The paths of the video files referenced by videos.
+///
+/// `running`:  The folders written to by running operations. The files in these folders are not
+/// reported.
+///
+/// `unexpected`:  The unexpected files that are found are appended to this list.
+///
+/// # Errors
+///
+/// [`crate::Error::StdIo`] if a folder cannot be read.
+fn find_unexpected_in(
+    root: &Path,
+    area: fn(PathBuf) -> MediaLocation,
+    known: &HashSet<PathBuf>,
+    running: &[PathBuf],
+    unexpected: &mut Vec<(MediaLocation, PathBuf)>,
+) -> Result<()> {
+    let mut files = Vec::new();
+    find_videos(root, &mut files)?;
+
+    for file_path in files {
+        if known.contains(&file_path) || running.iter().any(|f| file_path.starts_with(f)) {
+            continue;
+        }
+        let relative = file_path.strip_prefix(root)
+            .expect("file should be within the media area")
+            .to_path_buf();
+        unexpected.push((area(relative), file_path));
+    }
+
+    Ok(())
+}
+
+/// Recursively searches a folder for video files.
+///
+/// Symbolic links are not followed.
+///
+/// # Args
+///
+/// `folder`:  The folder to search.
+///
+/// `files`:  The video files that are found are appended to this list.
+///
+/// # Errors
+///
+/// [`crate::Error::StdIo`] if a folder cannot be read.
+fn find_videos(folder: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !folder.is_dir() {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(folder)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let entry_path = entry.path();
+
+        if file_type.is_dir() {
+            if entry.file_name() != path::PREVIEWS_FOLDER_NAME {
+                find_videos(&entry_path, files)?;
+            }
+        } else if file_type.is_file() && is_video(&entry_path) {
+            files.push(entry_path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if the file has one of the video extensions.
+fn is_video(file_path: &Path) -> bool {
+    file_path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| VIDEO_EXTENSIONS.iter().any(|v| e.eq_ignore_ascii_case(v)))
+}
+
+/// Returns how long to wait before running the next scheduled scan.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the application actors.
+///
+/// `interval`:  The time between scans.
+///
+/// # Errors
+///
+/// [`crate::Error::Database`] if the last scan cannot be read.
+async fn next_scan_delay(bus: &bus::Handle, interval: Duration) -> Result<Duration> {
+    let Some(last) = db::run(bus, |conn| db::integrity_scan::get_latest(conn)).await? else {
+        return Ok(Duration::ZERO);
+    };
+
+    let elapsed = (Utc::now() - last.started).to_std().unwrap_or_default();
+    Ok(interval.saturating_sub(elapsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::TempDir;
+
+    #[test]
+    fn test_is_video() {
+        assert!(is_video(Path::new("movies/title_t00.mkv")));
+        assert!(is_video(Path::new("movies/title_t00.MP4")));
+        assert!(!is_video(Path::new("movies/disc_info.json")));
+        assert!(!is_video(Path::new("movies/mkv")));
+    }
+
+    #[test]
+    fn test_find_videos() {
+        let temp = TempDir::new("artie.test.integrity.find_videos");
+        let root = temp.path();
+        fs::create_dir_all(root.join("show/previews")).unwrap();
+        fs::write(root.join("show/title_t00.mkv"), b"video").unwrap();
+        fs::write(root.join("show/makemkv-copy.log"), b"log").unwrap();
+        fs::write(root.join("show/previews/title_t00.mp4"), b"clip").unwrap();
+
+        let mut files = Vec::new();
+        find_videos(root, &mut files).unwrap();
+        assert_eq!(files, vec![root.join("show/title_t00.mkv")]);
+
+        let mut files = Vec::new();
+        find_videos(&root.join("missing"), &mut files).unwrap();
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn test_find_unexpected_in() {
+        let temp = TempDir::new("artie.test.integrity.find_unexpected_in");
+        let root = temp.path();
+        fs::create_dir_all(root.join("done")).unwrap();
+        fs::create_dir_all(root.join("copying")).unwrap();
+        fs::write(root.join("done/title_t00.mkv"), b"video").unwrap();
+        fs::write(root.join("done/title_t01.mkv"), b"video").unwrap();
+        fs::write(root.join("copying/title_t00.mkv"), b"video").unwrap();
+
+        let known = HashSet::from([root.join("done/title_t00.mkv")]);
+        let running = [root.join("copying")];
+
+        let mut unexpected = Vec::new();
+        find_unexpected_in(root, MediaLocation::Inbox, &known, &running, &mut unexpected)
+            .unwrap();
+        assert_eq!(unexpected, vec![(
+            MediaLocation::Inbox(PathBuf::from("done/title_t01.mkv")),
+            root.join("done/title_t01.mkv"),
+        )]);
+
+        let mut unexpected = Vec::new();
+        find_unexpected_in(root, MediaLocation::Inbox, &known, &[], &mut unexpected).unwrap();
+        assert_eq!(unexpected.len(), 2);
+    }
+
+    #[test]
+    fn test_checksum() {
+        let temp = TempDir::new("artie.test.integrity.checksum");
+        fs::create_dir_all(temp.path()).unwrap();
+        let file_path = temp.path().join("video.mkv");
+        fs::write(&file_path, b"test video data").unwrap();
+
+        assert_eq!(checksum(&file_path).unwrap(), blake3::hash(b"test video data"));
+        assert!(checksum(&temp.path().join("missing.mkv")).is_err());
+    }
+}
@@ -5,9 +5,40 @@
 //!
 //! # Video Ingest
 //!
-//! After the titles are copied from the disc, the video and title information in the database can
+//! After the titles are copied from the disc, their checksums are computed using the
+//! [`checksum_copy_operation`] function and the video and title information in the database can
 //! be created using the [`process_copy_operation`] function.
+//!
+//! # Episode Mapping
+//!
+//! The titles copied from a television show disc can be assigned episode numbers using the
+//! [`episodes`] module. It proposes an assignment for each title which is then confirmed by the
+//! user.
+//!
+//! # Integrity
+//!
+//! The [`integrity`] module verifies that the video files have not been moved, modified, or
+//! deleted since they were created.
+//!
+//! # Jellyfin Sidecars
+//!
+//! The [`nfo`] module writes the NFO files Jellyfin uses to identify the videos in the library.
+//!
+//! # Previews
+//!
+//! Contact sheets and preview clips can be created for the copied videos using the [`preview`]
+//! module to help identify what each title contains.
+//!
+//! # Catalog
+//!
+//! The records of the copied discs and videos can be exported as JSON or CSV and a JSON export can
+//! be imported to rebuild the database using the [`catalog`] module.
 
+pub mod catalog;
+pub mod episodes;
 mod ingest;
+pub mod integrity;
+pub mod nfo;
+pub mod preview;
 
-pub use ingest::process_copy_operation;
+pub use ingest::{checksum_copy_operation, process_copy_operation};
@@ -0,0 +1,563 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Generates the NFO sidecar files Jellyfin reads to identify the videos in the library.
+//!
+//! Without the sidecars, Jellyfin has to guess the movie or show from the folder and file names.
+//! The sidecars contain what is already known about each title so that the videos are identified
+//! correctly the first time the library is scanned.
+//!
+//! The following sidecars are created next to the video files in the library (videos in the inbox
+//! or archive are skipped):
+//!
+//! | Title                    | Sidecar                                                         |
+//! |--------------------------|-----------------------------------------------------------------|
+//! | Main feature of a movie  | `movie.nfo`                                                     |
+//! | Version of a movie       | `<video file name>.nfo` with the version as the `<edition>`     |
+//! | Episodes of a show       | `<video file name>.nfo` and `<video file name>-thumb.jpg`       |
+//! | Special feature          | `<video file name>.nfo`                                         |
+//!
+//! Episodes also create a `tvshow.nfo` in the show folder. The show folder is the parent of the
+//! video's folder if the video is in a season folder (e.g. `Season 01`) or the video's folder
+//! otherwise. The episode thumbnail is a copy of the video's contact sheet (see
+//! [`crate::library::preview`]) if one was created.
+//!
+//! The movie and show sidecars contain the metadata provider identifier when the title has been
+//! matched to a provider entry. Each movie, episode, and special feature sidecar also lists the
+//! codecs and languages of the video's tracks.
+//!
+//! The sidecars should be updated using [`update`] whenever a title is edited or its videos are
+//! moved into the library.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Result;
+use crate::metadata::ProviderId;
+use crate::models::{
+    AudioCodec,
+    Classification,
+    MediaLocation,
+    MediaType,
+    Title,
+    Video,
+    VideoCodec,
+};
+use crate::path;
+use crate::task;
+
+/// The name of the sidecar for the main feature of a movie.
+const MOVIE_FILENAME: &str = "movie.nfo";
+
+/// The name of the sidecar for a show.
+const SHOW_FILENAME: &str = "tvshow.nfo";
+
+/// The suffix added to a video's file name for its thumbnail.
+const THUMBNAIL_SUFFIX: &str = "-thumb.jpg";
+
+/// The prefix of the folder names Jellyfin recognizes as season folders.
+const SEASON_FOLDER_PREFIX: &str = "season ";
+
+/// A file created alongside a video in the library.
+#[derive(Debug)]
+pub struct Sidecar {
+    /// The absolute path of the file.
+    pub path: PathBuf,
+
+    /// What is written to the file.
+    contents: Contents,
+}
+
+/// Specifies what is written to a sidecar file.
+#[derive(Debug)]
+enum Contents {
+    /// The XML document of an NFO file.
+    Nfo(String),
+
+    /// A copy of another file (e.g. the thumbnail created from a contact sheet).
+    Copy(PathBuf),
+}
+
+/// Creates or replaces the sidecars of the titles' videos.
+///
+/// # Args
+///
+/// `titles`:  The titles to update the sidecars for. The videos of each title must be loaded.
+///
+/// # Errors
+///
+/// [`crate::Error::StdIo`] if one of the sidecars cannot be written. The sidecars before it will
+/// have been written.
+///
+/// [`crate::Error::JoinError`] if the task writing the sidecars panics.
+pub async fn update(titles: &[Title]) -> Result<usize> {
+    let sidecars: Vec<Sidecar> = titles.iter()
+        .flat_map(sidecars)
+        .collect();
+    write_all(sidecars).await
+}
+
+/// Writes sidecars to disk.
+///
+/// Sidecars shared by more than one video (e.g. `tvshow.nfo`) are only written once. Returns the
+/// number of files written.
+///
+/// # Args
+///
+/// `sidecars`:  The sidecars to write.
+///
+/// # Errors
+///
+/// See [`update`].
+pub async fn write_all(sidecars: Vec<Sidecar>) -> Result<usize> {
+    task::spawn_blocking(move || {
+        let mut written = HashSet::new();
+        for sidecar in sidecars {
+            if written.contains(&sidecar.path) {
+                continue;
+            }
+            match &sidecar.contents {
+                Contents::Nfo(xml) => fs::write(&sidecar.path, xml)?,
+                Contents::Copy(from) => {
+                    let _ = fs::copy(from, &sidecar.path)?;
+                },
+            }
+            tracing::debug!(path=?sidecar.path, "wrote sidecar");
+            written.insert(sidecar.path);
+        }
+        Ok(written.len())
+    }).await?
+}
+
+/// Returns the sidecars of a title's videos.
+///
+/// Discarded titles, titles whose videos are not loaded, and videos outside of the library do not
+/// have sidecars.
+pub fn sidecars(title: &Title) -> Vec<Sidecar> {
+    let Some(videos) = &title.videos else {
+        return Vec::new();
+    };
+
+    videos.iter()
+        .filter(|v| matches!(v.location, MediaLocation::Library(_)))
+        .filter_map(|v| path::location_path(&v.location).map(|p| (v, p)))
+        .flat_map(|(video, file_path)| video_sidecars(title, video, &file_path))
+        .collect()
+}
+
+/// Returns the sidecars for one of a title's videos.
+///
+/// # Args
+///
+/// `title`:  The title the video belongs to.
+///
+/// `video`:  The video.
+///
+/// `file_path`:  The absolute path of the video file.
+fn video_sidecars(title: &Title, video: &Video, file_path: &Path) -> Vec<Sidecar> {
+    let Some(folder) = file_path.parent() else {
+        return Vec::new();
+    };
+    let named = |suffix: &str| {
+        let stem = file_path.file_stem().unwrap_or_default().to_string_lossy();
+        folder.join(format!("{}{}", stem, suffix))
+    };
+
+    let mut sidecars = Vec::new();
+    match title.classification() {
+        Classification::Discard => {},
+        Classification::Main => {
+            sidecars.push(Sidecar {
+                path: folder.join(MOVIE_FILENAME),
+                contents: Contents::Nfo(movie_nfo(title, video)),
+            });
+        },
+        Classification::Version { .. } => {
+            sidecars.push(Sidecar {
+                path: named(".nfo"),
+                contents: Contents::Nfo(movie_nfo(title, video)),
+            });
+        },
+        Classification::Episodes { .. } => {
+            sidecars.push(Sidecar {
+                path: named(".nfo"),
+                contents: Contents::Nfo(episode_nfo(title, video)),
+            });
+            sidecars.push(Sidecar {
+                path: show_folder(folder).join(SHOW_FILENAME),
+                contents: Contents::Nfo(show_nfo(title)),
+            });
+            if let Some(contact_sheet) = video.contact_sheet.as_ref()
+                .and_then(path::location_path)
+                .filter(|p| p.is_file())
+            {
+                sidecars.push(Sidecar {
+                    path: named(THUMBNAIL_SUFFIX),
+                    contents: Contents::Copy(contact_sheet),
+                });
+            }
+        },
+        Classification::Special(_) => {
+            sidecars.push(Sidecar {
+                path: named(".nfo"),
+                contents: Contents::Nfo(special_feature_nfo(title, video)),
+            });
+        },
+    }
+
+    sidecars
+}
+
+/// Returns the show folder for an episode in a folder.
+///
+/// This is the parent of the folder if it is a season folder or the folder itself otherwise.
+fn show_folder(folder: &Path) -> &Path {
+    let is_season = folder.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.to_lowercase().starts_with(SEASON_FOLDER_PREFIX));
+
+    match folder.parent() {
+        Some(parent) if is_season => parent,
+        _ => folder,
+    }
+}
+
+/// Creates the NFO document for a movie or a version of a movie.
+fn movie_nfo(title: &Title, video: &Video) -> String {
+    let mut xml = Nfo::new("movie");
+    xml.element("title", &title.title);
+    xml.element("year", &title.year.to_string());
+    if !title.version.is_empty() {
+        xml.element("edition", &title.version);
+    }
+    if let Some(id) = &title.metadata_id {
+        xml.unique_id(id);
+    }
+    xml.file_info(video);
+    xml.finish()
+}
+
+/// Creates the NFO document for the episodes of a show.
+///
+/// The metadata identifier of the title belongs to the show so it is only included in the show's
+/// NFO document (see [`show_nfo`]).
+fn episode_nfo(title: &Title, video: &Video) -> String {
+    let mut xml = Nfo::new("episodedetails");
+    xml.element("showtitle", &title.title);
+    xml.element("season", &title.season.to_string());
+    xml.element("episode", &title.episode_number.to_string());
+    if title.episode_count > 1 {
+        let last = title.episode_number + title.episode_count - 1;
+        xml.element("episodenumberend", &last.to_string());
+    }
+    xml.file_info(video);
+    xml.finish()
+}
+
+/// Creates the NFO document for a show.
+fn show_nfo(title: &Title) -> String {
+    let mut xml = Nfo::new("tvshow");
+    xml.element("title", &title.title);
+    xml.element("year", &title.year.to_string());
+    if let Some(id) = &title.metadata_id {
+        xml.unique_id(id);
+    }
+    xml.finish()
+}
+
+/// Creates the NFO document for a special feature.
+///
+/// Jellyfin determines the type of special feature from its folder, so the type is only added as
+/// a tag.
+fn special_feature_nfo(title: &Title, video: &Video) -> String {
+    let root = match title.media_type {
+        MediaType::Movie => "movie",
+        MediaType::Show => "episodedetails",
+    };
+    let mut xml = Nfo::new(root);
+    if let Some(special_feature) = &title.special_feature {
+        xml.element("title", &special_feature.name);
+        xml.element("tag", special_feature.kind.as_str());
+    }
+    xml.file_info(video);
+    xml.finish()
+}
+
+/// Builds an NFO XML document.
+struct Nfo {
+    /// The name of the root element.
+    root: &'static str,
+
+    /// The document written so far.
+    xml: String,
+}
+
+impl Nfo {
+    /// Starts a document with a root element.
+    fn new(root: &'static str) -> Self {
+        let xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<{}>\n",
+            root,
+        );
+        Self { root, xml }
+    }
+
+    /// Adds an element containing text to the root element.
+    fn element(&mut self, name: &str, value: &str) {
+        self.indented(1, name, value);
+    }
+
+    /// Adds an element containing text at a depth within the document.
+    fn indented(&mut self, depth: usize, name: &str, value: &str) {
+        let _ = writeln!(self.xml, "{}<{}>{}</{}>", "  ".repeat(depth), name, escape(value), name);
+    }
+
+    /// Adds the metadata provider identifier as the default unique id.
+    fn unique_id(&mut self, id: &ProviderId) {
+        let _ = writeln!(
+            self.xml,
+            "  <uniqueid type=\"{}\" default=\"true\">{}</uniqueid>",
+            id.provider.as_str(),
+            id.id,
+        );
+    }
+
+    /// Adds the stream details of a video's tracks.
+    fn file_info(&mut self, video: &Video) {
+        self.xml.push_str("  <fileinfo>\n    <streamdetails>\n");
+
+        for track in &video.video_tracks {
+            self.xml.push_str("      <video>\n");
+            self.indented(4, "codec", video_codec_name(track.codec));
+            if let Some((width, height)) = track.size.split_once('x') {
+                self.indented(4, "width", width);
+                self.indented(4, "height", height);
+            }
+            self.indented(4, "durationinseconds", &video.duration.as_secs().to_string());
+            self.xml.push_str("      </video>\n");
+        }
+
+        for track in &video.audio_tracks {
+            self.xml.push_str("      <audio>\n");
+            self.indented(4, "codec", audio_codec_name(track.codec));
+            self.indented(4, "language", &track.language);
+            self.indented(4, "channels", &track.channel_count.to_string());
+            self.xml.push_str("      </audio>\n");
+        }
+
+        for track in &video.subtitle_tracks {
+            self.xml.push_str("      <subtitle>\n");
+            self.indented(4, "language", &track.language);
+            self.xml.push_str("      </subtitle>\n");
+        }
+
+        self.xml.push_str("    </streamdetails>\n  </fileinfo>\n");
+    }
+
+    /// Closes the root element and returns the document.
+    fn finish(mut self) -> String {
+        let _ = writeln!(self.xml, "</{}>", self.root);
+        self.xml
+    }
+}
+
+/// Escapes the characters that cannot appear in XML text.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns the name of an audio codec used in NFO stream details.
+fn audio_codec_name(codec: AudioCodec) -> &'static str {
+    match codec {
+        AudioCodec::AAC => "aac",
+        AudioCodec::AC3 => "ac3",
+        AudioCodec::ALAC16 | AudioCodec::ALAC24 => "alac",
+        AudioCodec::DTS => "dca",
+        AudioCodec::DTSHD => "dtshd_ma",
+        AudioCodec::EAC3 => "eac3",
+        AudioCodec::Flac16 | AudioCodec::Flac24 => "flac",
+        AudioCodec::MP2 => "mp2",
+        AudioCodec::MP3 => "mp3",
+        AudioCodec::Opus => "opus",
+        AudioCodec::TrueHD => "truehd",
+        AudioCodec::Vorbis => "vorbis",
+    }
+}
+
+/// Returns the name of a video codec used in NFO stream details.
+fn video_codec_name(codec: VideoCodec) -> &'static str {
+    match codec {
+        VideoCodec::H264 => "h264",
+        VideoCodec::H265 => "hevc",
+        VideoCodec::MPEG2 => "mpeg2video",
+        VideoCodec::MPEG4 => "mpeg4",
+        VideoCodec::Theora => "theora",
+        VideoCodec::VP8 => "vp8",
+        VideoCodec::VP9 => "vp9",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    use crate::metadata::ProviderKind;
+    use crate::models::{
+        AudioTrack,
+        ContainerType,
+        Reference,
+        SpecialFeature,
+        SpecialFeatureType,
+        SubtitleCodec,
+        SubtitleTrack,
+        VideoSource,
+        VideoTrack,
+    };
+
+    fn make_title(media_type: MediaType) -> Title {
+        Title {
+            id: 1,
+            index: 0,
+            media_type,
+            title: String::from("Fish & Chips"),
+            year: 2001,
+            season: 0,
+            episode_number: 0,
+            episode_count: 0,
+            special_feature: None,
+            version: String::new(),
+            disc: 1,
+            location: String::new(),
+            memo: String::new(),
+            metadata_id: Some(ProviderId { provider: ProviderKind::Tmdb, id: 42 }),
+            discarded: false,
+            videos: None,
+        }
+    }
+
+    fn make_video() -> Video {
+        Video {
+            id: 1,
+            location: MediaLocation::Library(PathBuf::from("movie/title_t00.mkv")),
+            checksum: blake3::hash(b"video"),
+            container: ContainerType::MKV,
+            video_tracks: vec![VideoTrack {
+                index: 1,
+                codec: VideoCodec::MPEG2,
+                size: String::from("720x480"),
+                aspect_ratio: String::from("16:9"),
+            }],
+            audio_tracks: vec![AudioTrack {
+                index: 1,
+                name: String::from("Stereo"),
+                codec: AudioCodec::AC3,
+                encode_method: None,
+                language: String::from("eng"),
+                channel_count: 2,
+                channel_layout: String::from("stereo"),
+            }],
+            subtitle_tracks: vec![SubtitleTrack {
+                index: 1,
+                codec: SubtitleCodec::PGS,
+                language: String::from("fra"),
+            }],
+            source: VideoSource::CopyOperation(Reference { id: 1, value: None }),
+            title: Reference { id: 1, value: None },
+            duration: Duration::from_secs(5400),
+            contact_sheet: None,
+            preview_clip: None,
+        }
+    }
+
+    #[test]
+    fn test_movie_nfo() {
+        let mut title = make_title(MediaType::Movie);
+        title.classify(Classification::Version { name: String::from("Director's Cut") });
+
+        let xml = movie_nfo(&title, &make_video());
+        assert!(xml.starts_with("<?xml version=\"1.0\""));
+        assert!(xml.contains("\n<movie>\n"));
+        assert!(xml.contains("  <title>Fish &amp; Chips</title>\n"));
+        assert!(xml.contains("  <year>2001</year>\n"));
+        assert!(xml.contains("  <edition>Director&apos;s Cut</edition>\n"));
+        assert!(xml.contains("  <uniqueid type=\"tmdb\" default=\"true\">42</uniqueid>\n"));
+        assert!(xml.contains("        <codec>mpeg2video</codec>\n"));
+        assert!(xml.contains("        <width>720</width>\n"));
+        assert!(xml.contains("        <durationinseconds>5400</durationinseconds>\n"));
+        assert!(xml.contains("        <language>eng</language>\n"));
+        assert!(xml.contains("        <channels>2</channels>\n"));
+        assert!(xml.contains("      <subtitle>\n        <language>fra</language>\n"));
+        assert!(xml.ends_with("</movie>\n"));
+    }
+
+    #[test]
+    fn test_episode_nfo() {
+        let mut title = make_title(MediaType::Show);
+        title.season = 2;
+        title.classify(Classification::Episodes { number: 5, count: 2 });
+
+        let xml = episode_nfo(&title, &make_video());
+        assert!(xml.contains("\n<episodedetails>\n"));
+        assert!(xml.contains("  <showtitle>Fish &amp; Chips</showtitle>\n"));
+        assert!(xml.contains("  <season>2</season>\n"));
+        assert!(xml.contains("  <episode>5</episode>\n"));
+        assert!(xml.contains("  <episodenumberend>6</episodenumberend>\n"));
+        assert!(!xml.contains("uniqueid"));
+
+        let xml = show_nfo(&title);
+        assert!(xml.contains("\n<tvshow>\n"));
+        assert!(xml.contains("  <uniqueid type=\"tmdb\" default=\"true\">42</uniqueid>\n"));
+    }
+
+    #[test]
+    fn test_video_sidecars() {
+        let video = make_video();
+
+        let title = make_title(MediaType::Movie);
+        let sidecars = video_sidecars(&title, &video, Path::new("/lib/Movie (2001)/movie.mkv"));
+        assert_eq!(sidecars.len(), 1);
+        assert_eq!(sidecars[0].path, Path::new("/lib/Movie (2001)/movie.nfo"));
+
+        let mut title = make_title(MediaType::Show);
+        title.classify(Classification::Episodes { number: 1, count: 1 });
+        let sidecars = video_sidecars(&title, &video, Path::new("/lib/Show/Season 01/s01e01.mkv"));
+        let paths: Vec<&Path> = sidecars.iter().map(|s| s.path.as_path()).collect();
+        assert_eq!(paths, vec![
+            Path::new("/lib/Show/Season 01/s01e01.nfo"),
+            Path::new("/lib/Show/tvshow.nfo"),
+        ]);
+
+        title.classify(Classification::Special(SpecialFeature {
+            kind: SpecialFeatureType::Trailers,
+            name: String::from("Teaser"),
+        }));
+        let sidecars = video_sidecars(&title, &video, Path::new("/lib/Show/trailers/teaser.mkv"));
+        assert_eq!(sidecars.len(), 1);
+        assert_eq!(sidecars[0].path, Path::new("/lib/Show/trailers/teaser.nfo"));
+
+        title.classify(Classification::Discard);
+        assert!(video_sidecars(&title, &video, Path::new("/lib/Show/discard.mkv")).is_empty());
+    }
+
+    #[test]
+    fn test_show_folder() {
+        assert_eq!(show_folder(Path::new("/lib/Show/Season 01")), Path::new("/lib/Show"));
+        assert_eq!(show_folder(Path::new("/lib/Show/season 2")), Path::new("/lib/Show"));
+        assert_eq!(show_folder(Path::new("/lib/Show")), Path::new("/lib/Show"));
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Generates previews of copied videos.
+//!
+//! A disc can contain dozens of titles where the only clues to what they contain are the duration
+//! and file name. To make it easier to identify them, a contact sheet (a grid of frames spread
+//! across the video) and optionally a short, low resolution clip are created for each video using
+//! FFmpeg. The previews are saved within the copy operation's inbox folder (see
+//! [`path::contact_sheet_location`] and [`path::preview_clip_location`]).
+//!
+//! The settings need initialized by calling [`init`] during application startup.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::Semaphore;
+
+use ffmpeg::{ContactSheetOptions, PreviewClipOptions};
+
+use crate::{Error, Result};
+use crate::models::{CopyOperation, MediaLocation, Video};
+use crate::path;
+use crate::task;
+
+/// The maximum number of videos previews are created for at the same time.
+///
+/// FFmpeg decodes each video using several threads, so this is kept low to avoid starving the rest
+/// of the application (e.g. other copy operations) when a disc with many titles is copied.
+const CONCURRENCY: usize = 2;
+
+/// Limits the number of videos previews are created for at the same time (see [`CONCURRENCY`]).
+static LIMIT: Semaphore = Semaphore::const_new(CONCURRENCY);
+
+/// The preview settings.
+///
+/// The settings are setup early in application initialization and are not expected to change
+/// afterwards.
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Preview settings.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Settings {
+    /// Create a contact sheet for each copied video.
+    #[serde(default = "Settings::default_contact_sheet")]
+    pub contact_sheet: bool,
+
+    /// Create a preview clip for each copied video.
+    ///
+    /// Disabled by default since this requires transcoding part of each video.
+    #[serde(default)]
+    pub preview_clip: bool,
+
+    /// The length of the preview clips in seconds.
+    #[serde(default = "Settings::default_clip_length")]
+    pub clip_length: u32,
+}
+
+impl Settings {
+    /// Contact sheets are created if not specified in the config.
+    fn default_contact_sheet() -> bool {
+        true
+    }
+
+    /// The length of the preview clips if not specified in the config.
+    fn default_clip_length() -> u32 {
+        30
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            contact_sheet: Self::default_contact_sheet(),
+            preview_clip: false,
+            clip_length: Self::default_clip_length(),
+        }
+    }
+}
+
+/// Initializes the preview settings.
+///
+/// Only the first call has any effect.
+pub fn init(settings: Settings) {
+    let _ = SETTINGS.set(settings);
+}
+
+/// Creates the previews for a video copied by a copy operation.
+///
+/// The locations of the previews are set on the video, but are not saved to the database. Previews
+/// that already exist are not recreated. If previews are already being created for
+/// [`CONCURRENCY`] other videos, this waits until one of them is done.
+///
+/// # Args
+///
+/// `copy_operation`:  The copy operation that created the video.
+///
+/// `video`:  The video to create the previews for.
+///
+/// # Errors
+///
+/// [`Error::InvalidMediaLocation`] if the video does not have a valid location.
+///
+/// [`Error::StdIo`] if the previews folder cannot be created.
+///
+/// [`Error::Ffmpeg`] if FFmpeg fails to create one of the previews.
+///
+/// [`Error::JoinError`] if the task running FFmpeg panics.
+///
+/// # Panics
+///
+/// If the module was not initialized.
+pub async fn create_previews(copy_operation: &CopyOperation, video: &mut Video) -> Result<()> {
+    let settings = SETTINGS.get().expect("preview module not initialized");
+    let _permit = LIMIT.acquire().await.expect("preview limit should not be closed");
+
+    let src_path = path::location_path(&video.location)
+        .ok_or(Error::InvalidMediaLocation { location: MediaLocation::Deleted })?;
+    let name = src_path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_owned();
+
+    if settings.contact_sheet {
+        let location = path::contact_sheet_location(copy_operation, &name);
+        let dst_path = preview_path(&location)?;
+        if !dst_path.exists() {
+            let opts = ContactSheetOptions::new(&src_path, &dst_path, video.duration);
+            task::spawn_blocking(move || ffmpeg::contact_sheet(&opts)).await??;
+            tracing::debug!(?dst_path, "created contact sheet");
+        }
+        video.contact_sheet = Some(location);
+    }
+
+    if settings.preview_clip {
+        let location = path::preview_clip_location(copy_operation, &name);
+        let dst_path = preview_path(&location)?;
+        if !dst_path.exists() {
+            let mut opts = PreviewClipOptions::new(&src_path, &dst_path);
+            opts.start_at(clip_start(video.duration, settings.clip_length))
+                .length(settings.clip_length);
+            task::spawn_blocking(move || ffmpeg::preview_clip(&opts)).await??;
+            tracing::debug!(?dst_path, "created preview clip");
+        }
+        video.preview_clip = Some(location);
+    }
+
+    Ok(())
+}
+
+/// Returns the path for a preview's location creating the previews folder if needed.
+///
+/// # Errors
+///
+/// [`Error::InvalidMediaLocation`] if the location is not valid.
+///
+/// [`Error::StdIo`] if the folder cannot be created.
+fn preview_path(location: &MediaLocation) -> Result<PathBuf> {
+    let path = path::location_path(location)
+        .ok_or(Error::InvalidMediaLocation { location: MediaLocation::Deleted })?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(path)
+}
+
+/// Returns the offset in seconds of where a preview clip should start.
+///
+/// Skipping the first tenth of the video usually gets past any logos and opening credits. The clip
+/// is moved earlier for short videos so that it is not cut off.
+fn clip_start(duration: Duration, length: u32) -> u32 {
+    let duration = duration.as_secs() as u32;
+    (duration / 10).min(duration.saturating_sub(length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_start() {
+        assert_eq!(clip_start(Duration::from_secs(3000), 30), 300);
+        assert_eq!(clip_start(Duration::from_secs(200), 30), 20);
+        assert_eq!(clip_start(Duration::from_secs(40), 30), 4);
+        assert_eq!(clip_start(Duration::from_secs(20), 30), 0);
+    }
+
+    #[test]
+    fn test_settings_defaults() {
+        let settings: Settings = toml::from_str("").unwrap();
+        assert!(settings.contact_sheet);
+        assert!(!settings.preview_clip);
+        assert_eq!(settings.clip_length, 30);
+    }
+}
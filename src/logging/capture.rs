@@ -0,0 +1,231 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Captures the events logged during an operation.
+//!
+//! Events logged within an operation span (e.g. `copy_operation`) are formatted and collected with
+//! the span, so they can be saved with the operation when it finishes. Events are collected by the
+//! nearest operation span, so nested operations do not capture the same events twice. Spans are
+//! created by the code that starts the operation, and the events are retrieved using [`take`]:
+//!
+//! ```ignore
+//! let span = tracing::info_span!("copy_operation", sn = %serial_number, id = Empty);
+//! async move {
+//!     // ...
+//!     let log = capture::take(&Span::current());
+//! }.instrument(span)
+//! ```
+
+use std::fmt::{Debug, Write};
+use std::sync::Mutex;
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Span, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{Layer, Registry};
+
+/// The name of the span that captures the events logged during a copy operation.
+pub const COPY_OPERATION: &str = "copy_operation";
+
+/// The names of the spans that capture events.
+const CAPTURED_SPANS: &[&str] = &[COPY_OPERATION];
+
+/// The maximum number of bytes captured for an operation.
+///
+/// Prevents a misbehaving operation from using an unbounded amount of memory and database space.
+const MAX_CAPTURE_SIZE: usize = 1024 * 1024;
+
+/// Appended when the captured events are truncated.
+const TRUNCATED: &str = "... log truncated\n";
+
+/// The events captured for a span, stored in the span's extensions.
+struct Captured(Mutex<String>);
+
+/// Layer that captures the events logged within operation spans.
+pub struct CaptureLayer;
+
+impl<S> Layer<S> for CaptureLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if CAPTURED_SPANS.contains(&attrs.metadata().name())
+            && let Some(span) = ctx.span(id)
+        {
+            span.extensions_mut().insert(Captured(Mutex::new(String::new())));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+
+        for span in scope {
+            let extensions = span.extensions();
+            if let Some(captured) = extensions.get::<Captured>() {
+                let mut text = captured.0.lock().expect("capture lock should not be poisoned");
+                if text.len() < MAX_CAPTURE_SIZE {
+                    text.push_str(&format_event(event));
+                    if text.len() >= MAX_CAPTURE_SIZE {
+                        text.push_str(TRUNCATED);
+                    }
+                }
+                return;
+            }
+        }
+    }
+}
+
+/// The filter applied to the capture layer.
+///
+/// Debug events from the application and the programs it runs are captured regardless of the
+/// logging settings, so they are available when troubleshooting an operation.
+pub fn filter() -> Targets {
+    Targets::new()
+        .with_target("artie", Level::DEBUG)
+        .with_target("handbrake", Level::DEBUG)
+        .with_target("makemkv", Level::DEBUG)
+}
+
+/// Takes the events captured by a span.
+///
+/// Returns an empty string if the span does not capture events or events are not being captured
+/// (e.g. logging was not initialized). The captured events are cleared, so calling this again
+/// returns only the events logged since.
+///
+/// # Args
+///
+/// `span`:  The operation span.
+pub fn take(span: &Span) -> String {
+    span.with_subscriber(|(id, dispatch)| {
+        let registry = dispatch.downcast_ref::<Registry>()?;
+        let span = registry.span(id)?;
+        let extensions = span.extensions();
+        let captured = extensions.get::<Captured>()?;
+        let mut text = captured.0.lock().expect("capture lock should not be poisoned");
+        Some(std::mem::take(&mut *text))
+    })
+    .flatten()
+    .unwrap_or_default()
+}
+
+/// Formats an event as a line of text.
+///
+/// # Args
+///
+/// `event`:  The event to format.
+fn format_event(event: &Event<'_>) -> String {
+    let mut visitor = EventVisitor::default();
+    event.record(&mut visitor);
+
+    let metadata = event.metadata();
+    format!(
+        "{} {:>5} {}: {}{}\n",
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        metadata.level(),
+        metadata.target(),
+        visitor.message,
+        visitor.fields,
+    )
+}
+
+/// Collects the message and fields of an event.
+#[derive(Default)]
+struct EventVisitor {
+    /// The event's message.
+    message: String,
+
+    /// The event's other fields, formatted as ` name=value`.
+    fields: String,
+}
+
+impl Visit for EventVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_owned();
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    /// Runs a function with the capture layer as the default subscriber.
+    fn with_capture<T>(f: impl FnOnce() -> T) -> T {
+        let subscriber = tracing_subscriber::registry()
+            .with(CaptureLayer.with_filter(filter()));
+        tracing::subscriber::with_default(subscriber, f)
+    }
+
+    #[test]
+    fn test_capture() {
+        let text = with_capture(|| {
+            tracing::info!("before the operation");
+
+            let span = tracing::info_span!("copy_operation", sn = "SN-1", id = 7);
+            let _enter = span.enter();
+            tracing::info!(title = "Movie", "copying title");
+            tracing::debug_span!("nested").in_scope(|| tracing::warn!(count = 2, "retrying"));
+            tracing::trace!("not captured");
+
+            take(&span)
+        });
+
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let target = "artie::logging::capture::tests";
+        assert!(lines[0].ends_with(&format!(r#" INFO {}: copying title title="Movie""#, target)));
+        assert!(lines[1].ends_with(&format!(" WARN {}: retrying count=2", target)));
+    }
+
+    #[test]
+    fn test_take_clears() {
+        with_capture(|| {
+            let span = tracing::info_span!("copy_operation");
+            span.in_scope(|| tracing::info!("first"));
+            assert!(take(&span).contains("first"));
+
+            span.in_scope(|| tracing::info!("second"));
+            let text = take(&span);
+            assert!(!text.contains("first"));
+            assert!(text.contains("second"));
+
+            assert_eq!(take(&tracing::info_span!("other")), "");
+        });
+
+        assert_eq!(take(&Span::none()), "");
+    }
+
+    #[test]
+    fn test_truncated() {
+        let text = with_capture(|| {
+            let span = tracing::info_span!("copy_operation");
+            let _enter = span.enter();
+            let message = "x".repeat(1024);
+            for _ in 0..2048 {
+                tracing::info!("{}", message);
+            }
+            take(&span)
+        });
+
+        assert!(text.len() < MAX_CAPTURE_SIZE + 2048);
+        assert!(text.ends_with(TRUNCATED));
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Configures application logging.
+//!
+//! Events are written to stdout and, if enabled, to log files in the data folder that are rotated
+//! on a schedule (see [`Settings::file`]). Which events are written is controlled by the filter
+//! directives (see [`Settings::filter`]), which can be overridden using the `RUST_LOG` environment
+//! variable. For example, the following writes debug events for the application and warnings for
+//! everything else as JSON to daily log files:
+//!
+//! ```toml
+//! [logging]
+//! filter = "warn,artie=debug"
+//! json = true
+//! file = true
+//! rotation = "daily"
+//! ```
+//!
+//! Events logged during a copy operation are also captured (see [`capture`]) and saved with the
+//! copy operation, so the application's decisions are available next to the MakeMKV logs when
+//! troubleshooting a failed copy.
+//!
+//! Logging is initialized by calling [`init`] during application startup.
+
+pub mod capture;
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tracing_appender::rolling::RollingFileAppender;
+use tracing_subscriber::filter::EnvFilter;
+use tracing_subscriber::fmt::{self, MakeWriter};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{Layer, Registry};
+
+use crate::Result;
+use crate::path;
+
+use capture::CaptureLayer;
+
+/// The prefix of the log file names.
+const FILE_PREFIX: &str = "artie";
+
+/// Logging settings.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Settings {
+    /// The filter directives used to select the events that are logged (e.g. `warn,artie=debug`).
+    ///
+    /// See the `tracing-subscriber` `EnvFilter` documentation for the syntax. Ignored if the
+    /// `RUST_LOG` environment variable is set.
+    #[serde(default = "Settings::default_filter")]
+    pub filter: String,
+
+    /// Whether events are written as JSON objects (one per line) instead of text.
+    #[serde(default)]
+    pub json: bool,
+
+    /// Whether events are also written to log files.
+    #[serde(default)]
+    pub file: bool,
+
+    /// How often a new log file is started.
+    #[serde(default = "Settings::default_rotation")]
+    pub rotation: Rotation,
+
+    /// The number of log files to keep. The oldest files are deleted when a new file is started.
+    #[serde(default = "Settings::default_max_files")]
+    pub max_files: usize,
+
+    /// The folder the log files are written to.
+    ///
+    /// The `logs` folder in the data folder is used if not set.
+    #[serde(default)]
+    pub folder: Option<PathBuf>,
+}
+
+impl Settings {
+    /// The filter directives if not specified in the config.
+    fn default_filter() -> String {
+        String::from("artie=debug,handbrake=debug,makemkv=debug")
+    }
+
+    /// The log file rotation if not specified in the config.
+    fn default_rotation() -> Rotation {
+        Rotation::Daily
+    }
+
+    /// The number of log files to keep if not specified in the config.
+    fn default_max_files() -> usize {
+        7
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            filter: Self::default_filter(),
+            json: false,
+            file: false,
+            rotation: Self::default_rotation(),
+            max_files: Self::default_max_files(),
+            folder: None,
+        }
+    }
+}
+
+/// Specifies how often a new log file is started.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    Hourly,
+    Daily,
+
+    /// A single log file is used.
+    Never,
+}
+
+impl From<Rotation> for tracing_appender::rolling::Rotation {
+    fn from(value: Rotation) -> Self {
+        match value {
+            Rotation::Hourly => tracing_appender::rolling::Rotation::HOURLY,
+            Rotation::Daily => tracing_appender::rolling::Rotation::DAILY,
+            Rotation::Never => tracing_appender::rolling::Rotation::NEVER,
+        }
+    }
+}
+
+/// Initializes logging.
+///
+/// Must only be called once. The path module needs to be initialized first if log files are
+/// enabled without a folder.
+///
+/// # Args
+///
+/// `settings`:  The logging settings.
+///
+/// # Errors
+///
+/// [`crate::Error::LogFilter`] if the filter directives cannot be parsed.
+///
+/// [`crate::Error::LogFile`] if the log file folder cannot be created.
+pub fn init(settings: &Settings) -> Result<()> {
+    let directives = std::env::var(EnvFilter::DEFAULT_ENV)
+        .unwrap_or_else(|_| settings.filter.clone());
+    let filter = EnvFilter::builder().parse(&directives)?;
+
+    let mut layers = vec![output_layer(settings.json, true, std::io::stdout)];
+    if settings.file {
+        let folder = settings.folder.clone().unwrap_or_else(|| path::data_path("logs"));
+        let appender = RollingFileAppender::builder()
+            .rotation(settings.rotation.into())
+            .filename_prefix(FILE_PREFIX)
+            .filename_suffix("log")
+            .max_log_files(settings.max_files.max(1))
+            .build(&folder)?;
+        layers.push(output_layer(settings.json, false, appender));
+    }
+
+    tracing_subscriber::registry()
+        .with(layers.with_filter(filter))
+        .with(CaptureLayer.with_filter(capture::filter()))
+        .init();
+
+    if settings.file {
+        tracing::info!(rotation=?settings.rotation, "writing log files");
+    }
+    Ok(())
+}
+
+/// Creates the layer that writes formatted events.
+///
+/// # Args
+///
+/// `json`:  Whether the events are formatted as JSON.
+///
+/// `ansi`:  Whether ANSI colors are used. Should be `false` for files.
+///
+/// `writer`:  Where the events are written.
+fn output_layer<W>(json: bool, ansi: bool, writer: W) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer()
+        .with_ansi(ansi)
+        .with_writer(writer);
+    if json {
+        layer.json().boxed()
+    } else {
+        layer.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings() {
+        let settings: Settings = toml::from_str(r#"
+            filter = "warn,artie=trace"
+            file = true
+            rotation = "hourly"
+        "#).unwrap();
+
+        assert_eq!(settings.filter, "warn,artie=trace");
+        assert!(!settings.json);
+        assert!(settings.file);
+        assert_eq!(settings.rotation, Rotation::Hourly);
+        assert_eq!(settings.max_files, 7);
+        assert!(EnvFilter::builder().parse(&settings.filter).is_ok());
+        assert!(EnvFilter::builder().parse(&Settings::default().filter).is_ok());
+    }
+}
@@ -9,7 +9,12 @@ mod bus;
 mod db;
 mod drive;
 mod library;
+mod logging;
+mod mediaserver;
+mod metadata;
+mod metrics;
 mod net;
+mod notify;
 mod path;
 mod models;
 mod settings;
@@ -19,14 +24,14 @@ mod ui;
 #[cfg(test)]
 mod test_utils;
 
-use std::path::PathBuf;
+#[cfg(all(test, feature = "faux_drives"))]
+mod harness;
 
-use clap::{ArgAction, Parser};
+use std::path::{Path, PathBuf};
 
-use tracing::Level;
-use tracing_subscriber::Layer;
-use tracing_subscriber::filter::Targets;
-use tracing_subscriber::prelude::*;
+use clap::{ArgAction, Parser, ValueEnum};
+
+use tokio_util::sync::CancellationToken;
 
 pub use error::Error;
 
@@ -52,32 +57,106 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Parser, Debug)]
 #[command(name = "artie", about = "Media library creation orchestration tool.")]
 struct Args {
+    /// The config file to use instead of the ARTIE_CONFIG variable or the default location.
+    #[arg(long = "config", value_name = "FILE")]
+    config: Option<PathBuf>,
+
     /// Indicates that the application should be run as a worker node.
     #[arg(short = 'w', long = "worker", action = ArgAction::SetTrue)]
     worker: bool,
+
+    /// Runs an integrity scan of the video files, prints the results, and exits.
+    #[arg(long = "verify", action = ArgAction::SetTrue, conflicts_with = "worker")]
+    verify: bool,
+
+    /// Exports the catalog of copied discs and videos to a file and exits.
+    #[arg(long = "export", value_name = "FILE", conflicts_with_all = ["worker", "import"])]
+    export: Option<PathBuf>,
+
+    /// The format of the exported catalog.
+    #[arg(long = "format", value_enum, default_value_t = ExportFormat::Json, requires = "export")]
+    format: ExportFormat,
+
+    /// Includes the MakeMKV logs and disc metadata in a JSON export.
+    #[arg(long = "include-logs", action = ArgAction::SetTrue, requires = "export")]
+    include_logs: bool,
+
+    /// Imports a catalog exported as JSON into an empty database and exits.
+    #[arg(long = "import", value_name = "FILE", conflicts_with = "worker")]
+    import: Option<PathBuf>,
+
+    /// Backs up the database and exits.
+    #[arg(long = "backup", action = ArgAction::SetTrue, conflicts_with = "worker")]
+    backup: bool,
+
+    /// Replaces the database with a backup and exits.
+    #[arg(long = "restore", value_name = "FILE", conflicts_with_all = ["worker", "backup"])]
+    restore: Option<PathBuf>,
+
+    /// Validates the config file, prints any problems, and exits.
+    #[arg(long = "check-config", action = ArgAction::SetTrue)]
+    check_config: bool,
 }
 
-/// Get the path to the application's config file.
+/// Specifies the formats the catalog can be exported as.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ExportFormat {
+    /// All of the catalog records (see [`library::catalog`]).
+    Json,
+
+    /// A flat listing of the videos for spreadsheets.
+    Csv,
+}
+
+/// Loads and validates the settings, printing any validation problems.
+///
+/// # Args
+///
+/// `path`:  The path of the config file.
 ///
-/// TODO: This currently just returns a hard-coded path for the purposes of development. It will
-///       need to be updated to look at an environment variable first, then fallback to a standard
-///       location based on the OS (e.g. ~/.config/artie or %AppData%/artie).
-fn get_config_path() -> PathBuf {
-    PathBuf::from("artie.toml")
+/// `create`:  Whether the default config is written if the file does not exist.
+///
+/// # Errors
+///
+/// See [`Settings::load_or_create`] and [`Settings::validate`].
+fn load_settings(path: &Path, create: bool) -> Result<Settings> {
+    let settings = if create {
+        Settings::load_or_create(path)?
+    } else {
+        Settings::from_file(path)?
+    };
+
+    if let Err(error) = settings.validate() {
+        if let Error::InvalidSettings { problems } = &error {
+            eprintln!("invalid config {}:", path.display());
+            for problem in problems {
+                eprintln!("  {}", problem);
+            }
+        }
+        return Err(error);
+    }
+
+    Ok(settings)
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    // Logging is configured by the settings, so they are loaded before anything is logged. The
+    // paths are initialized first since the log files are written to the data folder.
+    let config_path = settings::config_path(args.config.as_deref());
+
+    if args.check_config {
+        load_settings(&config_path, false)?;
+        println!("config {} is valid", config_path.display());
+        return Ok(());
+    }
 
-    let filter = Targets::new()
-        .with_target("artie", Level::DEBUG)
-        .with_target("handbrake", Level::DEBUG)
-        .with_target("makemkv", Level::DEBUG);
+    let settings = load_settings(&config_path, true)?;
+    let ui_settings = settings.clone();
 
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer().with_filter(filter))
-        .init();
+    path::init(settings.paths)?;
+    logging::init(&settings.logging)?;
 
     let mode = if args.worker {
         Mode::Worker
@@ -87,9 +166,18 @@ fn main() -> Result<()> {
 
     tracing::info!(?mode, "starting");
 
-    let settings = Settings::from_file(&get_config_path())?;
+    library::preview::init(settings.previews);
+    mediaserver::init(settings.media_server);
 
-    path::init(settings.paths)?;
+    // The database must be restored before it is opened.
+    if let Some(file_path) = &args.restore {
+        let previous = db::backup::restore(file_path, &settings.backup)?;
+        println!("restored {}", file_path.display());
+        if let Some(previous) = previous {
+            println!("previous database saved to {}", previous.display());
+        }
+        return Ok(());
+    }
 
     // Initialize the message bus channel first. Bus initialization is done in two parts so that
     // the channel can be provided to the other actors.
@@ -101,22 +189,70 @@ fn main() -> Result<()> {
         None
     };
 
+    // The command line operations only need the database, so they are run before the drive,
+    // network, and notify actors are started.
+    if args.verify || args.backup || args.export.is_some() || args.import.is_some() {
+        // The flags for these operations conflict with `--worker`, so this is the control node
+        // and the database was opened above.
+        let db = db.expect("database should be open for command line operations");
+        bus::init_db_processor(db, bus_recv);
+
+        if args.verify {
+            let scan = task::block_on(library::integrity::scan(&bus, &settings.integrity))?;
+            println!("{}", scan.summary());
+            for finding in &scan.findings {
+                println!("{:?}", finding);
+            }
+        } else if args.backup {
+            let backup_path = task::block_on(db::backup::backup(&bus, &settings.backup))?;
+            println!("backed up to {}", backup_path.display());
+        } else if let Some(file_path) = &args.export {
+            let summary = match args.format {
+                ExportFormat::Json => task::block_on(
+                    library::catalog::export_json(&bus, file_path, args.include_logs),
+                )?,
+                ExportFormat::Csv => {
+                    task::block_on(library::catalog::export_csv(&bus, file_path))?
+                },
+            };
+            println!("exported {}", summary);
+        } else if let Some(file_path) = &args.import {
+            let summary = task::block_on(library::catalog::import_json(&bus, file_path))?;
+            println!("imported {}", summary);
+        }
+        return Ok(());
+    }
+
     let drive_mgr = drive::init(&bus, mode)?;
 
+    let metadata = if mode == Mode::Control {
+        Some(metadata::init(&bus, &settings.metadata)?)
+    } else {
+        None
+    };
+
     let net = if mode == Mode::Control {
-        client::manager::init(&bus, &settings.net)
+        client::manager::init(&bus, &settings.net, Some(config_path.clone()))
     } else {
-        server::init(&bus, &settings.net)
+        server::init(&bus, &settings.net, CancellationToken::new())
     };
 
+    let notify = notify::init(&settings.notify)?;
+    metrics::init(&settings.metrics);
+
     // Start the message bus processing task.
-    let join_handle = bus::init_processor(db, drive_mgr, net, bus_recv);
+    let join_handle = bus::init_processor(db, drive_mgr, metadata, net, notify, bus_recv);
+
+    if mode == Mode::Control {
+        library::integrity::init(&bus, settings.integrity);
+        db::backup::init(&bus, settings.backup);
+    }
 
     // TODO: Eventually, we will want to use feature flags so that we can compile a version without
     //       the UI all together.
 
     if mode == Mode::Control {
-        let _ = ui::run(mode, &bus)?;
+        let _ = ui::run(mode, &bus, ui_settings, config_path)?;
     } else {
         task::block_on(join_handle).unwrap()
     }
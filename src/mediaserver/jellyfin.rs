@@ -0,0 +1,215 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Jellyfin media server.
+//!
+//! Uses the Jellyfin HTTP API (<https://api.jellyfin.org>) authenticated using an API key created
+//! in the dashboard. Changed paths are reported using the `/Library/Media/Updated` endpoint, which
+//! only scans the folders containing the paths instead of the whole library.
+//!
+//! The API cannot search for an item by its path, so the most recently added items are listed and
+//! their paths are compared instead (see [`RECENT_LIMIT`]).
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+use super::{MediaServer, ServerKind};
+
+/// The number of recently added items checked when looking for an item by its path.
+const RECENT_LIMIT: u32 = 200;
+
+/// Media server implementation for Jellyfin.
+pub struct JellyfinServer {
+    /// The client used to make requests.
+    client: reqwest::Client,
+
+    /// The base URL of the server.
+    base_url: String,
+
+    /// The API key.
+    api_key: String,
+}
+
+impl JellyfinServer {
+    /// Creates a new server.
+    ///
+    /// # Args
+    ///
+    /// `base_url`:  The base URL of the server (e.g. `http://localhost:8096`).
+    ///
+    /// `api_key`:  The API key.
+    pub fn new(base_url: &str, api_key: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            api_key: api_key.to_owned(),
+        }
+    }
+
+    /// Returns the value of the authorization header.
+    fn authorization(&self) -> String {
+        format!("MediaBrowser Token=\"{}\"", self.api_key)
+    }
+}
+
+impl MediaServer for JellyfinServer {
+    fn kind(&self) -> ServerKind {
+        ServerKind::Jellyfin
+    }
+
+    async fn refresh(&self, paths: &[PathBuf]) -> Result<()> {
+        let body = MediaUpdateInfo {
+            updates: paths.iter()
+                .map(|p| MediaUpdate {
+                    path: p.display().to_string(),
+                    update_type: "Created",
+                })
+                .collect(),
+        };
+
+        let url = format!("{}/Library/Media/Updated", self.base_url);
+        self.client.post(&url)
+            .header(reqwest::header::AUTHORIZATION, self.authorization())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn contains(&self, path: &Path) -> Result<bool> {
+        let url = format!("{}/Items", self.base_url);
+        let items: ItemsResult = self.client.get(&url)
+            .header(reqwest::header::AUTHORIZATION, self.authorization())
+            .query(&[
+                ("Recursive", "true"),
+                ("Fields", "Path"),
+                ("SortBy", "DateCreated"),
+                ("SortOrder", "Descending"),
+                ("Limit", &RECENT_LIMIT.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(items.items.iter().any(|i| i.path.as_deref().map(Path::new) == Some(path)))
+    }
+}
+
+/// The body of a media updated request.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct MediaUpdateInfo {
+    updates: Vec<MediaUpdate>,
+}
+
+/// A changed path in a media updated request.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct MediaUpdate {
+    path: String,
+    update_type: &'static str,
+}
+
+/// The response to an items request.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ItemsResult {
+    items: Vec<Item>,
+}
+
+/// An item in the library.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Item {
+    /// The path of the item's file. Not all items have a path (e.g. collections).
+    #[serde(default)]
+    path: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    use crate::Error;
+    use crate::mediaserver::{Settings, refresh_and_verify};
+    use crate::task;
+    use crate::test_utils::MockHttpServer;
+
+    const MOVIE_PATH: &str = "/media/Movie (2001)/Movie (2001).mkv";
+
+    fn settings() -> Settings {
+        Settings {
+            verify_attempts: 3,
+            verify_interval_secs: 0,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn test_refresh_and_verify() {
+        let lists = Arc::new(AtomicU32::new(0));
+        let counter = lists.clone();
+        let mock = MockHttpServer::start(move |request| {
+            if request.method == "POST" {
+                return (204, String::new());
+            }
+            // The item only appears on the second check.
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                (200, String::from(r#"{"Items": [{"Name": "Folder"}]}"#))
+            } else {
+                (200, format!(r#"{{"Items": [{{"Path": "{}"}}]}}"#, MOVIE_PATH))
+            }
+        });
+
+        let server = JellyfinServer::new(&mock.url(), "secret");
+        let paths = vec![PathBuf::from(MOVIE_PATH)];
+        task::block_on(refresh_and_verify(&server, &paths, &settings())).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].target, "/Library/Media/Updated");
+        assert_eq!(requests[0].header("Authorization"), Some("MediaBrowser Token=\"secret\""));
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(body["Updates"][0]["Path"], MOVIE_PATH);
+        assert_eq!(body["Updates"][0]["UpdateType"], "Created");
+
+        assert_eq!(requests[1].method, "GET");
+        assert!(requests[1].target.starts_with("/Items?Recursive=true&Fields=Path"));
+    }
+
+    #[test]
+    fn test_verify_not_found() {
+        let mock = MockHttpServer::start(|request| match request.method.as_str() {
+            "POST" => (204, String::new()),
+            _ => (200, String::from(r#"{"Items": []}"#)),
+        });
+
+        let server = JellyfinServer::new(&mock.url(), "secret");
+        let paths = vec![PathBuf::from(MOVIE_PATH)];
+        let result = task::block_on(refresh_and_verify(&server, &paths, &settings()));
+
+        assert!(matches!(result, Err(Error::MediaServerItemNotFound { .. })));
+        assert_eq!(mock.requests().len(), 4);
+    }
+
+    #[test]
+    fn test_refresh_unauthorized() {
+        let mock = MockHttpServer::start(|_| (401, String::new()));
+
+        let server = JellyfinServer::new(&mock.url(), "wrong");
+        let result = task::block_on(server.refresh(&[PathBuf::from(MOVIE_PATH)]));
+
+        assert!(matches!(result, Err(Error::Http(_))));
+    }
+}
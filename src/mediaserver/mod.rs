@@ -0,0 +1,260 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Notifies the media server when videos are added to the library.
+//!
+//! Media servers (see: [`MediaServer`]) only notice new files when they scan the library, which may
+//! not happen until hours later. After videos are cataloged, [`library_updated`] asks the server to
+//! refresh the paths of the videos and then waits for the videos to appear in the server's library.
+//!
+//! The following media servers are available:
+//!
+//! - [`jellyfin::JellyfinServer`] - [Jellyfin](https://jellyfin.org)
+//!
+//! The media server may see the library folder at a different path than this application does
+//! (e.g. when the server runs in a container). The path the server uses can be configured using
+//! [`Settings::library_path`].
+//!
+//! The settings need initialized by calling [`init`] during application startup.
+
+pub mod jellyfin;
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+use crate::models::MediaLocation;
+use crate::path;
+
+use jellyfin::JellyfinServer;
+
+/// The media server settings.
+///
+/// The settings are setup early in application initialization and are not expected to change
+/// afterwards.
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Specifies the supported media servers.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerKind {
+    /// Jellyfin (<https://jellyfin.org>).
+    Jellyfin,
+}
+
+/// Media server settings.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Settings {
+    /// The media server to notify.
+    ///
+    /// Notifications are disabled if not set.
+    #[serde(default)]
+    pub server: Option<ServerKind>,
+
+    /// The base URL of the media server (e.g. `http://localhost:8096`).
+    #[serde(default)]
+    pub url: String,
+
+    /// The API key used to authenticate with the media server.
+    #[serde(default)]
+    pub api_key: String,
+
+    /// The path of the library folder as seen by the media server.
+    ///
+    /// The library path from the path settings is used if not set.
+    #[serde(default)]
+    pub library_path: Option<PathBuf>,
+
+    /// The number of times to check for the videos after requesting the refresh.
+    #[serde(default = "Settings::default_verify_attempts")]
+    pub verify_attempts: u32,
+
+    /// The number of seconds between each check for the videos.
+    #[serde(default = "Settings::default_verify_interval_secs")]
+    pub verify_interval_secs: u64,
+}
+
+impl Settings {
+    /// The number of checks for the videos if not specified in the config.
+    fn default_verify_attempts() -> u32 {
+        12
+    }
+
+    /// The number of seconds between checks if not specified in the config.
+    fn default_verify_interval_secs() -> u64 {
+        5
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            server: None,
+            url: String::default(),
+            api_key: String::default(),
+            library_path: None,
+            verify_attempts: Self::default_verify_attempts(),
+            verify_interval_secs: Self::default_verify_interval_secs(),
+        }
+    }
+}
+
+/// A media server that streams the videos in the library.
+pub trait MediaServer {
+    /// Returns the kind of media server.
+    fn kind(&self) -> ServerKind;
+
+    /// Asks the media server to scan the provided paths for changes.
+    ///
+    /// # Args
+    ///
+    /// `paths`:  The paths of the added or changed files as seen by the media server.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Http`] if the request to the server fails.
+    fn refresh(&self, paths: &[PathBuf]) -> impl Future<Output = Result<()>> + Send;
+
+    /// Returns `true` if the media server's library contains an item for the file at the path.
+    ///
+    /// # Args
+    ///
+    /// `path`:  The path of the file as seen by the media server.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Http`] if the request to the server fails.
+    fn contains(&self, path: &Path) -> impl Future<Output = Result<bool>> + Send;
+}
+
+/// Initializes the media server settings.
+///
+/// Only the first call has any effect.
+pub fn init(settings: Settings) {
+    if settings.server.is_none() {
+        tracing::info!("media server not configured");
+    }
+    let _ = SETTINGS.set(settings);
+}
+
+/// Notifies the media server that videos were added to the library.
+///
+/// The media server is asked to refresh the paths of the videos and then checked until all of the
+/// videos appear in its library. Locations outside of the library are ignored.
+///
+/// # Args
+///
+/// `locations`:  The locations of the videos that were added.
+///
+/// # Errors
+///
+/// [`Error::MediaServerDisabled`] if a media server has not been configured.
+///
+/// [`Error::Http`] if a request to the server fails.
+///
+/// [`Error::MediaServerItemNotFound`] if a video did not appear in the media server's library.
+///
+/// # Panics
+///
+/// If the module was not initialized.
+pub async fn library_updated(locations: &[MediaLocation]) -> Result<()> {
+    let settings = SETTINGS.get().expect("mediaserver module not initialized");
+
+    let library_root = settings.library_path.clone()
+        .or_else(|| path::location_path(&MediaLocation::Library(PathBuf::new())))
+        .expect("library location should have a path");
+    let paths = server_paths(locations, &library_root);
+
+    match settings.server {
+        Some(ServerKind::Jellyfin) => {
+            let server = JellyfinServer::new(&settings.url, &settings.api_key);
+            refresh_and_verify(&server, &paths, settings).await
+        },
+        None => Err(Error::MediaServerDisabled),
+    }
+}
+
+/// Asks a media server to refresh the paths and waits for them to appear in its library.
+///
+/// # Args
+///
+/// `server`:  The media server.
+///
+/// `paths`:  The paths of the files as seen by the media server.
+///
+/// `settings`:  The media server settings.
+///
+/// # Errors
+///
+/// See [`library_updated`].
+async fn refresh_and_verify<S: MediaServer>(
+    server: &S,
+    paths: &[PathBuf],
+    settings: &Settings,
+) -> Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    server.refresh(paths).await?;
+    tracing::info!(server=?server.kind(), count=paths.len(), "requested media server refresh");
+
+    let interval = Duration::from_secs(settings.verify_interval_secs);
+    let mut pending: Vec<&PathBuf> = paths.iter().collect();
+    for attempt in 0..settings.verify_attempts.max(1) {
+        if attempt > 0 {
+            tokio::time::sleep(interval).await;
+        }
+
+        let mut remaining = Vec::with_capacity(pending.len());
+        for path in pending {
+            if !server.contains(path).await? {
+                remaining.push(path);
+            }
+        }
+        pending = remaining;
+
+        if pending.is_empty() {
+            tracing::info!(server=?server.kind(), "media server refresh verified");
+            return Ok(());
+        }
+    }
+
+    Err(Error::MediaServerItemNotFound { path: pending[0].clone() })
+}
+
+/// Converts the library locations to the paths used by the media server.
+///
+/// # Args
+///
+/// `locations`:  The locations to convert. Locations outside of the library are skipped.
+///
+/// `library_root`:  The path of the library folder as seen by the media server.
+fn server_paths(locations: &[MediaLocation], library_root: &Path) -> Vec<PathBuf> {
+    locations.iter()
+        .filter_map(|location| match location {
+            MediaLocation::Library(relative) => Some(library_root.join(relative)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_paths() {
+        let locations = [
+            MediaLocation::Library(PathBuf::from("Movie (2001)/Movie (2001).mkv")),
+            MediaLocation::Inbox(PathBuf::from("movie/1/title_t00.mkv")),
+            MediaLocation::Deleted,
+        ];
+        assert_eq!(server_paths(&locations, Path::new("/media")), vec![
+            PathBuf::from("/media/Movie (2001)/Movie (2001).mkv"),
+        ]);
+    }
+}
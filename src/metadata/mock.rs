@@ -0,0 +1,211 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! File based metadata provider.
+//!
+//! The mock provider reads the metadata from a local JSON file instead of an online service. This
+//! allows the metadata features to be tested and developed without an API key or network access.
+//!
+//! The file contains a JSON array of [`MediaDetails`] objects. For example:
+//!
+//! ```json
+//! [
+//!   {
+//!     "id": { "provider": "mock", "id": 1 },
+//!     "media_type": "Show",
+//!     "name": "Example Show",
+//!     "year": 2004,
+//!     "runtime": 2640,
+//!     "seasons": [
+//!       {
+//!         "number": 1,
+//!         "episodes": [
+//!           { "number": 1, "name": "Pilot", "runtime": 2880 },
+//!           { "number": 2, "name": "Second", "runtime": 2580 }
+//!         ]
+//!       }
+//!     ]
+//!   }
+//! ]
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use crate::{Error, Result};
+use crate::models::MediaType;
+
+use super::{MediaDetails, MetadataProvider, ProviderId, ProviderKind, SearchResult};
+
+/// Metadata provider that reads its data from a local JSON file.
+pub struct MockProvider {
+    /// The movies and shows known to the provider.
+    entries: Vec<MediaDetails>,
+}
+
+impl MockProvider {
+    /// Creates a new mock provider from the provided entries.
+    pub fn new(entries: Vec<MediaDetails>) -> Self {
+        Self { entries }
+    }
+
+    /// Loads the mock provider's data from a JSON file.
+    ///
+    /// # Args
+    ///
+    /// `path`:  The path to the JSON file.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::FileNotFound`] if the file does not exist.
+    ///
+    /// [`Error::StdIo`] if the file cannot be read.
+    ///
+    /// [`Error::SerdeJson`] if the file's content cannot be parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Err(Error::FileNotFound { path: path.to_owned() });
+        }
+        let text = fs::read_to_string(path)?;
+        let entries: Vec<MediaDetails> = serde_json::from_str(&text)?;
+        Ok(Self::new(entries))
+    }
+}
+
+impl MetadataProvider for MockProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Mock
+    }
+
+    async fn search(
+        &self,
+        media_type: MediaType,
+        query: &str,
+        year: Option<u16>,
+    ) -> Result<Vec<SearchResult>> {
+        let query = query.to_lowercase();
+        let results = self.entries.iter()
+            .filter(|e| e.media_type == media_type)
+            .filter(|e| e.name.to_lowercase().contains(&query))
+            .filter(|e| year.is_none_or(|year| e.year == year))
+            .map(|e| SearchResult {
+                id: e.id.clone(),
+                media_type: e.media_type,
+                name: e.name.clone(),
+                year: e.year,
+                overview: String::default(),
+            })
+            .collect();
+        Ok(results)
+    }
+
+    async fn details(&self, media_type: MediaType, id: u64) -> Result<MediaDetails> {
+        let id = ProviderId { provider: ProviderKind::Mock, id };
+        self.entries.iter()
+            .find(|e| e.media_type == media_type && e.id == id)
+            .cloned()
+            .ok_or(Error::MetadataNotFound { id: id.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use crate::metadata::{Episode, Season};
+    use crate::task;
+    use crate::test_utils::TempFile;
+
+    fn make_provider() -> MockProvider {
+        MockProvider::new(vec![
+            MediaDetails {
+                id: ProviderId { provider: ProviderKind::Mock, id: 1 },
+                media_type: MediaType::Movie,
+                name: String::from("The Test Movie"),
+                year: 2001,
+                runtime: Some(Duration::from_secs(6000)),
+                seasons: Vec::new(),
+            },
+            MediaDetails {
+                id: ProviderId { provider: ProviderKind::Mock, id: 2 },
+                media_type: MediaType::Movie,
+                name: String::from("The Test Movie"),
+                year: 2019,
+                runtime: Some(Duration::from_secs(6600)),
+                seasons: Vec::new(),
+            },
+            MediaDetails {
+                id: ProviderId { provider: ProviderKind::Mock, id: 3 },
+                media_type: MediaType::Show,
+                name: String::from("Test Show"),
+                year: 2004,
+                runtime: Some(Duration::from_secs(2640)),
+                seasons: vec![
+                    Season {
+                        number: 1,
+                        episodes: vec![
+                            Episode {
+                                number: 1,
+                                name: String::from("Pilot"),
+                                runtime: Some(Duration::from_secs(2880)),
+                            },
+                        ],
+                    },
+                ],
+            },
+        ])
+    }
+
+    #[test]
+    fn test_search_by_title() {
+        let provider = make_provider();
+        let results = task::block_on(provider.search(MediaType::Movie, "test movie", None))
+            .unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_search_by_title_and_year() {
+        let provider = make_provider();
+        let results = task::block_on(provider.search(MediaType::Movie, "test", Some(2019)))
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id.id, 2);
+    }
+
+    #[test]
+    fn test_search_filters_media_type() {
+        let provider = make_provider();
+        let results = task::block_on(provider.search(MediaType::Show, "test", None)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Test Show");
+    }
+
+    #[test]
+    fn test_details() {
+        let provider = make_provider();
+        let details = task::block_on(provider.details(MediaType::Show, 3)).unwrap();
+        assert_eq!(details.name, "Test Show");
+        assert_eq!(details.season(1).unwrap().episodes.len(), 1);
+        assert!(details.season(2).is_none());
+    }
+
+    #[test]
+    fn test_details_not_found() {
+        let provider = make_provider();
+        let result = task::block_on(provider.details(MediaType::Movie, 3));
+        assert!(matches!(result, Err(Error::MetadataNotFound { .. })));
+    }
+
+    #[test]
+    fn test_load() {
+        let temp_file = TempFile::new(Path::new("artie.test.metadata.mock.json"));
+        let json = serde_json::to_string(&make_provider().entries).unwrap();
+        fs::write(temp_file.path(), json).unwrap();
+
+        let provider = MockProvider::load(temp_file.path()).unwrap();
+        assert_eq!(provider.entries.len(), 3);
+    }
+}
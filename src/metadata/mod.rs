@@ -0,0 +1,746 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Online metadata lookups for movies and shows.
+//!
+//! Metadata providers (see: [`MetadataProvider`]) are used to look up the canonical name, release
+//! year, and identifiers of a movie or show along with season, episode, and runtime information.
+//! The canonical identifiers allow the library to generate folder names the media server can match
+//! without any guess work (e.g. `Movie (2001) [tmdbid-1234]`).
+//!
+//! The following providers are available:
+//!
+//! - [`tmdb::TmdbProvider`] - [The Movie Database](https://www.themoviedb.org)
+//! - [`tvdb::TvdbProvider`] - [TheTVDB](https://thetvdb.com)
+//! - [`mock::MockProvider`] - Reads metadata from a local JSON file. Used for testing and offline
+//!   development.
+//!
+//! # Actor
+//!
+//! Lookups are performed by the metadata actor which is created by calling [`init`] during
+//! application startup. Responses from the provider are cached in the database to limit the
+//! number of requests made to the online services. The following functions can be used to make
+//! requests to the actor:
+//!
+//! - [`search`] - Search for a movie or show by title and release year.
+//! - [`details`] - Get the details of a movie or show including its seasons and episodes.
+
+pub mod mock;
+pub mod tmdb;
+pub mod tvdb;
+
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::oneshot;
+
+use crate::{Error, Result};
+use crate::actor::{self, Response};
+use crate::bus;
+use crate::db;
+use crate::db::metadata_cache::Kind as CacheKind;
+use crate::models::MediaType;
+use crate::task;
+
+use mock::MockProvider;
+use tmdb::TmdbProvider;
+use tvdb::TvdbProvider;
+
+/// Handle used to communicate with the metadata actor.
+pub type Handle = actor::Handle<Message>;
+
+/// Messages used to send requests to the metadata actor.
+#[derive(Debug)]
+pub enum Message {
+    /// Get the details of a movie or show.
+    Details {
+        media_type: MediaType,
+        id: ProviderId,
+        response: Response<MediaDetails>,
+    },
+
+    /// Search for a movie or show.
+    Search {
+        media_type: MediaType,
+        query: String,
+        year: Option<u16>,
+        response: Response<Vec<SearchResult>>,
+    },
+}
+
+/// Specifies the metadata providers.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// The Movie Database (<https://www.themoviedb.org>).
+    Tmdb,
+
+    /// TheTVDB (<https://thetvdb.com>).
+    Tvdb,
+
+    /// Local JSON file used for testing.
+    Mock,
+}
+
+impl ProviderKind {
+    /// Convert the provider kind to its string value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProviderKind::Tmdb => "tmdb",
+            ProviderKind::Tvdb => "tvdb",
+            ProviderKind::Mock => "mock",
+        }
+    }
+
+    /// Create a provider kind from its string value returning `None` if the provided value cannot
+    /// be converted.
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "tmdb" => Some(ProviderKind::Tmdb),
+            "tvdb" => Some(ProviderKind::Tvdb),
+            "mock" => Some(ProviderKind::Mock),
+            _ => None,
+        }
+    }
+}
+
+/// The canonical identifier of a movie or show assigned by a metadata provider.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ProviderId {
+    /// The provider that assigned the identifier.
+    pub provider: ProviderKind,
+
+    /// The identifier assigned by the provider.
+    pub id: u64,
+}
+
+impl ProviderId {
+    /// Returns the tag used in folder and file names so that Jellyfin can match the media to the
+    /// exact movie or show (e.g. `[tmdbid-1234]`).
+    pub fn jellyfin_tag(&self) -> String {
+        format!("[{}id-{}]", self.provider.as_str(), self.id)
+    }
+
+    /// Parses an identifier from its string form (e.g. `tmdb:1234`) returning `None` if the value
+    /// cannot be parsed.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (provider, id) = s.split_once(':')?;
+        Some(Self {
+            provider: ProviderKind::from_string(provider)?,
+            id: id.parse::<u64>().ok()?,
+        })
+    }
+}
+
+impl Display for ProviderId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.provider.as_str(), self.id)
+    }
+}
+
+/// A movie or show found when searching a metadata provider.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SearchResult {
+    /// The canonical identifier of the movie or show.
+    pub id: ProviderId,
+
+    /// The type of media.
+    pub media_type: MediaType,
+
+    /// The canonical name of the movie or show.
+    pub name: String,
+
+    /// The release year.
+    ///
+    /// For television shows, this is the release year of the first season. Zero if unknown.
+    pub year: u16,
+
+    /// Brief description of the movie or show.
+    pub overview: String,
+}
+
+/// The details of a movie or show.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MediaDetails {
+    /// The canonical identifier of the movie or show.
+    pub id: ProviderId,
+
+    /// The type of media.
+    pub media_type: MediaType,
+
+    /// The canonical name of the movie or show.
+    pub name: String,
+
+    /// The release year.
+    ///
+    /// For television shows, this is the release year of the first season. Zero if unknown.
+    pub year: u16,
+
+    /// The runtime of a movie or the typical runtime of an episode for shows.
+    #[serde(default, with = "duration_secs")]
+    pub runtime: Option<Duration>,
+
+    /// List of seasons.
+    ///
+    /// This will be empty for movies.
+    #[serde(default)]
+    pub seasons: Vec<Season>,
+}
+
+impl MediaDetails {
+    /// Returns the season with the provided number or `None` if the season is not known.
+    pub fn season(&self, number: u16) -> Option<&Season> {
+        self.seasons.iter().find(|s| s.number == number)
+    }
+}
+
+/// A season of a show.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Season {
+    /// The season number.
+    ///
+    /// Season zero is typically used by the providers for specials.
+    pub number: u16,
+
+    /// The episodes in the season ordered by episode number.
+    pub episodes: Vec<Episode>,
+}
+
+/// An episode of a show.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Episode {
+    /// The episode number within the season.
+    pub number: u16,
+
+    /// The name of the episode.
+    pub name: String,
+
+    /// The episode's runtime if known.
+    #[serde(default, with = "duration_secs")]
+    pub runtime: Option<Duration>,
+}
+
+/// Metadata provider settings.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Settings {
+    /// The provider used for lookups.
+    ///
+    /// Lookups are disabled if not set.
+    #[serde(default)]
+    pub provider: Option<ProviderKind>,
+
+    /// The API key (v3) for The Movie Database.
+    #[serde(default)]
+    pub tmdb_api_key: String,
+
+    /// The API key for TheTVDB.
+    #[serde(default)]
+    pub tvdb_api_key: String,
+
+    /// Path to the JSON file used by the mock provider.
+    #[serde(default)]
+    pub mock_path: String,
+
+    /// Number of days a cached response is considered valid.
+    #[serde(default = "Settings::default_cache_days")]
+    pub cache_days: u32,
+}
+
+impl Settings {
+    /// The number of days to cache responses if not specified in the config.
+    fn default_cache_days() -> u32 {
+        30
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            provider: None,
+            tmdb_api_key: String::default(),
+            tvdb_api_key: String::default(),
+            mock_path: String::default(),
+            cache_days: Self::default_cache_days(),
+        }
+    }
+}
+
+/// Looks up movie and show metadata from an online service.
+///
+/// Implementations are expected to be cheap to share between tasks since a lookup is performed
+/// on its own task so that the metadata actor is not blocked while waiting for a response.
+pub trait MetadataProvider {
+    /// Returns the kind of provider.
+    fn kind(&self) -> ProviderKind;
+
+    /// Searches for a movie or show.
+    ///
+    /// # Args
+    ///
+    /// `media_type`:  The type of media to search for.
+    ///
+    /// `query`:  The title (or partial title) to search for.
+    ///
+    /// `year`:  If `Some`, limits the results to the provided release year.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Http`] if the request to the service fails.
+    fn search(
+        &self,
+        media_type: MediaType,
+        query: &str,
+        year: Option<u16>,
+    ) -> impl Future<Output = Result<Vec<SearchResult>>> + Send;
+
+    /// Gets the details for a movie or show.
+    ///
+    /// # Args
+    ///
+    /// `media_type`:  The type of media the identifier is for.
+    ///
+    /// `id`:  The provider's identifier for the movie or show.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Http`] if the request to the service fails.
+    ///
+    /// [`Error::MetadataNotFound`] if the movie or show could not be found.
+    fn details(
+        &self,
+        media_type: MediaType,
+        id: u64,
+    ) -> impl Future<Output = Result<MediaDetails>> + Send;
+}
+
+/// Get the details of a movie or show.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the metadata actor.
+///
+/// `media_type`:  The type of media the identifier is for.
+///
+/// `id`:  The canonical identifier of the movie or show.
+///
+/// # Errors
+///
+/// [`Error::ChannelSend`] if the request could not be sent to the metadata actor.
+///
+/// [`Error::Http`] if the request to the service fails.
+///
+/// [`Error::MetadataDisabled`] if a provider has not been configured.
+///
+/// [`Error::MetadataNotFound`] if the movie or show could not be found.
+///
+/// [`Error::ResponseRecv`] if the response to the request could not be processed.
+pub async fn details(
+    bus: &bus::Handle,
+    media_type: MediaType,
+    id: ProviderId,
+) -> Result<MediaDetails> {
+    let (tx, rx) = oneshot::channel();
+    let msg = Message::Details { media_type, id, response: tx };
+    bus.send(msg).await?;
+    rx.await?
+}
+
+/// Search for a movie or show.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the metadata actor.
+///
+/// `media_type`:  The type of media to search for.
+///
+/// `query`:  The title (or partial title) to search for.
+///
+/// `year`:  If `Some`, limits the results to the provided release year.
+///
+/// # Errors
+///
+/// [`Error::ChannelSend`] if the request could not be sent to the metadata actor.
+///
+/// [`Error::Http`] if the request to the service fails.
+///
+/// [`Error::MetadataDisabled`] if a provider has not been configured.
+///
+/// [`Error::ResponseRecv`] if the response to the request could not be processed.
+pub async fn search(
+    bus: &bus::Handle,
+    media_type: MediaType,
+    query: &str,
+    year: Option<u16>,
+) -> Result<Vec<SearchResult>> {
+    let (tx, rx) = oneshot::channel();
+    let msg = Message::Search {
+        media_type,
+        query: query.to_owned(),
+        year,
+        response: tx,
+    };
+    bus.send(msg).await?;
+    rx.await?
+}
+
+/// Create the metadata actor.
+///
+/// This will create the provider specified in the settings and spawn the task for processing
+/// requests.
+///
+/// # Args
+///
+/// `bus`:  Handle used to send messages to other actors via the message bus. Mainly used for
+/// connecting to the database to read and write the cache.
+///
+/// `settings`:  The metadata settings.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] or [`Error::SerdeJson`] if the mock provider is configured and its data file
+/// cannot be read.
+pub fn init(bus: &bus::Handle, settings: &Settings) -> Result<Handle> {
+    let cache_age = Duration::from_secs(u64::from(settings.cache_days) * 24 * 60 * 60);
+
+    let handle = match settings.provider {
+        Some(ProviderKind::Tmdb) => {
+            let provider = TmdbProvider::new(&settings.tmdb_api_key);
+            actor::create_and_run("metadata", MessageProcessor::new(bus, provider, cache_age))
+        },
+        Some(ProviderKind::Tvdb) => {
+            let provider = TvdbProvider::new(&settings.tvdb_api_key);
+            actor::create_and_run("metadata", MessageProcessor::new(bus, provider, cache_age))
+        },
+        Some(ProviderKind::Mock) => {
+            let provider = MockProvider::load(std::path::Path::new(&settings.mock_path))?;
+            actor::create_and_run("metadata", MessageProcessor::new(bus, provider, cache_age))
+        },
+        None => {
+            tracing::info!("metadata provider not configured");
+            actor::create_and_run("metadata", MessageProcessor::<MockProvider>::disabled(bus))
+        },
+    };
+
+    Ok(handle)
+}
+
+/// Processes messages sent to the metadata actor.
+struct MessageProcessor<P> {
+    /// Handle used to send messages to other actors via the message bus.
+    bus: bus::Handle,
+
+    /// The provider used to perform lookups.
+    ///
+    /// `None` if a provider has not been configured in which case all requests will fail with
+    /// [`Error::MetadataDisabled`].
+    provider: Option<Arc<P>>,
+
+    /// How long a cached response is considered valid.
+    cache_age: Duration,
+}
+
+impl<P> MessageProcessor<P>
+where
+    P: MetadataProvider + Send + Sync + 'static,
+{
+    /// Create a new instance of the message processor.
+    ///
+    /// # Args
+    ///
+    /// `bus`:  Handle used to send messages to other actors via the message bus.
+    ///
+    /// `provider`:  The provider used to perform lookups.
+    ///
+    /// `cache_age`:  How long a cached response is considered valid.
+    fn new(bus: &bus::Handle, provider: P, cache_age: Duration) -> Self {
+        Self {
+            bus: bus.clone(),
+            provider: Some(Arc::new(provider)),
+            cache_age,
+        }
+    }
+
+    /// Create a new instance of the message processor without a provider.
+    ///
+    /// # Args
+    ///
+    /// `bus`:  Handle used to send messages to other actors via the message bus.
+    fn disabled(bus: &bus::Handle) -> Self {
+        Self {
+            bus: bus.clone(),
+            provider: None,
+            cache_age: Duration::ZERO,
+        }
+    }
+}
+
+impl<P> actor::MessageProcessor<Message> for MessageProcessor<P>
+where
+    P: MetadataProvider + Send + Sync + 'static,
+{
+    async fn process(&mut self, msg: Message) -> Result<()> {
+        // Lookups may take a while so they are performed on their own task to avoid blocking the
+        // actor from processing other requests.
+        let bus = self.bus.clone();
+        let cache_age = self.cache_age;
+
+        let Some(provider) = self.provider.clone() else {
+            return match msg {
+                Message::Details { response, .. } => response.send(Err(Error::MetadataDisabled))
+                    .inspect_err(|_| send_error_trace("Details"))
+                    .map_err(|_| Error::ResponseSend),
+                Message::Search { response, .. } => response.send(Err(Error::MetadataDisabled))
+                    .inspect_err(|_| send_error_trace("Search"))
+                    .map_err(|_| Error::ResponseSend),
+            };
+        };
+
+        match msg {
+            Message::Details { media_type, id, response } => {
+                task::spawn(async move {
+                    let result = lookup_details(
+                        &bus,
+                        provider.as_ref(),
+                        media_type,
+                        id,
+                        cache_age,
+                    ).await;
+                    if response.send(result).is_err() {
+                        send_error_trace("Details");
+                    }
+                });
+            },
+            Message::Search { media_type, query, year, response } => {
+                task::spawn(async move {
+                    let result = lookup_search(
+                        &bus,
+                        provider.as_ref(),
+                        media_type,
+                        &query,
+                        year,
+                        cache_age,
+                    ).await;
+                    if response.send(result).is_err() {
+                        send_error_trace("Search");
+                    }
+                });
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the details for a movie or show using the cached value if available.
+///
+/// # Args
+///
+/// `bus`:  Handle used to connect to the database.
+///
+/// `provider`:  The provider used to perform the lookup if not cached.
+///
+/// `media_type`:  The type of media the identifier is for.
+///
+/// `id`:  The canonical identifier of the movie or show.
+///
+/// `cache_age`:  How long a cached response is considered valid.
+async fn lookup_details<P: MetadataProvider>(
+    bus: &bus::Handle,
+    provider: &P,
+    media_type: MediaType,
+    id: ProviderId,
+    cache_age: Duration,
+) -> Result<MediaDetails> {
+    if id.provider != provider.kind() {
+        return Err(Error::MetadataNotFound { id: id.to_string() });
+    }
+
+    let key = format!("{}:{}", media_type.as_str(), id.id);
+
+    if let Some(details) = read_cache(bus, provider.kind(), CacheKind::Details, &key, cache_age)
+        .await
+    {
+        return Ok(details);
+    }
+
+    let details = provider.details(media_type, id.id).await?;
+
+    write_cache(bus, provider.kind(), CacheKind::Details, &key, &details).await;
+
+    Ok(details)
+}
+
+/// Search for a movie or show using the cached results if available.
+///
+/// # Args
+///
+/// `bus`:  Handle used to connect to the database.
+///
+/// `provider`:  The provider used to perform the lookup if not cached.
+///
+/// `media_type`:  The type of media to search for.
+///
+/// `query`:  The title (or partial title) to search for.
+///
+/// `year`:  If `Some`, limits the results to the provided release year.
+///
+/// `cache_age`:  How long a cached response is considered valid.
+async fn lookup_search<P: MetadataProvider>(
+    bus: &bus::Handle,
+    provider: &P,
+    media_type: MediaType,
+    query: &str,
+    year: Option<u16>,
+    cache_age: Duration,
+) -> Result<Vec<SearchResult>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let key = format!(
+        "{}:{}:{}",
+        media_type.as_str(),
+        year.unwrap_or_default(),
+        query.to_lowercase(),
+    );
+
+    if let Some(results) = read_cache(bus, provider.kind(), CacheKind::Search, &key, cache_age)
+        .await
+    {
+        return Ok(results);
+    }
+
+    let results = provider.search(media_type, query, year).await?;
+
+    write_cache(bus, provider.kind(), CacheKind::Search, &key, &results).await;
+
+    Ok(results)
+}
+
+/// Reads a cached response returning `None` if not cached, expired, or the cache could not be read.
+///
+/// Failure to read the cache is not treated as an error since the lookup can still be performed.
+async fn read_cache<T>(
+    bus: &bus::Handle,
+    provider: ProviderKind,
+    kind: CacheKind,
+    key: &str,
+    cache_age: Duration,
+) -> Option<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let owned_key = key.to_owned();
+    let result = db::run(bus, move |conn| {
+        db::metadata_cache::get(conn, provider, kind, &owned_key, cache_age)
+    }).await;
+
+    let json = result
+        .inspect_err(|error| tracing::warn!(?error, key, "failed to read metadata cache"))
+        .ok()??;
+
+    serde_json::from_str(&json)
+        .inspect_err(|error| tracing::warn!(?error, key, "invalid metadata cache entry"))
+        .ok()
+}
+
+/// Writes a response to the cache.
+///
+/// Failure to write the cache is logged, but otherwise ignored.
+async fn write_cache<T>(
+    bus: &bus::Handle,
+    provider: ProviderKind,
+    kind: CacheKind,
+    key: &str,
+    value: &T,
+)
+where
+    T: Serialize,
+{
+    let result = async {
+        let json = serde_json::to_string(value)?;
+        let key = key.to_owned();
+        db::run(bus, move |conn| db::metadata_cache::set(conn, provider, kind, &key, &json)).await
+    }.await;
+
+    if let Err(error) = result {
+        tracing::warn!(?error, key, "failed to write metadata cache");
+    }
+}
+
+/// Log an error due to failure to send a response.
+///
+/// # Args
+///
+/// `request`:  The name of the request the response was being sent for.
+fn send_error_trace(request: &str) {
+    tracing::error!("failed to send {} response", request);
+}
+
+/// Serializes an optional duration as a number of seconds.
+mod duration_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => s.serialize_some(&duration.as_secs()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(d)?.map(Duration::from_secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jellyfin_tag() {
+        let tmdb = ProviderId { provider: ProviderKind::Tmdb, id: 1234 };
+        assert_eq!(tmdb.jellyfin_tag(), "[tmdbid-1234]");
+
+        let tvdb = ProviderId { provider: ProviderKind::Tvdb, id: 81189 };
+        assert_eq!(tvdb.jellyfin_tag(), "[tvdbid-81189]");
+    }
+
+    #[test]
+    fn test_provider_id_round_trip() {
+        let id = ProviderId { provider: ProviderKind::Tvdb, id: 42 };
+        assert_eq!(id.to_string(), "tvdb:42");
+        assert_eq!(ProviderId::parse("tvdb:42"), Some(id));
+    }
+
+    #[test]
+    fn test_provider_id_parse_invalid() {
+        assert_eq!(ProviderId::parse(""), None);
+        assert_eq!(ProviderId::parse("tmdb"), None);
+        assert_eq!(ProviderId::parse("imdb:42"), None);
+        assert_eq!(ProviderId::parse("tmdb:abc"), None);
+    }
+
+    #[test]
+    fn test_details_runtime_serialization() {
+        let details = MediaDetails {
+            id: ProviderId { provider: ProviderKind::Mock, id: 1 },
+            media_type: MediaType::Movie,
+            name: String::from("Test Movie"),
+            year: 2001,
+            runtime: Some(Duration::from_secs(5400)),
+            seasons: Vec::new(),
+        };
+
+        let json = serde_json::to_string(&details).unwrap();
+        let loaded: MediaDetails = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.runtime, Some(Duration::from_secs(5400)));
+    }
+}
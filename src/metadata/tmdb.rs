@@ -0,0 +1,363 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! The Movie Database metadata provider.
+//!
+//! Uses version 3 of the API (<https://developer.themoviedb.org/reference/intro/getting-started>)
+//! which is authenticated using an API key passed as a query parameter.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::{Error, Result};
+use crate::models::MediaType;
+
+use super::{Episode, MediaDetails, MetadataProvider, ProviderId, ProviderKind, Season};
+use super::SearchResult;
+
+/// The base URL of the API.
+const BASE_URL: &str = "https://api.themoviedb.org/3";
+
+/// Metadata provider for The Movie Database.
+pub struct TmdbProvider {
+    /// The client used to make requests.
+    client: reqwest::Client,
+
+    /// The API (v3) key.
+    api_key: String,
+
+    /// The base URL of the API.
+    base_url: String,
+}
+
+impl TmdbProvider {
+    /// Creates a new provider.
+    ///
+    /// # Args
+    ///
+    /// `api_key`:  The API (v3) key.
+    pub fn new(api_key: &str) -> Self {
+        Self::with_base_url(api_key, BASE_URL)
+    }
+
+    /// Creates a new provider that sends its requests to the provided URL.
+    ///
+    /// # Args
+    ///
+    /// `api_key`:  The API (v3) key.
+    ///
+    /// `base_url`:  The base URL of the API.
+    pub fn with_base_url(api_key: &str, base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: api_key.to_owned(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        }
+    }
+
+    /// Sends a GET request and deserializes the JSON response.
+    ///
+    /// # Args
+    ///
+    /// `path`:  The path of the endpoint relative to the base URL.
+    ///
+    /// `query`:  Additional query parameters.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Http`] if the request fails, the response status is not successful, or the
+    /// response body cannot be deserialized.
+    async fn get<T>(&self, path: &str, query: &[(&str, String)]) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self.client.get(&url)
+            .query(&[("api_key", self.api_key.as_str())])
+            .query(query)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.json::<T>().await?)
+    }
+
+    /// Gets the episodes of a season.
+    ///
+    /// # Args
+    ///
+    /// `id`:  The show's identifier.
+    ///
+    /// `number`:  The season number.
+    async fn season(&self, id: u64, number: u16) -> Result<Season> {
+        let path = format!("/tv/{}/season/{}", id, number);
+        let season: TmdbSeason = self.get(&path, &[]).await?;
+        Ok(season.into_season())
+    }
+}
+
+impl MetadataProvider for TmdbProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Tmdb
+    }
+
+    async fn search(
+        &self,
+        media_type: MediaType,
+        query: &str,
+        year: Option<u16>,
+    ) -> Result<Vec<SearchResult>> {
+        let mut params = vec![("query", query.to_owned())];
+
+        let results = match media_type {
+            MediaType::Movie => {
+                if let Some(year) = year {
+                    params.push(("year", year.to_string()));
+                }
+                let page: TmdbPage<TmdbMovie> = self.get("/search/movie", &params).await?;
+                page.results.into_iter().map(TmdbMovie::into_search_result).collect()
+            },
+            MediaType::Show => {
+                if let Some(year) = year {
+                    params.push(("first_air_date_year", year.to_string()));
+                }
+                let page: TmdbPage<TmdbShow> = self.get("/search/tv", &params).await?;
+                page.results.into_iter().map(TmdbShow::into_search_result).collect()
+            },
+        };
+
+        Ok(results)
+    }
+
+    async fn details(&self, media_type: MediaType, id: u64) -> Result<MediaDetails> {
+        let not_found = |error: Error| match error {
+            Error::Http(ref e) if e.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                let id = ProviderId { provider: ProviderKind::Tmdb, id };
+                Error::MetadataNotFound { id: id.to_string() }
+            },
+            _ => error,
+        };
+
+        match media_type {
+            MediaType::Movie => {
+                let movie: TmdbMovie = self.get(&format!("/movie/{}", id), &[])
+                    .await
+                    .map_err(not_found)?;
+                Ok(movie.into_details())
+            },
+            MediaType::Show => {
+                let show: TmdbShow = self.get(&format!("/tv/{}", id), &[])
+                    .await
+                    .map_err(not_found)?;
+                let mut seasons = Vec::with_capacity(show.seasons.len());
+                for season in &show.seasons {
+                    seasons.push(self.season(id, season.season_number).await?);
+                }
+                let mut details = show.into_details();
+                details.seasons = seasons;
+                Ok(details)
+            },
+        }
+    }
+}
+
+/// A page of search results.
+#[derive(Deserialize)]
+struct TmdbPage<T> {
+    results: Vec<T>,
+}
+
+/// A movie search result or movie details.
+#[derive(Deserialize)]
+struct TmdbMovie {
+    id: u64,
+    title: String,
+    #[serde(default)]
+    release_date: Option<String>,
+    #[serde(default)]
+    overview: Option<String>,
+    /// Runtime in minutes (details only).
+    #[serde(default)]
+    runtime: Option<u64>,
+}
+
+impl TmdbMovie {
+    fn into_search_result(self) -> SearchResult {
+        SearchResult {
+            id: ProviderId { provider: ProviderKind::Tmdb, id: self.id },
+            media_type: MediaType::Movie,
+            year: parse_year(self.release_date.as_deref()),
+            name: self.title,
+            overview: self.overview.unwrap_or_default(),
+        }
+    }
+
+    fn into_details(self) -> MediaDetails {
+        MediaDetails {
+            id: ProviderId { provider: ProviderKind::Tmdb, id: self.id },
+            media_type: MediaType::Movie,
+            year: parse_year(self.release_date.as_deref()),
+            name: self.title,
+            runtime: minutes(self.runtime),
+            seasons: Vec::new(),
+        }
+    }
+}
+
+/// A show search result or show details.
+#[derive(Deserialize)]
+struct TmdbShow {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    first_air_date: Option<String>,
+    #[serde(default)]
+    overview: Option<String>,
+    /// Typical episode runtimes in minutes (details only).
+    #[serde(default)]
+    episode_run_time: Vec<u64>,
+    /// Season summaries (details only).
+    #[serde(default)]
+    seasons: Vec<TmdbSeasonSummary>,
+}
+
+impl TmdbShow {
+    fn into_search_result(self) -> SearchResult {
+        SearchResult {
+            id: ProviderId { provider: ProviderKind::Tmdb, id: self.id },
+            media_type: MediaType::Show,
+            year: parse_year(self.first_air_date.as_deref()),
+            name: self.name,
+            overview: self.overview.unwrap_or_default(),
+        }
+    }
+
+    fn into_details(self) -> MediaDetails {
+        MediaDetails {
+            id: ProviderId { provider: ProviderKind::Tmdb, id: self.id },
+            media_type: MediaType::Show,
+            year: parse_year(self.first_air_date.as_deref()),
+            name: self.name,
+            runtime: minutes(self.episode_run_time.first().copied()),
+            seasons: Vec::new(),
+        }
+    }
+}
+
+/// Season summary included in the show details.
+#[derive(Deserialize)]
+struct TmdbSeasonSummary {
+    season_number: u16,
+}
+
+/// Season details.
+#[derive(Deserialize)]
+struct TmdbSeason {
+    season_number: u16,
+    #[serde(default)]
+    episodes: Vec<TmdbEpisode>,
+}
+
+impl TmdbSeason {
+    fn into_season(self) -> Season {
+        Season {
+            number: self.season_number,
+            episodes: self.episodes.into_iter()
+                .map(|e| Episode {
+                    number: e.episode_number,
+                    name: e.name,
+                    runtime: minutes(e.runtime),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// An episode in the season details.
+#[derive(Deserialize)]
+struct TmdbEpisode {
+    episode_number: u16,
+    #[serde(default)]
+    name: String,
+    /// Runtime in minutes.
+    #[serde(default)]
+    runtime: Option<u64>,
+}
+
+/// Converts a runtime in minutes to a duration.
+fn minutes(value: Option<u64>) -> Option<Duration> {
+    value.filter(|m| *m > 0).map(|m| Duration::from_secs(m * 60))
+}
+
+/// Parses the year from a date in the form `YYYY-MM-DD` returning zero if the date is missing or
+/// invalid.
+fn parse_year(date: Option<&str>) -> u16 {
+    date.and_then(|d| d.get(0..4))
+        .and_then(|y| y.parse::<u16>().ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_year() {
+        assert_eq!(parse_year(Some("2001-05-16")), 2001);
+        assert_eq!(parse_year(Some("")), 0);
+        assert_eq!(parse_year(None), 0);
+    }
+
+    #[test]
+    fn test_parse_movie_search() {
+        let json = r#"{
+            "page": 1,
+            "results": [
+                {"id": 348, "title": "Alien", "release_date": "1979-05-25", "overview": "Space"},
+                {"id": 1, "title": "Unknown", "release_date": null}
+            ]
+        }"#;
+
+        let page: TmdbPage<TmdbMovie> = serde_json::from_str(json).unwrap();
+        let results: Vec<_> = page.results.into_iter()
+            .map(TmdbMovie::into_search_result)
+            .collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, ProviderId { provider: ProviderKind::Tmdb, id: 348 });
+        assert_eq!(results[0].name, "Alien");
+        assert_eq!(results[0].year, 1979);
+        assert_eq!(results[1].year, 0);
+        assert_eq!(results[1].overview, "");
+    }
+
+    #[test]
+    fn test_parse_show_details() {
+        let show = r#"{
+            "id": 1399,
+            "name": "Test Show",
+            "first_air_date": "2011-04-17",
+            "episode_run_time": [60],
+            "seasons": [{"season_number": 0}, {"season_number": 1}]
+        }"#;
+        let season = r#"{
+            "season_number": 1,
+            "episodes": [
+                {"episode_number": 1, "name": "First", "runtime": 62},
+                {"episode_number": 2, "name": "Second", "runtime": null}
+            ]
+        }"#;
+
+        let show: TmdbShow = serde_json::from_str(show).unwrap();
+        assert_eq!(show.seasons.len(), 2);
+        let details = show.into_details();
+        assert_eq!(details.year, 2011);
+        assert_eq!(details.runtime, Some(Duration::from_secs(3600)));
+
+        let season: TmdbSeason = serde_json::from_str(season).unwrap();
+        let season = season.into_season();
+        assert_eq!(season.number, 1);
+        assert_eq!(season.episodes[0].runtime, Some(Duration::from_secs(62 * 60)));
+        assert_eq!(season.episodes[1].runtime, None);
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! TheTVDB metadata provider.
+//!
+//! Uses version 4 of the API (<https://thetvdb.github.io/v4-api>). The API key is exchanged for a
+//! bearer token on the first request. The token is then reused for all subsequent requests.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use tokio::sync::Mutex;
+
+use crate::{Error, Result};
+use crate::models::MediaType;
+
+use super::{Episode, MediaDetails, MetadataProvider, ProviderId, ProviderKind, Season};
+use super::SearchResult;
+
+/// The base URL of the API.
+const BASE_URL: &str = "https://api4.thetvdb.com/v4";
+
+/// Metadata provider for TheTVDB.
+pub struct TvdbProvider {
+    /// The client used to make requests.
+    client: reqwest::Client,
+
+    /// The API key.
+    api_key: String,
+
+    /// The base URL of the API.
+    base_url: String,
+
+    /// The bearer token received when logging in.
+    ///
+    /// `None` until the first request is made.
+    token: Mutex<Option<String>>,
+}
+
+impl TvdbProvider {
+    /// Creates a new provider.
+    ///
+    /// # Args
+    ///
+    /// `api_key`:  The API key.
+    pub fn new(api_key: &str) -> Self {
+        Self::with_base_url(api_key, BASE_URL)
+    }
+
+    /// Creates a new provider that sends its requests to the provided URL.
+    ///
+    /// # Args
+    ///
+    /// `api_key`:  The API key.
+    ///
+    /// `base_url`:  The base URL of the API.
+    pub fn with_base_url(api_key: &str, base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: api_key.to_owned(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Gets the bearer token logging in if a token has not yet been received.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Http`] if the login request fails.
+    async fn token(&self) -> Result<String> {
+        let mut token = self.token.lock().await;
+        if let Some(token) = token.as_ref() {
+            return Ok(token.clone());
+        }
+
+        let url = format!("{}/login", self.base_url);
+        let response: TvdbResponse<TvdbLogin> = self.client.post(&url)
+            .json(&serde_json::json!({ "apikey": self.api_key }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        tracing::debug!("logged in to TheTVDB");
+        *token = Some(response.data.token.clone());
+        Ok(response.data.token)
+    }
+
+    /// Sends a GET request and deserializes the JSON response.
+    ///
+    /// # Args
+    ///
+    /// `path`:  The path of the endpoint relative to the base URL.
+    ///
+    /// `query`:  Query parameters.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Http`] if the request fails, the response status is not successful, or the
+    /// response body cannot be deserialized.
+    async fn get<T>(&self, path: &str, query: &[(&str, String)]) -> Result<TvdbResponse<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let token = self.token().await?;
+        let url = format!("{}{}", self.base_url, path);
+        let response = self.client.get(&url)
+            .bearer_auth(token)
+            .query(query)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.json::<TvdbResponse<T>>().await?)
+    }
+
+    /// Gets the details for a movie.
+    ///
+    /// # Args
+    ///
+    /// `id`:  The movie's identifier.
+    async fn movie(&self, id: u64) -> Result<MediaDetails> {
+        let response: TvdbResponse<TvdbRecord> = self.get(&format!("/movies/{}", id), &[])
+            .await?;
+        Ok(response.data.into_details(MediaType::Movie))
+    }
+
+    /// Gets the details for a show including all of its episodes.
+    ///
+    /// # Args
+    ///
+    /// `id`:  The show's identifier.
+    async fn show(&self, id: u64) -> Result<MediaDetails> {
+        let path = format!("/series/{}/episodes/default", id);
+        let mut page = 0;
+        let mut details = None;
+        let mut episodes = Vec::new();
+
+        loop {
+            let query = [("page", page.to_string())];
+            let response: TvdbResponse<TvdbEpisodes> = self.get(&path, &query).await?;
+            if details.is_none() {
+                details = Some(response.data.series.into_details(MediaType::Show));
+            }
+            episodes.extend(response.data.episodes);
+
+            let has_next = response.links.and_then(|l| l.next).is_some();
+            if !has_next {
+                break;
+            }
+            page += 1;
+        }
+
+        let mut details = details.expect("at least one page was received");
+        details.seasons = group_seasons(episodes);
+        Ok(details)
+    }
+}
+
+impl MetadataProvider for TvdbProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Tvdb
+    }
+
+    async fn search(
+        &self,
+        media_type: MediaType,
+        query: &str,
+        year: Option<u16>,
+    ) -> Result<Vec<SearchResult>> {
+        let kind = match media_type {
+            MediaType::Movie => "movie",
+            MediaType::Show => "series",
+        };
+        let mut params = vec![("query", query.to_owned()), ("type", kind.to_owned())];
+        if let Some(year) = year {
+            params.push(("year", year.to_string()));
+        }
+
+        let response: TvdbResponse<Vec<TvdbSearchResult>> = self.get("/search", &params).await?;
+
+        let results = response.data.into_iter()
+            .filter_map(|r| r.into_search_result(media_type))
+            .collect();
+        Ok(results)
+    }
+
+    async fn details(&self, media_type: MediaType, id: u64) -> Result<MediaDetails> {
+        let result = match media_type {
+            MediaType::Movie => self.movie(id).await,
+            MediaType::Show => self.show(id).await,
+        };
+
+        result.map_err(|error| match error {
+            Error::Http(ref e) if e.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                let id = ProviderId { provider: ProviderKind::Tvdb, id };
+                Error::MetadataNotFound { id: id.to_string() }
+            },
+            _ => error,
+        })
+    }
+}
+
+/// The envelope all responses are wrapped in.
+#[derive(Deserialize)]
+struct TvdbResponse<T> {
+    data: T,
+    #[serde(default)]
+    links: Option<TvdbLinks>,
+}
+
+/// Pagination links.
+#[derive(Deserialize)]
+struct TvdbLinks {
+    #[serde(default)]
+    next: Option<String>,
+}
+
+/// Login response.
+#[derive(Deserialize)]
+struct TvdbLogin {
+    token: String,
+}
+
+/// A search result.
+#[derive(Deserialize)]
+struct TvdbSearchResult {
+    /// The identifier (as a string).
+    tvdb_id: String,
+    name: String,
+    #[serde(default)]
+    year: Option<String>,
+    #[serde(default)]
+    overview: Option<String>,
+}
+
+impl TvdbSearchResult {
+    /// Converts to a search result returning `None` if the identifier is invalid.
+    fn into_search_result(self, media_type: MediaType) -> Option<SearchResult> {
+        Some(SearchResult {
+            id: ProviderId { provider: ProviderKind::Tvdb, id: self.tvdb_id.parse().ok()? },
+            media_type,
+            name: self.name,
+            year: parse_year(self.year.as_deref()),
+            overview: self.overview.unwrap_or_default(),
+        })
+    }
+}
+
+/// A movie or series record.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TvdbRecord {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    year: Option<String>,
+    /// Runtime in minutes (movies only).
+    #[serde(default)]
+    runtime: Option<u64>,
+    /// Average episode runtime in minutes (series only).
+    #[serde(default)]
+    average_runtime: Option<u64>,
+}
+
+impl TvdbRecord {
+    fn into_details(self, media_type: MediaType) -> MediaDetails {
+        MediaDetails {
+            id: ProviderId { provider: ProviderKind::Tvdb, id: self.id },
+            media_type,
+            name: self.name,
+            year: parse_year(self.year.as_deref()),
+            runtime: minutes(self.runtime.or(self.average_runtime)),
+            seasons: Vec::new(),
+        }
+    }
+}
+
+/// A page of series episodes.
+#[derive(Deserialize)]
+struct TvdbEpisodes {
+    series: TvdbRecord,
+    #[serde(default)]
+    episodes: Vec<TvdbEpisode>,
+}
+
+/// An episode of a series.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TvdbEpisode {
+    season_number: u16,
+    number: u16,
+    #[serde(default)]
+    name: Option<String>,
+    /// Runtime in minutes.
+    #[serde(default)]
+    runtime: Option<u64>,
+}
+
+/// Groups the episodes into seasons ordered by season and episode number.
+fn group_seasons(episodes: Vec<TvdbEpisode>) -> Vec<Season> {
+    let mut seasons: BTreeMap<u16, Vec<Episode>> = BTreeMap::new();
+    for episode in episodes {
+        seasons.entry(episode.season_number).or_default().push(Episode {
+            number: episode.number,
+            name: episode.name.unwrap_or_default(),
+            runtime: minutes(episode.runtime),
+        });
+    }
+
+    seasons.into_iter()
+        .map(|(number, mut episodes)| {
+            episodes.sort_by_key(|e| e.number);
+            Season { number, episodes }
+        })
+        .collect()
+}
+
+/// Converts a runtime in minutes to a duration.
+fn minutes(value: Option<u64>) -> Option<Duration> {
+    value.filter(|m| *m > 0).map(|m| Duration::from_secs(m * 60))
+}
+
+/// Parses a year returning zero if the year is missing or invalid.
+fn parse_year(year: Option<&str>) -> u16 {
+    year.and_then(|y| y.parse::<u16>().ok()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search() {
+        let json = r#"{
+            "status": "success",
+            "data": [
+                {"tvdb_id": "81189", "name": "Test Show", "year": "2008", "overview": "Test"},
+                {"tvdb_id": "invalid", "name": "Invalid"}
+            ]
+        }"#;
+
+        let response: TvdbResponse<Vec<TvdbSearchResult>> = serde_json::from_str(json).unwrap();
+        let results: Vec<_> = response.data.into_iter()
+            .filter_map(|r| r.into_search_result(MediaType::Show))
+            .collect();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, ProviderId { provider: ProviderKind::Tvdb, id: 81189 });
+        assert_eq!(results[0].year, 2008);
+    }
+
+    #[test]
+    fn test_parse_episodes() {
+        let json = r#"{
+            "status": "success",
+            "data": {
+                "series": {"id": 81189, "name": "Test Show", "year": "2008", "averageRuntime": 47},
+                "episodes": [
+                    {"seasonNumber": 1, "number": 2, "name": "Second", "runtime": 48},
+                    {"seasonNumber": 0, "number": 1, "name": "Special", "runtime": null},
+                    {"seasonNumber": 1, "number": 1, "name": "First", "runtime": 58}
+                ]
+            },
+            "links": {"prev": null, "self": "", "next": null}
+        }"#;
+
+        let response: TvdbResponse<TvdbEpisodes> = serde_json::from_str(json).unwrap();
+        assert!(response.links.unwrap().next.is_none());
+
+        let details = response.data.series.into_details(MediaType::Show);
+        assert_eq!(details.runtime, Some(Duration::from_secs(47 * 60)));
+
+        let seasons = group_seasons(response.data.episodes);
+        assert_eq!(seasons.len(), 2);
+        assert_eq!(seasons[0].number, 0);
+        assert_eq!(seasons[1].episodes[0].name, "First");
+        assert_eq!(seasons[1].episodes[1].runtime, Some(Duration::from_secs(48 * 60)));
+    }
+}
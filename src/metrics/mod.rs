@@ -0,0 +1,480 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Prometheus metrics.
+//!
+//! Metrics are recorded in a process wide registry using the helper functions in this module and
+//! are exposed in the Prometheus text format by the HTTP endpoint started by [`init`] (see:
+//! [`server`]). The endpoint is disabled by default and is available on both the control and
+//! worker nodes. The following metrics are available:
+//!
+//! | Metric | Labels | Description |
+//! |--------|--------|-------------|
+//! | `artie_drive_state` | `drive`, `state` | `1` for the current state of each drive. |
+//! | `artie_makemkv_progress_ratio` | `drive`, `level` | Progress of the running MakeMKV command. |
+//! | `artie_copy_operations_total` | `outcome` | Finished copy operations. |
+//! | `artie_copy_duration_seconds` | `outcome` | Duration of the copy operations. |
+//! | `artie_copied_bytes_total` | | Bytes copied from discs by MakeMKV. |
+//! | `artie_transcode_operations_total` | `outcome` | Finished transcode operations. |
+//! | `artie_transcode_duration_seconds` | `outcome` | Duration of the transcode operations. |
+//! | `artie_handbrake_progress_ratio` | `title` | Progress of the running HandBrake command. |
+//! | `artie_worker_connected` | `worker` | `1` if the control node is connected to the worker. |
+//! | `artie_actor_queue_depth` | `actor` | Messages waiting in the actor's channel. |
+//! | `artie_actor_queue_capacity` | `actor` | Size of the actor's channel. |
+//!
+//! Drive states are only known by the control node since the worker node only relays the state of
+//! its drives. The transcode metrics are reserved for the transcode pipeline and are not recorded
+//! yet.
+//!
+//! An actor whose queue depth stays near its capacity is not keeping up with its requests, which
+//! will eventually block the actors sending it messages (including the message bus).
+
+pub mod server;
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use prometheus::{
+    Encoder,
+    GaugeVec,
+    HistogramOpts,
+    HistogramVec,
+    IntCounter,
+    IntCounterVec,
+    IntGaugeVec,
+    Opts,
+    Registry,
+    TextEncoder,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{Sender, WeakSender};
+
+use crate::Result;
+use crate::drive::{OpticalDrive, OpticalDriveState};
+use crate::task;
+
+/// The metrics registry.
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// Histogram buckets for operation durations (1 minute to 4 hours).
+const DURATION_BUCKETS: [f64; 9] = [
+    60.0, 300.0, 600.0, 1200.0, 1800.0, 2700.0, 3600.0, 7200.0, 14400.0,
+];
+
+/// Metrics endpoint settings.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Settings {
+    /// Whether the metrics endpoint is enabled.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Address to listen on.
+    #[serde(default = "Settings::default_addr")]
+    pub listen_addr: String,
+
+    /// Port to listen on.
+    #[serde(default = "Settings::default_port")]
+    pub listen_port: u16,
+}
+
+impl Settings {
+    /// The address to listen on if one is not specified in the config.
+    fn default_addr() -> String {
+        String::from("127.0.0.1")
+    }
+
+    /// The port to listen on if one is not specified in the config.
+    fn default_port() -> u16 {
+        9878
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_addr: Self::default_addr(),
+            listen_port: Self::default_port(),
+        }
+    }
+}
+
+/// Specifies how an operation finished.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl Outcome {
+    /// Returns the value of the `outcome` label.
+    fn label(&self) -> &'static str {
+        match self {
+            Outcome::Completed => "completed",
+            Outcome::Failed => "failed",
+            Outcome::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Starts the metrics endpoint if enabled.
+///
+/// # Args
+///
+/// `settings`:  The metrics endpoint settings.
+pub fn init(settings: &Settings) {
+    if !settings.enabled {
+        tracing::info!("metrics endpoint not enabled");
+        return;
+    }
+
+    let addr = format!("{}:{}", settings.listen_addr, settings.listen_port);
+    task::spawn(async move {
+        server::listen(&addr).await;
+    });
+}
+
+/// Records the state of a drive.
+///
+/// # Args
+///
+/// `drive`:  The drive.
+pub fn drive_updated(drive: &OpticalDrive) {
+    let metrics = &*METRICS;
+    let sn = drive.serial_number.as_str();
+    let state = drive.state.name();
+
+    let mut states = metrics.drive_states.lock().expect("drive states lock poisoned");
+    let previous = states.insert(sn.to_owned(), state);
+    if previous != Some(state) {
+        if let Some(previous) = previous {
+            let _ = metrics.drive_state.remove_label_values(&[sn, previous]);
+        }
+        metrics.drive_state.with_label_values(&[sn, state]).set(1);
+    }
+
+    let (task, subtask) = match &drive.state {
+        OpticalDriveState::Copying { task_progress, subtask_progress, .. } => {
+            (*task_progress, *subtask_progress)
+        },
+        _ => (0.0, 0.0),
+    };
+    metrics.makemkv_progress.with_label_values(&[sn, "task"]).set(task as f64);
+    metrics.makemkv_progress.with_label_values(&[sn, "subtask"]).set(subtask as f64);
+}
+
+/// Records a finished copy operation.
+///
+/// # Args
+///
+/// `outcome`:  How the copy operation finished.
+///
+/// `duration`:  How long the copy operation ran.
+pub fn copy_finished(outcome: Outcome, duration: Duration) {
+    let metrics = &*METRICS;
+    metrics.copy_operations.with_label_values(&[outcome.label()]).inc();
+    metrics.copy_duration.with_label_values(&[outcome.label()]).observe(duration.as_secs_f64());
+}
+
+/// Records the number of bytes copied from a disc.
+///
+/// # Args
+///
+/// `bytes`:  The number of bytes copied.
+pub fn bytes_copied(bytes: u64) {
+    METRICS.copied_bytes.inc_by(bytes);
+}
+
+/// Records a finished transcode operation.
+///
+/// # Args
+///
+/// `outcome`:  How the transcode operation finished.
+///
+/// `duration`:  How long the transcode operation ran.
+pub fn transcode_finished(outcome: Outcome, duration: Duration) {
+    let metrics = &*METRICS;
+    metrics.transcode_operations.with_label_values(&[outcome.label()]).inc();
+    metrics.transcode_duration
+        .with_label_values(&[outcome.label()])
+        .observe(duration.as_secs_f64());
+}
+
+/// Records the progress of a HandBrake command.
+///
+/// # Args
+///
+/// `title`:  The title being transcoded.
+///
+/// `progress`:  The progress of the command from `0.0` to `1.0`.
+pub fn handbrake_progress(title: &str, progress: f64) {
+    METRICS.handbrake_progress.with_label_values(&[title]).set(progress);
+}
+
+/// Records whether the control node is connected to a worker node.
+///
+/// # Args
+///
+/// `worker`:  The address of the worker node.
+///
+/// `connected`:  Whether the worker is connected.
+pub fn worker_connected(worker: &str, connected: bool) {
+    METRICS.worker_connected.with_label_values(&[worker]).set(connected as i64);
+}
+
+/// Stops recording the connection state of a worker node that was removed.
+///
+/// # Args
+///
+/// `worker`:  The address of the worker node.
+pub fn worker_removed(worker: &str) {
+    let _ = METRICS.worker_connected.remove_label_values(&[worker]);
+}
+
+/// Tracks the number of messages queued in an actor's channel.
+///
+/// The depth is sampled each time the metrics are gathered. The channel is no longer tracked once
+/// all of its senders are dropped.
+///
+/// # Args
+///
+/// `actor`:  The name of the actor.
+///
+/// `tx`:  The transmission end of the actor's channel.
+pub fn track_queue<T: Send + 'static>(actor: &str, tx: &Sender<T>) {
+    let queue = Queue {
+        actor: actor.to_owned(),
+        tx: Box::new(tx.downgrade()),
+    };
+    METRICS.queues.lock().expect("queues lock poisoned").push(queue);
+}
+
+/// Encodes the current value of all metrics in the Prometheus text format.
+///
+/// # Errors
+///
+/// [`crate::Error::Prometheus`] if the metrics cannot be encoded.
+pub fn gather() -> Result<String> {
+    let metrics = &*METRICS;
+    metrics.sample_queues();
+
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&metrics.registry.gather(), &mut buffer)?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+/// The content type of the text returned by [`gather`].
+pub fn content_type() -> &'static str {
+    prometheus::TEXT_FORMAT
+}
+
+/// Provides the depth of a channel without knowing the type of its messages.
+trait QueueDepth: Send {
+    /// Returns the number of queued messages and the capacity of the channel or `None` if the
+    /// channel was closed.
+    fn depth(&self) -> Option<(usize, usize)>;
+}
+
+impl<T: Send> QueueDepth for WeakSender<T> {
+    fn depth(&self) -> Option<(usize, usize)> {
+        let tx = self.upgrade()?;
+        Some((tx.max_capacity() - tx.capacity(), tx.max_capacity()))
+    }
+}
+
+/// A tracked actor channel.
+struct Queue {
+    /// The name of the actor.
+    actor: String,
+
+    /// The transmission end of the channel. Weak so that tracking does not keep it open.
+    tx: Box<dyn QueueDepth>,
+}
+
+/// The registered metrics.
+struct Metrics {
+    registry: Registry,
+    drive_state: IntGaugeVec,
+    makemkv_progress: GaugeVec,
+    copy_operations: IntCounterVec,
+    copy_duration: HistogramVec,
+    copied_bytes: IntCounter,
+    transcode_operations: IntCounterVec,
+    transcode_duration: HistogramVec,
+    handbrake_progress: GaugeVec,
+    worker_connected: IntGaugeVec,
+    actor_queue_depth: IntGaugeVec,
+    actor_queue_capacity: IntGaugeVec,
+
+    /// The last recorded state of each drive by serial number.
+    drive_states: Mutex<HashMap<String, &'static str>>,
+
+    /// The tracked actor channels.
+    queues: Mutex<Vec<Queue>>,
+}
+
+impl Metrics {
+    /// Creates and registers the metrics.
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some(String::from("artie")), None)
+            .expect("registry prefix should be valid");
+
+        let metrics = Self {
+            drive_state: IntGaugeVec::new(
+                Opts::new("drive_state", "Current state of each optical drive."),
+                &["drive", "state"],
+            ).expect("drive_state should be valid"),
+            makemkv_progress: GaugeVec::new(
+                Opts::new("makemkv_progress_ratio", "Progress of the running MakeMKV command."),
+                &["drive", "level"],
+            ).expect("makemkv_progress_ratio should be valid"),
+            copy_operations: IntCounterVec::new(
+                Opts::new("copy_operations_total", "Number of finished copy operations."),
+                &["outcome"],
+            ).expect("copy_operations_total should be valid"),
+            copy_duration: HistogramVec::new(
+                HistogramOpts::new("copy_duration_seconds", "Duration of copy operations.")
+                    .buckets(DURATION_BUCKETS.to_vec()),
+                &["outcome"],
+            ).expect("copy_duration_seconds should be valid"),
+            copied_bytes: IntCounter::new(
+                "copied_bytes_total",
+                "Number of bytes copied from discs.",
+            ).expect("copied_bytes_total should be valid"),
+            transcode_operations: IntCounterVec::new(
+                Opts::new("transcode_operations_total", "Number of finished transcode operations."),
+                &["outcome"],
+            ).expect("transcode_operations_total should be valid"),
+            transcode_duration: HistogramVec::new(
+                HistogramOpts::new("transcode_duration_seconds", "Duration of transcodes.")
+                    .buckets(DURATION_BUCKETS.to_vec()),
+                &["outcome"],
+            ).expect("transcode_duration_seconds should be valid"),
+            handbrake_progress: GaugeVec::new(
+                Opts::new("handbrake_progress_ratio", "Progress of the running HandBrake command."),
+                &["title"],
+            ).expect("handbrake_progress_ratio should be valid"),
+            worker_connected: IntGaugeVec::new(
+                Opts::new("worker_connected", "Whether the worker node is connected."),
+                &["worker"],
+            ).expect("worker_connected should be valid"),
+            actor_queue_depth: IntGaugeVec::new(
+                Opts::new("actor_queue_depth", "Number of messages queued for the actor."),
+                &["actor"],
+            ).expect("actor_queue_depth should be valid"),
+            actor_queue_capacity: IntGaugeVec::new(
+                Opts::new("actor_queue_capacity", "Size of the actor's message queue."),
+                &["actor"],
+            ).expect("actor_queue_capacity should be valid"),
+            drive_states: Mutex::new(HashMap::new()),
+            queues: Mutex::new(Vec::new()),
+            registry,
+        };
+
+        let collectors: [Box<dyn prometheus::core::Collector>; 11] = [
+            Box::new(metrics.drive_state.clone()),
+            Box::new(metrics.makemkv_progress.clone()),
+            Box::new(metrics.copy_operations.clone()),
+            Box::new(metrics.copy_duration.clone()),
+            Box::new(metrics.copied_bytes.clone()),
+            Box::new(metrics.transcode_operations.clone()),
+            Box::new(metrics.transcode_duration.clone()),
+            Box::new(metrics.handbrake_progress.clone()),
+            Box::new(metrics.worker_connected.clone()),
+            Box::new(metrics.actor_queue_depth.clone()),
+            Box::new(metrics.actor_queue_capacity.clone()),
+        ];
+        for collector in collectors {
+            metrics.registry.register(collector).expect("metric should only be registered once");
+        }
+
+        metrics
+    }
+
+    /// Updates the queue depth gauges and stops tracking closed channels.
+    ///
+    /// Actors sharing a name (e.g. a drive actor recreated for the same drive) are summed.
+    fn sample_queues(&self) {
+        let mut queues = self.queues.lock().expect("queues lock poisoned");
+        let mut totals: HashMap<String, (usize, usize)> = HashMap::new();
+        let mut closed = Vec::new();
+
+        queues.retain(|queue| match queue.tx.depth() {
+            Some((depth, capacity)) => {
+                let total = totals.entry(queue.actor.clone()).or_default();
+                total.0 += depth;
+                total.1 += capacity;
+                true
+            },
+            None => {
+                closed.push(queue.actor.clone());
+                false
+            },
+        });
+
+        for (actor, (depth, capacity)) in &totals {
+            self.actor_queue_depth.with_label_values(&[actor]).set(*depth as i64);
+            self.actor_queue_capacity.with_label_values(&[actor]).set(*capacity as i64);
+        }
+
+        for actor in closed.iter().filter(|a| !totals.contains_key(*a)) {
+            let _ = self.actor_queue_depth.remove_label_values(&[actor]);
+            let _ = self.actor_queue_capacity.remove_label_values(&[actor]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    use crate::drive::OpticalDriveState;
+
+    #[test]
+    fn test_drive_updated() {
+        let mut drive = OpticalDrive::disconnected("Drive", "METRICS-SN-1");
+        drive_updated(&drive);
+        drive.state = OpticalDriveState::Idle;
+        drive_updated(&drive);
+
+        let text = gather().unwrap();
+        assert!(text.contains(r#"artie_drive_state{drive="METRICS-SN-1",state="Idle"} 1"#));
+        assert!(!text.contains(r#"artie_drive_state{drive="METRICS-SN-1",state="Disconnected"}"#));
+        let progress = r#"artie_makemkv_progress_ratio{drive="METRICS-SN-1",level="task"} 0"#;
+        assert!(text.contains(progress));
+    }
+
+    #[test]
+    fn test_copy_finished() {
+        copy_finished(Outcome::Cancelled, Duration::from_secs(90));
+
+        let text = gather().unwrap();
+        assert!(text.contains(r#"artie_copy_operations_total{outcome="cancelled"}"#));
+        let bucket = r#"artie_copy_duration_seconds_bucket{outcome="cancelled",le="300"}"#;
+        assert!(text.contains(bucket));
+    }
+
+    #[test]
+    fn test_track_queue() {
+        let (tx, mut rx) = mpsc::channel::<u32>(10);
+        track_queue("metrics test actor", &tx);
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+
+        let text = gather().unwrap();
+        assert!(text.contains(r#"artie_actor_queue_depth{actor="metrics test actor"} 2"#));
+        assert!(text.contains(r#"artie_actor_queue_capacity{actor="metrics test actor"} 10"#));
+
+        rx.try_recv().unwrap();
+        let text = gather().unwrap();
+        assert!(text.contains(r#"artie_actor_queue_depth{actor="metrics test actor"} 1"#));
+
+        drop(tx);
+        let text = gather().unwrap();
+        assert!(!text.contains(r#"actor="metrics test actor""#));
+    }
+}
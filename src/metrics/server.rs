@@ -0,0 +1,156 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! HTTP endpoint for scraping the metrics.
+//!
+//! Only `GET /metrics` is supported, so a minimal HTTP/1.1 server is used instead of a web
+//! framework. Each request is answered on its own task and the connection is closed after the
+//! response is sent.
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::Result;
+use crate::metrics;
+use crate::task;
+
+/// The maximum number of bytes read from a request.
+///
+/// Scrape requests are small, so anything larger is truncated.
+const MAX_REQUEST_SIZE: u64 = 8192;
+
+/// Listen for scrape requests.
+///
+/// # Args
+///
+/// `addr`:  The address and port to listen on.
+pub async fn listen(addr: &str) {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            tracing::error!(?error, ?addr, "failed to listen for metrics requests");
+            return;
+        }
+    };
+
+    tracing::info!(?addr, "metrics endpoint listening");
+    serve(listener).await;
+}
+
+/// Answers the requests made to the listener.
+///
+/// # Args
+///
+/// `listener`:  The listener to accept connections from.
+async fn serve(listener: TcpListener) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer_addr)) => {
+                task::spawn(async move {
+                    if let Err(error) = respond(stream).await {
+                        tracing::debug!(?error, ?peer_addr, "failed to answer metrics request");
+                    }
+                });
+            },
+            Err(error) => {
+                tracing::error!(?error, "failed to accept metrics connection");
+            },
+        }
+    }
+}
+
+/// Reads a request from the stream and writes the response.
+///
+/// # Args
+///
+/// `stream`:  The connection to answer.
+///
+/// # Errors
+///
+/// [`crate::Error::StdIo`] if the request cannot be read or the response cannot be written.
+async fn respond(stream: TcpStream) -> Result<()> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_SIZE));
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+
+    // The headers are not used, but they need to be read before responding.
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next()
+        .and_then(|target| target.split('?').next())
+        .unwrap_or_default();
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => match metrics::gather() {
+            Ok(text) => ("200 OK", metrics::content_type(), text),
+            Err(error) => {
+                tracing::error!(?error, "failed to gather metrics");
+                let body = String::from("failed to gather metrics\n");
+                ("500 Internal Server Error", "text/plain", body)
+            },
+        },
+        ("GET", _) => ("404 Not Found", "text/plain", String::from("not found\n")),
+        _ => ("405 Method Not Allowed", "text/plain", String::from("method not allowed\n")),
+    };
+
+    let mut stream = reader.into_inner().into_inner();
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len(),
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Starts the server on a random port and returns its base URL.
+    fn start() -> String {
+        let listener = task::block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        task::spawn(serve(listener));
+        url
+    }
+
+    #[test]
+    fn test_scrape() {
+        let url = start();
+        metrics::worker_connected("10.0.0.9:7878", true);
+
+        let response = task::block_on(reqwest::get(format!("{}/metrics", url))).unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers()[reqwest::header::CONTENT_TYPE],
+            metrics::content_type(),
+        );
+
+        let text = task::block_on(response.text()).unwrap();
+        assert!(text.contains(r#"artie_worker_connected{worker="10.0.0.9:7878"} 1"#));
+    }
+
+    #[test]
+    fn test_not_found() {
+        let url = start();
+
+        let response = task::block_on(reqwest::get(format!("{}/other", url))).unwrap();
+        assert_eq!(response.status(), 404);
+
+        let client = reqwest::Client::new();
+        let response = task::block_on(client.post(format!("{}/metrics", url)).send()).unwrap();
+        assert_eq!(response.status(), 405);
+    }
+}
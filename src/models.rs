@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use chrono::prelude::{DateTime, Utc};
 
 use crate::{Error, Result};
+use crate::metadata::ProviderId;
 
 /// Specifies the various audio codecs.
 ///
@@ -129,9 +130,40 @@ pub enum AudioEncodeMethod {
     AAC,
 }
 
+/// Specifies what a title contains.
+///
+/// Titles are classified when they are catalogued. The classification controls whether the title is
+/// transcoded and where it is stored in the library. See [`Title::classification`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Classification {
+    /// The main feature of a movie disc.
+    Main,
+
+    /// One or more consecutive episodes of a show.
+    Episodes {
+        /// The number of the first episode.
+        number: u16,
+
+        /// The number of episodes.
+        count: u16,
+    },
+
+    /// A special feature such as a trailer or behind the scenes footage.
+    Special(SpecialFeature),
+
+    /// An alternate version of the main feature (e.g. Directors Cut).
+    Version {
+        /// The name of the version.
+        name: String,
+    },
+
+    /// The title is not wanted (e.g. duplicates, "play all" titles, or disc menus).
+    Discard,
+}
+
 /// Media container types.
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Debug)]
+#[derive(Debug, Deserialize, Serialize)]
 pub enum ContainerType {
     /// Matroska Container Format
     /// <https://en.wikipedia.org/wiki/Matroska>
@@ -142,6 +174,43 @@ pub enum ContainerType {
     MP4,
 }
 
+/// A problem found by an integrity scan (see [`crate::library::integrity`]).
+#[derive(Debug, Deserialize, Serialize)]
+pub enum IntegrityFinding {
+    /// The video file does not exist at the video's location.
+    Missing {
+        video_id: u32,
+        location: MediaLocation,
+    },
+
+    /// The checksum of the video file does not match the checksum recorded when the video was
+    /// created.
+    Modified {
+        video_id: u32,
+        location: MediaLocation,
+        checksum: String,
+    },
+
+    /// The video file could not be read.
+    Unreadable {
+        video_id: u32,
+        location: MediaLocation,
+        error: String,
+    },
+
+    /// A video file exists that is not referenced by any video.
+    Unexpected {
+        location: MediaLocation,
+    },
+
+    /// The video file was found at a different location and the video's location was updated.
+    Relocated {
+        video_id: u32,
+        from: MediaLocation,
+        to: MediaLocation,
+    },
+}
+
 /// Location of a media file.
 ///
 /// The application has three main locations (not counting the application's data directory) where
@@ -163,7 +232,7 @@ pub enum ContainerType {
 ///
 /// There is also the [`MediaLocation::Deleted`] location. This is used mainly for path fields in
 /// the database for a file that was deleted by the user.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum MediaLocation {
     /// File path is relative to the media inbox root directory.
     Inbox(PathBuf),
@@ -179,7 +248,7 @@ pub enum MediaLocation {
 }
 
 /// Specifies the different types of media.
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub enum MediaType {
     #[default]
     Movie,
@@ -237,7 +306,7 @@ impl Display for MediaType {
 }
 
 /// Specifies the states of an operation (e.g. copy or transcode).
-#[derive(Debug)]
+#[derive(Debug, Deserialize, Serialize)]
 pub enum OperationState {
     /// The operation was requested and waiting to be started.
     Requested,
@@ -262,7 +331,7 @@ pub enum OperationState {
 ///
 /// [`SpecialFeatureType::None`] is used when a value is needed for something that is not a special
 /// feature. Avoids having to wrap values with [`Option`].
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum SpecialFeatureType {
     None,
     BehindTheScenes,
@@ -277,6 +346,39 @@ pub enum SpecialFeatureType {
     Trailers,
 }
 
+impl SpecialFeatureType {
+    /// All of the types that are special features (excludes [`SpecialFeatureType::None`]).
+    pub const ALL: &'static [Self] = &[
+        Self::BehindTheScenes,
+        Self::DeletedScenes,
+        Self::Interviews,
+        Self::Scenes,
+        Self::Samples,
+        Self::Shorts,
+        Self::Featurettes,
+        Self::Clips,
+        Self::Extras,
+        Self::Trailers,
+    ];
+
+    /// Convert the special feature type to its display value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpecialFeatureType::None => "None",
+            SpecialFeatureType::BehindTheScenes => "Behind The Scenes",
+            SpecialFeatureType::DeletedScenes => "Deleted Scenes",
+            SpecialFeatureType::Interviews => "Interviews",
+            SpecialFeatureType::Scenes => "Scenes",
+            SpecialFeatureType::Samples => "Samples",
+            SpecialFeatureType::Shorts => "Shorts",
+            SpecialFeatureType::Featurettes => "Featurettes",
+            SpecialFeatureType::Clips => "Clips",
+            SpecialFeatureType::Extras => "Extras",
+            SpecialFeatureType::Trailers => "Trailers",
+        }
+    }
+}
+
 /// Specifies the various subtitle codecs.
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
@@ -492,9 +594,17 @@ pub struct CopyOperation {
     /// Raw log output captured when running the MakeMKV copy command.
     pub copy_log: String,
 
+    /// Events logged by the application while performing the copy operation.
+    pub app_log: String,
+
     /// The computer the copy operation was performed on.
     pub host: Reference<Host>,
 
+    /// The canonical identifier of the movie or show assigned by the metadata provider.
+    ///
+    /// `None` if the user did not select a movie or show from the metadata search results.
+    pub metadata_id: Option<ProviderId>,
+
     /// List of titles created from this copy operation.
     ///
     /// Whether this is `Some` or `None` will depend on the database query.
@@ -506,6 +616,59 @@ pub struct CopyOperation {
     pub videos: Option<Vec<Video>>,
 }
 
+impl Title {
+    /// Determines the classification of the title from its episode, special feature, version, and
+    /// discarded fields.
+    ///
+    /// A title that has not been catalogued will be classified as the main feature.
+    pub fn classification(&self) -> Classification {
+        if self.discarded {
+            Classification::Discard
+        } else if let Some(special_feature) = &self.special_feature {
+            Classification::Special(special_feature.clone())
+        } else if self.episode_number > 0 {
+            Classification::Episodes { number: self.episode_number, count: self.episode_count }
+        } else if !self.version.is_empty() {
+            Classification::Version { name: self.version.clone() }
+        } else {
+            Classification::Main
+        }
+    }
+
+    /// Updates the episode, special feature, version, and discarded fields to match the
+    /// classification.
+    ///
+    /// The fields that do not apply to the classification are cleared.
+    ///
+    /// # Args
+    ///
+    /// `classification`:  The new classification of the title.
+    pub fn classify(&mut self, classification: Classification) {
+        self.episode_number = 0;
+        self.episode_count = 0;
+        self.special_feature = None;
+        self.version = String::default();
+        self.discarded = false;
+
+        match classification {
+            Classification::Main => {},
+            Classification::Episodes { number, count } => {
+                self.episode_number = number;
+                self.episode_count = count.max(1);
+            },
+            Classification::Special(special_feature) => {
+                self.special_feature = Some(special_feature);
+            },
+            Classification::Version { name } => {
+                self.version = name;
+            },
+            Classification::Discard => {
+                self.discarded = true;
+            },
+        }
+    }
+}
+
 impl Default for CopyOperation {
     fn default() -> Self {
         Self {
@@ -528,10 +691,12 @@ impl Default for CopyOperation {
             },
             info_log: String::default(),
             copy_log: String::default(),
+            app_log: String::default(),
             host: Reference {
                 id: 0,
                 value: None
             },
+            metadata_id: None,
             titles: None,
             videos: None,
         }
@@ -563,6 +728,11 @@ pub struct CopyParamaters {
 
     /// Additional information provided by the user.
     pub memo: String,
+
+    /// The canonical identifier of the movie or show assigned by the metadata provider.
+    ///
+    /// `None` if the user did not select a movie or show from the metadata search results.
+    pub metadata_id: Option<ProviderId>,
 }
 
 /// Represents a specific computer an operation was performed on.
@@ -575,6 +745,60 @@ pub struct Host {
     pub hostname: String,
 }
 
+/// Represents a scan verifying the video files have not been moved, modified, or deleted.
+#[derive(Debug, Default)]
+pub struct IntegrityScan {
+    /// Unique id of the scan (primary key).
+    pub id: u32,
+
+    /// When the scan started.
+    pub started: DateTime<Utc>,
+
+    /// When the scan completed.
+    ///
+    /// This will be the default value (the Unix epoch) while the scan is running or if the scan
+    /// did not complete.
+    pub completed: DateTime<Utc>,
+
+    /// The number of videos whose file checksum matched.
+    pub verified: u32,
+
+    /// The problems found by the scan.
+    ///
+    /// This field is stored as JSON data in the database.
+    pub findings: Vec<IntegrityFinding>,
+}
+
+impl IntegrityScan {
+    /// Summarizes the results of the scan.
+    pub fn summary(&self) -> String {
+        let mut missing = 0;
+        let mut modified = 0;
+        let mut unreadable = 0;
+        let mut unexpected = 0;
+        let mut relocated = 0;
+        for finding in &self.findings {
+            match finding {
+                IntegrityFinding::Missing { .. } => missing += 1,
+                IntegrityFinding::Modified { .. } => modified += 1,
+                IntegrityFinding::Unreadable { .. } => unreadable += 1,
+                IntegrityFinding::Unexpected { .. } => unexpected += 1,
+                IntegrityFinding::Relocated { .. } => relocated += 1,
+            }
+        }
+
+        format!(
+            "{} verified, {} missing, {} modified, {} unreadable, {} unexpected, {} relocated",
+            self.verified,
+            missing,
+            modified,
+            unreadable,
+            unexpected,
+            relocated,
+        )
+    }
+}
+
 /// Represents an optical drive.
 ///
 /// This is the representation of a drive within the database. The [`crate::drive`] module contains
@@ -602,7 +826,7 @@ pub struct Reference<T> {
 }
 
 /// Represents a special feature in a DVD or Blu-ray.
-#[derive(Debug)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SpecialFeature {
     /// The type of special feature.
     pub kind: SpecialFeatureType,
@@ -690,13 +914,23 @@ pub struct Title {
     /// Additional information/context provided by the user.
     pub memo: String,
 
+    /// The canonical identifier of the movie or show assigned by the metadata provider.
+    ///
+    /// `None` if the movie or show has not been matched to a metadata provider entry.
+    pub metadata_id: Option<ProviderId>,
+
+    /// Indicates the title was marked as unwanted when it was catalogued.
+    ///
+    /// Discarded titles are not transcoded or added to the library.
+    pub discarded: bool,
+
     /// List of videos associated with the title.
     ///
     /// Whether this is `Some` or `None` will depend on the database query.
     ///
     /// In general, each title will have one or two videos where one is from the copy operation and
     /// the other from the transcode operation.
-    pub videos: Option<Vec<Title>>,
+    pub videos: Option<Vec<Video>>,
 }
 
 /// Represents a transcode operation.
@@ -800,6 +1034,45 @@ pub struct Video {
 
     /// The video's runtime.
     pub duration: Duration,
+
+    /// The location of an image containing a grid of frames from the video.
+    ///
+    /// `None` if a contact sheet has not been created.
+    pub contact_sheet: Option<MediaLocation>,
+
+    /// The location of a short, low resolution clip of the video.
+    ///
+    /// `None` if a preview clip has not been created.
+    pub preview_clip: Option<MediaLocation>,
+}
+
+impl Video {
+    /// Summarizes the video's tracks.
+    ///
+    /// For example: `MPEG2 720x480 | 2 audio (eng, fra) | 3 subtitles (eng, fra, spa)`
+    pub fn track_summary(&self) -> String {
+        let mut parts = Vec::with_capacity(3);
+
+        if let Some(track) = self.video_tracks.first() {
+            parts.push(format!("{:?} {}", track.codec, track.size));
+        }
+
+        let audio = self.audio_tracks.iter().map(|t| t.language.as_str()).collect::<Vec<_>>();
+        if !audio.is_empty() {
+            parts.push(format!("{} audio ({})", audio.len(), audio.join(", ")));
+        }
+
+        let subtitles = self.subtitle_tracks.iter()
+            .map(|t| t.language.as_str())
+            .collect::<Vec<_>>();
+        match subtitles.len() {
+            0 => {},
+            1 => parts.push(format!("1 subtitle ({})", subtitles[0])),
+            n => parts.push(format!("{} subtitles ({})", n, subtitles.join(", "))),
+        }
+
+        parts.join(" | ")
+    }
 }
 
 /// Represents a video track.
@@ -824,5 +1097,59 @@ pub struct VideoTrack {
 
 #[cfg(test)]
 mod tests {
-    // TODO[TESTS]
+    use super::*;
+
+    fn make_title() -> Title {
+        Title {
+            id: 1,
+            index: 0,
+            media_type: MediaType::Show,
+            title: String::from("Test Show"),
+            year: 2004,
+            season: 1,
+            episode_number: 0,
+            episode_count: 0,
+            special_feature: None,
+            version: String::default(),
+            disc: 1,
+            location: String::default(),
+            memo: String::default(),
+            metadata_id: None,
+            discarded: false,
+            videos: None,
+        }
+    }
+
+    #[test]
+    fn test_classification_round_trip() {
+        let classifications = [
+            Classification::Main,
+            Classification::Episodes { number: 3, count: 2 },
+            Classification::Special(SpecialFeature {
+                kind: SpecialFeatureType::Trailers,
+                name: String::from("Teaser"),
+            }),
+            Classification::Version { name: String::from("Extended") },
+            Classification::Discard,
+        ];
+
+        let mut title = make_title();
+        for classification in classifications {
+            title.classify(classification.clone());
+            assert_eq!(title.classification(), classification);
+        }
+    }
+
+    #[test]
+    fn test_classify_clears_other_fields() {
+        let mut title = make_title();
+        title.classify(Classification::Episodes { number: 3, count: 0 });
+        assert_eq!(title.episode_count, 1);
+
+        title.classify(Classification::Version { name: String::from("Extended") });
+        assert_eq!(title.episode_number, 0);
+        assert_eq!(title.episode_count, 0);
+        assert!(title.special_feature.is_none());
+        assert!(!title.discarded);
+    }
 }
@@ -9,18 +9,33 @@
 //!
 //! The actor can be initialized by calling [`init`] which will start the task for processing
 //! requests for the actor. This is called when the server or client is initialized
+//!
+//! File transfers with the connected node (see [`crate::net::transfer`]) run in their own tasks.
+//! The actor starts them and forwards the transfer messages it receives to them.
 
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 
 use makemkv::{CopyCommandOutput, InfoCommandOutput};
 
-use crate::Result;
+use crate::{Error, Result};
 use crate::actor;
 use crate::bus;
 use crate::drive::{self, OsOpticalDrive};
 use crate::models::MediaLocation;
-use crate::net::{Handle, IncomingMessage, Message, OutgoingMessage};
-use crate::net::protocol;
+use crate::net::{
+    Handle,
+    IncomingMessage,
+    ManagerRequest,
+    Message,
+    OutgoingMessage,
+    TransferDirection,
+    TransferRequest,
+};
+use crate::net::{protocol, sentinel};
+use crate::net::protocol::NackCode;
+use crate::net::sentinel::{SentinelRead, StorageCheck};
+use crate::net::transfer::{self, Event, StorageMode, TransferFile, Transfers};
+use crate::task;
 
 /// Maximum number of queued messages.
 const CHANNEL_BUFFER_SIZE: usize = 10;
@@ -31,9 +46,16 @@ const CHANNEL_BUFFER_SIZE: usize = 10;
 /// processing requests for the actor and then return a handle for sending requests to the actor as
 /// well as the receiving end of the channel used by the actor to send messages over the network
 /// to the connected peer.
-pub fn init(name: &str, bus: &bus::Handle) -> (Handle, mpsc::Receiver<OutgoingMessage>)  {
+///
+/// `storage` is how the worker node's media folders are shared with the control node. On a worker
+/// node, it is replaced by the mode sent by the control node when it connects.
+pub fn init(
+    name: &str,
+    bus: &bus::Handle,
+    storage: StorageMode,
+) -> (Handle, mpsc::Receiver<OutgoingMessage>) {
     let (net_tx, net_rx) = mpsc::channel(CHANNEL_BUFFER_SIZE);
-    let msg_processor = MessageProcessor::new(bus.clone(), net_tx);
+    let msg_processor = MessageProcessor::new(bus.clone(), net_tx, storage);
     (actor::create_and_run(name, msg_processor), net_rx)
 }
 
@@ -44,6 +66,12 @@ struct MessageProcessor {
 
     /// Transmission end of the channel used to send messages over the network.
     net_tx: mpsc::Sender<OutgoingMessage>,
+
+    /// How the worker node's media folders are shared with the control node.
+    storage: StorageMode,
+
+    /// The file transfers running with the connected node.
+    transfers: Transfers,
 }
 
 impl MessageProcessor {
@@ -54,8 +82,15 @@ impl MessageProcessor {
     /// `bus`:  Handle used to send messages to other actors via the message bus.
     ///
     /// `net_tx`:  Transmission end of the channel used to send messages over the network.
-    fn new(bus: bus::Handle, net_tx: mpsc::Sender<OutgoingMessage>) -> Self {
-        Self { bus, net_tx }
+    ///
+    /// `storage`:  How the worker node's media folders are shared with the control node.
+    fn new(bus: bus::Handle, net_tx: mpsc::Sender<OutgoingMessage>, storage: StorageMode) -> Self {
+        Self {
+            bus,
+            net_tx,
+            storage,
+            transfers: Transfers::default(),
+        }
     }
 
     /// Processes an incoming drive status update.
@@ -84,14 +119,43 @@ impl MessageProcessor {
     /// `msg`:  The received messsage. The request within the message will be sent to the
     /// appropriate actor for processing.
     ///
+    /// Messages that must be acknowledged are accepted or rejected based on the result of
+    /// processing them.
+    ///
+    /// # Errors
+    ///
+    /// The potential errors will depend on the received message.
+    async fn process_incoming(&mut self, mut incoming: IncomingMessage) -> Result<()> {
+        let ack = incoming.ack.take();
+        let result = self.process_request(incoming).await;
+        if let Some(ack) = ack {
+            acknowledge(ack, &result);
+        }
+        result
+    }
+
+    /// Relay the request within a message received from the network to the appropriate actor.
+    ///
+    /// # Args
+    ///
+    /// `incoming`:  The received message.
+    ///
     /// # Errors
     ///
     /// The potential errors will depend on the received message.
-    async fn process_incoming(&self, incoming: IncomingMessage) -> Result<()> {
+    async fn process_request(&mut self, incoming: IncomingMessage) -> Result<()> {
         match incoming.msg {
             protocol::Message::DriveStatusUpdate { drive } => {
                 self.process_drive_status_update(drive, incoming.sender).await
             },
+            protocol::Message::Ack
+            | protocol::Message::Hello { .. }
+            | protocol::Message::Nack { .. } => {
+                // These are handled by the connection (see `net::process_stream`).
+                let msg = &incoming.msg;
+                tracing::warn!(sender=incoming.sender, ?msg, "ignoring unexpected message");
+                Ok(())
+            },
             protocol::Message::MakeMkvCancel { drive } => {
                 self.process_makemkv_cancel(drive).await
             },
@@ -113,6 +177,36 @@ impl MessageProcessor {
             protocol::Message::RunMakeMkvInfo { drive, log_file } => {
                 self.process_makemkv_info(drive, log_file).await
             },
+            protocol::Message::StorageMode { mode } => {
+                tracing::info!(?mode, "storage mode set by control node");
+                self.storage = mode;
+                Ok(())
+            },
+            protocol::Message::StorageVerified { sentinels } => {
+                self.process_storage_verified(sentinels, incoming.sender);
+                Ok(())
+            },
+            protocol::Message::TransferChunk { id, index, offset, data } => {
+                self.transfers.forward(&id, Event::Chunk { index, offset, data });
+                Ok(())
+            },
+            protocol::Message::TransferComplete { id, error } => {
+                self.transfers.forward(&id, Event::Complete { error });
+                Ok(())
+            },
+            protocol::Message::TransferOffer { id, files } => {
+                self.process_transfer_offer(id, files).await
+            },
+            protocol::Message::TransferPull { id, location } => {
+                self.process_transfer_pull(id, location).await
+            },
+            protocol::Message::TransferRequest { id, index, offset, length } => {
+                self.transfers.forward(&id, Event::Request { index, offset, length });
+                Ok(())
+            },
+            protocol::Message::VerifyStorage { locations } => {
+                self.process_verify_storage(locations).await
+            },
         }
     }
 
@@ -241,6 +335,173 @@ impl MessageProcessor {
         drive::worker_makemkv_info(&self.bus, drive, log_file).await
     }
 
+    /// Processes the sentinel files read by a worker node.
+    ///
+    /// # Args
+    ///
+    /// `sentinels`:  The sentinel files read by the worker node.
+    ///
+    /// `sender`:  The address of the worker node.
+    fn process_storage_verified(&self, sentinels: Vec<SentinelRead>, sender: String) {
+        let check = sentinel::verify(&sentinels);
+        match &check {
+            StorageCheck::Mismatch { problems } => {
+                tracing::error!(worker=sender, ?problems, "worker media folders are not shared");
+            },
+            _ => tracing::info!(worker=sender, "worker media folders verified"),
+        }
+        sentinel::set_status(&sender, check);
+    }
+
+    /// Processes a request from the control node to read the sentinel files in the media folders.
+    ///
+    /// # Args
+    ///
+    /// `locations`:  The locations of the sentinel files.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::JoinError`] if the task reading the files panics.
+    ///
+    /// See [`transfer::send_message`] for the errors sending the result.
+    async fn process_verify_storage(&self, locations: Vec<MediaLocation>) -> Result<()> {
+        let sentinels = task::spawn_blocking(move || sentinel::read(locations)).await?;
+        let msg = protocol::Message::StorageVerified { sentinels };
+        transfer::send_message(&self.net_tx, msg).await
+    }
+
+    /// Processes files offered by the other node.
+    ///
+    /// If the transfer is already running (e.g. the files were pulled or the sending node is
+    /// offering them again), the offer is forwarded to it. Otherwise, a task is started to receive
+    /// the files. Offers are rejected if the worker node shares its media folders with the control
+    /// node.
+    ///
+    /// # Args
+    ///
+    /// `id`:  The ID of the transfer.
+    ///
+    /// `files`:  The files offered.
+    ///
+    /// # Errors
+    ///
+    /// See [`transfer::send_message`] for the errors rejecting the offer.
+    async fn process_transfer_offer(&mut self, id: String, files: Vec<TransferFile>) -> Result<()> {
+        if self.transfers.is_running(&id) {
+            self.transfers.forward(&id, Event::Offer { files });
+            return Ok(());
+        }
+
+        if self.storage == StorageMode::Shared {
+            return self.reject_transfer(id, "media folders are shared").await;
+        }
+
+        let events = self.transfers.register(&id);
+        task::spawn(transfer::receive(id, Some(files), self.net_tx.clone(), events, None));
+        Ok(())
+    }
+
+    /// Processes a request from the other node to send it the files in a folder.
+    ///
+    /// A task is started to send the files. Requests are rejected if the worker node shares its
+    /// media folders with the control node or the location is outside of the media folders (see
+    /// [`transfer::check_location`]).
+    ///
+    /// # Args
+    ///
+    /// `id`:  The ID of the transfer.
+    ///
+    /// `location`:  The location of the folder to send.
+    ///
+    /// # Errors
+    ///
+    /// See [`transfer::send_message`] for the errors rejecting the request.
+    async fn process_transfer_pull(&mut self, id: String, location: MediaLocation) -> Result<()> {
+        if self.storage == StorageMode::Shared {
+            return self.reject_transfer(id, "media folders are shared").await;
+        }
+
+        if let Err(error) = transfer::check_location(&location) {
+            return self.reject_transfer(id, &format!("{:?}", error)).await;
+        }
+
+        let events = self.transfers.register(&id);
+        let net_tx = self.net_tx.clone();
+        task::spawn(async move {
+            if let Err(error) = transfer::send(id.clone(), location, net_tx, events, None).await {
+                tracing::error!(id, ?error, "failed to send pulled files");
+            }
+        });
+        Ok(())
+    }
+
+    /// Reject a transfer started by the other node.
+    ///
+    /// # Args
+    ///
+    /// `id`:  The ID of the transfer.
+    ///
+    /// `reason`:  Why the transfer is rejected, which is reported to the other node.
+    ///
+    /// # Errors
+    ///
+    /// See [`transfer::send_message`].
+    async fn reject_transfer(&self, id: String, reason: &str) -> Result<()> {
+        tracing::warn!(id, reason, "rejecting transfer");
+        let msg = protocol::Message::TransferComplete {
+            id,
+            error: Some(reason.to_owned()),
+        };
+        transfer::send_message(&self.net_tx, msg).await
+    }
+
+    /// Process a request to transfer files with the connected node.
+    ///
+    /// The transfer runs in its own task, which sends the result to the requester. Nothing is
+    /// transferred if the worker node shares its media folders with the control node.
+    ///
+    /// # Args
+    ///
+    /// `request`:  The transfer request.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ResponseSend`] if the response cannot be sent.
+    fn process_transfer(&mut self, request: TransferRequest) -> Result<()> {
+        let TransferRequest { direction, location, progress, response, .. } = request;
+        if self.storage == StorageMode::Shared {
+            tracing::debug!(?direction, ?location, "media folders are shared, nothing to transfer");
+            return response.send(Ok(()))
+                .map_err(|_| Error::ResponseSend);
+        }
+
+        let id = transfer::new_id();
+        let events = self.transfers.register(&id);
+        let net_tx = self.net_tx.clone();
+        match direction {
+            TransferDirection::Push => {
+                task::spawn(async move {
+                    let result = transfer::send(id, location, net_tx, events, progress).await;
+                    let _ = response.send(result)
+                        .inspect_err(|_| send_error_trace("TransferPush"));
+                });
+            },
+            TransferDirection::Pull => {
+                task::spawn(async move {
+                    let msg = protocol::Message::TransferPull { id: id.clone(), location };
+                    match transfer::send_message(&net_tx, msg).await {
+                        Ok(()) => transfer::receive(id, None, net_tx, events, Some(response)).await,
+                        Err(error) => {
+                            let _ = response.send(Err(error))
+                                .inspect_err(|_| send_error_trace("TransferPull"));
+                        },
+                    }
+                });
+            },
+        }
+        Ok(())
+    }
+
     /// Process a request to send a message over the network.
     ///
     /// `msg`:  The message to send over the network.
@@ -260,11 +521,89 @@ impl actor::MessageProcessor<Message> for MessageProcessor {
         match msg {
             Message::Incoming(incoming) => self.process_incoming(incoming).await,
             Message::Outgoing(outgoing) => self.process_outgoing(outgoing).await,
+            Message::Transfer(request) => self.process_transfer(request),
+            Message::Manager(request) => {
+                tracing::warn!(?request, "network actor received client manager request");
+                match request {
+                    ManagerRequest::AddWorker { response, .. }
+                    | ManagerRequest::RemoveWorker { response, .. } => {
+                        response.send(Err(Error::InvalidNetRequest))
+                            .map_err(|_| Error::ResponseSend)
+                    },
+                    ManagerRequest::ListDiscovered { response } => {
+                        response.send(Err(Error::InvalidNetRequest))
+                            .map_err(|_| Error::ResponseSend)
+                    },
+                    ManagerRequest::ListWorkers { response } => {
+                        response.send(Err(Error::InvalidNetRequest))
+                            .map_err(|_| Error::ResponseSend)
+                    },
+                    ManagerRequest::Discovered { .. } => Ok(()),
+                }
+            },
         }
     }
 }
 
+/// Accept or reject a message received from the other node.
+///
+/// The reply is sent by the connection the message was received on, so this does not wait for it
+/// to be written to the network.
+///
+/// # Args
+///
+/// `ack`:  Transmission end of the channel to send the reply to.
+///
+/// `result`:  The result of processing the message.
+fn acknowledge(ack: oneshot::Sender<protocol::Message>, result: &Result<()>) {
+    let msg = match result {
+        Ok(()) => protocol::Message::Ack,
+        Err(error) => protocol::Message::Nack {
+            code: NackCode::from(error),
+            error: format!("{:?}", error),
+        },
+    };
+    let _ = ack.send(msg)
+        .inspect_err(|_| tracing::warn!("connection closed before acknowledging message"));
+}
+
+/// Log an error due to failure to send the response for a transfer.
+///
+/// # Args
+///
+/// `request`:  The name of the request the response was being sent for.
+fn send_error_trace(request: &str) {
+    tracing::error!("failed to send {} response", request);
+}
+
 #[cfg(test)]
 mod tests {
-    // TODO[TESTS]
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_transfer_pull_outside_media_folder() {
+        task::block_on(async {
+            let (bus, _bus_rx) = bus::init_channel();
+            let (net_tx, mut net_rx) = mpsc::channel(CHANNEL_BUFFER_SIZE);
+            let mut processor = MessageProcessor::new(bus, net_tx, StorageMode::Transfer);
+
+            let reply = async {
+                let outgoing = net_rx.recv().await.unwrap();
+                outgoing.response.send(Ok(())).unwrap();
+                outgoing.msg
+            };
+            let location = MediaLocation::Inbox(PathBuf::from("movie/../../etc"));
+            let pull = processor.process_transfer_pull(String::from("1"), location);
+            let (result, msg) = tokio::join!(pull, reply);
+
+            assert!(result.is_ok());
+            assert!(matches!(
+                msg,
+                protocol::Message::TransferComplete { ref id, error: Some(_) } if id == "1"
+            ));
+            assert!(!processor.transfers.is_running("1"));
+        });
+    }
 }
@@ -6,26 +6,51 @@
 //! The client manager is responsible for managing the client actor instance and serves as the
 //! broker for client related requests coming from other actors via message bus. The manager is
 //! initialized by calling [`init`] (done during application startup).
+//!
+//! Worker nodes can be added and removed while the application is running (see
+//! [`crate::net::add_worker`] and [`crate::net::remove_worker`]). The changes are saved to the
+//! config file, so the same workers are connected to the next time the application starts.
+//!
+//! If discovery is enabled, the manager also keeps the worker nodes found on the local network
+//! (see [`crate::net::discovery`]) until they are added or stop announcing themselves.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use tokio_util::sync::CancellationToken;
 
 use crate::{Error, Result};
-use crate::actor;
+use crate::actor::{self, Response};
 use crate::bus;
-use crate::net::{Handle, Message, Settings};
-use crate::client;
+use crate::metrics;
+use crate::net::{Handle, ManagerRequest, Message, Settings, discovery, sentinel};
+use crate::net::discovery::DiscoveredWorker;
+use crate::net::transfer::StorageMode;
+use crate::client::{self, SharedStatus, WorkerStatus};
+use crate::settings;
 
 /// Create the client manager actor.
 ///
 /// This will create the actor and spawn the task for processing requests. It will also initialize
-/// the client for each of the configured worker nodes.
+/// the client for each of the configured worker nodes and start listening for worker nodes on the
+/// local network if discovery is enabled.
 ///
 /// # Args
 ///
 /// `bus`:  Handle used to send messages to other actors via the message bus.
 ///
-/// `settings`:  Settings containing the list of worker nodes to connect to.
-pub fn init(bus: &bus::Handle, settings: &Settings) -> Handle {
-    let msg_processor = MessageProcessor::new(bus.clone(), &settings.workers);
-    actor::create_and_run("client manager", msg_processor)
+/// `settings`:  Settings containing the list of worker nodes to connect to and the discovery
+/// settings.
+///
+/// `config_path`:  The config file that added and removed workers are saved to. Changes are not
+/// saved if `None`.
+pub fn init(bus: &bus::Handle, settings: &Settings, config_path: Option<PathBuf>) -> Handle {
+    let msg_processor = MessageProcessor::new(bus.clone(), settings, config_path);
+    let handle = actor::create_and_run("client manager", msg_processor);
+    discovery::listen(&settings.discovery, handle.clone());
+    handle
 }
 
 /// Handle for interfacing with a client actor.
@@ -38,6 +63,12 @@ struct ClientHandle {
 
     /// The underlying actor handle.
     actor: Handle,
+
+    /// The status of the connection to the worker node.
+    status: SharedStatus,
+
+    /// Cancels the task connecting to the worker node when the worker is removed.
+    cancellation_token: CancellationToken,
 }
 
 /// Processes messages sent to the client manager.
@@ -54,6 +85,15 @@ struct MessageProcessor {
     /// nodes. They automatically handle reconnect attemps and any request sent to them while
     /// disconnect will fail.
     clients: Vec<ClientHandle>,
+
+    /// The config file that added and removed workers are saved to.
+    config_path: Option<PathBuf>,
+
+    /// The worker nodes found on the local network by address.
+    discovered: HashMap<String, DiscoveredWorker>,
+
+    /// How each worker node's media folders are shared with the control node, by address.
+    storage: HashMap<String, StorageMode>,
 }
 
 impl MessageProcessor {
@@ -63,33 +103,210 @@ impl MessageProcessor {
     ///
     /// `bus`:  Handle used to send messages to other actors via the message bus.
     ///
-    /// `clients`:  List of network addresses for the worker nodes to create clients for.
-    fn new(bus: bus::Handle, clients: &[String]) -> Self {
-        let clients = clients.iter()
-            .map(|addr| create_client(&bus, addr))
+    /// `settings`:  Settings containing the list of worker nodes to create clients for and how
+    /// their media folders are shared.
+    ///
+    /// `config_path`:  The config file that added and removed workers are saved to.
+    fn new(bus: bus::Handle, settings: &Settings, config_path: Option<PathBuf>) -> Self {
+        let clients = settings.workers.iter()
+            .map(|addr| create_client(&bus, addr, settings.storage_mode(addr)))
             .collect();
-        Self { bus, clients }
+        Self {
+            bus,
+            clients,
+            config_path,
+            discovered: HashMap::new(),
+            storage: settings.storage.clone(),
+        }
     }
-}
 
-impl actor::MessageProcessor<Message> for MessageProcessor {
-    async fn process(&mut self, msg: Message) -> Result<()> {
-        let Message::Outgoing(outgoing) = msg else {
-            tracing::warn!("client manager received incoming message");
-            return Ok(());
+    /// Process a request to add a worker node.
+    ///
+    /// # Args
+    ///
+    /// `addr`:  The address of the worker node.
+    ///
+    /// `response`:  The transmission end of the channel to send the response. See
+    /// [`crate::net::add_worker`] for more information on the response.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ResponseSend`] if the response cannot be sent.
+    fn add_worker(&mut self, addr: String, response: Response<()>) -> Result<()> {
+        let reply = if self.clients.iter().any(|c| c.addr == addr) {
+            Err(Error::WorkerExists { addr })
+        } else {
+            tracing::info!(addr, "adding worker");
+            let storage = self.storage.get(&addr).copied().unwrap_or_default();
+            self.clients.push(create_client(&self.bus, &addr, storage));
+            self.save_workers();
+            Ok(())
+        };
+
+        response.send(reply)
+            .map_err(|_| Error::ResponseSend)
+    }
+
+    /// Process a worker node found on the local network.
+    ///
+    /// # Args
+    ///
+    /// `worker`:  The discovered worker.
+    fn discovered(&mut self, worker: DiscoveredWorker) {
+        if !self.discovered.contains_key(&worker.addr) {
+            tracing::info!(addr = worker.addr, hostname = worker.hostname, "discovered worker");
+        }
+        self.discovered.insert(worker.addr.clone(), worker);
+    }
+
+    /// Process a request to list the worker nodes found on the local network.
+    ///
+    /// Workers that were already added or stopped announcing themselves are not included.
+    ///
+    /// # Args
+    ///
+    /// `response`:  The transmission end of the channel to send the response.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ResponseSend`] if the response cannot be sent.
+    fn list_discovered(&mut self, response: Response<Vec<DiscoveredWorker>>) -> Result<()> {
+        let now = Utc::now();
+        self.discovered.retain(|_, worker| worker.expires > now);
+
+        let mut workers = self.discovered.values()
+            .filter(|worker| !self.clients.iter().any(|c| c.addr == worker.addr))
+            .cloned()
+            .collect::<Vec<_>>();
+        workers.sort_by(|a, b| a.hostname.cmp(&b.hostname).then_with(|| a.addr.cmp(&b.addr)));
+
+        response.send(Ok(workers))
+            .map_err(|_| Error::ResponseSend)
+    }
+
+    /// Process a request to list the status of each worker node.
+    ///
+    /// # Args
+    ///
+    /// `response`:  The transmission end of the channel to send the response.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ResponseSend`] if the response cannot be sent.
+    fn list_workers(&self, response: Response<Vec<WorkerStatus>>) -> Result<()> {
+        let statuses = self.clients.iter()
+            .map(|c| c.status.lock().expect("worker status lock should not be poisoned").clone())
+            .collect();
+
+        response.send(Ok(statuses))
+            .map_err(|_| Error::ResponseSend)
+    }
+
+    /// Process a request to remove a worker node.
+    ///
+    /// The client's connection task is cancelled and its actor stops once the handle is dropped.
+    ///
+    /// # Args
+    ///
+    /// `addr`:  The address of the worker node.
+    ///
+    /// `response`:  The transmission end of the channel to send the response. See
+    /// [`crate::net::remove_worker`] for more information on the response.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ResponseSend`] if the response cannot be sent.
+    fn remove_worker(&mut self, addr: String, response: Response<()>) -> Result<()> {
+        let reply = match self.clients.iter().position(|c| c.addr == addr) {
+            Some(index) => {
+                tracing::info!(addr, "removing worker");
+                let client = self.clients.remove(index);
+                client.cancellation_token.cancel();
+                metrics::worker_removed(&addr);
+                sentinel::remove(&addr);
+                self.save_workers();
+                Ok(())
+            },
+            None => Err(Error::WorkerNotFound { addr }),
         };
 
-        let Some(addr) = &outgoing.worker else {
-            return outgoing.response.send(Err(Error::WorkerNone))
-                .map_err(|_| Error::ResponseSend);
+        response.send(reply)
+            .map_err(|_| Error::ResponseSend)
+    }
+
+    /// Find the client for a worker node.
+    ///
+    /// # Args
+    ///
+    /// `worker`:  The address of the worker node.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::WorkerNone`] if the worker is `None`.
+    ///
+    /// [`Error::WorkerNotFound`] if there is no client for the worker.
+    fn find_client(&self, worker: Option<&str>) -> Result<&ClientHandle> {
+        let addr = worker.ok_or(Error::WorkerNone)?;
+        self.clients.iter()
+            .find(|c| c.addr == addr)
+            .ok_or_else(|| Error::WorkerNotFound { addr: addr.to_owned() })
+    }
+
+    /// Save the current list of workers to the config file.
+    ///
+    /// The workers have already been added or removed at this point, so a failure is only logged.
+    fn save_workers(&self) {
+        let Some(path) = &self.config_path else {
+            return;
         };
 
-        let Some(client) = self.clients.iter().find(|c| c.addr == *addr) else {
-            return outgoing.response.send(Err(Error::WorkerNotFound { addr: addr.to_owned() }))
-                .map_err(|_| Error::ResponseSend);
+        let workers = self.clients.iter()
+            .map(|c| c.addr.clone())
+            .collect::<Vec<_>>();
+        if let Err(error) = save_workers(path, workers) {
+            tracing::error!(?error, ?path, "failed to save workers to config");
+        }
+    }
+}
+
+impl actor::MessageProcessor<Message> for MessageProcessor {
+    async fn process(&mut self, msg: Message) -> Result<()> {
+        let outgoing = match msg {
+            Message::Outgoing(outgoing) => outgoing,
+            Message::Manager(ManagerRequest::AddWorker { addr, response }) => {
+                return self.add_worker(addr, response);
+            },
+            Message::Manager(ManagerRequest::Discovered { worker }) => {
+                self.discovered(worker);
+                return Ok(());
+            },
+            Message::Manager(ManagerRequest::ListDiscovered { response }) => {
+                return self.list_discovered(response);
+            },
+            Message::Manager(ManagerRequest::ListWorkers { response }) => {
+                return self.list_workers(response);
+            },
+            Message::Manager(ManagerRequest::RemoveWorker { addr, response }) => {
+                return self.remove_worker(addr, response);
+            },
+            Message::Transfer(request) => {
+                return match self.find_client(request.worker.as_deref()) {
+                    Ok(client) => client.actor.send(Message::Transfer(request)).await,
+                    Err(error) => request.response.send(Err(error))
+                        .map_err(|_| Error::ResponseSend),
+                };
+            },
+            Message::Incoming(_) => {
+                tracing::warn!("client manager received incoming message");
+                return Ok(());
+            },
         };
 
-        client.actor.send(Message::Outgoing(outgoing)).await
+        match self.find_client(outgoing.worker.as_deref()) {
+            Ok(client) => client.actor.send(Message::Outgoing(outgoing)).await,
+            Err(error) => outgoing.response.send(Err(error))
+                .map_err(|_| Error::ResponseSend),
+        }
     }
 }
 
@@ -103,15 +320,137 @@ impl actor::MessageProcessor<Message> for MessageProcessor {
 /// `bus`:  Handle used to send messages to other actors via the message bus.
 ///
 /// `addr`:  The address of the client.
-fn create_client(bus: &bus::Handle, addr: &String) -> ClientHandle {
+///
+/// `storage`:  How the worker node's media folders are shared with the control node.
+fn create_client(bus: &bus::Handle, addr: &String, storage: StorageMode) -> ClientHandle {
+    let status = Arc::new(Mutex::new(WorkerStatus::new(addr)));
+    let cancellation_token = CancellationToken::new();
     ClientHandle {
         addr: addr.to_owned(),
-        actor: client::init(bus, addr),
+        actor: client::init(bus, addr, status.clone(), storage, cancellation_token.clone()),
+        status,
+        cancellation_token,
     }
 }
 
+/// Save the list of workers to the config file.
+///
+/// # Args
+///
+/// `path`:  The path of the config file.
+///
+/// `workers`:  The addresses of the worker nodes.
+///
+/// # Errors
+///
+/// See [`settings::Settings::update_file`].
+fn save_workers(path: &Path, workers: Vec<String>) -> Result<()> {
+    settings::Settings::update_file(path, |settings| settings.net.workers = workers)
+}
+
 
 #[cfg(test)]
 mod tests {
-    // TODO[TESTS]
+    use std::fs;
+
+    use actor::MessageProcessor as _;
+    use tokio::sync::oneshot;
+
+    use crate::task;
+    use crate::test_utils::TempDir;
+
+    use super::*;
+
+    /// Send a manager request to the message processor, returning the response.
+    fn request<T>(
+        processor: &mut MessageProcessor,
+        request: impl FnOnce(Response<T>) -> ManagerRequest,
+    ) -> Result<T> {
+        let (tx, rx) = oneshot::channel();
+        task::block_on(processor.process(Message::Manager(request(tx)))).unwrap();
+        task::block_on(rx).unwrap()
+    }
+
+    #[test]
+    fn test_add_remove_list() {
+        let dir = TempDir::new("artie.test.client.manager");
+        fs::create_dir_all(dir.path()).unwrap();
+        let path = dir.path().join("artie.toml");
+        fs::write(&path, "[paths]\ninbox = \"/in\"\nlibrary = \"/lib\"\n\
+                          archive = \"/arc\"\ndata = \"/data\"\n\
+                          [net]\nworkers = [\"127.0.0.1:1\"]\n").unwrap();
+
+        let (bus, _bus_rx) = bus::init_channel();
+        let workers = [String::from("127.0.0.1:1")];
+        let settings = Settings { workers: workers.to_vec(), ..Default::default() };
+        let mut processor = MessageProcessor::new(bus, &settings, Some(path.clone()));
+
+        let addr = String::from("127.0.0.1:2");
+        let result = request(&mut processor, |response| {
+            ManagerRequest::AddWorker { addr: addr.clone(), response }
+        });
+        assert!(result.is_ok());
+        let result = request(&mut processor, |response| {
+            ManagerRequest::AddWorker { addr: addr.clone(), response }
+        });
+        assert!(matches!(result, Err(Error::WorkerExists { .. })));
+        let saved = settings::Settings::from_file(&path).unwrap();
+        assert_eq!(saved.net.workers, vec![workers[0].clone(), addr.clone()]);
+
+        let statuses = request(&mut processor, |response| {
+            ManagerRequest::ListWorkers { response }
+        }).unwrap();
+        let addrs = statuses.iter().map(|s| s.addr.as_str()).collect::<Vec<_>>();
+        assert_eq!(addrs, vec!["127.0.0.1:1", "127.0.0.1:2"]);
+        assert!(statuses.iter().all(|s| s.remote_addr.is_none()));
+
+        let ct = processor.clients[0].cancellation_token.clone();
+        let result = request(&mut processor, |response| {
+            ManagerRequest::RemoveWorker { addr: workers[0].clone(), response }
+        });
+        assert!(result.is_ok());
+        assert!(ct.is_cancelled());
+        let result = request(&mut processor, |response| {
+            ManagerRequest::RemoveWorker { addr: workers[0].clone(), response }
+        });
+        assert!(matches!(result, Err(Error::WorkerNotFound { .. })));
+        let saved = settings::Settings::from_file(&path).unwrap();
+        assert_eq!(saved.net.workers, vec![addr]);
+        assert_eq!(saved.paths.inbox, PathBuf::from("/in"));
+    }
+
+    #[test]
+    fn test_discovered() {
+        let (bus, _bus_rx) = bus::init_channel();
+        let workers = [String::from("127.0.0.1:1")];
+        let settings = Settings { workers: workers.to_vec(), ..Default::default() };
+        let mut processor = MessageProcessor::new(bus, &settings, None);
+
+        let now = Utc::now();
+        let worker = |addr: &str, hostname: &str, expires| DiscoveredWorker {
+            addr: addr.to_owned(),
+            hostname: hostname.to_owned(),
+            version: crate::net::protocol::VERSION,
+            last_seen: now,
+            expires,
+        };
+        let later = now + chrono::Duration::seconds(15);
+        let discovered = [
+            worker("127.0.0.1:1", "added", later),
+            worker("10.0.0.5:7878", "worker-b", later),
+            worker("10.0.0.6:7878", "worker-a", later),
+            worker("10.0.0.7:7878", "expired", now - chrono::Duration::seconds(1)),
+        ];
+        for worker in discovered {
+            let msg = Message::Manager(ManagerRequest::Discovered { worker });
+            task::block_on(processor.process(msg)).unwrap();
+        }
+
+        let listed = request(&mut processor, |response| {
+            ManagerRequest::ListDiscovered { response }
+        }).unwrap();
+        let hostnames = listed.iter().map(|w| w.hostname.as_str()).collect::<Vec<_>>();
+        assert_eq!(hostnames, vec!["worker-a", "worker-b"]);
+        assert!(!processor.discovered.contains_key("10.0.0.7:7878"));
+    }
 }
@@ -8,20 +8,36 @@
 //! and processing requests from the worker node. The connection task will automatically handle
 //! retrying connects if the connection fails or the network connection is broken. This will be
 //! called by the client manager actor during application startup for each configured node or when
-//! a new node is added to the configuration.
+//! a new node is added to the configuration. The connection task runs until the node is removed and
+//! the client's cancellation token is cancelled. Messages sent to the worker node while the client
+//! is not connected fail with [`crate::Error::Disconnected`].
+//!
+//! The state of the connection is tracked in a [`WorkerStatus`] shared with the client manager, so
+//! the state of every worker node can be listed (see [`crate::net::list_workers`]). Each time the
+//! client connects, the worker node is asked to check its media folders (see [`sentinel`]).
 //!
 //! Messages can be sent to the control node by using one of the helper methods in the
 //! [`crate::net`] module.
 
 pub mod manager;
 
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use tokio::net::TcpStream;
 use tokio::sync::mpsc;
+use tokio_util::future::FutureExt;
+use tokio_util::sync::CancellationToken;
 
+use crate::Error;
 use crate::bus;
-use crate::net::{self, Handle, OutgoingMessage};
+use crate::metrics;
+use crate::net::{self, Handle, OutgoingMessage, protocol, sentinel};
+use crate::net::sentinel::StorageCheck;
+use crate::net::transfer::StorageMode;
+use crate::notify::{self, Event};
 use crate::task;
 
 /// Initial amount of time to wait before attempting to connect to a worker node after a failed
@@ -38,6 +54,62 @@ const MAX_DELAY: Duration = Duration::from_secs(60);
 /// maximum delay. `2^6=64` which is greater than the [`MAX_DELAY`] of 60 seconds.
 const MAX_BACKOFF_COUNT: u32 = 6;
 
+/// The state of the connection to a worker node.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConnectionState {
+    /// Attempting to connect to the worker node.
+    Connecting,
+
+    /// Connected to the worker node.
+    Connected,
+
+    /// Waiting to reconnect after a failed attempt or a lost connection.
+    Waiting,
+}
+
+/// The status of a client's connection to its worker node.
+#[derive(Clone, Debug)]
+pub struct WorkerStatus {
+    /// The address of the worker node as configured.
+    pub addr: String,
+
+    /// The state of the connection.
+    pub state: ConnectionState,
+
+    /// The address of the worker node the connection was made to.
+    ///
+    /// Only `Some` while connected.
+    pub remote_addr: Option<SocketAddr>,
+
+    /// When the connection was made or a message was last received from the worker node.
+    ///
+    /// `None` if the client has never connected.
+    pub last_seen: Option<DateTime<Utc>>,
+
+    /// The number of failed connection attempts since the client was last connected.
+    pub attempt: u32,
+}
+
+impl WorkerStatus {
+    /// Create the status of a client that has not attempted to connect yet.
+    ///
+    /// # Args
+    ///
+    /// `addr`:  The address of the worker node.
+    pub fn new(addr: &str) -> Self {
+        Self {
+            addr: addr.to_owned(),
+            state: ConnectionState::Connecting,
+            remote_addr: None,
+            last_seen: None,
+            attempt: 0,
+        }
+    }
+}
+
+/// Status of a client's connection shared between the connection task and the client manager.
+pub type SharedStatus = Arc<Mutex<WorkerStatus>>;
+
 /// Create the client actor.
 ///
 /// This will create the actor and spawn the tasks for handling communication with the application
@@ -48,14 +120,35 @@ const MAX_BACKOFF_COUNT: u32 = 6;
 /// `bus`:  Handle used to send messages to other actors via the message bus.
 ///
 /// `addr`:  The address to connect to.
-pub fn init(bus: &bus::Handle, addr: &str) -> Handle {
+///
+/// `status`:  Updated with the state of the connection.
+///
+/// `storage`:  How the worker node's media folders are shared with the control node. Sent to the
+/// worker node each time the client connects.
+///
+/// `cancellation_token`:  Stops the connection task, closing the connection, when cancelled.
+pub fn init(
+    bus: &bus::Handle,
+    addr: &str,
+    status: SharedStatus,
+    storage: StorageMode,
+    cancellation_token: CancellationToken,
+) -> Handle {
     let name = format!("client {}", &addr);
-    let (handle, net_rx) = net::actor::init(&name, bus);
+    let (handle, net_rx) = net::actor::init(&name, bus, storage);
+
+    metrics::worker_connected(addr, false);
 
     let addr = addr.to_owned();
+    let bus = bus.clone();
     let handle_clone = handle.clone();
     task::spawn(async move {
-        connect(addr, handle_clone, net_rx).await
+        let result = connect(addr.clone(), bus, handle_clone, net_rx, status, storage)
+            .with_cancellation_token_owned(cancellation_token)
+            .await;
+        if result.is_none() {
+            tracing::info!(?addr, "client stopped");
+        }
     });
 
     handle
@@ -63,25 +156,62 @@ pub fn init(bus: &bus::Handle, addr: &str) -> Handle {
 
 /// Connect to the worker node.
 ///
+/// A [`Event::WorkerDisconnected`] notification is published each time an established connection
+/// is lost.
+///
 /// # Args
 ///
 /// `addr`:  The address of the node to connect to.
 ///
+/// `bus`:  Handle used to send messages to other actors via the message bus.
+///
 /// `client`:  Handle for the client instance.
 ///
 /// `net_rx`:  Receiving end of the channel used by the server actor to send messages to the
 /// connected client.
-async fn connect(addr: String, client: Handle, mut net_rx: mpsc::Receiver<OutgoingMessage>) {
+///
+/// `status`:  Updated with the state of the connection.
+///
+/// `storage`:  How the worker node's media folders are shared with the control node.
+async fn connect(
+    addr: String,
+    bus: bus::Handle,
+    client: Handle,
+    mut net_rx: mpsc::Receiver<OutgoingMessage>,
+    status: SharedStatus,
+    storage: StorageMode,
+) {
     let mut attempt: u32 = 0;
 
     loop {
-        match TcpStream::connect(&addr).await {
+        update_status(&status, |s| s.state = ConnectionState::Connecting);
+        match reject_while(&mut net_rx, TcpStream::connect(&addr)).await {
             Ok(stream) => {
                 attempt = 0;
+                update_status(&status, |s| {
+                    s.state = ConnectionState::Connected;
+                    s.remote_addr = stream.peer_addr().ok();
+                    s.last_seen = Some(Utc::now());
+                    s.attempt = 0;
+                });
 
                 tracing::info!(?addr, "client connected");
-                net::process_stream(stream, &addr, &client, &mut net_rx).await;
+                metrics::worker_connected(&addr, true);
+                let on_receive = || update_status(&status, |s| s.last_seen = Some(Utc::now()));
+                let greetings = greetings(&addr, storage).await;
+                net::process_stream(
+                    stream,
+                    &addr,
+                    &client,
+                    &mut net_rx,
+                    on_receive,
+                    greetings,
+                    net::Handshake::Accept,
+                ).await;
+                metrics::worker_connected(&addr, false);
+                update_status(&status, |s| s.remote_addr = None);
                 tracing::warn!(?addr, "connection lost, will attempt to reconnect");
+                notify::publish(&bus, Event::WorkerDisconnected { addr: addr.clone() }).await;
             }
             Err(error) => {
                 tracing::error!(?error, ?addr, attempt, "failed to connect");
@@ -89,6 +219,11 @@ async fn connect(addr: String, client: Handle, mut net_rx: mpsc::Receiver<Outgoi
         }
 
         attempt = attempt.saturating_add(1);
+        update_status(&status, |s| {
+            s.state = ConnectionState::Waiting;
+            s.attempt = attempt;
+        });
+
         let delay = if attempt >= MAX_BACKOFF_COUNT {
             MAX_DELAY
         } else {
@@ -96,10 +231,83 @@ async fn connect(addr: String, client: Handle, mut net_rx: mpsc::Receiver<Outgoi
         };
 
         tracing::trace!(?addr, attempt, ?delay, "reconnecting after delay");
-        tokio::time::sleep(delay).await;
+        reject_while(&mut net_rx, tokio::time::sleep(delay)).await;
     }
 }
 
+/// Fail the messages sent to the worker node until `future` completes.
+///
+/// Used while the client is not connected so that requests fail with [`Error::Disconnected`] like
+/// they do on the server, instead of waiting until the client reconnects.
+///
+/// # Args
+///
+/// `net_rx`:  Receiving end of the channel used to send messages to the worker node.
+///
+/// `future`:  The future to wait for.
+async fn reject_while<F: Future>(
+    net_rx: &mut mpsc::Receiver<OutgoingMessage>,
+    future: F,
+) -> F::Output {
+    tokio::pin!(future);
+    loop {
+        tokio::select! {
+            output = &mut future => return output,
+            Some(msg) = net_rx.recv() => {
+                tracing::trace!("attempted to send message when disconnected");
+                let _ = msg.response.send(Err(Error::Disconnected))
+                    .inspect_err(|_| tracing::error!("failed to send response"));
+            },
+        }
+    }
+}
+
+/// Returns the messages sent to the worker node each time the client connects.
+///
+/// The worker node is told how its media folders are shared. If they are shared, the sentinel
+/// files are written, if they have not been already, and the worker node is asked to read them
+/// back (see [`sentinel`]).
+///
+/// # Args
+///
+/// `addr`:  The address of the worker node.
+///
+/// `storage`:  How the worker node's media folders are shared with the control node.
+async fn greetings(addr: &str, storage: StorageMode) -> Vec<protocol::Message> {
+    let mut greetings = vec![protocol::Message::StorageMode { mode: storage }];
+    if storage == StorageMode::Transfer {
+        sentinel::set_status(addr, StorageCheck::NotShared);
+        return greetings;
+    }
+
+    let result = task::spawn_blocking(sentinel::write).await
+        .unwrap_or_else(|error| Err(error.into()));
+    match result {
+        Ok(locations) => {
+            sentinel::set_status(addr, StorageCheck::Pending);
+            greetings.push(protocol::Message::VerifyStorage { locations });
+        },
+        Err(error) => {
+            tracing::error!(?addr, ?error, "failed to write sentinel files");
+            let problems = vec![format!("The sentinel files cannot be written: {:?}", error)];
+            sentinel::set_status(addr, StorageCheck::Mismatch { problems });
+        },
+    }
+    greetings
+}
+
+/// Update the shared status of a client's connection.
+///
+/// # Args
+///
+/// `status`:  The status to update.
+///
+/// `f`:  Function that modifies the status.
+fn update_status(status: &SharedStatus, f: impl FnOnce(&mut WorkerStatus)) {
+    let mut status = status.lock().expect("worker status lock should not be poisoned");
+    f(&mut status);
+}
+
 
 #[cfg(test)]
 mod tests {
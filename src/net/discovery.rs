@@ -0,0 +1,409 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Discovers worker nodes on the local network.
+//!
+//! When discovery is enabled, worker nodes periodically broadcast a UDP beacon containing their
+//! hostname, the port they listen on and the protocol version (see [`announce`]). The control node
+//! listens for the beacons and forwards them to the client manager (see [`listen`]), which keeps
+//! the list of discovered workers that have not been added yet (see
+//! [`crate::net::list_discovered`]). A discovered worker is adopted by adding it like any other
+//! worker (see [`crate::net::add_worker`]).
+//!
+//! Discovery is disabled by default. It is enabled on both the control and worker nodes with:
+//!
+//! ```toml
+//! [net.discovery]
+//! enabled = true
+//! ```
+//!
+//! Beacons are sent to the broadcast address by default. Multiple worker instances can be tested
+//! on a single machine by setting the broadcast address to `127.0.0.1`.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::net::UdpSocket;
+
+use crate::Result;
+use crate::net::{Handle, ManagerRequest, Message};
+use crate::net::protocol;
+use crate::task;
+
+/// Identifies a datagram as a beacon sent by this application.
+const SERVICE: &str = "artie";
+
+/// The maximum size of a beacon.
+const MAX_BEACON_SIZE: usize = 1024;
+
+/// The number of beacon intervals after which a worker that stopped announcing itself is no
+/// longer listed.
+const EXPIRY_INTERVALS: u32 = 3;
+
+/// Worker discovery settings.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Settings {
+    /// Whether worker nodes announce themselves and the control node listens for them.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The UDP port the beacons are sent to and received on.
+    #[serde(default = "Settings::default_port")]
+    pub port: u16,
+
+    /// The address the beacons are sent to.
+    ///
+    /// Only valid for worker node instances of the application.
+    #[serde(default = "Settings::default_broadcast_addr")]
+    pub broadcast_addr: String,
+
+    /// The number of seconds between beacons.
+    ///
+    /// Only valid for worker node instances of the application.
+    #[serde(default = "Settings::default_interval")]
+    pub interval: u64,
+}
+
+impl Settings {
+    /// The port if one is not specified in the config.
+    fn default_port() -> u16 {
+        7879
+    }
+
+    /// The broadcast address if one is not specified in the config.
+    fn default_broadcast_addr() -> String {
+        String::from("255.255.255.255")
+    }
+
+    /// The beacon interval if one is not specified in the config.
+    fn default_interval() -> u64 {
+        5
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: Self::default_port(),
+            broadcast_addr: Self::default_broadcast_addr(),
+            interval: Self::default_interval(),
+        }
+    }
+}
+
+/// The beacon a worker node broadcasts to announce itself.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Beacon {
+    /// Always [`SERVICE`]. Used to ignore datagrams sent by other applications.
+    service: String,
+
+    /// The protocol version of the worker node (see [`protocol::VERSION`]).
+    pub version: u32,
+
+    /// The hostname of the worker node.
+    pub hostname: String,
+
+    /// The port the worker node listens on.
+    pub port: u16,
+
+    /// The number of seconds until the next beacon.
+    pub interval: u64,
+}
+
+impl Beacon {
+    /// Create the beacon for this worker node.
+    ///
+    /// # Args
+    ///
+    /// `hostname`:  The hostname of the worker node.
+    ///
+    /// `port`:  The port the worker node listens on.
+    ///
+    /// `interval`:  The number of seconds between beacons.
+    pub fn new(hostname: &str, port: u16, interval: u64) -> Self {
+        Self {
+            service: SERVICE.to_owned(),
+            version: protocol::VERSION,
+            hostname: hostname.to_owned(),
+            port,
+            interval,
+        }
+    }
+
+    /// Parse a datagram received from the network.
+    ///
+    /// Returns `None` if the datagram is not a beacon.
+    ///
+    /// # Args
+    ///
+    /// `bytes`:  The datagram's payload.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice::<Self>(bytes)
+            .ok()
+            .filter(|beacon| beacon.service == SERVICE)
+    }
+
+    /// Serializes the beacon as JSON.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::SerdeJson`] if the beacon cannot be serialized.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+}
+
+/// A worker node found on the local network.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiscoveredWorker {
+    /// The address and port used to connect to the worker node.
+    pub addr: String,
+
+    /// The hostname of the worker node.
+    pub hostname: String,
+
+    /// The protocol version of the worker node.
+    pub version: u32,
+
+    /// When the last beacon was received from the worker node.
+    pub last_seen: DateTime<Utc>,
+
+    /// When the worker node is no longer listed if another beacon is not received.
+    pub expires: DateTime<Utc>,
+}
+
+impl DiscoveredWorker {
+    /// Create a discovered worker from a received beacon.
+    ///
+    /// # Args
+    ///
+    /// `beacon`:  The beacon received.
+    ///
+    /// `sender`:  The address the beacon was sent from.
+    pub fn new(beacon: Beacon, sender: SocketAddr) -> Self {
+        let last_seen = Utc::now();
+        let expiry = Duration::from_secs(beacon.interval.max(1)) * EXPIRY_INTERVALS;
+        Self {
+            addr: SocketAddr::new(sender.ip(), beacon.port).to_string(),
+            hostname: beacon.hostname,
+            version: beacon.version,
+            last_seen,
+            expires: last_seen + expiry,
+        }
+    }
+
+    /// Whether the worker node uses the same protocol version as this node.
+    pub fn is_compatible(&self) -> bool {
+        self.version == protocol::VERSION
+    }
+}
+
+/// Start announcing this worker node on the local network.
+///
+/// Does nothing if discovery is disabled.
+///
+/// # Args
+///
+/// `settings`:  Settings containing the port the worker node listens on and the discovery
+/// settings.
+pub fn announce(settings: &crate::net::Settings) {
+    let discovery = settings.discovery.clone();
+    if !discovery.enabled {
+        return;
+    }
+
+    let is_loopback = |addr: &str| addr == "localhost"
+        || addr.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback());
+    if is_loopback(&settings.listen_addr) && !is_loopback(&discovery.broadcast_addr) {
+        tracing::warn!(
+            listen_addr = settings.listen_addr,
+            "announcing a worker that only accepts local connections"
+        );
+    }
+
+    let hostname = gethostname::gethostname()
+        .to_string_lossy()
+        .into_owned();
+    let beacon = Beacon::new(&hostname, settings.listen_port, discovery.interval);
+    task::spawn(async move {
+        broadcast(&discovery, beacon).await;
+    });
+}
+
+/// Start listening for worker nodes announcing themselves on the local network.
+///
+/// Does nothing if discovery is disabled.
+///
+/// # Args
+///
+/// `settings`:  The discovery settings.
+///
+/// `manager`:  Handle used to send the discovered workers to the client manager.
+pub fn listen(settings: &Settings, manager: Handle) {
+    if !settings.enabled {
+        return;
+    }
+
+    let addr = format!("0.0.0.0:{}", settings.port);
+    task::spawn(async move {
+        let socket = match UdpSocket::bind(&addr).await {
+            Ok(socket) => socket,
+            Err(error) => {
+                tracing::error!(?error, ?addr, "failed to listen for worker beacons");
+                return;
+            }
+        };
+
+        tracing::info!(?addr, "listening for worker beacons");
+        loop {
+            let worker = match receive(&socket).await {
+                Ok(Some(worker)) => worker,
+                Ok(None) => continue,
+                Err(error) => {
+                    tracing::error!(?error, "failed to receive worker beacon");
+                    continue;
+                }
+            };
+
+            let request = ManagerRequest::Discovered { worker };
+            if let Err(error) = manager.send(Message::Manager(request)).await {
+                tracing::error!(?error, "failed to send discovered worker to client manager");
+                break;
+            }
+        }
+    });
+}
+
+/// Broadcast the beacon until the application exits.
+///
+/// # Args
+///
+/// `settings`:  The discovery settings.
+///
+/// `beacon`:  The beacon to broadcast.
+async fn broadcast(settings: &Settings, beacon: Beacon) {
+    let socket = match UdpSocket::bind("0.0.0.0:0").await {
+        Ok(socket) => socket,
+        Err(error) => {
+            tracing::error!(?error, "failed to create socket for worker beacon");
+            return;
+        }
+    };
+    if let Err(error) = socket.set_broadcast(true) {
+        tracing::error!(?error, "failed to enable broadcast for worker beacon");
+        return;
+    }
+
+    let target = format!("{}:{}", settings.broadcast_addr, settings.port);
+    let interval = Duration::from_secs(settings.interval.max(1));
+    tracing::info!(?target, ?interval, "announcing worker");
+
+    loop {
+        if let Err(error) = send(&socket, &target, &beacon).await {
+            tracing::warn!(?error, ?target, "failed to send worker beacon");
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Send a beacon.
+///
+/// # Args
+///
+/// `socket`:  The socket the beacon is sent from.
+///
+/// `target`:  The address and port the beacon is sent to.
+///
+/// `beacon`:  The beacon to send.
+///
+/// # Errors
+///
+/// [`crate::Error::SerdeJson`] if the beacon cannot be serialized.
+///
+/// [`crate::Error::StdIo`] if the beacon cannot be sent.
+async fn send(socket: &UdpSocket, target: &str, beacon: &Beacon) -> Result<()> {
+    socket.send_to(&beacon.serialize()?, target).await?;
+    Ok(())
+}
+
+/// Receive a beacon.
+///
+/// Returns `None` if the datagram received is not a beacon.
+///
+/// # Args
+///
+/// `socket`:  The socket the beacon is received on.
+///
+/// # Errors
+///
+/// [`crate::Error::StdIo`] if the datagram cannot be received.
+async fn receive(socket: &UdpSocket) -> Result<Option<DiscoveredWorker>> {
+    let mut bytes = [0; MAX_BEACON_SIZE];
+    let (len, sender) = socket.recv_from(&mut bytes).await?;
+
+    let Some(beacon) = Beacon::parse(&bytes[..len]) else {
+        tracing::trace!(?sender, "ignoring datagram that is not a worker beacon");
+        return Ok(None);
+    };
+
+    tracing::trace!(?sender, ?beacon, "received worker beacon");
+    Ok(Some(DiscoveredWorker::new(beacon, sender)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beacon() {
+        let beacon = Beacon::new("worker-1", 7878, 5);
+        let bytes = beacon.serialize().unwrap();
+        assert_eq!(Beacon::parse(&bytes), Some(beacon));
+
+        assert_eq!(Beacon::parse(b"not a beacon"), None);
+        let other = br#"{"service":"other","version":1,"hostname":"a","port":1,"interval":5}"#;
+        assert_eq!(Beacon::parse(other), None);
+    }
+
+    #[test]
+    fn test_discovered_worker() {
+        let mut beacon = Beacon::new("worker-1", 7878, 5);
+        let worker = DiscoveredWorker::new(beacon.clone(), "192.168.1.20:50123".parse().unwrap());
+        assert_eq!(worker.addr, "192.168.1.20:7878");
+        assert_eq!(worker.hostname, "worker-1");
+        assert_eq!(worker.expires - worker.last_seen, chrono::Duration::seconds(15));
+        assert!(worker.is_compatible());
+
+        beacon.version += 1;
+        let worker = DiscoveredWorker::new(beacon, "[::1]:50123".parse().unwrap());
+        assert_eq!(worker.addr, "[::1]:7878");
+        assert!(!worker.is_compatible());
+    }
+
+    #[test]
+    fn test_loopback() {
+        task::block_on(async {
+            let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let target = receiver.local_addr().unwrap().to_string();
+
+            // Multiple worker instances on the same machine are told apart by their ports.
+            for port in [7001, 7002] {
+                let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+                let beacon = Beacon::new(&format!("worker-{}", port), port, 5);
+                send(&socket, &target, &beacon).await.unwrap();
+            }
+            let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            socket.send_to(b"noise", &target).await.unwrap();
+
+            let first = receive(&receiver).await.unwrap().unwrap();
+            let second = receive(&receiver).await.unwrap().unwrap();
+            assert_eq!(first.addr, "127.0.0.1:7001");
+            assert_eq!(first.hostname, "worker-7001");
+            assert_eq!(second.addr, "127.0.0.1:7002");
+            assert!(receive(&receiver).await.unwrap().is_none());
+        });
+    }
+}
@@ -0,0 +1,339 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Encodes the messages sent between the control and worker nodes.
+//!
+//! Nodes originally sent each message as a line of JSON. The MakeMKV results carry the full logs
+//! of the command, so a single line can be several megabytes and there was no limit on how much a
+//! node would read while looking for the end of the line.
+//!
+//! Messages are now sent as length-prefixed frames. Each frame starts with a header:
+//!
+//! | Field      | Size    | Description                                                        |
+//! |------------|---------|--------------------------------------------------------------------|
+//! | `length`   | 4 bytes | The number of bytes following this field.                          |
+//! | `flags`    | 1 byte  | [`COMPRESSED`] if the payload is compressed using [`compress`].    |
+//! | `id`       | 4 bytes | Identifies the message on the connection. Starts at 1.             |
+//! | `reply_to` | 4 bytes | The `id` of the message this is a response to, or 0.               |
+//!
+//! followed by the JSON representation of the message. All integers are big-endian. Large
+//! payloads are compressed if both nodes support it, except for file chunks which are mostly video
+//! that is already compressed.
+//!
+//! A worker node offers frames in a [`protocol::Message::Hello`] sent as a line as soon as the
+//! control node connects, and the control node replies with the options both nodes support. Both
+//! nodes then switch to frames.
+//!
+//! The control node does not send anything until the worker node does, since a worker node running
+//! an older version fails on any message it does not know. If the first message from the worker
+//! node is not a hello, or it does not send anything within a few seconds, the control node keeps
+//! using lines and only sends the messages older versions know (see
+//! [`protocol::Message::is_legacy`]).
+//!
+//! Compatibility is only one way. A control node running an older version fails on the hello and
+//! cannot communicate with an upgraded worker node at all, so the control node must be upgraded
+//! before the worker nodes.
+//!
+//! [`protocol::Message::Hello`]: crate::net::protocol::Message::Hello
+//! [`protocol::Message::is_legacy`]: crate::net::protocol::Message::is_legacy
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+use crate::compress;
+
+/// The largest frame a node accepts unless the other node asks for a smaller limit.
+pub const MAX_FRAME_SIZE: u32 = 64 * 1024 * 1024;
+
+/// Payloads smaller than this are not compressed since it would not save much.
+const COMPRESS_THRESHOLD: usize = 16 * 1024;
+
+/// Flag set when the payload of a frame is compressed.
+pub const COMPRESSED: u8 = 0x01;
+
+/// The size of the fields following the `length` field of the header.
+const HEADER_SIZE: usize = 9;
+
+/// The options for sending frames, agreed to by both nodes when they connect.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FrameOptions {
+    /// The largest frame (not including the `length` field) either node will send or receive.
+    pub max_frame_size: u32,
+
+    /// Whether large payloads are compressed.
+    pub compression: bool,
+}
+
+impl FrameOptions {
+    /// Returns the options supported by both this node and the other node.
+    ///
+    /// # Args
+    ///
+    /// `offer`:  The options offered by the other node.
+    pub fn negotiate(&self, offer: &FrameOptions) -> FrameOptions {
+        FrameOptions {
+            max_frame_size: self.max_frame_size.min(offer.max_frame_size),
+            compression: self.compression && offer.compression,
+        }
+    }
+}
+
+impl Default for FrameOptions {
+    fn default() -> Self {
+        Self {
+            max_frame_size: MAX_FRAME_SIZE,
+            compression: true,
+        }
+    }
+}
+
+/// How messages are encoded on a connection.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Framing {
+    /// Each message is a line of JSON. Used with nodes that do not support frames.
+    ///
+    /// Lines are still limited to [`MAX_FRAME_SIZE`] bytes.
+    Lines,
+
+    /// Each message is a length-prefixed frame.
+    Frames(FrameOptions),
+}
+
+impl Framing {
+    /// The largest message that can be sent or received.
+    fn max_size(&self) -> usize {
+        let max = match self {
+            Framing::Lines => MAX_FRAME_SIZE,
+            Framing::Frames(options) => options.max_frame_size,
+        };
+        usize::try_from(max).unwrap_or(usize::MAX)
+    }
+}
+
+/// A message sent or received on a connection.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Frame {
+    /// Identifies the message on the connection, or 0 if the message was received as a line.
+    pub id: u32,
+
+    /// The `id` of the message this is a response to, or 0 if it is not a response.
+    pub reply_to: u32,
+
+    /// The JSON representation of the message.
+    pub payload: Vec<u8>,
+}
+
+/// Encode a message to be written to a connection.
+///
+/// Lines only contain the payload, so the `id` and `reply_to` fields of the frame are dropped.
+///
+/// # Args
+///
+/// `framing`:  How messages are encoded on the connection.
+///
+/// `frame`:  The message to encode.
+///
+/// # Errors
+///
+/// [`Error::FrameTooLarge`] if the message is larger than the maximum frame size.
+///
+/// [`Error::StdIo`] if the payload cannot be compressed.
+pub fn encode(framing: &Framing, frame: Frame) -> Result<Vec<u8>> {
+    let max = framing.max_size();
+    let Framing::Frames(options) = framing else {
+        if frame.payload.len() > max {
+            return Err(Error::FrameTooLarge { size: frame.payload.len(), max });
+        }
+        let mut bytes = frame.payload;
+        bytes.push(b'\n');
+        return Ok(bytes);
+    };
+
+    let (flags, payload) = if options.compression && frame.payload.len() >= COMPRESS_THRESHOLD {
+        (COMPRESSED, compress::compress(&String::from_utf8(frame.payload)?)?)
+    } else {
+        (0, frame.payload)
+    };
+
+    let size = HEADER_SIZE + payload.len();
+    if size > max {
+        return Err(Error::FrameTooLarge { size, max });
+    }
+
+    let length = u32::try_from(size).map_err(|_| Error::FrameTooLarge { size, max })?;
+    let mut bytes = Vec::with_capacity(4 + size);
+    bytes.extend_from_slice(&length.to_be_bytes());
+    bytes.push(flags);
+    bytes.extend_from_slice(&frame.id.to_be_bytes());
+    bytes.extend_from_slice(&frame.reply_to.to_be_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Decode the next message from the bytes read from a connection.
+///
+/// The bytes of the message are removed from the buffer. Returns `None` if the buffer does not
+/// contain a complete message yet.
+///
+/// # Args
+///
+/// `framing`:  How messages are encoded on the connection.
+///
+/// `buffer`:  The bytes read from the connection that have not been decoded yet.
+///
+/// # Errors
+///
+/// [`Error::FrameTooLarge`] if the message is larger than the maximum frame size.
+///
+/// [`Error::InvalidFrame`] if the header of the frame is not valid.
+///
+/// [`Error::StdIo`] if the payload cannot be decompressed.
+pub fn decode(framing: &Framing, buffer: &mut Vec<u8>) -> Result<Option<Frame>> {
+    let max = framing.max_size();
+    let Framing::Frames(options) = framing else {
+        let Some(end) = buffer.iter().position(|b| *b == b'\n') else {
+            if buffer.len() > max {
+                return Err(Error::FrameTooLarge { size: buffer.len(), max });
+            }
+            return Ok(None);
+        };
+        if end > max {
+            return Err(Error::FrameTooLarge { size: end, max });
+        }
+        let mut payload = buffer.drain(..=end).collect::<Vec<_>>();
+        payload.pop();
+        return Ok(Some(Frame { payload, ..Default::default() }));
+    };
+
+    let Some(length) = buffer.first_chunk::<4>() else {
+        return Ok(None);
+    };
+    let size = usize::try_from(u32::from_be_bytes(*length)).unwrap_or(usize::MAX);
+    if size > max {
+        return Err(Error::FrameTooLarge { size, max });
+    }
+    if size < HEADER_SIZE {
+        return Err(Error::InvalidFrame { reason: format!("frame of {} bytes is too short", size) });
+    }
+    if buffer.len() < 4 + size {
+        return Ok(None);
+    }
+
+    let bytes = buffer.drain(..4 + size).skip(4).collect::<Vec<_>>();
+    let (header, payload) = bytes.split_at(HEADER_SIZE);
+    let flags = header[0];
+    let id = u32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+    let reply_to = u32::from_be_bytes([header[5], header[6], header[7], header[8]]);
+
+    let payload = match flags {
+        0 => payload.to_vec(),
+        COMPRESSED if options.compression => {
+            compress::decompress_max(payload, max)?.into_bytes()
+        },
+        _ => {
+            let reason = format!("unsupported flags {:#04x}", flags);
+            return Err(Error::InvalidFrame { reason });
+        },
+    };
+
+    Ok(Some(Frame { id, reply_to, payload }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the options used by the tests.
+    fn options(max_frame_size: u32, compression: bool) -> FrameOptions {
+        FrameOptions { max_frame_size, compression }
+    }
+
+    /// Returns a frame with the payload.
+    fn frame(id: u32, reply_to: u32, payload: &str) -> Frame {
+        Frame { id, reply_to, payload: payload.as_bytes().to_vec() }
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let ours = options(1024, true);
+        assert_eq!(ours.negotiate(&options(2048, false)), options(1024, false));
+        assert_eq!(ours.negotiate(&options(512, true)), options(512, true));
+    }
+
+    #[test]
+    fn test_lines() {
+        let framing = Framing::Lines;
+        let mut buffer = encode(&framing, frame(1, 0, r#"{"type":"A"}"#)).unwrap();
+        assert_eq!(buffer, b"{\"type\":\"A\"}\n");
+
+        buffer.extend_from_slice(b"{\"type\":");
+        let decoded = decode(&framing, &mut buffer).unwrap();
+        assert_eq!(decoded, Some(frame(0, 0, r#"{"type":"A"}"#)));
+        assert_eq!(decode(&framing, &mut buffer).unwrap(), None);
+        buffer.extend_from_slice(b"\"B\"}\n");
+        let decoded = decode(&framing, &mut buffer).unwrap();
+        assert_eq!(decoded, Some(frame(0, 0, r#"{"type":"B"}"#)));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_frames() {
+        let framing = Framing::Frames(options(1024, true));
+        let mut buffer = encode(&framing, frame(7, 3, "first")).unwrap();
+        assert_eq!(&buffer[..4], &14u32.to_be_bytes());
+        assert_eq!(buffer[4], 0);
+        buffer.extend(encode(&framing, frame(8, 0, "second")).unwrap());
+
+        // Only part of the next frame has been read.
+        let mut rest = buffer.split_off(buffer.len() - 3);
+        assert_eq!(decode(&framing, &mut buffer).unwrap(), Some(frame(7, 3, "first")));
+        assert_eq!(decode(&framing, &mut buffer).unwrap(), None);
+        buffer.append(&mut rest);
+        assert_eq!(decode(&framing, &mut buffer).unwrap(), Some(frame(8, 0, "second")));
+        assert_eq!(decode(&framing, &mut buffer).unwrap(), None);
+    }
+
+    #[test]
+    fn test_compression() {
+        let log = "MSG:5010,0,0,\"Failed to open disc\"\n".repeat(1000);
+        let payload = serde_json::to_string(&log).unwrap();
+
+        let framing = Framing::Frames(options(MAX_FRAME_SIZE, true));
+        let mut buffer = encode(&framing, frame(1, 0, &payload)).unwrap();
+        assert_eq!(buffer[4], COMPRESSED);
+        assert!(buffer.len() < payload.len() / 10);
+        assert_eq!(decode(&framing, &mut buffer).unwrap(), Some(frame(1, 0, &payload)));
+
+        let framing = Framing::Frames(options(MAX_FRAME_SIZE, false));
+        let buffer = encode(&framing, frame(1, 0, &payload)).unwrap();
+        assert_eq!(buffer[4], 0);
+        assert_eq!(buffer.len(), 4 + HEADER_SIZE + payload.len());
+    }
+
+    #[test]
+    fn test_max_frame_size() {
+        let framing = Framing::Frames(options(16, false));
+        let result = encode(&framing, frame(1, 0, "a payload too big"));
+        assert!(matches!(result, Err(Error::FrameTooLarge { size: 26, max: 16 })));
+
+        // The length is checked before the rest of the frame is read.
+        let mut buffer = 1000u32.to_be_bytes().to_vec();
+        let result = decode(&framing, &mut buffer);
+        assert!(matches!(result, Err(Error::FrameTooLarge { size: 1000, max: 16 })));
+
+        let mut buffer = 4u32.to_be_bytes().to_vec();
+        assert!(matches!(decode(&framing, &mut buffer), Err(Error::InvalidFrame { .. })));
+
+        let mut buffer = vec![b'a'; MAX_FRAME_SIZE as usize + 1];
+        let result = decode(&Framing::Lines, &mut buffer);
+        assert!(matches!(result, Err(Error::FrameTooLarge { .. })));
+    }
+
+    #[test]
+    fn test_unsupported_flags() {
+        let framing = Framing::Frames(options(1024, false));
+        let mut buffer = encode(&framing, frame(1, 0, "payload")).unwrap();
+        buffer[4] = COMPRESSED;
+        assert!(matches!(decode(&framing, &mut buffer), Err(Error::InvalidFrame { .. })));
+    }
+}
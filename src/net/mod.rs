@@ -22,6 +22,34 @@
 //! - [`send_run_makemkv_copy`]
 //! - [`send_run_makemkv_info`]
 //!
+//! These requests are acknowledged by the worker node, so they resolve once the worker node has
+//! accepted or rejected them (see [`protocol::Message::requires_ack`]).
+//!
+//! # Client Manager Requests
+//!
+//! The following helper methods can be used to manage the worker nodes the control node is
+//! connected to.
+//!
+//! - [`add_worker`]
+//! - [`list_discovered`]
+//! - [`list_workers`]
+//! - [`remove_worker`]
+//!
+//! Messages are sent as length-prefixed frames, or as lines of JSON to nodes running an older
+//! version (see [`frame`]).
+//!
+//! Worker nodes can also be found on the local network (see [`discovery`]). The media folders of
+//! worker nodes using shared storage are checked when the control node connects (see
+//! [`sentinel`]).
+//!
+//! # File Transfers
+//!
+//! The following helper methods can be used to transfer files between the control node and a
+//! worker node that does not share its media folders with the control node (see [`transfer`]).
+//!
+//! - [`pull_files`]
+//! - [`push_files`]
+//!
 //! # Worker to Control Requests
 //!
 //! The following helper methods can be used to send messages from a worker node to the control
@@ -35,16 +63,25 @@
 
 mod actor;
 pub mod client;
+pub mod discovery;
+pub mod frame;
 pub mod protocol;
+pub mod sentinel;
 pub mod server;
+pub mod transfer;
+
+use std::collections::HashMap;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tokio::net::tcp::OwnedWriteHalf;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
 
 use makemkv::{CopyCommandOutput, InfoCommandOutput};
 
@@ -53,6 +90,21 @@ use crate::actor::Response;
 use crate::bus;
 use crate::drive::OsOpticalDrive;
 use crate::models::MediaLocation;
+use crate::net::frame::{Frame, FrameOptions, Framing};
+use crate::net::sentinel::StorageCheck;
+use crate::net::transfer::StorageMode;
+use crate::task;
+
+/// How long a node waits for the hello from the other node before assuming it is an older version
+/// that only knows the original line protocol.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for the other node to acknowledge a request (see
+/// [`protocol::Message::requires_ack`]).
+const ACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often requests waiting to be acknowledged are checked for [`ACK_TIMEOUT`].
+const ACK_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Handle used to communicate with the client or server actor.
 pub type Handle = crate::actor::Handle<Message>;
@@ -63,6 +115,12 @@ pub type Handle = crate::actor::Handle<Message>;
 pub enum Message {
     Incoming(IncomingMessage),
     Outgoing(OutgoingMessage),
+
+    /// A request for the client manager (see [`client::manager`]).
+    Manager(ManagerRequest),
+
+    /// A request to transfer files to or from another node.
+    Transfer(TransferRequest),
 }
 
 impl Message {
@@ -76,6 +134,7 @@ impl Message {
         let msg = OutgoingMessage {
             msg,
             worker: None,
+            reply_to: 0,
             response: tx,
         };
         (Message::Outgoing(msg), rx)
@@ -93,6 +152,7 @@ impl Message {
         let msg = OutgoingMessage {
             msg,
             worker: Some(worker.to_owned()),
+            reply_to: 0,
             response: tx,
         };
         (Message::Outgoing(msg), rx)
@@ -107,6 +167,13 @@ pub struct IncomingMessage {
 
     /// The IP address of the application instance that sent the request.
     sender: String,
+
+    /// Transmission end of the channel to send the reply to a message that must be acknowledged
+    /// (see [`protocol::Message::requires_ack`]). `None` if the message does not need a reply or
+    /// was received as a line.
+    ///
+    /// The connection writes the reply, so the actor does not wait for it to be sent.
+    ack: Option<oneshot::Sender<protocol::Message>>,
 }
 
 /// Message for sending requests another node.
@@ -120,20 +187,22 @@ pub struct OutgoingMessage {
     /// `Some` if message is meant for a worker node or `None` if meant for the control node.
     worker: Option<String>,
 
+    /// The ID of the message this is a response to, or 0 if it is not a response.
+    reply_to: u32,
+
     /// Transmission end of the channel to send the response for the request.
     ///
-    /// The response will be `Ok` if the message was sent successfully. If the message cannot be
-    /// sent, the error will either be [`crate::Error::Disconnected`] or
-    /// [`crate::Error::NetworkSend`].
+    /// The response will be `Ok` if the message was sent successfully, or once the other node
+    /// accepts it for messages that must be acknowledged. If the message cannot be sent, the error
+    /// will either be [`crate::Error::Disconnected`], [`crate::Error::FrameTooLarge`] or
+    /// [`crate::Error::NetworkSend`]. If the other node does not accept the message, the error
+    /// will be [`crate::Error::RequestRejected`] or [`crate::Error::AckTimeout`].
     response: Response<()>,
 }
 
 impl OutgoingMessage {
     /// Serializes the message as JSON.
     ///
-    /// This will automatically add a newline character to the end of the byte array before
-    /// returning.
-    ///
     /// # Errors
     ///
     /// [`crate::Error::SerdeJson`] if the bytes cannot be serialized.
@@ -142,6 +211,72 @@ impl OutgoingMessage {
     }
 }
 
+/// Whether files are sent to or received from the other node.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransferDirection {
+    /// Send the files to the other node.
+    Push,
+
+    /// Receive the files from the other node.
+    Pull,
+}
+
+/// Message for transferring the files in a folder between nodes.
+#[derive(Debug)]
+pub struct TransferRequest {
+    /// Whether the files are sent to or received from the other node.
+    direction: TransferDirection,
+
+    /// The location of the folder to transfer.
+    location: MediaLocation,
+
+    /// If applicable, the address and port of the worker node to transfer the files with.
+    ///
+    /// `Some` if sent from the control node or `None` if sent from a worker node.
+    worker: Option<String>,
+
+    /// Transmission end of the channel the progress of sending the files is reported to, if any.
+    progress: Option<mpsc::Sender<transfer::Progress>>,
+
+    /// Transmission end of the channel to send the result of the transfer.
+    response: Response<()>,
+}
+
+/// Requests handled by the client manager.
+#[derive(Debug)]
+pub enum ManagerRequest {
+    /// Add a worker node and start connecting to it.
+    ///
+    /// The response is [`crate::Error::WorkerExists`] if a client already exists for the address.
+    AddWorker {
+        addr: String,
+        response: Response<()>,
+    },
+
+    /// A worker node was found on the local network. Sent by the discovery listener.
+    Discovered {
+        worker: discovery::DiscoveredWorker,
+    },
+
+    /// List the worker nodes found on the local network that have not been added.
+    ListDiscovered {
+        response: Response<Vec<discovery::DiscoveredWorker>>,
+    },
+
+    /// List the status of the connection to each worker node.
+    ListWorkers {
+        response: Response<Vec<client::WorkerStatus>>,
+    },
+
+    /// Remove a worker node, closing the connection to it and stopping reconnect attempts.
+    ///
+    /// The response is [`crate::Error::WorkerNotFound`] if there is no client for the address.
+    RemoveWorker {
+        addr: String,
+        response: Response<()>,
+    },
+}
+
 /// Networking application settings.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Settings {
@@ -162,6 +297,17 @@ pub struct Settings {
     /// Only valid for the control node application instance.
     #[serde(default)]
     pub workers: Vec<String>,
+
+    /// Settings for finding worker nodes on the local network.
+    #[serde(default)]
+    pub discovery: discovery::Settings,
+
+    /// How each worker node's media folders are shared with the control node, by address.
+    ///
+    /// Workers that are not listed use [`StorageMode::Shared`]. Only valid for the control node
+    /// application instance.
+    #[serde(default)]
+    pub storage: HashMap<String, StorageMode>,
 }
 
 impl Settings {
@@ -174,6 +320,15 @@ impl Settings {
     fn default_port() -> u16 {
         7878
     }
+
+    /// Returns how a worker node's media folders are shared with the control node.
+    ///
+    /// # Args
+    ///
+    /// `addr`:  The address and port of the worker node.
+    pub fn storage_mode(&self, addr: &str) -> StorageMode {
+        self.storage.get(addr).copied().unwrap_or_default()
+    }
 }
 
 impl Default for Settings {
@@ -181,11 +336,182 @@ impl Default for Settings {
         Self {
             listen_addr: Self::default_addr(),
             listen_port: Self::default_port(),
-            workers: Default::default()
+            workers: Default::default(),
+            discovery: Default::default(),
+            storage: Default::default(),
         }
     }
 }
 
+/// Add a worker node to the client manager.
+///
+/// A client is created for the worker which connects to it in the background, so this does not
+/// wait for the connection to be made. The worker is also added to the config file. Only valid for
+/// the control node.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending the request to the client manager.
+///
+/// `addr`:  The address and port of the worker node.
+///
+/// # Errors
+///
+/// [`crate::Error::WorkerExists`] if the worker was already added.
+///
+/// [`crate::Error::InvalidNetRequest`] if the application is running as a worker node.
+pub async fn add_worker(bus: &bus::Handle, addr: &str) -> Result<()> {
+    let (tx, rx) = oneshot::channel();
+    let request = ManagerRequest::AddWorker {
+        addr: addr.to_owned(),
+        response: tx,
+    };
+    bus.send(Message::Manager(request)).await?;
+    rx.await?
+}
+
+/// List the worker nodes found on the local network that have not been added yet.
+///
+/// The list is empty if discovery is disabled. Only valid for the control node.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending the request to the client manager.
+///
+/// # Errors
+///
+/// [`crate::Error::InvalidNetRequest`] if the application is running as a worker node.
+pub async fn list_discovered(bus: &bus::Handle) -> Result<Vec<discovery::DiscoveredWorker>> {
+    let (tx, rx) = oneshot::channel();
+    bus.send(Message::Manager(ManagerRequest::ListDiscovered { response: tx })).await?;
+    rx.await?
+}
+
+/// List the status of the connection to each worker node.
+///
+/// Only valid for the control node.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending the request to the client manager.
+///
+/// # Errors
+///
+/// [`crate::Error::InvalidNetRequest`] if the application is running as a worker node.
+pub async fn list_workers(bus: &bus::Handle) -> Result<Vec<client::WorkerStatus>> {
+    let (tx, rx) = oneshot::channel();
+    bus.send(Message::Manager(ManagerRequest::ListWorkers { response: tx })).await?;
+    rx.await?
+}
+
+/// Remove a worker node from the client manager.
+///
+/// The connection to the worker is closed and no further attempts are made to connect to it. The
+/// worker is also removed from the config file. Only valid for the control node.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending the request to the client manager.
+///
+/// `addr`:  The address and port of the worker node.
+///
+/// # Errors
+///
+/// [`crate::Error::WorkerNotFound`] if the worker was not added.
+///
+/// [`crate::Error::InvalidNetRequest`] if the application is running as a worker node.
+pub async fn remove_worker(bus: &bus::Handle, addr: &str) -> Result<()> {
+    let (tx, rx) = oneshot::channel();
+    let request = ManagerRequest::RemoveWorker {
+        addr: addr.to_owned(),
+        response: tx,
+    };
+    bus.send(Message::Manager(request)).await?;
+    rx.await?
+}
+
+/// Receive the files in a folder from the other node.
+///
+/// Files that were already received are skipped and partially received files are resumed. This
+/// does nothing if the worker node shares its media folders with the control node.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending the request to the network actor.
+///
+/// `worker`:  The worker node to receive the files from. Must be `None` on a worker node.
+///
+/// `location`:  The location of the folder to receive.
+///
+/// # Errors
+///
+/// [`crate::Error::WorkerNone`] or [`crate::Error::WorkerNotFound`] if the worker node is not
+/// specified or was not added on the control node.
+///
+/// [`crate::Error::TransferFailed`] or [`crate::Error::TransferTimeout`] if the files could not
+/// be received.
+pub async fn pull_files(
+    bus: &bus::Handle,
+    worker: Option<&str>,
+    location: MediaLocation,
+) -> Result<()> {
+    transfer_files(bus, TransferDirection::Pull, worker, location, None).await
+}
+
+/// Send the files in a folder to the other node.
+///
+/// The files are offered again if the transfer is interrupted (e.g. the connection is lost). This
+/// does nothing if the worker node shares its media folders with the control node.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending the request to the network actor.
+///
+/// `worker`:  The worker node to send the files to. Must be `None` on a worker node.
+///
+/// `location`:  The location of the folder to send.
+///
+/// `progress`:  Transmission end of the channel the progress is reported to, if any. Updates are
+/// skipped while the previous one has not been received.
+///
+/// # Errors
+///
+/// [`crate::Error::WorkerNone`] or [`crate::Error::WorkerNotFound`] if the worker node is not
+/// specified or was not added on the control node.
+///
+/// [`crate::Error::InvalidMediaLocation`] if the folder is not in one of the media folders.
+///
+/// [`crate::Error::TransferFailed`], [`crate::Error::TransferTimeout`] or
+/// [`crate::Error::Disconnected`] if the files could not be sent.
+pub async fn push_files(
+    bus: &bus::Handle,
+    worker: Option<&str>,
+    location: MediaLocation,
+    progress: Option<mpsc::Sender<transfer::Progress>>,
+) -> Result<()> {
+    transfer_files(bus, TransferDirection::Push, worker, location, progress).await
+}
+
+/// Send a request to transfer files to the network actor and wait for the result.
+async fn transfer_files(
+    bus: &bus::Handle,
+    direction: TransferDirection,
+    worker: Option<&str>,
+    location: MediaLocation,
+    progress: Option<mpsc::Sender<transfer::Progress>>,
+) -> Result<()> {
+    let (tx, rx) = oneshot::channel();
+    let request = TransferRequest {
+        direction,
+        location,
+        worker: worker.map(str::to_owned),
+        progress,
+        response: tx,
+    };
+    bus.send(Message::Transfer(request)).await?;
+    rx.await?
+}
+
 /// Send a request to a worker node to cancel a running MakeMKV operation.
 ///
 /// # Args
@@ -195,6 +521,15 @@ impl Default for Settings {
 /// `worker`:  The worker node to send the request to.
 ///
 /// `drive`:  The serial number of the drive to running the MakeMKV command to cancel.
+///
+/// # Errors
+///
+/// [`crate::Error::Disconnected`] if the node is not connected to the worker node.
+///
+/// [`crate::Error::NetworkSend`] if the message could not be sent.
+///
+/// [`crate::Error::RequestRejected`] if the worker node rejects the request or
+/// [`crate::Error::AckTimeout`] if it does not reply in time.
 pub async fn send_cancel_makemkv_op(bus: &bus::Handle, worker: &str, drive: &str) -> Result<()> {
     let msg = protocol::Message::MakeMkvCancel { drive: drive.to_owned() };
     let (msg, rx) = Message::worker(worker, msg);
@@ -240,6 +575,9 @@ pub async fn send_drive_status_update(bus: &bus::Handle, drive: OsOpticalDrive)
 /// [`crate::Error::Disconnected`] if the node is not connected to the control node.
 ///
 /// [`crate::Error::NetworkSend`] if the message could not be sent.
+///
+/// [`crate::Error::RequestRejected`] if the worker node rejects the request (e.g. the drive is
+/// busy) or [`crate::Error::AckTimeout`] if it does not reply in time.
 pub async fn send_run_makemkv_copy(
     bus: &bus::Handle,
     worker: &str,
@@ -273,6 +611,9 @@ pub async fn send_run_makemkv_copy(
 /// [`crate::Error::Disconnected`] if the node is not connected to the control node.
 ///
 /// [`crate::Error::NetworkSend`] if the message could not be sent.
+///
+/// [`crate::Error::RequestRejected`] if the worker node rejects the request (e.g. the drive is
+/// busy) or [`crate::Error::AckTimeout`] if it does not reply in time.
 pub async fn send_run_makemkv_info(
     bus: &bus::Handle,
     worker: &str,
@@ -414,6 +755,140 @@ pub async fn send_makemkv_progress(
     rx.await?
 }
 
+/// How a node starts communicating on a new connection (see [`frame`]).
+///
+/// The handshake is only compatible in one direction. A control node accepting the handshake falls
+/// back to lines when the worker node does not offer frames, but a control node that predates the
+/// handshake fails on the offer. The control node must be upgraded before the worker nodes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Handshake {
+    /// Offer to send frames as soon as the connection is made and wait for the other node to reply
+    /// before sending any other messages. Used by worker nodes.
+    Offer,
+
+    /// Wait for the other node to offer to send frames and reply to it before sending any messages.
+    /// Used by the control node.
+    Accept,
+}
+
+/// The sending half of a connection.
+struct StreamWriter {
+    /// The writer used to send messages thru the TCP socket.
+    writer: OwnedWriteHalf,
+
+    /// How messages are encoded on the connection.
+    framing: Framing,
+
+    /// Whether the other node only knows the original line protocol (see
+    /// [`protocol::Message::is_legacy`]).
+    legacy: bool,
+
+    /// The ID of the next frame sent.
+    next_id: u32,
+}
+
+impl StreamWriter {
+    /// Send a message over the network.
+    ///
+    /// Returns the ID of the frame the message was sent in. Messages that are not worth compressing
+    /// are never compressed (see [`protocol::Message::is_compressible`]).
+    ///
+    /// # Args
+    ///
+    /// `msg`:  The message to send.
+    ///
+    /// `reply_to`:  The ID of the message this is a response to, or 0.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::SerdeJson`] if the message cannot serialized.
+    ///
+    /// [`Error::FrameTooLarge`] if the message is larger than the maximum frame size.
+    ///
+    /// [`Error::NetworkSend`] if an error occurs while trying to write to the network. The
+    /// connection cannot be used after this error.
+    async fn write(&mut self, msg: &protocol::Message, reply_to: u32) -> Result<u32> {
+        let id = self.next_id;
+        let frame = Frame { id, reply_to, payload: msg.serialize()? };
+        let framing = match self.framing {
+            Framing::Frames(options) if !msg.is_compressible() => {
+                Framing::Frames(FrameOptions { compression: false, ..options })
+            },
+            framing => framing,
+        };
+        let bytes = frame::encode(&framing, frame)?;
+        self.writer.write_all(&bytes).await
+            .map_err(|error| {
+                tracing::error!(?error, "failed to send message");
+                Error::NetworkSend
+            })?;
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        Ok(id)
+    }
+
+    /// Whether the other node acknowledges requests (see [`protocol::Message::requires_ack`]).
+    ///
+    /// Nodes that only send lines do not know about acknowledgements.
+    fn acknowledges(&self) -> bool {
+        matches!(self.framing, Framing::Frames(_))
+    }
+}
+
+/// Requests sent to the other node that are waiting to be acknowledged.
+#[derive(Default)]
+struct PendingAcks {
+    /// When each request was sent and the channel to send its response, by frame ID.
+    requests: HashMap<u32, (Instant, Response<()>)>,
+}
+
+impl PendingAcks {
+    /// Wait for the other node to acknowledge a request.
+    ///
+    /// # Args
+    ///
+    /// `id`:  The ID of the frame the request was sent in.
+    ///
+    /// `response`:  Transmission end of the channel to send the response for the request.
+    fn insert(&mut self, id: u32, response: Response<()>) {
+        self.requests.insert(id, (Instant::now(), response));
+    }
+
+    /// Send the response for a request acknowledged by the other node.
+    ///
+    /// # Args
+    ///
+    /// `reply_to`:  The ID of the frame the request was sent in.
+    ///
+    /// `result`:  `Ok` if the request was accepted or [`Error::RequestRejected`] if not.
+    fn resolve(&mut self, reply_to: u32, result: Result<()>) {
+        match self.requests.remove(&reply_to) {
+            Some((_, response)) => respond(response, result),
+            None => tracing::warn!(reply_to, "acknowledgement for unknown request"),
+        }
+    }
+
+    /// Fail the requests that have not been acknowledged within [`ACK_TIMEOUT`].
+    fn expire(&mut self) {
+        let expired = self.requests.iter()
+            .filter(|(_, (sent, _))| sent.elapsed() >= ACK_TIMEOUT)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in expired {
+            tracing::warn!(id, "request was not acknowledged");
+            if let Some((_, response)) = self.requests.remove(&id) {
+                respond(response, Err(Error::AckTimeout));
+            }
+        }
+    }
+
+    /// Fail the requests that are still waiting once the connection is closed.
+    fn disconnect(&mut self) {
+        for (_, (_, response)) in self.requests.drain() {
+            respond(response, Err(Error::Disconnected));
+        }
+    }
+}
+
 /// Process communication from a network connection.
 ///
 /// This will run until the connection is dropped or the actor closes the channel it uses to send
@@ -430,43 +905,144 @@ pub async fn send_makemkv_progress(
 ///
 /// `net_rx`:  Receiving end of the channel used by the client or server actor to send messages to
 /// the connected control node.
+///
+/// `on_receive`:  Called each time a message is received.
+///
+/// `greetings`:  Messages sent as soon as the connection is made, before any queued messages.
+///
+/// `handshake`:  Whether this node offers to send frames or replies to the offer.
 async fn process_stream(
     stream: TcpStream,
     peer_addr: &str,
     actor: &Handle,
     net_rx: &mut mpsc::Receiver<OutgoingMessage>,
+    on_receive: impl Fn(),
+    greetings: Vec<protocol::Message>,
+    handshake: Handshake,
 ) {
-    let (reader, mut writer) = stream.into_split();
+    let (mut reader, writer) = stream.into_split();
+    let mut writer = StreamWriter { writer, framing: Framing::Lines, legacy: false, next_id: 1 };
+    let mut greetings = Some(greetings);
+    let mut acks = PendingAcks::default();
+    let mut replies = JoinSet::new();
+
+    // The control node waits for the worker node to speak first since an older worker node fails on
+    // any message it does not know, including the hello.
+    if handshake == Handshake::Offer {
+        let hello = protocol::Message::Hello {
+            version: protocol::VERSION,
+            frames: Some(FrameOptions::default()),
+        };
+        if let Err(error) = writer.write(&hello, 0).await {
+            tracing::error!(?peer_addr, ?error, "failed to send hello");
+            return;
+        }
+    }
+
+    let hello_timeout = tokio::time::sleep(HELLO_TIMEOUT);
+    tokio::pin!(hello_timeout);
+    let mut ack_check = tokio::time::interval(ACK_CHECK_INTERVAL);
+    let mut buffer = Vec::new();
+
+    'connection: loop {
+        loop {
+            let frame = match frame::decode(&writer.framing, &mut buffer) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => break,
+                Err(error) => {
+                    tracing::error!(?peer_addr, ?error, "failed to read message");
+                    break 'connection;
+                },
+            };
+            on_receive();
 
-    let mut reader = BufReader::new(reader);
-    let mut bytes = Vec::new();
+            let msg = match protocol::Message::parse(&frame.payload) {
+                Ok(msg) => msg,
+                Err(error) => {
+                    // The other node may be running a newer version with messages this node does
+                    // not know about.
+                    tracing::warn!(?peer_addr, ?error, id=frame.id, "ignoring unknown message");
+                    continue;
+                },
+            };
+            tracing::trace!(?peer_addr, id=frame.id, reply_to=frame.reply_to, "received message");
+
+            let is_hello = matches!(msg, protocol::Message::Hello { .. });
+            if !is_hello && handshake == Handshake::Accept && greetings.is_some() {
+                tracing::info!(?peer_addr, "no hello from peer, using the line protocol");
+                if use_line_protocol(&mut writer, &mut greetings, peer_addr).await.is_err() {
+                    break 'connection;
+                }
+            }
+
+            match msg {
+                protocol::Message::Hello { version, frames } => {
+                    let hello = Hello { id: frame.id, version, frames };
+                    let result = process_hello(
+                        &mut writer,
+                        hello,
+                        handshake,
+                        &mut greetings,
+                        peer_addr,
+                    ).await;
+                    if result.is_err() {
+                        break 'connection;
+                    }
+                },
+                protocol::Message::Ack => acks.resolve(frame.reply_to, Ok(())),
+                protocol::Message::Nack { code, error } => {
+                    acks.resolve(frame.reply_to, Err(Error::RequestRejected { code, error }));
+                },
+                msg => {
+                    let ack = (msg.requires_ack() && frame.id != 0)
+                        .then(|| wait_for_reply(&mut replies, frame.id));
+                    let incoming = msg.incoming_message(peer_addr, ack);
+                    if let Err(error) = actor.send(incoming).await {
+                        tracing::error!(?peer_addr, ?error, "failed to process message");
+                        break 'connection;
+                    }
+                },
+            }
+        }
 
-    loop {
+        // Messages are only sent after the reply to the hello so that the other node knows how
+        // they are encoded.
+        let waiting = greetings.is_some();
         tokio::select! {
-            result = reader.read_until(b'\n', &mut bytes) => {
+            result = reader.read_buf(&mut buffer) => {
                 match result {
                     Ok(0) => {
                         tracing::info!(?peer_addr, "connection close by remote");
                         break;
                     },
-                    Ok(_) => {
-                        let msg = protocol::Message::parse(&bytes).unwrap();
-                        if let Err(error) = actor.send(msg.incoming_message(peer_addr)).await {
-                            tracing::error!(?peer_addr, ?error, "failed to process message");
-                            break;
-                        }
-                        bytes.clear();
-                    },
+                    Ok(_) => {},
                     Err(error) => {
                         tracing::error!(?peer_addr, ?error, "failed to read message");
                         break;
                     },
                 }
             }
-            result = net_rx.recv() => {
+            () = &mut hello_timeout, if waiting => {
+                tracing::info!(?peer_addr, "no hello from peer, using the line protocol");
+                if use_line_protocol(&mut writer, &mut greetings, peer_addr).await.is_err() {
+                    break;
+                }
+            }
+            _ = ack_check.tick(), if !acks.requests.is_empty() => {
+                acks.expire();
+            }
+            Some(Ok((reply_to, reply))) = replies.join_next(), if !replies.is_empty() => {
+                if let Err(error) = writer.write(&reply, reply_to).await {
+                    tracing::error!(?peer_addr, ?error, "failed to send reply");
+                    if matches!(error, Error::NetworkSend) {
+                        break;
+                    }
+                }
+            }
+            result = net_rx.recv(), if !waiting => {
                 match result {
                     Some(msg) => {
-                        if send(msg, peer_addr, &mut writer).await.is_err() {
+                        if send(msg, peer_addr, &mut writer, &mut acks).await.is_err() {
                             break;
                         }
                     },
@@ -479,6 +1055,161 @@ async fn process_stream(
 
         }
     }
+
+    acks.disconnect();
+}
+
+/// Wait for the actor to process a message that must be acknowledged.
+///
+/// Returns the transmission end of the channel the actor sends the reply to. The reply is added to
+/// `replies` along with the ID of the message so the connection can send it without the actor
+/// waiting for the network. The message is rejected if the actor drops the channel.
+///
+/// # Args
+///
+/// `replies`:  The replies waiting to be sent.
+///
+/// `id`:  The ID of the frame the message was received in.
+fn wait_for_reply(
+    replies: &mut JoinSet<(u32, protocol::Message)>,
+    id: u32,
+) -> oneshot::Sender<protocol::Message> {
+    let (tx, rx) = oneshot::channel();
+    task::spawn_in(replies, async move {
+        let reply = rx.await.unwrap_or_else(|_| protocol::Message::Nack {
+            code: protocol::NackCode::Failed,
+            error: String::from("request was not processed"),
+        });
+        (id, reply)
+    });
+    tx
+}
+
+/// A hello received from the other node.
+struct Hello {
+    /// The ID of the frame the hello was received in.
+    id: u32,
+
+    /// The protocol version of the other node.
+    version: u32,
+
+    /// The frame options offered or agreed to by the other node.
+    frames: Option<FrameOptions>,
+}
+
+/// Process a hello received from the other node.
+///
+/// The control node replies to the worker node's offer and switches to frames if both nodes support
+/// them. The worker node switches to the framing agreed to by the control node. Both nodes then
+/// send their greetings.
+///
+/// # Args
+///
+/// `writer`:  The sending half of the connection.
+///
+/// `hello`:  The hello received from the other node.
+///
+/// `handshake`:  Whether this node offered to send frames or replies to the offer.
+///
+/// `greetings`:  The messages to send once the hello is processed, if it has not been yet.
+///
+/// `peer_addr`:  The address of the other node.
+///
+/// # Errors
+///
+/// See [`StreamWriter::write`].
+async fn process_hello(
+    writer: &mut StreamWriter,
+    hello: Hello,
+    handshake: Handshake,
+    greetings: &mut Option<Vec<protocol::Message>>,
+    peer_addr: &str,
+) -> Result<()> {
+    if hello.version != protocol::VERSION {
+        tracing::warn!(?peer_addr, hello.version, "peer is using a different protocol version");
+    }
+
+    if greetings.is_none() {
+        tracing::warn!(?peer_addr, "ignoring unexpected hello");
+        return Ok(());
+    }
+
+    match handshake {
+        Handshake::Offer => {
+            writer.framing = hello.frames.map_or(Framing::Lines, Framing::Frames);
+        },
+        Handshake::Accept => {
+            let frames = hello.frames.map(|offer| FrameOptions::default().negotiate(&offer));
+            let reply = protocol::Message::Hello { version: protocol::VERSION, frames };
+            writer.write(&reply, hello.id).await
+                .inspect_err(|error| {
+                    tracing::error!(?peer_addr, ?error, "failed to reply to hello");
+                })?;
+            writer.framing = frames.map_or(Framing::Lines, Framing::Frames);
+        },
+    }
+
+    tracing::info!(?peer_addr, framing=?writer.framing, "hello received");
+    send_greetings(writer, greetings.take(), peer_addr).await
+}
+
+/// Switch to the original line protocol for a node that did not send a hello.
+///
+/// Only the greetings the other node knows are sent (see [`protocol::Message::is_legacy`]). The
+/// other node cannot check its media folders, so they are left unverified (see [`sentinel`]).
+///
+/// # Args
+///
+/// `writer`:  The sending half of the connection.
+///
+/// `greetings`:  The messages to send.
+///
+/// `peer_addr`:  The address of the other node.
+///
+/// # Errors
+///
+/// See [`StreamWriter::write`].
+async fn use_line_protocol(
+    writer: &mut StreamWriter,
+    greetings: &mut Option<Vec<protocol::Message>>,
+    peer_addr: &str,
+) -> Result<()> {
+    writer.framing = Framing::Lines;
+    writer.legacy = true;
+    send_greetings(writer, greetings.take(), peer_addr).await
+}
+
+/// Send the messages sent as soon as the connection is made.
+///
+/// # Args
+///
+/// `writer`:  The sending half of the connection.
+///
+/// `greetings`:  The messages to send.
+///
+/// `peer_addr`:  The address the messages are being sent to.
+///
+/// # Errors
+///
+/// See [`StreamWriter::write`].
+async fn send_greetings(
+    writer: &mut StreamWriter,
+    greetings: Option<Vec<protocol::Message>>,
+    peer_addr: &str,
+) -> Result<()> {
+    for greeting in greetings.unwrap_or_default() {
+        if writer.legacy && !greeting.is_legacy() {
+            tracing::debug!(?peer_addr, ?greeting, "peer does not support greeting");
+            if matches!(greeting, protocol::Message::VerifyStorage { .. }) {
+                tracing::warn!(?peer_addr, "peer cannot check its media folders, not verified");
+                sentinel::set_status(peer_addr, StorageCheck::Unverified);
+            }
+            continue;
+        }
+        writer.write(&greeting, 0).await
+            .inspect_err(|error| tracing::error!(?peer_addr, ?error, "failed to send greeting"))?;
+    }
+    Ok(())
 }
 
 /// Helper function to send a message over the network.
@@ -486,55 +1217,271 @@ async fn process_stream(
 /// # Args
 ///
 /// `msg`:  The message to send over the network. This function will also send the success/fail
-/// response back to the sender.
+/// response back to the sender. If the message must be acknowledged by the other node, the
+/// response is sent once it is.
 ///
 /// `peer_addr`:  The address the message is being sent to.
 ///
-/// `writer`:  The writer used to send messages thru the TCP socket.
+/// `writer`:  The sending half of the connection.
 ///
-/// # Errors
+/// `acks`:  The requests waiting to be acknowledged by the other node.
 ///
-/// [`Error::SerdeJson`] if the message cannot serialized.
+/// # Errors
 ///
-/// [`Error::StdIo`] if an error occurs while trying to write to the network.
+/// [`Error::NetworkSend`] if an error occurs while trying to write to the network. Errors that
+/// only affect the message (e.g. it is too large or the other node does not know it) are sent to
+/// the requester instead so the connection is not closed.
 async fn send(
     msg: OutgoingMessage,
     peer_addr: &str,
-    writer: &mut OwnedWriteHalf,
+    writer: &mut StreamWriter,
+    acks: &mut PendingAcks,
 ) -> Result<()> {
-    let result = {
-        let bytes = match msg.serialize() {
-            Ok(bytes) => bytes,
-            Err(error) => {
-                tracing::error!(?error, "failed to serialize message");
-                return Err(error);
-            }
-        };
+    if writer.legacy && !msg.msg.is_legacy() {
+        tracing::warn!(?peer_addr, "peer only supports the line protocol, message not sent");
+        let code = protocol::NackCode::Unsupported;
+        let error = String::from("peer only supports the line protocol");
+        respond(msg.response, Err(Error::RequestRejected { code, error }));
+        return Ok(());
+    }
 
-        writer.write_all(&bytes).await
-            .map_err(|error| {
-                tracing::error!(?peer_addr, ?error, "failed to send message");
-                error.into()
-            })
-    };
+    let result = writer.write(&msg.msg, msg.reply_to).await
+        .inspect_err(|error| tracing::error!(?peer_addr, ?error, "failed to send message"));
+    let closed = matches!(result, Err(Error::NetworkSend));
 
-    let reply = if result.is_ok() {
-        Ok(())
-    } else {
+    match result {
+        Ok(id) if msg.msg.requires_ack() && writer.acknowledges() => acks.insert(id, msg.response),
+        result => respond(msg.response, result.map(|_| ())),
+    }
+
+    if closed {
         Err(Error::NetworkSend)
-    };
+    } else {
+        Ok(())
+    }
+}
 
+/// Send the response for a request to send a message.
+///
+/// # Args
+///
+/// `response`:  Transmission end of the channel to send the response for the request.
+///
+/// `result`:  The result of sending the message.
+fn respond(response: Response<()>, result: Result<()>) {
     // Ignore error sending the response so that the network connection isn't closed if sending the
     // message was successful, but sending the response was not. This could happen if the requester
     // doesn't wait for the response and the receiving end of the channel goes out of scope and is
     // dropped.
-    let _ = msg.response.send(reply)
+    let _ = response.send(result)
         .inspect_err(|_| tracing::error!("failed to send response"));
-
-    result
 }
 
 #[cfg(test)]
 mod tests {
-    // TODO[TESTS]
+    use std::path::PathBuf;
+
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::net::TcpListener;
+
+    use crate::net::protocol::NackCode;
+
+    use super::*;
+
+    /// The messages known by nodes that only support the original line protocol.
+    const LINE_PROTOCOL_MESSAGES: [&str; 8] = [
+        "DriveStatusUpdate",
+        "MakeMkvCancel",
+        "MakeMkvCopyComplete",
+        "MakeMkvFailed",
+        "MakeMkvInfoComplete",
+        "MakeMkvProgress",
+        "RunMakeMkvCopy",
+        "RunMakeMkvInfo",
+    ];
+
+    /// Sends a message to the other node, returning the response.
+    async fn send_outgoing(
+        net_tx: &mpsc::Sender<OutgoingMessage>,
+        msg: protocol::Message,
+    ) -> Result<()> {
+        let (response, rx) = oneshot::channel();
+        let msg = OutgoingMessage { msg, worker: None, reply_to: 0, response };
+        net_tx.send(msg).await.unwrap();
+        rx.await.unwrap()
+    }
+
+    #[test]
+    fn test_line_protocol_peer() {
+        task::block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+
+            // A worker node that only knows the line protocol. It sends a drive status update when
+            // the control node connects and fails on any message it does not know.
+            let worker = task::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let (reader, mut writer) = stream.into_split();
+                let update = concat!(
+                    r#"{"type":"DriveStatusUpdate","data":{"drive":{"path":"/dev/sr0","#,
+                    r#""serial_number":"SN0001","disc":"None","hostname":"worker"}}}"#,
+                    "\n",
+                );
+                writer.write_all(update.as_bytes()).await.unwrap();
+
+                let mut lines = BufReader::new(reader).lines();
+                let line = lines.next_line().await.unwrap().unwrap();
+                let msg: serde_json::Value = serde_json::from_str(&line).unwrap();
+                let msg_type = msg["type"].as_str().unwrap().to_owned();
+                assert!(LINE_PROTOCOL_MESSAGES.contains(&msg_type.as_str()), "{}", msg_type);
+                msg_type
+            });
+
+            let (actor_tx, mut actor_rx) = mpsc::channel(10);
+            let (net_tx, mut net_rx) = mpsc::channel(10);
+            let stream = TcpStream::connect(&addr).await.unwrap();
+            let control = task::spawn(async move {
+                let actor = Handle::new(actor_tx);
+                let greetings = vec![
+                    protocol::Message::StorageMode { mode: StorageMode::Shared },
+                    protocol::Message::VerifyStorage { locations: Vec::new() },
+                ];
+                process_stream(
+                    stream,
+                    "worker",
+                    &actor,
+                    &mut net_rx,
+                    || {},
+                    greetings,
+                    Handshake::Accept,
+                ).await;
+            });
+
+            let Some(Message::Incoming(incoming)) = actor_rx.recv().await else {
+                panic!("expected drive status update");
+            };
+            assert!(matches!(incoming.msg, protocol::Message::DriveStatusUpdate { .. }));
+            assert!(incoming.ack.is_none());
+
+            // Messages the worker node does not know are rejected without being sent.
+            let msg = protocol::Message::TransferPull {
+                id: String::from("1"),
+                location: MediaLocation::Inbox(PathBuf::from("title")),
+            };
+            assert!(matches!(
+                send_outgoing(&net_tx, msg).await,
+                Err(Error::RequestRejected { code: NackCode::Unsupported, .. }),
+            ));
+
+            let msg = protocol::Message::RunMakeMkvInfo {
+                drive: String::from("SN0001"),
+                log_file: MediaLocation::Inbox(PathBuf::from("info.log")),
+            };
+            send_outgoing(&net_tx, msg).await.unwrap();
+
+            // The greetings were not sent, so the first message the worker node reads is the
+            // request.
+            assert_eq!(worker.await.unwrap(), "RunMakeMkvInfo");
+            control.await.unwrap();
+
+            // The worker node could not check its media folders, which does not prevent copies.
+            assert_eq!(sentinel::status("worker"), StorageCheck::Unverified);
+            assert!(sentinel::status("worker").allows_copy());
+        });
+    }
+
+    #[test]
+    fn test_reply_sent_by_connection() {
+        task::block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+
+            let (worker_actor_tx, mut worker_actor_rx) = mpsc::channel(1);
+            let (_worker_net_tx, mut worker_net_rx) = mpsc::channel(10);
+            let worker = task::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let actor = Handle::new(worker_actor_tx);
+                process_stream(
+                    stream,
+                    "control",
+                    &actor,
+                    &mut worker_net_rx,
+                    || {},
+                    Vec::new(),
+                    Handshake::Offer,
+                ).await;
+            });
+
+            let (control_actor_tx, _control_actor_rx) = mpsc::channel(1);
+            let (net_tx, mut net_rx) = mpsc::channel(10);
+            let stream = TcpStream::connect(&addr).await.unwrap();
+            let control = task::spawn(async move {
+                let actor = Handle::new(control_actor_tx);
+                process_stream(
+                    stream,
+                    "worker",
+                    &actor,
+                    &mut net_rx,
+                    || {},
+                    Vec::new(),
+                    Handshake::Accept,
+                ).await;
+            });
+
+            // The worker's actor replies without waiting for the reply to be written.
+            let actor = task::spawn(async move {
+                for reply in [None, Some(protocol::Message::Ack)] {
+                    let Some(Message::Incoming(incoming)) = worker_actor_rx.recv().await else {
+                        panic!("expected request");
+                    };
+                    let ack = incoming.ack.expect("request should be acknowledged");
+                    if let Some(reply) = reply {
+                        ack.send(reply).unwrap();
+                    }
+                }
+            });
+
+            let info = || protocol::Message::RunMakeMkvInfo {
+                drive: String::from("SN0001"),
+                log_file: MediaLocation::Inbox(PathBuf::from("info.log")),
+            };
+            assert!(matches!(
+                send_outgoing(&net_tx, info()).await,
+                Err(Error::RequestRejected { code: NackCode::Failed, .. }),
+            ));
+            send_outgoing(&net_tx, info()).await.unwrap();
+            actor.await.unwrap();
+
+            drop(net_tx);
+            control.await.unwrap();
+            worker.await.unwrap();
+        });
+    }
+
+    #[test]
+    fn test_pending_acks() {
+        let mut acks = PendingAcks::default();
+        let (accepted_tx, mut accepted_rx) = oneshot::channel();
+        let (rejected_tx, mut rejected_rx) = oneshot::channel();
+        let (waiting_tx, mut waiting_rx) = oneshot::channel();
+        acks.insert(1, accepted_tx);
+        acks.insert(2, rejected_tx);
+        acks.insert(3, waiting_tx);
+
+        acks.resolve(1, Ok(()));
+        let error = String::from("busy");
+        acks.resolve(2, Err(Error::RequestRejected { code: NackCode::DriveBusy, error }));
+        acks.resolve(4, Ok(()));
+        acks.expire();
+        assert!(matches!(accepted_rx.try_recv(), Ok(Ok(()))));
+        assert!(matches!(
+            rejected_rx.try_recv(),
+            Ok(Err(Error::RequestRejected { code: NackCode::DriveBusy, .. })),
+        ));
+        assert!(waiting_rx.try_recv().is_err());
+
+        acks.disconnect();
+        assert!(matches!(waiting_rx.try_recv(), Ok(Err(Error::Disconnected))));
+        assert!(acks.requests.is_empty());
+    }
 }
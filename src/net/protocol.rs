@@ -5,13 +5,23 @@
 
 use serde::{self, Deserialize, Serialize};
 use serde_json;
+use tokio::sync::oneshot;
 
 use makemkv::{CopyCommandOutput, InfoCommandOutput};
 
-use crate::Result;
+use crate::{Error, Result};
 use crate::drive::OsOpticalDrive;
 use crate::models::MediaLocation;
 use crate::net::{self, IncomingMessage};
+use crate::net::frame::FrameOptions;
+use crate::net::sentinel::SentinelRead;
+use crate::net::transfer::{StorageMode, TransferFile};
+
+/// The version of the protocol used between the control and worker nodes.
+///
+/// Must be incremented when a change is made that prevents nodes running different versions from
+/// communicating.
+pub const VERSION: u32 = 1;
 
 /// Messages that can be send between the control and worker nodes.
 ///
@@ -19,14 +29,30 @@ use crate::net::{self, IncomingMessage};
 ///
 /// - (c -> w): Indicates the message is meant to be sent from the control node to a worker node.
 /// - (w -> c): Indicates the message is meant to be sent from a worker node to the control node.
+/// - (c <-> w): Indicates the message can be sent in either direction.
+///
+/// Some requests must be acknowledged by the receiving node with a [`Message::Ack`] or
+/// [`Message::Nack`] (see [`Message::requires_ack`]).
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum Message {
+    /// (c <-> w) The request was accepted. Sent in reply to a message that requires an
+    /// acknowledgement.
+    Ack,
+
     /// (w -> c) The updated status of an optical drive.
     DriveStatusUpdate {
         drive: OsOpticalDrive,
     },
 
+    /// (c <-> w) Sent as a line by a worker node as soon as the control node connects to offer
+    /// sending frames instead of lines (see [`crate::net::frame`]). The control node replies with
+    /// the options supported by both nodes, or `None` to keep using lines.
+    Hello {
+        version: u32,
+        frames: Option<FrameOptions>,
+    },
+
     /// (c -> w) Request to run the MakeMKV copy command.
     /// system.
     RunMakeMkvCopy {
@@ -64,6 +90,13 @@ pub enum Message {
         output: InfoCommandOutput,
     },
 
+    /// (c <-> w) The request was rejected. Sent in reply to a message that requires an
+    /// acknowledgement.
+    Nack {
+        code: NackCode,
+        error: String,
+    },
+
     /// (w -> c) Progress information about a running MakeMKV command.
     MakeMkvProgress {
         drive: String,
@@ -72,9 +105,103 @@ pub enum Message {
         subop: String,
         subop_prog: u8,
     },
+
+    /// (c -> w) How the worker node's media folders are shared with the control node. Sent when
+    /// the control node connects.
+    StorageMode {
+        mode: StorageMode,
+    },
+
+    /// (w -> c) The sentinel files read by the worker node in response to
+    /// [`Message::VerifyStorage`].
+    StorageVerified {
+        sentinels: Vec<SentinelRead>,
+    },
+
+    /// (c <-> w) A chunk of a file being transferred, encoded as base64.
+    TransferChunk {
+        id: String,
+        index: usize,
+        offset: u64,
+        data: String,
+    },
+
+    /// (c <-> w) The result of a file transfer, sent by the receiving node once all of the files
+    /// are received and verified. `error` is `None` if the transfer succeeded.
+    TransferComplete {
+        id: String,
+        error: Option<String>,
+    },
+
+    /// (c <-> w) Files the sending node is about to transfer.
+    TransferOffer {
+        id: String,
+        files: Vec<TransferFile>,
+    },
+
+    /// (c <-> w) Request for the files in a folder to be transferred to the requesting node.
+    TransferPull {
+        id: String,
+        location: MediaLocation,
+    },
+
+    /// (c <-> w) Request for `length` bytes of a file to be sent by the sending node, starting at
+    /// an offset.
+    TransferRequest {
+        id: String,
+        index: usize,
+        offset: u64,
+        length: u64,
+    },
+
+    /// (c -> w) Request for the worker node to read the sentinel files written by the control node
+    /// to its media folders (see [`crate::net::sentinel`]). Sent when the control node connects.
+    VerifyStorage {
+        locations: Vec<MediaLocation>,
+    },
 }
 
 impl Message {
+    /// Whether the receiving node must reply with a [`Message::Ack`] or [`Message::Nack`] once it
+    /// has processed the message.
+    ///
+    /// The sender of the message waits for the reply, so this should only be used for requests
+    /// that are processed quickly (e.g. starting a command rather than waiting for it to finish).
+    /// Messages are only acknowledged when sent as frames (see [`crate::net::frame`]).
+    pub fn requires_ack(&self) -> bool {
+        matches!(
+            self,
+            Message::MakeMkvCancel { .. }
+            | Message::RunMakeMkvCopy { .. }
+            | Message::RunMakeMkvInfo { .. }
+        )
+    }
+
+    /// Whether the message is worth compressing when sent in a frame (see [`crate::net::frame`]).
+    ///
+    /// File chunks are mostly video, which is already compressed.
+    pub fn is_compressible(&self) -> bool {
+        !matches!(self, Message::TransferChunk { .. })
+    }
+
+    /// Whether nodes that only know the original line protocol can parse the message.
+    ///
+    /// Those nodes fail on any other message, so only these messages are sent to a node that does
+    /// not send a [`Message::Hello`] (see [`crate::net::frame`]).
+    pub fn is_legacy(&self) -> bool {
+        matches!(
+            self,
+            Message::DriveStatusUpdate { .. }
+            | Message::MakeMkvCancel { .. }
+            | Message::MakeMkvCopyComplete { .. }
+            | Message::MakeMkvFailed { .. }
+            | Message::MakeMkvInfoComplete { .. }
+            | Message::MakeMkvProgress { .. }
+            | Message::RunMakeMkvCopy { .. }
+            | Message::RunMakeMkvInfo { .. }
+        )
+    }
+
     /// Parse a message received from the network.
     ///
     /// # Args
@@ -92,16 +219,13 @@ impl Message {
 
     /// Serializes the message as JSON.
     ///
-    /// This will automatically add a newline character to the end of the byte array before
-    /// returning.
+    /// The bytes are encoded as a line or a frame before being sent (see [`net::frame::encode`]).
     ///
     /// # Errors
     ///
     /// [`crate::Error::SerdeJson`] if the bytes cannot be serialized.
     pub fn serialize(&self) -> Result<Vec<u8>> {
-        let mut bytes = serde_json::to_vec(self)?;
-        bytes.push(b'\n');
-        Ok(bytes)
+        serde_json::to_vec(self).map_err(|e| e.into())
     }
 
     /// Convert the network message into a incoming message for the network actor.
@@ -109,16 +233,106 @@ impl Message {
     /// # Args
     ///
     /// `sender`:  The IP address of the
-    pub fn incoming_message(self, sender: &str) -> net::Message {
+    ///
+    /// `ack`:  Transmission end of the channel to send the reply to the message, if it must be
+    /// acknowledged.
+    pub fn incoming_message(
+        self,
+        sender: &str,
+        ack: Option<oneshot::Sender<Message>>,
+    ) -> net::Message {
         let msg = IncomingMessage {
             msg: self,
             sender: sender.to_owned(),
+            ack,
         };
         net::Message::Incoming(msg)
     }
 }
 
+/// Why a node rejected a request.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NackCode {
+    /// The drive is already running a MakeMKV command.
+    DriveBusy,
+
+    /// The node does not have a drive with the serial number.
+    DriveNotFound,
+
+    /// A media location is not valid or a folder cannot be created.
+    InvalidPath,
+
+    /// The node does not support the request.
+    Unsupported,
+
+    /// The request failed for any other reason.
+    Failed,
+}
+
+impl From<&Error> for NackCode {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::AlreadyRunning | Error::InvalidDriveState { .. } => NackCode::DriveBusy,
+            Error::DriveNotFound { .. } => NackCode::DriveNotFound,
+            Error::InvalidMediaLocation { .. } | Error::StdIo(_) => NackCode::InvalidPath,
+            Error::UnsupportedRequest { .. } => NackCode::Unsupported,
+            _ => NackCode::Failed,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    // TODO[TESTS]
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_requires_ack() {
+        let msg = Message::MakeMkvCancel { drive: String::from("SN0001") };
+        assert!(msg.requires_ack());
+        assert!(!Message::Ack.requires_ack());
+    }
+
+    #[test]
+    fn test_is_compressible() {
+        let chunk = Message::TransferChunk {
+            id: String::from("1"),
+            index: 0,
+            offset: 0,
+            data: String::new(),
+        };
+        assert!(!chunk.is_compressible());
+        assert!(Message::MakeMkvFailed {
+            drive: String::from("SN0001"),
+            error: String::new(),
+        }.is_compressible());
+    }
+
+    #[test]
+    fn test_is_legacy() {
+        let msg = Message::RunMakeMkvInfo {
+            drive: String::from("SN0001"),
+            log_file: MediaLocation::Inbox(PathBuf::from("info.log")),
+        };
+        assert!(msg.is_legacy());
+        assert!(!Message::StorageMode { mode: StorageMode::Shared }.is_legacy());
+        assert!(!Message::Hello { version: VERSION, frames: None }.is_legacy());
+    }
+
+    #[test]
+    fn test_nack_code() {
+        let error = Error::DriveNotFound { serial_number: String::from("SN0001") };
+        assert_eq!(NackCode::from(&error), NackCode::DriveNotFound);
+        assert_eq!(NackCode::from(&Error::AlreadyRunning), NackCode::DriveBusy);
+        assert_eq!(NackCode::from(&Error::NetworkSend), NackCode::Failed);
+
+        let msg = Message::Nack { code: NackCode::DriveBusy, error: String::from("busy") };
+        let bytes = msg.serialize().unwrap();
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            r#"{"type":"Nack","data":{"code":"drive_busy","error":"busy"}}"#,
+        );
+    }
 }
@@ -0,0 +1,335 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Verifies that worker nodes see the same media folders as the control node.
+//!
+//! [`MediaLocation`] paths are resolved against each node's own inbox, library and archive folders.
+//! If the network share is not mounted on a worker node (or is mounted somewhere else), the titles
+//! it copies end up where the control node cannot see them.
+//!
+//! When a client connects to a worker node that uses shared storage, the control node writes a
+//! sentinel file containing a random token to the root of each media folder and asks the worker
+//! node to read them back. The result of the check is kept for each worker node (see [`status`])
+//! and copies are not started on the worker node's drives until the tokens match.
+//!
+//! Worker nodes that only know the original line protocol cannot read the sentinel files. Their
+//! media folders are left unverified and copies are allowed so they keep working until they are
+//! upgraded.
+
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::fs;
+use std::hash::BuildHasher;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+use crate::models::MediaLocation;
+use crate::net::transfer::Locate;
+use crate::path;
+
+/// The name of the sentinel file written to the root of each media folder.
+const FILENAME: &str = ".artie-sentinel";
+
+/// The result of checking a worker node's media folders.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum StorageCheck {
+    /// The worker node has not read the sentinel files yet.
+    #[default]
+    Pending,
+
+    /// The worker node read the same tokens from each of the sentinel files.
+    Verified,
+
+    /// The worker node's media folders are not shared, so there is nothing to check.
+    NotShared,
+
+    /// The worker node only knows the original line protocol, so it cannot read the sentinel
+    /// files.
+    Unverified,
+
+    /// The worker node could not read one or more of the sentinel files or read a different token.
+    Mismatch {
+        problems: Vec<String>,
+    },
+}
+
+impl StorageCheck {
+    /// Whether discs can be copied on the worker node's drives.
+    pub fn allows_copy(&self) -> bool {
+        matches!(self, StorageCheck::Verified | StorageCheck::NotShared | StorageCheck::Unverified)
+    }
+}
+
+impl fmt::Display for StorageCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageCheck::Pending => {
+                write!(f, "Waiting for the worker to check its media folders.")
+            },
+            StorageCheck::Verified => write!(f, "The worker's media folders are shared."),
+            StorageCheck::NotShared => write!(f, "The worker's media folders are not shared."),
+            StorageCheck::Unverified => {
+                write!(f, "The worker must be upgraded to check its media folders.")
+            },
+            StorageCheck::Mismatch { problems } => write!(f, "{}", problems.join("\n")),
+        }
+    }
+}
+
+/// A sentinel file read by a worker node.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SentinelRead {
+    /// The location of the sentinel file.
+    pub location: MediaLocation,
+
+    /// The token read from the file or the error reading it.
+    pub token: std::result::Result<String, String>,
+}
+
+/// Returns the locations of the sentinel files.
+pub fn locations() -> Vec<MediaLocation> {
+    vec![
+        MediaLocation::Inbox(PathBuf::from(FILENAME)),
+        MediaLocation::Library(PathBuf::from(FILENAME)),
+        MediaLocation::Archive(PathBuf::from(FILENAME)),
+    ]
+}
+
+/// Write the sentinel files to the root of each media folder.
+///
+/// Returns the locations of the files, which are sent to the worker node to read back. The token
+/// does not change until the application is restarted, so the files are only written the first time
+/// this succeeds. This blocks while writing to the media folders, which may be network shares, so
+/// it should be called using [`crate::task::spawn_blocking`].
+///
+/// # Errors
+///
+/// [`Error::InvalidMediaLocation`] if the path of a media folder cannot be determined.
+///
+/// [`Error::StdIo`] if a sentinel file cannot be written.
+pub fn write() -> Result<Vec<MediaLocation>> {
+    static WRITTEN: Mutex<bool> = Mutex::new(false);
+
+    let mut written = WRITTEN.lock().expect("sentinel lock should not be poisoned");
+    if *written {
+        return Ok(locations());
+    }
+
+    let locations = write_with(path::location_path)?;
+    *written = true;
+    Ok(locations)
+}
+
+/// Write the sentinel files, using `locate` to find the media folders.
+///
+/// See [`write`].
+fn write_with(locate: Locate) -> Result<Vec<MediaLocation>> {
+    let locations = locations();
+    for location in &locations {
+        let file_path = locate(location)
+            .ok_or_else(|| Error::InvalidMediaLocation { location: location.clone() })?;
+        fs::write(file_path, token())?;
+    }
+    Ok(locations)
+}
+
+/// Read the sentinel files requested by the control node.
+///
+/// Only sentinel files are read. Any other location is reported as an error.
+///
+/// # Args
+///
+/// `locations`:  The locations of the sentinel files.
+pub fn read(locations: Vec<MediaLocation>) -> Vec<SentinelRead> {
+    read_with(locations, path::location_path)
+}
+
+/// Read the sentinel files, using `locate` to find them.
+///
+/// See [`read`].
+fn read_with(locations: Vec<MediaLocation>, locate: Locate) -> Vec<SentinelRead> {
+    locations.into_iter()
+        .map(|location| {
+            let token = match locate(&location) {
+                Some(file_path) if file_path.file_name().is_some_and(|name| name == FILENAME) => {
+                    fs::read_to_string(&file_path)
+                        .map_err(|error| format!("{}: {}", file_path.display(), error))
+                },
+                _ => Err(String::from("not a sentinel file")),
+            };
+            SentinelRead { location, token }
+        })
+        .collect()
+}
+
+/// Compare the sentinel files read by a worker node to the ones written by this node.
+///
+/// # Args
+///
+/// `sentinels`:  The sentinel files read by the worker node.
+pub fn verify(sentinels: &[SentinelRead]) -> StorageCheck {
+    let problems = locations().iter()
+        .filter_map(|location| {
+            let folder = folder_name(location);
+            match sentinels.iter().find(|s| s.location == *location).map(|s| &s.token) {
+                Some(Ok(read)) if read == token() => None,
+                Some(Ok(_)) => Some(format!("The {} folder is not the same folder.", folder)),
+                Some(Err(error)) => {
+                    Some(format!("The {} folder cannot be read: {}", folder, error))
+                },
+                None => Some(format!("The {} folder was not checked.", folder)),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if problems.is_empty() {
+        StorageCheck::Verified
+    } else {
+        StorageCheck::Mismatch { problems }
+    }
+}
+
+/// Returns the result of checking a worker node's media folders.
+///
+/// # Args
+///
+/// `addr`:  The address and port of the worker node.
+pub fn status(addr: &str) -> StorageCheck {
+    checks().lock()
+        .expect("storage check lock should not be poisoned")
+        .get(addr)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Set the result of checking a worker node's media folders.
+///
+/// # Args
+///
+/// `addr`:  The address and port of the worker node.
+///
+/// `check`:  The result of the check.
+pub fn set_status(addr: &str, check: StorageCheck) {
+    checks().lock()
+        .expect("storage check lock should not be poisoned")
+        .insert(addr.to_owned(), check);
+}
+
+/// Forget the result of checking a worker node's media folders (e.g. when it is removed).
+///
+/// # Args
+///
+/// `addr`:  The address and port of the worker node.
+pub fn remove(addr: &str) {
+    checks().lock()
+        .expect("storage check lock should not be poisoned")
+        .remove(addr);
+}
+
+/// Returns the results of checking each worker node's media folders by address.
+fn checks() -> &'static Mutex<HashMap<String, StorageCheck>> {
+    static CHECKS: OnceLock<Mutex<HashMap<String, StorageCheck>>> = OnceLock::new();
+    CHECKS.get_or_init(Default::default)
+}
+
+/// Returns the token written to the sentinel files.
+///
+/// The token is generated the first time it is needed and is the same for every worker node until
+/// the application is restarted.
+fn token() -> &'static str {
+    static TOKEN: OnceLock<String> = OnceLock::new();
+    TOKEN.get_or_init(|| {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&Utc::now().timestamp_nanos_opt().unwrap_or_default().to_le_bytes());
+        hasher.update(&std::process::id().to_le_bytes());
+        hasher.update(&RandomState::new().hash_one(FILENAME).to_le_bytes());
+        hasher.finalize().to_hex().to_string()
+    })
+}
+
+/// Returns the name of the media folder a location is in.
+fn folder_name(location: &MediaLocation) -> &'static str {
+    match location {
+        MediaLocation::Inbox(_) => "inbox",
+        MediaLocation::Library(_) => "library",
+        MediaLocation::Archive(_) => "archive",
+        MediaLocation::Deleted => "deleted",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::test_utils::TempDir;
+
+    use super::*;
+
+    /// The temp folder containing the media folders used by the tests.
+    const MEDIA: &str = "artie.test.net.sentinel";
+
+    /// Returns the path of a location in the test media folders.
+    fn locate(location: &MediaLocation) -> Option<PathBuf> {
+        let media = env::temp_dir().join(MEDIA);
+        match location {
+            MediaLocation::Inbox(path) => Some(media.join("inbox").join(path)),
+            MediaLocation::Library(path) => Some(media.join("library").join(path)),
+            MediaLocation::Archive(path) => Some(media.join("archive").join(path)),
+            MediaLocation::Deleted => None,
+        }
+    }
+
+    #[test]
+    fn test_write_read_verify() {
+        let media = TempDir::new(MEDIA);
+        for folder in ["inbox", "library", "archive"] {
+            fs::create_dir_all(media.path().join(folder)).unwrap();
+        }
+
+        let locations = write_with(locate).unwrap();
+        let sentinels = read_with(locations.clone(), locate);
+        assert_eq!(sentinels.len(), 3);
+        assert_eq!(verify(&sentinels), StorageCheck::Verified);
+
+        // A worker with a different library folder.
+        fs::write(media.path().join("library").join(FILENAME), "other").unwrap();
+        fs::remove_file(media.path().join("archive").join(FILENAME)).unwrap();
+        let sentinels = read_with(locations, locate);
+        let StorageCheck::Mismatch { problems } = verify(&sentinels) else {
+            panic!("expected mismatch");
+        };
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0], "The library folder is not the same folder.");
+        assert!(problems[1].starts_with("The archive folder cannot be read: "));
+
+        let check = verify(&sentinels[..1]);
+        assert!(!check.allows_copy());
+        assert!(check.to_string().contains("The library folder was not checked."));
+    }
+
+    #[test]
+    fn test_read_only_sentinels() {
+        let sentinels = read_with(vec![
+            MediaLocation::Library(PathBuf::from("movie/movie.mkv")),
+            MediaLocation::Deleted,
+        ], locate);
+        assert!(sentinels.iter().all(|s| s.token == Err(String::from("not a sentinel file"))));
+    }
+
+    #[test]
+    fn test_status() {
+        let addr = "192.0.2.1:7878";
+        assert_eq!(status(addr), StorageCheck::Pending);
+        set_status(addr, StorageCheck::NotShared);
+        assert_eq!(status(addr), StorageCheck::NotShared);
+        assert!(status(addr).allows_copy());
+        remove(addr);
+        assert_eq!(status(addr), StorageCheck::Pending);
+        assert!(!status(addr).allows_copy());
+    }
+}
@@ -14,30 +14,50 @@
 use tokio::net::TcpListener;
 use tokio::sync::mpsc;
 
+use tokio_util::future::FutureExt;
+use tokio_util::sync::CancellationToken;
+
 use crate::Error;
 use crate::bus;
-use crate::net::{self, Handle, OutgoingMessage, Settings};
+use crate::net::{self, Handle, OutgoingMessage, Settings, discovery};
+use crate::net::transfer::StorageMode;
 use crate::task;
 
 /// Create the the server actor.
 ///
 /// This will create the actor, spawn the task for processing requests from the application and
-/// spawn the task for listening for and processing messages from the control node,
+/// spawn the task for listening for and processing messages from the control node. The worker
+/// node is also announced on the local network if discovery is enabled.
 ///
 /// # Args
 ///
 /// `bus`:  Handle used to send messages to other actors via the message bus.
 ///
 /// `settings`:  Settings containing the address and port to listen on.
-pub fn init(bus: &bus::Handle, settings: &Settings) -> Handle {
-    let (handle, net_rx) = net::actor::init("server", bus);
+///
+/// `cancellation_token`:  Stops listening for connections, closing the connection to the control
+/// node, when cancelled.
+pub fn init(
+    bus: &bus::Handle,
+    settings: &Settings,
+    cancellation_token: CancellationToken,
+) -> Handle {
+    // The control node sends the storage mode for this worker when it connects.
+    let (handle, net_rx) = net::actor::init("server", bus, StorageMode::Shared);
 
     let addr = format!("{}:{}", settings.listen_addr, settings.listen_port);
     let handle_clone = handle.clone();
     task::spawn(async move {
-        listen(&addr, handle_clone, net_rx).await;
+        let result = listen(&addr, handle_clone, net_rx)
+            .with_cancellation_token_owned(cancellation_token)
+            .await;
+        if result.is_none() {
+            tracing::info!(?addr, "server stopped");
+        }
     });
 
+    discovery::announce(settings);
+
     handle
 }
 
@@ -70,7 +90,16 @@ async fn listen(addr: &str, server: Handle, mut net_rx: mpsc::Receiver<OutgoingM
                         // only ever be one control node.
                         tracing::info!(?peer_addr, "client connected");
                         let peer_addr = peer_addr.to_string();
-                        net::process_stream(stream, &peer_addr, &server, &mut net_rx).await;
+                        let greetings = Vec::new();
+                        net::process_stream(
+                            stream,
+                            &peer_addr,
+                            &server,
+                            &mut net_rx,
+                            || {},
+                            greetings,
+                            net::Handshake::Offer,
+                        ).await;
                         tracing::info!("client disconnected");
                     },
                     Err(error) => {
@@ -0,0 +1,1295 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Transfers files between the control and worker nodes.
+//!
+//! By default, every node is expected to see the same inbox, library and archive folders (e.g.
+//! using a network share), so only [`MediaLocation`] values are sent between nodes. A worker node
+//! whose media folders are on its own disks uses the transfer [`StorageMode`], which is configured
+//! per worker on the control node and sent to the worker when the control node connects:
+//!
+//! ```toml
+//! [net.storage]
+//! "192.168.1.30:7878" = "transfer"
+//! ```
+//!
+//! Files are transferred over the existing connection between the nodes. The sending node offers
+//! the files in a folder along with their sizes and checksums. The receiving node then requests
+//! each file starting from the end of the partial file (`<name>.part`) it has already received,
+//! so an interrupted transfer resumes where it left off when offered again. A file is moved into
+//! place once its checksum is verified, and the receiving node reports the result once all of the
+//! files are received.
+//!
+//! The receiving node requests a window of each file at a time, so only a few chunks are queued
+//! for a transfer and the network actor never waits to forward them. The sending node queues the
+//! chunks of a window without waiting for each one to be written.
+//!
+//! Transfers are started by pushing files to the other node (see [`crate::net::push_files`]) or
+//! pulling them from the other node (see [`crate::net::pull_files`]). Both do nothing when the
+//! worker node uses shared storage.
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
+use tokio::sync::{mpsc, oneshot};
+use tokio::sync::mpsc::error::TrySendError;
+
+use crate::{Error, Result};
+use crate::actor::Response;
+use crate::library::integrity;
+use crate::models::MediaLocation;
+use crate::net::OutgoingMessage;
+use crate::net::protocol;
+use crate::path;
+use crate::task;
+
+/// The maximum number of bytes sent in a chunk.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// How long to wait for the other node before a transfer is considered interrupted.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The number of times files are offered before a push fails.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// How long to wait before offering the files again after an interrupted transfer.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// The extension added to a file while it is being received.
+const PART_EXTENSION: &str = "part";
+
+/// The maximum number of events queued for a transfer.
+const EVENT_BUFFER_SIZE: usize = 16;
+
+/// The number of bytes of a file the receiving node requests at a time.
+///
+/// The next window is requested once half of the previous one is received, so at most one and a
+/// half windows of chunks are queued for a transfer. This must fit in [`EVENT_BUFFER_SIZE`].
+const WINDOW_SIZE: u64 = 8 * CHUNK_SIZE as u64;
+
+/// Returns the path of a media location on this node.
+///
+/// Always [`path::location_path`] outside of tests, where each side of a transfer needs its own
+/// folders.
+pub(super) type Locate = fn(&MediaLocation) -> Option<PathBuf>;
+
+/// How a worker node's media folders are shared with the control node.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageMode {
+    /// The worker node uses the same media folders as the control node.
+    #[default]
+    Shared,
+
+    /// The worker node's media folders are local, so files are transferred over the network.
+    Transfer,
+}
+
+/// A file offered by the sending node.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TransferFile {
+    /// The location of the file.
+    pub location: MediaLocation,
+
+    /// The size of the file in bytes.
+    pub size: u64,
+
+    /// The BLAKE3 checksum of the file as a hex string.
+    pub checksum: String,
+}
+
+/// The progress of sending the files in a folder to the other node.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Progress {
+    /// The name of the file being sent.
+    pub file: String,
+
+    /// The number of bytes of the file that were sent.
+    pub file_sent: u64,
+
+    /// The size of the file in bytes.
+    pub file_size: u64,
+
+    /// The number of bytes of all of the files that were sent.
+    pub sent: u64,
+
+    /// The size of all of the files in bytes.
+    pub size: u64,
+}
+
+impl Progress {
+    /// The percentage of all of the files that was sent.
+    pub fn percent(&self) -> u8 {
+        percent(self.sent, self.size)
+    }
+
+    /// The percentage of the file being sent that was sent.
+    pub fn file_percent(&self) -> u8 {
+        percent(self.file_sent, self.file_size)
+    }
+}
+
+/// Messages received from the other node for a running transfer.
+#[derive(Debug)]
+pub(super) enum Event {
+    /// The files being transferred. Sent again if the sending node restarts the transfer.
+    Offer {
+        files: Vec<TransferFile>,
+    },
+
+    /// Request for `length` bytes of a file starting at an offset.
+    Request {
+        index: usize,
+        offset: u64,
+        length: u64,
+    },
+
+    /// A chunk of a file encoded as base64.
+    Chunk {
+        index: usize,
+        offset: u64,
+        data: String,
+    },
+
+    /// The result reported by the receiving node.
+    Complete {
+        error: Option<String>,
+    },
+}
+
+/// The transfers running on a connection.
+///
+/// Used by the network actor to forward the transfer messages it receives to the task running the
+/// transfer.
+#[derive(Default)]
+pub(super) struct Transfers {
+    /// Transmission end of the channel for the events of each transfer by ID.
+    events: HashMap<String, mpsc::Sender<Event>>,
+}
+
+impl Transfers {
+    /// Register a transfer, returning the receiving end of the channel for its events.
+    ///
+    /// Transfers that finished are removed.
+    ///
+    /// # Args
+    ///
+    /// `id`:  The ID of the transfer.
+    pub(super) fn register(&mut self, id: &str) -> mpsc::Receiver<Event> {
+        self.events.retain(|_, tx| !tx.is_closed());
+
+        let (tx, rx) = mpsc::channel(EVENT_BUFFER_SIZE);
+        self.events.insert(id.to_owned(), tx);
+        rx
+    }
+
+    /// Whether a transfer is running.
+    ///
+    /// # Args
+    ///
+    /// `id`:  The ID of the transfer.
+    pub(super) fn is_running(&self, id: &str) -> bool {
+        self.events.get(id).is_some_and(|tx| !tx.is_closed())
+    }
+
+    /// Forward an event to the task running a transfer without waiting.
+    ///
+    /// The event is dropped if the transfer is not running, which is expected for messages that
+    /// arrive after a transfer was interrupted. It is also dropped if too many events are queued
+    /// for the transfer, which only happens if the other node sends more than it was asked for
+    /// (see [`WINDOW_SIZE`]). The transfer then fails once the receiving task notices the gap.
+    ///
+    /// # Args
+    ///
+    /// `id`:  The ID of the transfer.
+    ///
+    /// `event`:  The event to forward.
+    pub(super) fn forward(&mut self, id: &str, event: Event) {
+        let Some(tx) = self.events.get(id) else {
+            tracing::debug!(id, "dropping event for unknown transfer");
+            return;
+        };
+
+        match tx.try_send(event) {
+            Ok(()) => {},
+            Err(TrySendError::Full(_)) => {
+                tracing::warn!(id, "dropping event, too many events queued for transfer");
+            },
+            Err(TrySendError::Closed(_)) => {
+                tracing::debug!(id, "dropping event for finished transfer");
+                self.events.remove(id);
+            },
+        }
+    }
+}
+
+/// Create a new transfer ID.
+///
+/// IDs only need to be unique between two connected nodes.
+pub(super) fn new_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    format!("{:x}-{:x}", nanos, count)
+}
+
+/// Send the files in a folder to the other node.
+///
+/// The files are offered again if the transfer is interrupted, up to [`MAX_ATTEMPTS`] times.
+///
+/// # Args
+///
+/// `id`:  The ID of the transfer.
+///
+/// `location`:  The location of the folder to send.
+///
+/// `net_tx`:  Transmission end of the channel used to send messages over the network.
+///
+/// `events`:  Receiving end of the channel for the transfer's events.
+///
+/// `progress`:  Transmission end of the channel the progress is reported to, if any. Updates are
+/// skipped while the previous one has not been received.
+///
+/// # Errors
+///
+/// [`Error::InvalidMediaLocation`] if the location is not a folder in one of the media folders.
+///
+/// [`Error::TransferFailed`] if the receiving node reports an error.
+///
+/// [`Error::TransferTimeout`] or [`Error::Disconnected`] if the transfer is interrupted too many
+/// times.
+pub(super) async fn send(
+    id: String,
+    location: MediaLocation,
+    net_tx: mpsc::Sender<OutgoingMessage>,
+    events: mpsc::Receiver<Event>,
+    progress: Option<mpsc::Sender<Progress>>,
+) -> Result<()> {
+    send_with(id, location, net_tx, events, progress, path::location_path).await
+}
+
+/// Send the files in a folder to the other node, using `locate` to find the files.
+///
+/// See [`send`].
+async fn send_with(
+    id: String,
+    location: MediaLocation,
+    net_tx: mpsc::Sender<OutgoingMessage>,
+    mut events: mpsc::Receiver<Event>,
+    progress: Option<mpsc::Sender<Progress>>,
+    locate: Locate,
+) -> Result<()> {
+    let files = list_files(&location, locate).await?;
+    tracing::info!(id, ?location, count = files.len(), "sending files");
+
+    let sender = Sender { id: &id, files: &files, net_tx: &net_tx, progress, locate };
+    let mut attempt = 1;
+    loop {
+        match sender.send_attempt(&mut events).await {
+            Err(error @ (Error::Disconnected | Error::NetworkSend | Error::TransferTimeout))
+                if attempt < MAX_ATTEMPTS =>
+            {
+                tracing::warn!(id, ?error, attempt, "transfer interrupted, will offer again");
+                attempt += 1;
+                tokio::time::sleep(RETRY_DELAY).await;
+            },
+            result => {
+                tracing::info!(id, ?result, "finished sending files");
+                return result;
+            },
+        }
+    }
+}
+
+/// Sends the files offered to the other node.
+struct Sender<'a> {
+    /// The ID of the transfer.
+    id: &'a str,
+
+    /// The files being sent.
+    files: &'a [TransferFile],
+
+    /// Transmission end of the channel used to send messages over the network.
+    net_tx: &'a mpsc::Sender<OutgoingMessage>,
+
+    /// Transmission end of the channel the progress is reported to, if any.
+    progress: Option<mpsc::Sender<Progress>>,
+
+    /// Returns the path of a file being sent.
+    locate: Locate,
+}
+
+impl Sender<'_> {
+    /// Offer the files to the other node and send the parts of the files it requests.
+    ///
+    /// # Args
+    ///
+    /// `events`:  Receiving end of the channel for the transfer's events.
+    ///
+    /// # Errors
+    ///
+    /// See [`send`].
+    async fn send_attempt(&self, events: &mut mpsc::Receiver<Event>) -> Result<()> {
+        let offer = protocol::Message::TransferOffer {
+            id: self.id.to_owned(),
+            files: self.files.to_vec(),
+        };
+        send_message(self.net_tx, offer).await?;
+
+        loop {
+            match next_event(events).await? {
+                Event::Request { index, offset, length } => {
+                    self.send_file(index, offset, length).await?;
+                },
+                Event::Complete { error: None } => return Ok(()),
+                Event::Complete { error: Some(error) } => {
+                    return Err(Error::TransferFailed { error });
+                },
+                event => {
+                    tracing::debug!(id=self.id, ?event, "ignoring unexpected event while sending");
+                },
+            }
+        }
+    }
+
+    /// Send part of a file to the other node in chunks.
+    ///
+    /// The chunks are queued without waiting for each one to be written, since the receiving node
+    /// limits how much is requested at a time.
+    ///
+    /// # Args
+    ///
+    /// `index`:  The index of the file to send.
+    ///
+    /// `offset`:  The offset to start sending from.
+    ///
+    /// `length`:  The number of bytes to send.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::TransferFailed`] if the file index is invalid or the file is shorter than offered.
+    ///
+    /// [`Error::StdIo`] if the file cannot be read.
+    ///
+    /// See [`send_message`] for the errors sending the chunks.
+    async fn send_file(&self, index: usize, mut offset: u64, length: u64) -> Result<()> {
+        let file = self.files.get(index)
+            .ok_or_else(|| Error::TransferFailed { error: format!("no file at index {}", index) })?;
+        let file_path = (self.locate)(&file.location)
+            .ok_or_else(|| Error::InvalidMediaLocation { location: file.location.clone() })?;
+        let end = offset.saturating_add(length).min(file.size);
+
+        tracing::debug!(id=self.id, ?file_path, offset, end, size = file.size, "sending file");
+
+        let mut reader = File::open(&file_path).await?;
+        reader.seek(SeekFrom::Start(offset)).await?;
+
+        let mut written = Vec::new();
+        let mut buffer = vec![0; CHUNK_SIZE];
+        while offset < end {
+            let len = usize::try_from(end - offset).map_or(CHUNK_SIZE, |left| left.min(CHUNK_SIZE));
+            reader.read_exact(&mut buffer[..len]).await
+                .map_err(|error| match error.kind() {
+                    ErrorKind::UnexpectedEof => Error::TransferFailed {
+                        error: format!("{} is shorter than offered", file_path.display()),
+                    },
+                    _ => error.into(),
+                })?;
+
+            let chunk = protocol::Message::TransferChunk {
+                id: self.id.to_owned(),
+                index,
+                offset,
+                data: BASE64.encode(&buffer[..len]),
+            };
+            written.push(queue_message(self.net_tx, chunk).await?);
+            offset += len as u64;
+            self.report(index, offset);
+        }
+
+        for rx in written {
+            wait_for_write(rx).await?;
+        }
+        Ok(())
+    }
+
+    /// Report the progress of sending the files.
+    ///
+    /// The update is skipped if the previous one has not been received yet.
+    ///
+    /// # Args
+    ///
+    /// `index`:  The index of the file being sent.
+    ///
+    /// `file_sent`:  The number of bytes of the file that were sent.
+    fn report(&self, index: usize, file_sent: u64) {
+        let Some(progress) = &self.progress else {
+            return;
+        };
+
+        let file = &self.files[index];
+        let before = self.files[..index].iter().map(|file| file.size).sum::<u64>();
+        let _ = progress.try_send(Progress {
+            file: relative_path(&file.location)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            file_sent,
+            file_size: file.size,
+            sent: before + file_sent,
+            size: self.files.iter().map(|file| file.size).sum(),
+        });
+    }
+}
+
+/// Receive the files offered by the other node.
+///
+/// The result is reported to the sending node and, if provided, sent to `response`. Nothing is
+/// reported if the transfer is abandoned (e.g. the network actor stopped).
+///
+/// # Args
+///
+/// `id`:  The ID of the transfer.
+///
+/// `files`:  The files offered. `None` if the files have not been offered yet (e.g. waiting for
+/// the other node to respond to a pull request).
+///
+/// `net_tx`:  Transmission end of the channel used to send messages over the network.
+///
+/// `events`:  Receiving end of the channel for the transfer's events.
+///
+/// `response`:  Transmission end of the channel to send the result to.
+pub(super) async fn receive(
+    id: String,
+    files: Option<Vec<TransferFile>>,
+    net_tx: mpsc::Sender<OutgoingMessage>,
+    events: mpsc::Receiver<Event>,
+    response: Option<Response<()>>,
+) {
+    receive_with(id, files, net_tx, events, response, path::location_path).await
+}
+
+/// Receive the files offered by the other node, using `locate` to find where to write them.
+///
+/// See [`receive`].
+async fn receive_with(
+    id: String,
+    mut files: Option<Vec<TransferFile>>,
+    net_tx: mpsc::Sender<OutgoingMessage>,
+    mut events: mpsc::Receiver<Event>,
+    response: Option<Response<()>>,
+    locate: Locate,
+) {
+    let result = loop {
+        let offered = match files.take() {
+            Some(offered) => offered,
+            None => match next_event(&mut events).await {
+                Ok(Event::Offer { files }) => files,
+                Ok(Event::Complete { error: Some(error) }) => {
+                    break Err(Error::TransferFailed { error });
+                },
+                Ok(event) => {
+                    tracing::debug!(id, ?event, "ignoring event before files are offered");
+                    continue;
+                },
+                Err(error) => break Err(error),
+            },
+        };
+
+        tracing::info!(id, count = offered.len(), "receiving files");
+        match receive_files(&id, &offered, &net_tx, &mut events, locate).await {
+            Ok(()) => break Ok(()),
+            Err(Stop::Offered(offered)) => {
+                tracing::info!(id, "files offered again, restarting transfer");
+                files = Some(offered);
+            },
+            Err(Stop::Error(error)) => break Err(error),
+            Err(Stop::Closed) => return,
+        }
+    };
+
+    tracing::info!(id, ?result, "finished receiving files");
+
+    let error = result.as_ref().err().map(|error| format!("{:?}", error));
+    let complete = protocol::Message::TransferComplete { id: id.clone(), error };
+    if let Err(error) = send_message(&net_tx, complete).await {
+        tracing::warn!(id, ?error, "failed to send transfer result");
+    }
+
+    if let Some(response) = response {
+        let _ = response.send(result)
+            .inspect_err(|_| tracing::error!(id, "failed to send transfer response"));
+    }
+}
+
+/// Why receiving the offered files stopped before they were all received.
+enum Stop {
+    /// The files were offered again, so the transfer needs to be restarted.
+    Offered(Vec<TransferFile>),
+
+    /// The transfer failed.
+    Error(Error),
+
+    /// The channel for the transfer's events was closed.
+    Closed,
+}
+
+impl From<Error> for Stop {
+    fn from(value: Error) -> Self {
+        Stop::Error(value)
+    }
+}
+
+/// Receive each of the offered files.
+///
+/// # Args
+///
+/// `id`:  The ID of the transfer.
+///
+/// `files`:  The files offered.
+///
+/// `net_tx`:  Transmission end of the channel used to send messages over the network.
+///
+/// `events`:  Receiving end of the channel for the transfer's events.
+///
+/// `locate`:  Returns the path a received file is written to.
+async fn receive_files(
+    id: &str,
+    files: &[TransferFile],
+    net_tx: &mpsc::Sender<OutgoingMessage>,
+    events: &mut mpsc::Receiver<Event>,
+    locate: Locate,
+) -> std::result::Result<(), Stop> {
+    for (index, file) in files.iter().enumerate() {
+        receive_file(id, index, file, net_tx, events, locate).await?;
+    }
+    Ok(())
+}
+
+/// Receive an offered file.
+///
+/// The file is skipped if it was already received. If the checksum of the received file does not
+/// match, it is received again once.
+///
+/// # Args
+///
+/// `id`:  The ID of the transfer.
+///
+/// `index`:  The index of the file.
+///
+/// `file`:  The file offered.
+///
+/// `net_tx`:  Transmission end of the channel used to send messages over the network.
+///
+/// `events`:  Receiving end of the channel for the transfer's events.
+///
+/// `locate`:  Returns the path the file is written to.
+async fn receive_file(
+    id: &str,
+    index: usize,
+    file: &TransferFile,
+    net_tx: &mpsc::Sender<OutgoingMessage>,
+    events: &mut mpsc::Receiver<Event>,
+    locate: Locate,
+) -> std::result::Result<(), Stop> {
+    let file_path = target_path(&file.location, locate)?;
+    if is_received(&file_path, file).await? {
+        tracing::debug!(id, ?file_path, "file already received");
+        return Ok(());
+    }
+
+    let part_path = part_path(&file_path);
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent).await.map_err(Error::from)?;
+    }
+
+    for _ in 0..2 {
+        let mut writer = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&part_path)
+            .await
+            .map_err(Error::from)?;
+
+        let mut offset = writer.metadata().await.map_err(Error::from)?.len();
+        if offset > file.size {
+            writer.set_len(0).await.map_err(Error::from)?;
+            offset = 0;
+        }
+
+        tracing::debug!(id, ?file_path, offset, size = file.size, "receiving file");
+        let mut requested = offset;
+        while offset < file.size {
+            // The next window is requested once half of the previous one is received, so the
+            // sending node does not wait for it.
+            if requested < file.size && requested - offset <= WINDOW_SIZE / 2 {
+                let length = WINDOW_SIZE.min(file.size - requested);
+                let request = protocol::Message::TransferRequest {
+                    id: id.to_owned(),
+                    index,
+                    offset: requested,
+                    length,
+                };
+                send_message(net_tx, request).await?;
+                requested += length;
+            }
+
+            match recv_event(events).await? {
+                Event::Chunk { index: chunk_index, offset: chunk_offset, data }
+                    if chunk_index == index =>
+                {
+                    if chunk_offset != offset {
+                        return Err(Error::TransferFailed {
+                            error: format!("expected chunk at {}, got {}", offset, chunk_offset),
+                        }.into());
+                    }
+
+                    let bytes = BASE64.decode(data)
+                        .map_err(|error| Error::TransferFailed { error: error.to_string() })?;
+                    writer.write_all(&bytes).await.map_err(Error::from)?;
+                    offset += bytes.len() as u64;
+                },
+                Event::Offer { files } => return Err(Stop::Offered(files)),
+                event => tracing::debug!(id, ?event, "ignoring unexpected event while receiving"),
+            }
+        }
+
+        writer.sync_all().await.map_err(Error::from)?;
+        drop(writer);
+
+        if checksum(&part_path).await? == file.checksum {
+            fs::rename(&part_path, &file_path).await.map_err(Error::from)?;
+            tracing::debug!(id, ?file_path, "file received");
+            return Ok(());
+        }
+
+        tracing::warn!(id, ?file_path, "checksum mismatch, receiving file again");
+        fs::remove_file(&part_path).await.map_err(Error::from)?;
+    }
+
+    Err(Error::TransferFailed {
+        error: format!("checksum of {} does not match", file_path.display()),
+    }.into())
+}
+
+/// Wait for the next event of a transfer while receiving files.
+///
+/// # Args
+///
+/// `events`:  Receiving end of the channel for the transfer's events.
+async fn recv_event(events: &mut mpsc::Receiver<Event>) -> std::result::Result<Event, Stop> {
+    match tokio::time::timeout(IDLE_TIMEOUT, events.recv()).await {
+        Ok(Some(event)) => Ok(event),
+        Ok(None) => Err(Stop::Closed),
+        Err(_) => Err(Stop::Error(Error::TransferTimeout)),
+    }
+}
+
+/// Wait for the next event of a transfer.
+///
+/// # Args
+///
+/// `events`:  Receiving end of the channel for the transfer's events.
+///
+/// # Errors
+///
+/// [`Error::TransferTimeout`] if an event is not received within [`IDLE_TIMEOUT`].
+///
+/// [`Error::TransferFailed`] if the channel is closed.
+async fn next_event(events: &mut mpsc::Receiver<Event>) -> Result<Event> {
+    match recv_event(events).await {
+        Ok(event) => Ok(event),
+        Err(Stop::Error(error)) => Err(error),
+        Err(_) => Err(Error::TransferFailed { error: String::from("transfer abandoned") }),
+    }
+}
+
+/// Send a message to the other node, waiting for it to be written to the network.
+///
+/// # Args
+///
+/// `net_tx`:  Transmission end of the channel used to send messages over the network.
+///
+/// `msg`:  The message to send.
+///
+/// # Errors
+///
+/// [`Error::TransferTimeout`] if the message is not sent within [`IDLE_TIMEOUT`] (e.g. while a
+/// client is reconnecting).
+///
+/// [`Error::Disconnected`] or [`Error::NetworkSend`] if the message cannot be sent.
+pub(super) async fn send_message(
+    net_tx: &mpsc::Sender<OutgoingMessage>,
+    msg: protocol::Message,
+) -> Result<()> {
+    let written = queue_message(net_tx, msg).await?;
+    wait_for_write(written).await
+}
+
+/// Queue a message to be sent to the other node.
+///
+/// Returns the receiving end of the channel for the result of writing the message to the network
+/// (see [`wait_for_write`]).
+///
+/// # Args
+///
+/// `net_tx`:  Transmission end of the channel used to send messages over the network.
+///
+/// `msg`:  The message to send.
+///
+/// # Errors
+///
+/// [`Error::TransferTimeout`] if the message is not queued within [`IDLE_TIMEOUT`].
+///
+/// [`Error::NetworkChannelSend`] if the network actor stopped.
+async fn queue_message(
+    net_tx: &mpsc::Sender<OutgoingMessage>,
+    msg: protocol::Message,
+) -> Result<oneshot::Receiver<Result<()>>> {
+    let (tx, rx) = oneshot::channel();
+    let outgoing = OutgoingMessage {
+        msg,
+        worker: None,
+        reply_to: 0,
+        response: tx,
+    };
+
+    tokio::time::timeout(IDLE_TIMEOUT, net_tx.send(outgoing)).await
+        .map_err(|_| Error::TransferTimeout)??;
+    Ok(rx)
+}
+
+/// Wait for a queued message to be written to the network.
+///
+/// # Args
+///
+/// `written`:  Receiving end of the channel for the result of writing the message.
+///
+/// # Errors
+///
+/// [`Error::TransferTimeout`] if the message is not written within [`IDLE_TIMEOUT`].
+///
+/// [`Error::Disconnected`] or [`Error::NetworkSend`] if the message cannot be sent.
+async fn wait_for_write(written: oneshot::Receiver<Result<()>>) -> Result<()> {
+    tokio::time::timeout(IDLE_TIMEOUT, written).await
+        .map_err(|_| Error::TransferTimeout)??
+}
+
+/// List the files in a folder, including the files in its subfolders.
+///
+/// # Args
+///
+/// `location`:  The location of the folder.
+///
+/// `locate`:  Returns the path of the folder.
+///
+/// # Errors
+///
+/// [`Error::InvalidMediaLocation`] if the location is not in one of the media folders.
+///
+/// [`Error::StdIo`] if the folder or one of its files cannot be read.
+async fn list_files(location: &MediaLocation, locate: Locate) -> Result<Vec<TransferFile>> {
+    let root = locate(location)
+        .ok_or_else(|| Error::InvalidMediaLocation { location: location.clone() })?;
+
+    let location = location.clone();
+    task::spawn_blocking(move || {
+        let mut paths = Vec::new();
+        collect_files(&root, &mut paths)?;
+        paths.sort();
+
+        paths.into_iter()
+            .map(|file_path| {
+                let relative = file_path.strip_prefix(&root)
+                    .expect("file should be in the folder being listed");
+                Ok(TransferFile {
+                    location: child_location(&location, relative)?,
+                    size: std::fs::metadata(&file_path)?.len(),
+                    checksum: integrity::checksum(&file_path)?.to_hex().to_string(),
+                })
+            })
+            .collect()
+    }).await?
+}
+
+/// Collect the paths of the files in a folder and its subfolders.
+fn collect_files(folder: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), paths)?;
+        } else {
+            paths.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the location of a file in a folder.
+///
+/// # Args
+///
+/// `location`:  The location of the folder.
+///
+/// `relative`:  The path of the file relative to the folder.
+///
+/// # Errors
+///
+/// [`Error::InvalidMediaLocation`] if the folder location is [`MediaLocation::Deleted`].
+fn child_location(location: &MediaLocation, relative: &Path) -> Result<MediaLocation> {
+    match location {
+        MediaLocation::Inbox(folder) => Ok(MediaLocation::Inbox(folder.join(relative))),
+        MediaLocation::Library(folder) => Ok(MediaLocation::Library(folder.join(relative))),
+        MediaLocation::Archive(folder) => Ok(MediaLocation::Archive(folder.join(relative))),
+        MediaLocation::Deleted => Err(Error::InvalidMediaLocation { location: location.clone() }),
+    }
+}
+
+/// Returns the path a received file is written to.
+///
+/// # Args
+///
+/// `location`:  The location of the file offered by the other node.
+///
+/// `locate`:  Returns the path of the location.
+///
+/// # Errors
+///
+/// [`Error::InvalidMediaLocation`] if the location is not a relative path within one of the
+/// media folders. Locations that would write outside of the media folders (e.g. `../file`) are
+/// rejected.
+fn target_path(location: &MediaLocation, locate: Locate) -> Result<PathBuf> {
+    check_location(location)?;
+    locate(location)
+        .ok_or_else(|| Error::InvalidMediaLocation { location: location.clone() })
+}
+
+/// Check that a location sent by the other node stays within its media folder.
+///
+/// # Args
+///
+/// `location`:  The location sent by the other node.
+///
+/// # Errors
+///
+/// [`Error::InvalidMediaLocation`] if the location is not a relative path made up of only normal
+/// components (e.g. `/etc` or `../file`).
+pub(super) fn check_location(location: &MediaLocation) -> Result<()> {
+    let relative = relative_path(location);
+    let is_valid = relative.components().count() > 0
+        && relative.components().all(|c| matches!(c, Component::Normal(_)));
+    if !is_valid {
+        return Err(Error::InvalidMediaLocation { location: location.clone() });
+    }
+    Ok(())
+}
+
+/// Returns the path of a location relative to its media folder.
+fn relative_path(location: &MediaLocation) -> &Path {
+    match location {
+        MediaLocation::Inbox(path)
+        | MediaLocation::Library(path)
+        | MediaLocation::Archive(path) => path.as_path(),
+        MediaLocation::Deleted => Path::new(""),
+    }
+}
+
+/// Returns the percentage of `size` that `sent` is.
+fn percent(sent: u64, size: u64) -> u8 {
+    if size == 0 {
+        return 100;
+    }
+    u8::try_from(sent.min(size) * 100 / size).unwrap_or(100)
+}
+
+/// Returns the path of the partial file used while receiving a file.
+fn part_path(file_path: &Path) -> PathBuf {
+    let mut name = file_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(PART_EXTENSION);
+    file_path.with_file_name(name)
+}
+
+/// Whether a file was already received.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] if the existing file cannot be read.
+async fn is_received(file_path: &Path, file: &TransferFile) -> Result<bool> {
+    match fs::metadata(file_path).await {
+        Ok(metadata) if metadata.len() == file.size => {
+            Ok(checksum(file_path).await? == file.checksum)
+        },
+        _ => Ok(false),
+    }
+}
+
+/// Computes the checksum of a file as a hex string.
+///
+/// # Errors
+///
+/// [`Error::StdIo`] if the file cannot be read.
+async fn checksum(file_path: &Path) -> Result<String> {
+    let file_path = file_path.to_owned();
+    task::spawn_blocking(move || integrity::checksum(&file_path))
+        .await?
+        .map(|hash| hash.to_hex().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+
+    use crate::test_utils::TempDir;
+
+    use super::*;
+
+    /// The temp folder used as the inbox of the sending node.
+    const SENDER_INBOX: &str = "artie.test.net.transfer.sender";
+
+    /// The temp folder used as the inbox of the receiving node.
+    const RECEIVER_INBOX: &str = "artie.test.net.transfer.receiver";
+
+    /// The size of the video file sent by the tests, so it is sent in multiple chunks.
+    const VIDEO_SIZE: usize = CHUNK_SIZE * 2 + 1000;
+
+    fn locate_sender(location: &MediaLocation) -> Option<PathBuf> {
+        match location {
+            MediaLocation::Inbox(path) => Some(env::temp_dir().join(SENDER_INBOX).join(path)),
+            _ => None,
+        }
+    }
+
+    fn locate_receiver(location: &MediaLocation) -> Option<PathBuf> {
+        match location {
+            MediaLocation::Inbox(path) => Some(env::temp_dir().join(RECEIVER_INBOX).join(path)),
+            _ => None,
+        }
+    }
+
+    fn video() -> Vec<u8> {
+        (0..VIDEO_SIZE).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Create the folder sent by a test, returning the temp folders of both nodes.
+    fn setup(name: &str) -> (TempDir, TempDir) {
+        let sender = TempDir::new(Path::new(SENDER_INBOX).join(name));
+        let receiver = TempDir::new(Path::new(RECEIVER_INBOX).join(name));
+        fs::create_dir_all(sender.path().join("logs")).unwrap();
+        fs::write(sender.path().join("title_t00.mkv"), video()).unwrap();
+        fs::write(sender.path().join("logs/copy.log"), "copy complete").unwrap();
+        (sender, receiver)
+    }
+
+    /// Deliver the messages sent by one node to the other, passing each chunk to `tamper`.
+    fn wire(
+        mut net_rx: mpsc::Receiver<OutgoingMessage>,
+        events_tx: mpsc::Sender<Event>,
+        mut tamper: impl FnMut(u64, &mut String) + Send + 'static,
+    ) {
+        task::spawn(async move {
+            while let Some(outgoing) = net_rx.recv().await {
+                let event = match outgoing.msg {
+                    protocol::Message::TransferOffer { files, .. } => Event::Offer { files },
+                    protocol::Message::TransferRequest { index, offset, length, .. } => {
+                        Event::Request { index, offset, length }
+                    },
+                    protocol::Message::TransferChunk { index, offset, mut data, .. } => {
+                        tamper(offset, &mut data);
+                        Event::Chunk { index, offset, data }
+                    },
+                    protocol::Message::TransferComplete { error, .. } => Event::Complete { error },
+                    msg => panic!("unexpected message {:?}", msg),
+                };
+                outgoing.response.send(Ok(())).unwrap();
+                if events_tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Pull the folder for a test from the sending node, returning the result for each node.
+    fn pull(
+        name: &str,
+        tamper: impl FnMut(u64, &mut String) + Send + 'static,
+    ) -> (Result<()>, Result<()>) {
+        pull_with_progress(name, tamper, None)
+    }
+
+    /// Pull the folder for a test, reporting the progress of the sending node to `progress`.
+    fn pull_with_progress(
+        name: &str,
+        tamper: impl FnMut(u64, &mut String) + Send + 'static,
+        progress: Option<mpsc::Sender<Progress>>,
+    ) -> (Result<()>, Result<()>) {
+        task::block_on(async {
+            let (sender_net_tx, sender_net_rx) = mpsc::channel(EVENT_BUFFER_SIZE);
+            let (receiver_net_tx, receiver_net_rx) = mpsc::channel(EVENT_BUFFER_SIZE);
+            let (sender_events_tx, sender_events) = mpsc::channel(EVENT_BUFFER_SIZE);
+            let (receiver_events_tx, receiver_events) = mpsc::channel(EVENT_BUFFER_SIZE);
+            wire(sender_net_rx, receiver_events_tx, tamper);
+            wire(receiver_net_rx, sender_events_tx, |_, _| {});
+
+            let id = new_id();
+            let (tx, rx) = oneshot::channel();
+            task::spawn(receive_with(
+                id.clone(),
+                None,
+                receiver_net_tx,
+                receiver_events,
+                Some(tx),
+                locate_receiver,
+            ));
+            let location = MediaLocation::Inbox(PathBuf::from(name));
+            let sent = send_with(
+                id,
+                location,
+                sender_net_tx,
+                sender_events,
+                progress,
+                locate_sender,
+            ).await;
+            (sent, rx.await.unwrap())
+        })
+    }
+
+    /// Record the offset of each chunk sent.
+    fn record(offsets: &Arc<Mutex<Vec<u64>>>) -> impl FnMut(u64, &mut String) + use<> {
+        let offsets = offsets.clone();
+        move |offset, _| offsets.lock().unwrap().push(offset)
+    }
+
+    /// Change the first byte of a chunk.
+    fn corrupt(data: &mut String) {
+        let mut bytes = BASE64.decode(&data).unwrap();
+        bytes[0] = bytes[0].wrapping_add(1);
+        *data = BASE64.encode(bytes);
+    }
+
+    #[test]
+    fn test_list_files() {
+        let (sender, _receiver) = setup("list");
+        let location = MediaLocation::Inbox(PathBuf::from("list"));
+        let files = task::block_on(list_files(&location, locate_sender)).unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].location, MediaLocation::Inbox(PathBuf::from("list/logs/copy.log")));
+        assert_eq!(files[0].size, 13);
+        assert_eq!(files[1].location, MediaLocation::Inbox(PathBuf::from("list/title_t00.mkv")));
+        assert_eq!(files[1].size, VIDEO_SIZE as u64);
+        assert_eq!(files[1].checksum, blake3::hash(&video()).to_hex().to_string());
+
+        let missing = MediaLocation::Inbox(PathBuf::from("missing"));
+        assert!(task::block_on(list_files(&missing, locate_sender)).is_err());
+        drop(sender);
+    }
+
+    #[test]
+    fn test_transfer() {
+        let (_sender, receiver) = setup("transfer");
+        let offsets = Arc::new(Mutex::new(Vec::new()));
+        let (sent, received) = pull("transfer", record(&offsets));
+        assert!(sent.is_ok());
+        assert!(received.is_ok());
+
+        assert_eq!(fs::read(receiver.path().join("title_t00.mkv")).unwrap(), video());
+        let log = fs::read_to_string(receiver.path().join("logs/copy.log")).unwrap();
+        assert_eq!(log, "copy complete");
+        assert!(!receiver.path().join("title_t00.mkv.part").exists());
+        assert_eq!(offsets.lock().unwrap().len(), 4);
+
+        // Files that were already received are skipped.
+        offsets.lock().unwrap().clear();
+        let (sent, received) = pull("transfer", record(&offsets));
+        assert!(sent.is_ok());
+        assert!(received.is_ok());
+        assert!(offsets.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_progress() {
+        let (_sender, _receiver) = setup("progress");
+        let (progress_tx, mut progress_rx) = mpsc::channel(EVENT_BUFFER_SIZE);
+        let (sent, received) = pull_with_progress("progress", |_, _| {}, Some(progress_tx));
+        assert!(sent.is_ok());
+        assert!(received.is_ok());
+
+        let mut updates = Vec::new();
+        while let Ok(progress) = progress_rx.try_recv() {
+            updates.push(progress);
+        }
+        assert_eq!(updates.len(), 4);
+        assert_eq!(updates[0].file, "copy.log");
+        assert_eq!(updates[1].file_sent, CHUNK_SIZE as u64);
+        assert_eq!(updates[1].file_percent(), 49);
+        assert_eq!(updates[3], Progress {
+            file: String::from("title_t00.mkv"),
+            file_sent: VIDEO_SIZE as u64,
+            file_size: VIDEO_SIZE as u64,
+            sent: VIDEO_SIZE as u64 + 13,
+            size: VIDEO_SIZE as u64 + 13,
+        });
+        assert_eq!(updates[3].percent(), 100);
+    }
+
+    #[test]
+    fn test_window() {
+        let (sender, receiver) = setup("window");
+        let large = vec![7; WINDOW_SIZE as usize * 2 + 10];
+        fs::write(sender.path().join("title_t00.mkv"), &large).unwrap();
+
+        let offsets = Arc::new(Mutex::new(Vec::new()));
+        let (sent, received) = pull("window", record(&offsets));
+        assert!(sent.is_ok());
+        assert!(received.is_ok());
+        assert_eq!(fs::read(receiver.path().join("title_t00.mkv")).unwrap(), large);
+
+        // Each chunk is sent once, in order, even though the file is requested a window at a time.
+        let expected = std::iter::once(0)
+            .chain((0..large.len() as u64).step_by(CHUNK_SIZE))
+            .collect::<Vec<_>>();
+        assert_eq!(*offsets.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_resume() {
+        let (_sender, receiver) = setup("resume");
+        fs::create_dir_all(receiver.path()).unwrap();
+        let part = &video()[..CHUNK_SIZE + 10];
+        fs::write(receiver.path().join("title_t00.mkv.part"), part).unwrap();
+
+        let offsets = Arc::new(Mutex::new(Vec::new()));
+        let (sent, received) = pull("resume", record(&offsets));
+        assert!(sent.is_ok());
+        assert!(received.is_ok());
+
+        assert_eq!(fs::read(receiver.path().join("title_t00.mkv")).unwrap(), video());
+        let resumed = CHUNK_SIZE as u64 + 10;
+        assert_eq!(*offsets.lock().unwrap(), vec![0, resumed, resumed + CHUNK_SIZE as u64]);
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let (_sender, receiver) = setup("mismatch");
+        let mut corrupted = false;
+        let (sent, received) = pull("mismatch", move |offset, data| {
+            if offset > 0 && !corrupted {
+                corrupted = true;
+                corrupt(data);
+            }
+        });
+        assert!(sent.is_ok());
+        assert!(received.is_ok());
+        assert_eq!(fs::read(receiver.path().join("title_t00.mkv")).unwrap(), video());
+
+        let (_sender, receiver) = setup("mismatch_again");
+        let (sent, received) = pull("mismatch_again", |offset, data| {
+            if offset > 0 {
+                corrupt(data);
+            }
+        });
+        assert!(matches!(sent, Err(Error::TransferFailed { .. })));
+        assert!(matches!(received, Err(Error::TransferFailed { .. })));
+        assert!(!receiver.path().join("title_t00.mkv").exists());
+    }
+
+    #[test]
+    fn test_target_path() {
+        let location = MediaLocation::Inbox(PathBuf::from("movie/title_t00.mkv"));
+        let expected = env::temp_dir().join(RECEIVER_INBOX).join("movie/title_t00.mkv");
+        assert_eq!(target_path(&location, locate_receiver).unwrap(), expected);
+
+        let invalid = [
+            MediaLocation::Inbox(PathBuf::from("../etc/passwd")),
+            MediaLocation::Inbox(PathBuf::from("/etc/passwd")),
+            MediaLocation::Inbox(PathBuf::new()),
+            MediaLocation::Library(PathBuf::from("movie.mkv")),
+            MediaLocation::Deleted,
+        ];
+        for location in invalid {
+            assert!(target_path(&location, locate_receiver).is_err(), "{:?}", location);
+        }
+    }
+
+    #[test]
+    fn test_percent() {
+        assert_eq!(percent(0, 0), 100);
+        assert_eq!(percent(1, 3), 33);
+        assert_eq!(percent(5, 4), 100);
+    }
+
+    #[test]
+    fn test_part_path() {
+        let file_path = Path::new("/inbox/movie/title_t00.mkv");
+        assert_eq!(part_path(file_path), PathBuf::from("/inbox/movie/title_t00.mkv.part"));
+    }
+
+    #[test]
+    fn test_child_location() {
+        let folder = MediaLocation::Archive(PathBuf::from("movie"));
+        let location = child_location(&folder, Path::new("logs/copy.log")).unwrap();
+        assert_eq!(location, MediaLocation::Archive(PathBuf::from("movie/logs/copy.log")));
+        assert!(child_location(&MediaLocation::Deleted, Path::new("a")).is_err());
+    }
+
+    #[test]
+    fn test_storage_mode() {
+        #[derive(Deserialize)]
+        struct Config {
+            storage: HashMap<String, StorageMode>,
+        }
+
+        let config: Config = toml::from_str(r#"
+            [storage]
+            "192.168.1.20:7878" = "shared"
+            "192.168.1.30:7878" = "transfer"
+        "#).unwrap();
+        assert_eq!(config.storage["192.168.1.20:7878"], StorageMode::Shared);
+        assert_eq!(config.storage["192.168.1.30:7878"], StorageMode::Transfer);
+        assert_eq!(StorageMode::default(), StorageMode::Shared);
+    }
+
+    #[test]
+    fn test_forward() {
+        task::block_on(async {
+            let mut transfers = Transfers::default();
+            let mut events = transfers.register("a");
+            assert!(transfers.is_running("a"));
+            assert!(!transfers.is_running("b"));
+
+            transfers.forward("a", Event::Request { index: 1, offset: 2, length: 3 });
+            transfers.forward("b", Event::Complete { error: None });
+            assert!(matches!(
+                events.recv().await,
+                Some(Event::Request { index: 1, offset: 2, length: 3 }),
+            ));
+
+            // Events are dropped instead of waiting when too many are queued.
+            for _ in 0..EVENT_BUFFER_SIZE + 1 {
+                transfers.forward("a", Event::Complete { error: None });
+            }
+            assert!(transfers.is_running("a"));
+            for _ in 0..EVENT_BUFFER_SIZE {
+                assert!(matches!(events.try_recv(), Ok(Event::Complete { error: None })));
+            }
+            assert!(events.try_recv().is_err());
+
+            drop(events);
+            assert!(!transfers.is_running("a"));
+            transfers.forward("a", Event::Complete { error: None });
+            assert!(transfers.events.is_empty());
+        });
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Desktop notifications.
+//!
+//! Notifications are shown using the GTK application (see [`gio::Notification`]), so they are only
+//! available while the UI is running. The notification is sent from the GTK main loop since the
+//! application cannot be used from other threads. When running headless, the notification is
+//! logged and dropped.
+
+use gtk::gio::prelude::*;
+use gtk::gio;
+use gtk::glib;
+
+use crate::Result;
+
+use super::{Event, NotificationSink};
+
+/// Sink that shows desktop notifications.
+pub struct DesktopSink;
+
+impl NotificationSink for DesktopSink {
+    async fn send(&self, event: &Event) -> Result<()> {
+        let title = event.subject();
+        let body = event.message();
+        let priority = if event.is_failure() {
+            gio::NotificationPriority::High
+        } else {
+            gio::NotificationPriority::Normal
+        };
+
+        glib::MainContext::default().invoke(move || {
+            let Some(app) = gio::Application::default().filter(|a| a.is_registered()) else {
+                tracing::debug!(title, "desktop notification skipped, UI is not running");
+                return;
+            };
+
+            let notification = gio::Notification::new(title);
+            notification.set_body(Some(&body));
+            notification.set_priority(priority);
+            app.send_notification(None, &notification);
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Email notifications.
+//!
+//! Emails are sent as plain text using an SMTP server. The connection is upgraded using STARTTLS
+//! before signing in, so the server needs to support it (most submission servers on port 587 do).
+
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+
+use crate::{Error, Result};
+
+use super::{Event, NotificationSink};
+
+/// Sink that emails notifications.
+pub struct EmailSink {
+    /// The transport used to send the emails.
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+
+    /// The address the emails are sent from.
+    from: Mailbox,
+
+    /// The addresses the emails are sent to.
+    to: Vec<Mailbox>,
+}
+
+impl EmailSink {
+    /// Creates a new sink.
+    ///
+    /// A connection to the server is not made until the first email is sent.
+    ///
+    /// # Args
+    ///
+    /// `host`:  The host name of the SMTP server.
+    ///
+    /// `port`:  The port of the SMTP server.
+    ///
+    /// `username`:  The user name used to sign in. Does not sign in if empty.
+    ///
+    /// `password`:  The password used to sign in.
+    ///
+    /// `from`:  The address the emails are sent from (e.g. `Artie <artie@example.com>`).
+    ///
+    /// `to`:  The addresses the emails are sent to.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidEmail`] if an address cannot be parsed or there are no addresses to send
+    /// the emails to.
+    ///
+    /// [`Error::Smtp`] if the transport cannot be created.
+    pub fn new(
+        host: &str,
+        port: u16,
+        username: &str,
+        password: &str,
+        from: &str,
+        to: &[String],
+    ) -> Result<Self> {
+        let from = parse_mailbox(from)?;
+        let to = to.iter()
+            .map(|address| parse_mailbox(address))
+            .collect::<Result<Vec<_>>>()?;
+        if to.is_empty() {
+            return Err(Error::InvalidEmail {
+                address: String::new(),
+                error: String::from("no recipients"),
+            });
+        }
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+            .port(port);
+        if !username.is_empty() {
+            let credentials = Credentials::new(username.to_owned(), password.to_owned());
+            builder = builder.credentials(credentials);
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            to,
+        })
+    }
+
+    /// Builds the email for an event.
+    ///
+    /// # Args
+    ///
+    /// `event`:  The event to build the email for.
+    fn message(&self, event: &Event) -> lettre::Message {
+        let mut builder = lettre::Message::builder()
+            .from(self.from.clone())
+            .subject(format!("Artie: {}", event.subject()))
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+
+        builder.body(event.message())
+            .expect("email should have a sender and recipients")
+    }
+}
+
+impl NotificationSink for EmailSink {
+    async fn send(&self, event: &Event) -> Result<()> {
+        self.transport.send(self.message(event)).await?;
+        Ok(())
+    }
+}
+
+/// Parses an email address.
+///
+/// # Args
+///
+/// `address`:  The address, optionally with a display name (e.g. `Artie <artie@example.com>`).
+///
+/// # Errors
+///
+/// [`Error::InvalidEmail`] if the address cannot be parsed.
+fn parse_mailbox(address: &str) -> Result<Mailbox> {
+    address.parse()
+        .map_err(|error: lettre::address::AddressError| Error::InvalidEmail {
+            address: address.to_owned(),
+            error: error.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message() {
+        let sink = EmailSink::new(
+            "smtp.example.com",
+            587,
+            "artie",
+            "secret",
+            "Artie <artie@example.com>",
+            &[String::from("me@example.com"), String::from("you@example.com")],
+        ).unwrap();
+
+        let event = Event::CopyCompleted {
+            drive: String::from("SN-1"),
+            title: String::from("Movie (2001)"),
+        };
+        let message = String::from_utf8(sink.message(&event).formatted()).unwrap();
+
+        assert!(message.contains("From: Artie <artie@example.com>"));
+        assert!(message.contains("To: me@example.com, you@example.com"));
+        assert!(message.contains("Subject: Artie: Copy completed"));
+        assert!(message.contains("Finished copying Movie (2001) on drive SN-1."));
+    }
+
+    #[test]
+    fn test_invalid_address() {
+        let result = EmailSink::new(
+            "smtp.example.com", 587, "", "", "artie@example.com", &[String::from("not an address")],
+        );
+        assert!(matches!(
+            result,
+            Err(Error::InvalidEmail { address, .. }) if address == "not an address",
+        ));
+
+        let result = EmailSink::new("smtp.example.com", 587, "", "", "artie@example.com", &[]);
+        assert!(matches!(result, Err(Error::InvalidEmail { .. })));
+    }
+}
@@ -0,0 +1,612 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Sends notifications when operations complete or fail.
+//!
+//! Copying a disc can take most of an hour, so the user is notified when it is time to swap discs
+//! instead of having to watch the window. Events (see [`Event`]) are published to the notify actor
+//! which forwards them to each of the configured sinks:
+//!
+//! - [`webhook::WebhookSink`] - Posts the event as JSON to a URL.
+//! - [`push::NtfySink`] - Sends a push notification using [ntfy](https://ntfy.sh).
+//! - [`push::GotifySink`] - Sends a push notification using [Gotify](https://gotify.net).
+//! - [`email::EmailSink`] - Sends an email using an SMTP server.
+//! - [`desktop::DesktopSink`] - Shows a desktop notification using the GTK application.
+//!
+//! Each sink can be limited to specific events using [`SinkSettings::events`]. For example, the
+//! following sends a push notification when a copy completes or fails, and posts every event to a
+//! webhook:
+//!
+//! ```toml
+//! [[notify.sinks]]
+//! kind = "ntfy"
+//! url = "https://ntfy.sh/artie-rips"
+//! events = ["copy_completed", "copy_failed"]
+//!
+//! [[notify.sinks]]
+//! kind = "webhook"
+//! url = "http://localhost:8080/artie"
+//! ```
+//!
+//! # Actor
+//!
+//! The notify actor is created by calling [`init`] during application startup. Events are
+//! published using [`publish`]. Each notification is sent on its own task so a slow sink does not
+//! delay the others. Failures to send a notification are logged, but are not reported to the
+//! publisher.
+//!
+//! Events are currently published when a copy operation completes or fails, when titles are
+//! cataloged, and when the connection to a worker is lost. The transcode events are reserved for
+//! the transcode pipeline and are not published yet.
+
+pub mod desktop;
+pub mod email;
+pub mod push;
+pub mod webhook;
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::oneshot;
+
+use crate::{Error, Result};
+use crate::actor::{self, Response};
+use crate::bus;
+use crate::task;
+
+use desktop::DesktopSink;
+use email::EmailSink;
+use push::{GotifySink, NtfySink};
+use webhook::WebhookSink;
+
+/// Handle used to communicate with the notify actor.
+pub type Handle = actor::Handle<Message>;
+
+/// Messages used to send requests to the notify actor.
+#[derive(Debug)]
+pub enum Message {
+    /// Send the notifications for an event.
+    Publish {
+        event: Event,
+    },
+
+    /// Replace the sinks with the ones in the settings.
+    Configure {
+        settings: Settings,
+        response: Response<()>,
+    },
+}
+
+/// Specifies the events notifications are sent for.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A disc was copied successfully.
+    CopyCompleted {
+        /// The serial number of the drive the disc was copied with.
+        drive: String,
+
+        /// The movie or show title.
+        title: String,
+    },
+
+    /// Copying a disc failed.
+    CopyFailed {
+        /// The serial number of the drive the disc was being copied with.
+        drive: String,
+
+        /// The movie or show title if the copy operation was created before the failure.
+        title: Option<String>,
+
+        /// Describes the failure.
+        error: String,
+    },
+
+    /// A video was transcoded successfully.
+    TranscodeCompleted {
+        /// The movie or show title.
+        title: String,
+    },
+
+    /// Transcoding a video failed.
+    TranscodeFailed {
+        /// The movie or show title.
+        title: String,
+
+        /// Describes the failure.
+        error: String,
+    },
+
+    /// The titles copied from a disc were cataloged.
+    CatalogSaved {
+        /// Describes the copy operation the titles were copied by.
+        title: String,
+
+        /// The number of titles.
+        count: usize,
+    },
+
+    /// The connection to a worker node was lost.
+    WorkerDisconnected {
+        /// The address of the worker node.
+        addr: String,
+    },
+}
+
+impl Event {
+    /// Returns the kind of event.
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Event::CopyCompleted { .. } => EventKind::CopyCompleted,
+            Event::CopyFailed { .. } => EventKind::CopyFailed,
+            Event::TranscodeCompleted { .. } => EventKind::TranscodeCompleted,
+            Event::TranscodeFailed { .. } => EventKind::TranscodeFailed,
+            Event::CatalogSaved { .. } => EventKind::CatalogSaved,
+            Event::WorkerDisconnected { .. } => EventKind::WorkerDisconnected,
+        }
+    }
+
+    /// Returns `true` if the event reports a failure.
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            Event::CopyFailed { .. }
+                | Event::TranscodeFailed { .. }
+                | Event::WorkerDisconnected { .. },
+        )
+    }
+
+    /// Returns the short description of the event used as the notification title.
+    pub fn subject(&self) -> &'static str {
+        match self {
+            Event::CopyCompleted { .. } => "Copy completed",
+            Event::CopyFailed { .. } => "Copy failed",
+            Event::TranscodeCompleted { .. } => "Transcode completed",
+            Event::TranscodeFailed { .. } => "Transcode failed",
+            Event::CatalogSaved { .. } => "Titles cataloged",
+            Event::WorkerDisconnected { .. } => "Worker disconnected",
+        }
+    }
+
+    /// Returns the description of the event used as the notification body.
+    pub fn message(&self) -> String {
+        match self {
+            Event::CopyCompleted { drive, title } => {
+                format!("Finished copying {} on drive {}. The disc is ready to swap.", title, drive)
+            },
+            Event::CopyFailed { drive, title: Some(title), error } => {
+                format!("Copying {} on drive {} failed: {}", title, drive, error)
+            },
+            Event::CopyFailed { drive, title: None, error } => {
+                format!("Copying the disc on drive {} failed: {}", drive, error)
+            },
+            Event::TranscodeCompleted { title } => {
+                format!("Finished transcoding {}.", title)
+            },
+            Event::TranscodeFailed { title, error } => {
+                format!("Transcoding {} failed: {}", title, error)
+            },
+            Event::CatalogSaved { title, count } => {
+                format!("Cataloged {} titles from {}.", count, title)
+            },
+            Event::WorkerDisconnected { addr } => {
+                format!("Lost the connection to the worker at {}.", addr)
+            },
+        }
+    }
+}
+
+/// Specifies the kinds of events (see [`Event`]).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    CopyCompleted,
+    CopyFailed,
+    TranscodeCompleted,
+    TranscodeFailed,
+    CatalogSaved,
+    WorkerDisconnected,
+}
+
+impl EventKind {
+    /// All of the event kinds.
+    pub const ALL: [EventKind; 6] = [
+        EventKind::CopyCompleted,
+        EventKind::CopyFailed,
+        EventKind::TranscodeCompleted,
+        EventKind::TranscodeFailed,
+        EventKind::CatalogSaved,
+        EventKind::WorkerDisconnected,
+    ];
+
+    /// Returns the name of the event kind displayed to the user.
+    pub fn label(&self) -> &'static str {
+        match self {
+            EventKind::CopyCompleted => "Copy completed",
+            EventKind::CopyFailed => "Copy failed",
+            EventKind::TranscodeCompleted => "Transcode completed",
+            EventKind::TranscodeFailed => "Transcode failed",
+            EventKind::CatalogSaved => "Titles cataloged",
+            EventKind::WorkerDisconnected => "Worker disconnected",
+        }
+    }
+}
+
+/// Notification settings.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Settings {
+    /// The sinks the notifications are sent to.
+    ///
+    /// Notifications are disabled if empty.
+    #[serde(default)]
+    pub sinks: Vec<SinkSettings>,
+}
+
+/// The settings of a notification sink.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SinkSettings {
+    /// The type of sink and its settings.
+    #[serde(flatten)]
+    pub kind: SinkKind,
+
+    /// The events sent to the sink.
+    ///
+    /// All events are sent if empty.
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+}
+
+/// Specifies the types of notification sinks and their settings.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SinkKind {
+    /// Post the event as JSON to a URL.
+    Webhook {
+        /// The URL the events are posted to.
+        url: String,
+    },
+
+    /// Send a push notification to an ntfy topic.
+    Ntfy {
+        /// The URL of the topic (e.g. `https://ntfy.sh/my-topic`).
+        url: String,
+
+        /// The access token used if the topic is protected.
+        #[serde(default)]
+        token: String,
+    },
+
+    /// Send a push notification to a Gotify server.
+    Gotify {
+        /// The base URL of the server.
+        url: String,
+
+        /// The application token.
+        token: String,
+    },
+
+    /// Send an email using an SMTP server.
+    ///
+    /// The connection is secured using STARTTLS.
+    Email {
+        /// The host name of the SMTP server.
+        host: String,
+
+        /// The port of the SMTP server.
+        #[serde(default = "SinkKind::default_smtp_port")]
+        port: u16,
+
+        /// The user name used to sign in to the SMTP server.
+        #[serde(default)]
+        username: String,
+
+        /// The password used to sign in to the SMTP server.
+        #[serde(default)]
+        password: String,
+
+        /// The address the emails are sent from.
+        from: String,
+
+        /// The addresses the emails are sent to.
+        to: Vec<String>,
+    },
+
+    /// Show a desktop notification.
+    ///
+    /// Only available when the UI is running.
+    Desktop,
+}
+
+impl SinkKind {
+    /// The SMTP submission port is used if not specified in the config.
+    fn default_smtp_port() -> u16 {
+        587
+    }
+}
+
+/// Sends notifications to a destination.
+pub trait NotificationSink {
+    /// Sends the notification for an event.
+    ///
+    /// # Args
+    ///
+    /// `event`:  The event to send the notification for.
+    ///
+    /// # Errors
+    ///
+    /// The errors will vary by the sink. Refer to the specific sinks for more information.
+    fn send(&self, event: &Event) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Publishes an event to the notify actor.
+///
+/// This does not wait for the notifications to be sent. A failure to publish the event is logged.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the notify actor.
+///
+/// `event`:  The event to publish.
+pub async fn publish(bus: &bus::Handle, event: Event) {
+    let kind = event.kind();
+    if let Err(error) = bus.send(Message::Publish { event }).await {
+        tracing::warn!(?kind, ?error, "failed to publish notification event");
+    }
+}
+
+/// Create the notify actor.
+///
+/// This will create the configured sinks and spawn the task for processing requests.
+///
+/// # Args
+///
+/// `settings`:  The notification settings.
+///
+/// # Errors
+///
+/// [`crate::Error::InvalidEmail`] if one of the email addresses of an email sink is invalid.
+///
+/// [`crate::Error::Smtp`] if the SMTP transport of an email sink cannot be created.
+pub fn init(settings: &Settings) -> Result<Handle> {
+    let sinks = create_sinks(settings)?;
+    if sinks.is_empty() {
+        tracing::info!("notifications not configured");
+    }
+
+    Ok(actor::create_and_run("notify", MessageProcessor { sinks }))
+}
+
+/// Replaces the notification sinks.
+///
+/// Notifications already being sent are not affected.
+///
+/// # Args
+///
+/// `bus`:  Handle for sending messages to the notify actor.
+///
+/// `settings`:  The notification settings to use.
+///
+/// # Errors
+///
+/// [`crate::Error::InvalidEmail`] if one of the email addresses of an email sink is invalid. The
+/// existing sinks are kept.
+///
+/// [`crate::Error::Smtp`] if the SMTP transport of an email sink cannot be created. The existing
+/// sinks are kept.
+pub async fn configure(bus: &bus::Handle, settings: Settings) -> Result<()> {
+    let (tx, rx) = oneshot::channel();
+    bus.send(Message::Configure { settings, response: tx }).await?;
+    rx.await?
+}
+
+/// Creates the sinks in the settings.
+///
+/// # Args
+///
+/// `settings`:  The notification settings.
+///
+/// # Errors
+///
+/// See [`init`].
+fn create_sinks(settings: &Settings) -> Result<Vec<Filtered>> {
+    let mut sinks = Vec::with_capacity(settings.sinks.len());
+    for sink_settings in &settings.sinks {
+        let sink = match &sink_settings.kind {
+            SinkKind::Webhook { url } => Sink::Webhook(WebhookSink::new(url)),
+            SinkKind::Ntfy { url, token } => Sink::Ntfy(NtfySink::new(url, token)),
+            SinkKind::Gotify { url, token } => Sink::Gotify(GotifySink::new(url, token)),
+            SinkKind::Email { host, port, username, password, from, to } => {
+                Sink::Email(Box::new(EmailSink::new(host, *port, username, password, from, to)?))
+            },
+            SinkKind::Desktop => Sink::Desktop(DesktopSink),
+        };
+        sinks.push(Filtered {
+            sink: Arc::new(sink),
+            events: sink_settings.events.clone(),
+        });
+    }
+
+    Ok(sinks)
+}
+
+/// One of the configured notification sinks.
+enum Sink {
+    Webhook(WebhookSink),
+    Ntfy(NtfySink),
+    Gotify(GotifySink),
+    Email(Box<EmailSink>),
+    Desktop(DesktopSink),
+}
+
+impl NotificationSink for Sink {
+    async fn send(&self, event: &Event) -> Result<()> {
+        match self {
+            Sink::Webhook(sink) => sink.send(event).await,
+            Sink::Ntfy(sink) => sink.send(event).await,
+            Sink::Gotify(sink) => sink.send(event).await,
+            Sink::Email(sink) => sink.send(event).await,
+            Sink::Desktop(sink) => sink.send(event).await,
+        }
+    }
+}
+
+/// A sink along with the events that are sent to it.
+struct Filtered {
+    /// The sink.
+    sink: Arc<Sink>,
+
+    /// The events sent to the sink. All events are sent if empty.
+    events: Vec<EventKind>,
+}
+
+impl Filtered {
+    /// Returns `true` if the event should be sent to the sink.
+    fn accepts(&self, kind: EventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+/// Processes messages sent to the notify actor.
+struct MessageProcessor {
+    /// The configured sinks.
+    sinks: Vec<Filtered>,
+}
+
+impl MessageProcessor {
+    /// Sends the notifications for an event to the sinks that accept it.
+    ///
+    /// # Args
+    ///
+    /// `event`:  The event to send the notifications for.
+    fn publish(&self, event: Event) {
+        let kind = event.kind();
+        for filtered in self.sinks.iter().filter(|f| f.accepts(kind)) {
+            let sink = filtered.sink.clone();
+            let event = event.clone();
+            task::spawn(async move {
+                if let Err(error) = sink.send(&event).await {
+                    tracing::warn!(?kind, ?error, "failed to send notification");
+                }
+            });
+        }
+    }
+}
+
+impl actor::MessageProcessor<Message> for MessageProcessor {
+    async fn process(&mut self, msg: Message) -> Result<()> {
+        match msg {
+            Message::Publish { event } => {
+                self.publish(event);
+                Ok(())
+            },
+            Message::Configure { settings, response } => {
+                let reply = create_sinks(&settings).map(|sinks| {
+                    tracing::info!(count=sinks.len(), "notification sinks configured");
+                    self.sinks = sinks;
+                });
+                response.send(reply).map_err(|_| Error::ResponseSend)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filtered_accepts() {
+        let filtered = Filtered {
+            sink: Arc::new(Sink::Desktop(DesktopSink)),
+            events: vec![EventKind::CopyCompleted, EventKind::CopyFailed],
+        };
+        assert!(filtered.accepts(EventKind::CopyCompleted));
+        assert!(!filtered.accepts(EventKind::CatalogSaved));
+
+        let all = Filtered { events: Vec::new(), ..filtered };
+        assert!(all.accepts(EventKind::WorkerDisconnected));
+    }
+
+    #[test]
+    fn test_event_message() {
+        let event = Event::CopyFailed {
+            drive: String::from("SN-1"),
+            title: None,
+            error: String::from("Copying disc failed."),
+        };
+        assert_eq!(event.kind(), EventKind::CopyFailed);
+        assert!(event.is_failure());
+        assert_eq!(event.message(), "Copying the disc on drive SN-1 failed: Copying disc failed.");
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "copy_failed");
+        assert_eq!(json["drive"], "SN-1");
+    }
+
+    #[test]
+    fn test_sink_settings() {
+        let toml = r#"
+            [[sinks]]
+            kind = "ntfy"
+            url = "https://ntfy.sh/rips"
+            events = ["copy_completed", "worker_disconnected"]
+
+            [[sinks]]
+            kind = "email"
+            host = "smtp.example.com"
+            from = "artie@example.com"
+            to = ["me@example.com"]
+
+            [[sinks]]
+            kind = "desktop"
+        "#;
+
+        let settings: Settings = toml::from_str(toml).unwrap();
+        assert_eq!(settings.sinks.len(), 3);
+        assert!(matches!(
+            &settings.sinks[0].kind,
+            SinkKind::Ntfy { token, .. } if token.is_empty(),
+        ));
+        assert_eq!(settings.sinks[0].events, vec![
+            EventKind::CopyCompleted,
+            EventKind::WorkerDisconnected,
+        ]);
+        assert!(matches!(settings.sinks[1].kind, SinkKind::Email { port: 587, .. }));
+        assert!(matches!(settings.sinks[2].kind, SinkKind::Desktop));
+        assert!(settings.sinks[2].events.is_empty());
+    }
+
+    #[test]
+    fn test_configure() {
+        use actor::MessageProcessor as _;
+
+        let mut processor = MessageProcessor { sinks: Vec::new() };
+
+        let settings: Settings = toml::from_str(r#"
+            [[sinks]]
+            kind = "desktop"
+
+            [[sinks]]
+            kind = "webhook"
+            url = "http://localhost:8080/artie"
+        "#).unwrap();
+        let (tx, rx) = oneshot::channel();
+        task::block_on(processor.process(Message::Configure { settings, response: tx })).unwrap();
+        assert!(task::block_on(rx).unwrap().is_ok());
+        assert_eq!(processor.sinks.len(), 2);
+
+        // The existing sinks are kept if the new ones cannot be created.
+        let settings: Settings = toml::from_str(r#"
+            [[sinks]]
+            kind = "email"
+            host = "smtp.example.com"
+            from = "artie@example.com"
+            to = ["not an address"]
+        "#).unwrap();
+        let (tx, rx) = oneshot::channel();
+        task::block_on(processor.process(Message::Configure { settings, response: tx })).unwrap();
+        assert!(matches!(task::block_on(rx).unwrap(), Err(Error::InvalidEmail { .. })));
+        assert_eq!(processor.sinks.len(), 2);
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Push notifications using self-hostable push services.
+//!
+//! Both services deliver the notification to their phone and desktop apps. Failures are sent with a
+//! higher priority than completions so they are more noticeable.
+//!
+//! - [ntfy](https://docs.ntfy.sh/publish/) - The message is posted as the body of a request to the
+//!   topic URL with the title, priority, and tags as headers.
+//! - [Gotify](https://gotify.net/api-docs) - The message is posted as JSON to the `/message`
+//!   endpoint using an application token.
+
+use serde::Serialize;
+
+use crate::Result;
+
+use super::{Event, NotificationSink};
+
+/// Sink that publishes notifications to an ntfy topic.
+pub struct NtfySink {
+    /// The client used to make requests.
+    client: reqwest::Client,
+
+    /// The URL of the topic.
+    url: String,
+
+    /// The access token. Not sent if empty.
+    token: String,
+}
+
+impl NtfySink {
+    /// Creates a new sink.
+    ///
+    /// # Args
+    ///
+    /// `url`:  The URL of the topic (e.g. `https://ntfy.sh/my-topic`).
+    ///
+    /// `token`:  The access token used if the topic is protected. Ignored if empty.
+    pub fn new(url: &str, token: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_owned(),
+            token: token.to_owned(),
+        }
+    }
+}
+
+impl NotificationSink for NtfySink {
+    async fn send(&self, event: &Event) -> Result<()> {
+        let (priority, tags) = if event.is_failure() {
+            ("high", "warning")
+        } else {
+            ("default", "white_check_mark")
+        };
+
+        let mut request = self.client.post(&self.url)
+            .header("Title", event.subject())
+            .header("Priority", priority)
+            .header("Tags", tags)
+            .body(event.message());
+        if !self.token.is_empty() {
+            request = request.bearer_auth(&self.token);
+        }
+
+        request.send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Sink that sends notifications to a Gotify server.
+pub struct GotifySink {
+    /// The client used to make requests.
+    client: reqwest::Client,
+
+    /// The base URL of the server.
+    base_url: String,
+
+    /// The application token.
+    token: String,
+}
+
+impl GotifySink {
+    /// Creates a new sink.
+    ///
+    /// # Args
+    ///
+    /// `base_url`:  The base URL of the server (e.g. `https://gotify.example.com`).
+    ///
+    /// `token`:  The application token.
+    pub fn new(base_url: &str, token: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            token: token.to_owned(),
+        }
+    }
+}
+
+impl NotificationSink for GotifySink {
+    async fn send(&self, event: &Event) -> Result<()> {
+        let message = GotifyMessage {
+            title: event.subject(),
+            message: event.message(),
+            priority: if event.is_failure() { 8 } else { 4 },
+        };
+
+        let url = format!("{}/message", self.base_url);
+        self.client.post(&url)
+            .header("X-Gotify-Key", &self.token)
+            .json(&message)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// The body of a Gotify create message request.
+#[derive(Serialize)]
+struct GotifyMessage {
+    title: &'static str,
+    message: String,
+    priority: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::task;
+    use crate::test_utils::MockHttpServer;
+
+    fn copy_failed() -> Event {
+        Event::CopyFailed {
+            drive: String::from("SN-1"),
+            title: Some(String::from("Movie (2001)")),
+            error: String::from("Copying disc failed."),
+        }
+    }
+
+    #[test]
+    fn test_ntfy_send() {
+        let mock = MockHttpServer::start(|_| (200, String::from("{}")));
+
+        let sink = NtfySink::new(&format!("{}/rips", mock.url()), "tk_secret");
+        task::block_on(sink.send(&copy_failed())).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].target, "/rips");
+        assert_eq!(requests[0].header("Title"), Some("Copy failed"));
+        assert_eq!(requests[0].header("Priority"), Some("high"));
+        assert_eq!(requests[0].header("Authorization"), Some("Bearer tk_secret"));
+        assert_eq!(
+            requests[0].body,
+            "Copying Movie (2001) on drive SN-1 failed: Copying disc failed.",
+        );
+    }
+
+    #[test]
+    fn test_ntfy_send_without_token() {
+        let mock = MockHttpServer::start(|_| (200, String::from("{}")));
+
+        let sink = NtfySink::new(&format!("{}/rips", mock.url()), "");
+        let event = Event::TranscodeCompleted { title: String::from("Movie (2001)") };
+        task::block_on(sink.send(&event)).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].header("Authorization"), None);
+        assert_eq!(requests[0].header("Priority"), Some("default"));
+    }
+
+    #[test]
+    fn test_gotify_send() {
+        let mock = MockHttpServer::start(|_| (200, String::from("{}")));
+
+        let sink = GotifySink::new(&format!("{}/", mock.url()), "app_token");
+        task::block_on(sink.send(&copy_failed())).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].target, "/message");
+        assert_eq!(requests[0].header("X-Gotify-Key"), Some("app_token"));
+
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(body["title"], "Copy failed");
+        assert_eq!(body["priority"], 8);
+    }
+}
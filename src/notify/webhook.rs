@@ -0,0 +1,134 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Generic webhook notifications.
+//!
+//! The event is posted as a JSON object containing the event's fields along with the notification
+//! text so the receiver does not need to format the event itself. For example:
+//!
+//! ```json
+//! {
+//!     "event": "copy_completed",
+//!     "drive": "SN-1",
+//!     "title": "Movie (2001)",
+//!     "subject": "Copy completed",
+//!     "message": "Finished copying Movie (2001) on drive SN-1. The disc is ready to swap.",
+//!     "failure": false,
+//!     "sent": "2026-01-01T12:00:00Z"
+//! }
+//! ```
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::Result;
+
+use super::{Event, NotificationSink};
+
+/// Sink that posts events to a URL.
+pub struct WebhookSink {
+    /// The client used to make requests.
+    client: reqwest::Client,
+
+    /// The URL the events are posted to.
+    url: String,
+}
+
+impl WebhookSink {
+    /// Creates a new sink.
+    ///
+    /// # Args
+    ///
+    /// `url`:  The URL the events are posted to.
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_owned(),
+        }
+    }
+}
+
+impl NotificationSink for WebhookSink {
+    async fn send(&self, event: &Event) -> Result<()> {
+        let body = Payload {
+            event,
+            subject: event.subject(),
+            message: event.message(),
+            failure: event.is_failure(),
+            sent: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        };
+
+        self.client.post(&self.url)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// The body of a webhook request.
+#[derive(Serialize)]
+struct Payload<'a> {
+    /// The event's fields, including its kind.
+    #[serde(flatten)]
+    event: &'a Event,
+
+    /// The notification title.
+    subject: &'static str,
+
+    /// The notification body.
+    message: String,
+
+    /// Whether the event reports a failure.
+    failure: bool,
+
+    /// When the notification was sent (RFC 3339).
+    sent: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Error;
+    use crate::task;
+    use crate::test_utils::MockHttpServer;
+
+    #[test]
+    fn test_send() {
+        let mock = MockHttpServer::start(|_| (200, String::new()));
+
+        let sink = WebhookSink::new(&format!("{}/hooks/artie", mock.url()));
+        let event = Event::CatalogSaved {
+            title: String::from("Movie (2001)"),
+            count: 3,
+        };
+        task::block_on(sink.send(&event)).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].target, "/hooks/artie");
+        assert_eq!(requests[0].header("Content-Type"), Some("application/json"));
+
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(body["event"], "catalog_saved");
+        assert_eq!(body["title"], "Movie (2001)");
+        assert_eq!(body["count"], 3);
+        assert_eq!(body["subject"], "Titles cataloged");
+        assert_eq!(body["failure"], false);
+        assert!(body["sent"].is_string());
+    }
+
+    #[test]
+    fn test_send_error_status() {
+        let mock = MockHttpServer::start(|_| (500, String::new()));
+
+        let sink = WebhookSink::new(&mock.url());
+        let event = Event::WorkerDisconnected { addr: String::from("10.0.0.2:50051") };
+        let result = task::block_on(sink.send(&event));
+
+        assert!(matches!(result, Err(Error::Http(_))));
+    }
+}
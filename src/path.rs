@@ -37,6 +37,8 @@
 //! - [`mkv_copy_log_location`] - Get the media location of the log created when copying a disc.
 //! - [`mkv_info_log_location`] - Get the media location of the log created when getting disc info.
 //! - [`disc_info_path`] - Get the path of the file containing disc info extracted from the disc.
+//! - [`contact_sheet_location`] - Get the media location of the contact sheet for a copied video.
+//! - [`preview_clip_location`] - Get the media location of the preview clip for a copied video.
 //!
 //! # Library
 //!
@@ -76,6 +78,9 @@ pub const MAKEMKV_INFO_LOG_FILENAME: &str = "makemkv-info.log";
 /// Name of the file that is used to log MakeMKV output when running the copy (mkv) command.
 pub const MAKEMKV_COPY_LOG_FILENAME: &str = "makemkv-copy.log";
 
+/// Name of the folder within a copy operation's inbox folder where the video previews are saved.
+pub const PREVIEWS_FOLDER_NAME: &str = "previews";
+
 /// The file path settings.
 ///
 /// The settings are setup early in application initialization and are not expected to change
@@ -83,7 +88,7 @@ pub const MAKEMKV_COPY_LOG_FILENAME: &str = "makemkv-copy.log";
 static PATH: OnceLock<Path> = OnceLock::new();
 
 /// File path settings.
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Settings {
     /// Path to the media inbox directory.
     ///
@@ -113,6 +118,18 @@ pub fn archive_exists() -> bool {
         .archive_exists()
 }
 
+/// Returns the media location of the contact sheet image for a video created by a copy operation.
+///
+/// # Args
+///
+/// `copy_operation`:  The copy operation that created the video.
+///
+/// `video_name`:  The name of the video file without its extension.
+pub fn contact_sheet_location(copy_operation: &CopyOperation, video_name: &str) -> MediaLocation {
+    let filename = format!("{}/{}.jpg", PREVIEWS_FOLDER_NAME, video_name);
+    inbox_location(copy_operation, Some(&filename))
+}
+
 /// Returns `true` if the data directory exists, is a directory, and accessible by the user or
 /// `false` otherwise.
 pub fn data_exists() -> bool {
@@ -200,6 +217,18 @@ pub fn mkv_copy_log_location(copy_operation: &CopyOperation) -> MediaLocation {
     inbox_location(copy_operation, Some(MAKEMKV_COPY_LOG_FILENAME))
 }
 
+/// Returns the media location of the preview clip for a video created by a copy operation.
+///
+/// # Args
+///
+/// `copy_operation`:  The copy operation that created the video.
+///
+/// `video_name`:  The name of the video file without its extension.
+pub fn preview_clip_location(copy_operation: &CopyOperation, video_name: &str) -> MediaLocation {
+    let filename = format!("{}/{}.mp4", PREVIEWS_FOLDER_NAME, video_name);
+    inbox_location(copy_operation, Some(&filename))
+}
+
 // NOTE: The Path struct was created to enable some level of testing given that the public API uses
 //       a global variable. Any API function that relies on an application setting will essentially
 //       just be a wrapper around the Path method.
@@ -607,6 +636,25 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_preview_locations() {
+        let copy_op = CopyOperation {
+            id: 2,
+            media_type: MediaType::Movie,
+            title: "Preview".to_string(),
+            disc: 1,
+            ..CopyOperation::default()
+        };
+
+        let folder = PathBuf::from("0x00000002.Preview.D1/previews");
+
+        let sheet = contact_sheet_location(&copy_op, "title_t00");
+        assert!(matches!(sheet, MediaLocation::Inbox(p) if p == folder.join("title_t00.jpg")));
+
+        let clip = preview_clip_location(&copy_op, "title_t00");
+        assert!(matches!(clip, MediaLocation::Inbox(p) if p == folder.join("title_t00.mp4")));
+    }
+
     #[test]
     fn test_inbox_path() {
         let temp = TempDir::new("artie.test.path.test_inbox_path");
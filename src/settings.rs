@@ -2,16 +2,45 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 //! Manages application settings.
+//!
+//! # Config File
+//!
+//! The settings are loaded from a TOML config file. The first of the following is used:
+//!
+//! 1. The path provided with the `--config` command line flag.
+//! 2. The path in the `ARTIE_CONFIG` environment variable.
+//! 3. `$XDG_CONFIG_HOME/artie/artie.toml`, or `~/.config/artie/artie.toml` if `XDG_CONFIG_HOME`
+//!    is not set.
+//!
+//! A commented config file with the default settings is written if the file does not exist (see
+//! [`Settings::load_or_create`]). The settings are validated after being loaded, so that mistakes
+//! such as relative paths or malformed worker addresses are reported with the offending key
+//! instead of failing later (see [`Settings::validate`]).
 
+use std::env;
+use std::fmt;
 use std::fs;
-use std::io::Write;
-use std::path::Path;
+use std::io::{self, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
-use crate::Result;
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+use crate::{Error, Result};
+use crate::notify::SinkKind;
 use crate::path;
 
+/// The environment variable containing the path to the config file.
+pub const CONFIG_ENV: &str = "ARTIE_CONFIG";
+
+/// The name of the config file.
+const CONFIG_FILENAME: &str = "artie.toml";
+
+/// The name of the application's folder within the config and data folders.
+const APP_FOLDER_NAME: &str = "artie";
+
 /// The application configuration settings.
 #[derive(Clone, Default, Deserialize, Serialize)]
 pub struct Settings {
@@ -21,6 +50,38 @@ pub struct Settings {
     /// Network settings.
     #[serde(default)]
     pub net: crate::net::Settings,
+
+    /// Metadata provider settings.
+    #[serde(default)]
+    pub metadata: crate::metadata::Settings,
+
+    /// Video preview settings.
+    #[serde(default)]
+    pub previews: crate::library::preview::Settings,
+
+    /// Integrity scan settings.
+    #[serde(default)]
+    pub integrity: crate::library::integrity::Settings,
+
+    /// Database backup settings.
+    #[serde(default)]
+    pub backup: crate::db::backup::Settings,
+
+    /// Media server settings.
+    #[serde(default)]
+    pub media_server: crate::mediaserver::Settings,
+
+    /// Notification settings.
+    #[serde(default)]
+    pub notify: crate::notify::Settings,
+
+    /// Metrics endpoint settings.
+    #[serde(default)]
+    pub metrics: crate::metrics::Settings,
+
+    /// Logging settings.
+    #[serde(default)]
+    pub logging: crate::logging::Settings,
 }
 
 impl Settings {
@@ -28,37 +89,508 @@ impl Settings {
     ///
     /// # Errors
     ///
-    /// [`crate::Error::StdIo`] if the file cannot be read.
+    /// [`crate::Error::SettingsRead`] if the file cannot be read.
     ///
     /// [`crate::Error::TomlDeserialize`] if the file's content cannot be deserialized.
     pub fn from_file(path: &Path) -> Result<Self> {
-        let contents = fs::read_to_string(path)?;
+        let contents = fs::read_to_string(path)
+            .map_err(|error| Error::SettingsRead { path: path.to_owned(), error })?;
         let settings: Settings = toml::from_str(&contents)?;
         tracing::info!(?path, "settings loaded");
         Ok(settings)
     }
 
+    /// Loads the settings stored in a TOML file, writing the default config to the file first if
+    /// it does not exist.
+    ///
+    /// # Args
+    ///
+    /// `path`:  The path of the config file (see [`config_path`]).
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::SettingsRead`] if the file cannot be read or the default config cannot be
+    /// written.
+    ///
+    /// [`crate::Error::TomlDeserialize`] if the file's content cannot be deserialized.
+    pub fn load_or_create(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            let home = home_dir();
+            let contents = default_config(&home, &data_dir(&home));
+            write_new(path, &contents)
+                .map_err(|error| Error::SettingsRead { path: path.to_owned(), error })?;
+            eprintln!("created default config at {}", path.display());
+        }
+
+        Self::from_file(path)
+    }
+
     /// Saves the settings to the TOML file at the provided path.
     ///
+    /// If the file exists, only the settings that changed are updated so the comments and
+    /// formatting of the file (e.g. the comments of the default config) are kept. The file is
+    /// replaced using [`write_atomic`], so it is never left partially written.
+    ///
     /// # Errors
     ///
-    /// [`crate::Error::StdIo`] if the file cannot be written to.
+    /// [`crate::Error::StdIo`] if the file cannot be read or written to.
+    ///
+    /// [`crate::Error::TomlEdit`] if the existing file is not valid TOML.
     ///
     /// [`crate::Error::TomlSerialize`] if the settings cannot be serialized.
     pub fn save(&self, path: &Path) -> Result<()> {
-        let toml_string = toml::to_string_pretty(self)?;
-        let mut file = fs::File::create(path)?;
-        file.write_all(toml_string.as_bytes())?;
+        let updated: DocumentMut = toml::to_string_pretty(self)?.parse()?;
+        let document = match fs::read_to_string(path) {
+            Ok(contents) => {
+                let mut document: DocumentMut = contents.parse()?;
+                merge_table(document.as_table_mut(), updated.as_table());
+                document
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => updated,
+            Err(error) => return Err(error.into()),
+        };
+
+        write_atomic(path, &document.to_string())?;
         Ok(())
     }
+
+    /// Updates the settings stored in a TOML file.
+    ///
+    /// The file is loaded, modified and saved again, so settings changed by other parts of the
+    /// application since startup are kept.
+    ///
+    /// # Args
+    ///
+    /// `path`:  The path of the config file.
+    ///
+    /// `f`:  Function that modifies the settings.
+    ///
+    /// # Errors
+    ///
+    /// See [`Settings::from_file`] and [`Settings::save`].
+    pub fn update_file(path: &Path, f: impl FnOnce(&mut Settings)) -> Result<()> {
+        let mut settings = Self::from_file(path)?;
+        f(&mut settings);
+        settings.save(path)
+    }
+
+    /// Validates the settings.
+    ///
+    /// All of the settings are checked, so every problem can be reported at once.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::InvalidSettings`] with the problems found.
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        let paths = [
+            ("paths.inbox", &self.paths.inbox),
+            ("paths.library", &self.paths.library),
+            ("paths.archive", &self.paths.archive),
+            ("paths.data", &self.paths.data),
+        ];
+        for (key, path) in paths {
+            check_path(&mut problems, key, Some(path.as_path()));
+        }
+        check_path(&mut problems, "backup.folder", self.backup.folder.as_deref());
+        check_path(&mut problems, "logging.folder", self.logging.folder.as_deref());
+
+        check_host(&mut problems, "net.listen_addr", &self.net.listen_addr);
+        check_port(&mut problems, "net.listen_port", self.net.listen_port);
+        for (index, worker) in self.net.workers.iter().enumerate() {
+            let key = format!("net.workers[{}]", index);
+            if let Some(first) = self.net.workers[..index].iter().position(|w| w == worker) {
+                problems.push(Problem::new(&key, format!("duplicate of net.workers[{}]", first)));
+            } else {
+                check_worker(&mut problems, &key, worker);
+            }
+        }
+        let mut storage = self.net.storage.keys().collect::<Vec<_>>();
+        storage.sort();
+        for worker in storage {
+            check_worker(&mut problems, &format!("net.storage.\"{}\"", worker), worker);
+        }
+        let discovery = &self.net.discovery;
+        check_host(&mut problems, "net.discovery.broadcast_addr", &discovery.broadcast_addr);
+        check_port(&mut problems, "net.discovery.port", discovery.port);
+        if discovery.interval == 0 {
+            problems.push(Problem::new("net.discovery.interval", "must be at least 1 second"));
+        }
+
+        check_host(&mut problems, "metrics.listen_addr", &self.metrics.listen_addr);
+        check_port(&mut problems, "metrics.listen_port", self.metrics.listen_port);
+
+        for (index, sink) in self.notify.sinks.iter().enumerate() {
+            check_sink(&mut problems, &format!("notify.sinks[{}]", index), &sink.kind);
+        }
+
+        if let Err(error) = tracing_subscriber::EnvFilter::builder().parse(&self.logging.filter) {
+            problems.push(Problem::new("logging.filter", error.to_string()));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidSettings { problems })
+        }
+    }
+}
+
+/// A problem found while validating the settings.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Problem {
+    /// The config key with the problem (e.g. `net.workers[1]`).
+    pub key: String,
+
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl Problem {
+    fn new(key: &str, message: impl Into<String>) -> Self {
+        Self {
+            key: key.to_owned(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// Returns the path of the config file.
+///
+/// See the module documentation for the lookup order.
+///
+/// # Args
+///
+/// `flag`:  The path provided on the command line, if any.
+pub fn config_path(flag: Option<&Path>) -> PathBuf {
+    resolve_config_path(
+        flag,
+        env::var_os(CONFIG_ENV).map(PathBuf::from),
+        env::var_os("XDG_CONFIG_HOME").map(PathBuf::from),
+        &home_dir(),
+    )
+}
+
+/// Returns the path of the config file using the provided lookup values.
+///
+/// Empty values are ignored, as are relative `XDG_CONFIG_HOME` values per the XDG specification.
+fn resolve_config_path(
+    flag: Option<&Path>,
+    config_env: Option<PathBuf>,
+    xdg_config_home: Option<PathBuf>,
+    home: &Path,
+) -> PathBuf {
+    if let Some(path) = flag {
+        return path.to_owned();
+    }
+
+    if let Some(path) = config_env.filter(|path| !path.as_os_str().is_empty()) {
+        return path;
+    }
+
+    xdg_config_home
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| home.join(".config"))
+        .join(APP_FOLDER_NAME)
+        .join(CONFIG_FILENAME)
+}
+
+/// Returns the user's home folder, or the working directory if it cannot be determined.
+fn home_dir() -> PathBuf {
+    env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::current_dir().ok())
+        .unwrap_or_default()
+}
+
+/// Returns the default application data folder.
+///
+/// # Args
+///
+/// `home`:  The user's home folder, used if `XDG_DATA_HOME` is not set.
+fn data_dir(home: &Path) -> PathBuf {
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| home.join(".local").join("share"))
+        .join(APP_FOLDER_NAME)
+}
+
+/// Creates the contents of the default config file.
+///
+/// # Args
+///
+/// `home`:  The user's home folder, where the media folders are created.
+///
+/// `data`:  The application data folder.
+fn default_config(home: &Path, data: &Path) -> String {
+    let quote = |path: PathBuf| toml::Value::String(path.display().to_string()).to_string();
+    let media = home.join("Artie");
+    let net = crate::net::Settings::default();
+    let metrics = crate::metrics::Settings::default();
+    let logging = crate::logging::Settings::default();
+
+    format!(
+r#"# Artie configuration.
+#
+# Written with the default settings because no config file was found. Run
+# `artie --check-config` after making changes to validate the settings.
+
+[paths]
+# Where the titles are saved when copying a disc, along with the MakeMKV logs.
+inbox = {inbox}
+# Where the media server looks for the videos.
+library = {library}
+# Where the videos that are not moved to the library are kept.
+archive = {archive}
+# Where application data, such as the database and log files, is stored.
+data = {data}
+
+[net]
+# The address and port a worker node listens on.
+listen_addr = "{listen_addr}"
+listen_port = {listen_port}
+# The worker nodes used by the control node (e.g. ["192.168.1.20:7878"]).
+workers = []
+
+[net.storage]
+# Worker nodes whose media folders are not shared with the control node, so the copied titles are
+# transferred over the network (e.g. "192.168.1.20:7878" = "transfer").
+
+[net.discovery]
+# Whether worker nodes announce themselves on the local network so the control node can find them.
+# Must be enabled on both the control and worker nodes.
+enabled = false
+port = {discovery_port}
+
+[metrics]
+# Whether the Prometheus metrics endpoint is enabled.
+enabled = false
+listen_addr = "{metrics_addr}"
+listen_port = {metrics_port}
+
+[logging]
+# The events that are logged. The RUST_LOG environment variable overrides this.
+filter = "{filter}"
+# Whether events are written as JSON instead of text.
+json = false
+# Whether events are also written to log files in the logs folder of the data folder.
+file = false
+# How often a new log file is started: "hourly", "daily" or "never".
+rotation = "daily"
+"#,
+        inbox = quote(media.join("inbox")),
+        library = quote(media.join("library")),
+        archive = quote(media.join("archive")),
+        data = quote(data.to_owned()),
+        listen_addr = net.listen_addr,
+        listen_port = net.listen_port,
+        discovery_port = net.discovery.port,
+        metrics_addr = metrics.listen_addr,
+        metrics_port = metrics.listen_port,
+        filter = logging.filter,
+    )
+}
+
+/// Writes a new file, creating its parent folders.
+///
+/// # Args
+///
+/// `path`:  The path of the file.
+///
+/// `contents`:  The file's contents.
+fn write_new(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = fs::File::create_new(path)?;
+    file.write_all(contents.as_bytes())
+}
+
+/// Replaces a file by writing to a temporary file in the same folder and renaming it over the file.
+///
+/// The file is either left as it was or fully replaced, even if the application exits or the disk
+/// fills up while writing. The permissions of the file are kept since the config can contain
+/// passwords.
+///
+/// # Args
+///
+/// `path`:  The path of the file.
+///
+/// `contents`:  The file's new contents.
+fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(".tmp");
+    let temp_path = path.with_file_name(file_name);
+
+    let result = fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            if let Ok(metadata) = fs::metadata(path) {
+                file.set_permissions(metadata.permissions())?;
+            }
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Updates a table of a TOML document to match the updated settings.
+///
+/// Keys that are no longer set are removed and new keys are added. Values that did not change are
+/// left as they are, and values that did change keep their comments.
+///
+/// # Args
+///
+/// `existing`:  The table from the existing file.
+///
+/// `updated`:  The table serialized from the updated settings.
+fn merge_table(existing: &mut Table, updated: &Table) {
+    existing.retain(|key, _| updated.contains_key(key));
+
+    for (key, item) in updated.iter() {
+        match (existing.get_mut(key), item) {
+            (Some(Item::Table(existing)), Item::Table(updated)) => merge_table(existing, updated),
+            (Some(Item::ArrayOfTables(existing)), Item::ArrayOfTables(updated))
+                if existing.len() == updated.len() =>
+            {
+                for (existing, updated) in existing.iter_mut().zip(updated.iter()) {
+                    merge_table(existing, updated);
+                }
+            },
+            (Some(Item::Value(existing)), Item::Value(updated)) => {
+                if !same_value(existing, updated) {
+                    let decor = existing.decor().clone();
+                    *existing = updated.clone();
+                    *existing.decor_mut() = decor;
+                }
+            },
+            (Some(existing), item) => *existing = item.clone(),
+            (None, item) => {
+                existing.insert(key, item.clone());
+            },
+        }
+    }
+}
+
+/// Whether two TOML values are the same, ignoring how they are formatted.
+fn same_value(a: &Value, b: &Value) -> bool {
+    let parse = |value: &Value| toml::from_str::<toml::Table>(&format!("v = {}", value)).ok();
+    parse(a).is_some_and(|a| Some(a) == parse(b))
+}
+
+/// Checks that a path setting is set to an absolute path.
+///
+/// Relative paths depend on the working directory the application is started from, which has
+/// caused files to be written to unexpected places.
+fn check_path(problems: &mut Vec<Problem>, key: &str, path: Option<&Path>) {
+    match path {
+        Some(path) if path.as_os_str().is_empty() => {
+            problems.push(Problem::new(key, "must be set"));
+        },
+        Some(path) if !path.is_absolute() => {
+            let message = format!("must be an absolute path, not {}", path.display());
+            problems.push(Problem::new(key, message));
+        },
+        _ => {},
+    }
+}
+
+/// Checks that an address setting is an IP address or host name.
+fn check_host(problems: &mut Vec<Problem>, key: &str, host: &str) {
+    if !is_host(host) {
+        problems.push(Problem::new(key, format!("{:?} is not an IP address or host name", host)));
+    }
+}
+
+/// Checks that a port setting is not zero.
+fn check_port(problems: &mut Vec<Problem>, key: &str, port: u16) {
+    if port == 0 {
+        problems.push(Problem::new(key, "must be between 1 and 65535"));
+    }
+}
+
+/// Checks that a worker entry is an address and port (e.g. `192.168.1.20:7878`).
+fn check_worker(problems: &mut Vec<Problem>, key: &str, worker: &str) {
+    if worker.parse::<SocketAddr>().is_ok() {
+        return;
+    }
+
+    let message = match worker.rsplit_once(':') {
+        None => format!("{:?} is missing the port (e.g. \"{}:7878\")", worker, worker),
+        Some((host, _)) if !is_host(host) => {
+            format!("{:?} is not an IP address or host name", host)
+        },
+        Some((_, port)) => match port.parse::<u16>() {
+            Ok(0) | Err(_) => format!("{:?} is not a valid port", port),
+            Ok(_) => return,
+        },
+    };
+    problems.push(Problem::new(key, message));
+}
+
+/// Checks the settings of a notification sink.
+///
+/// Email addresses are not checked since they are validated when the sink is created.
+fn check_sink(problems: &mut Vec<Problem>, key: &str, kind: &SinkKind) {
+    match kind {
+        SinkKind::Webhook { url } | SinkKind::Ntfy { url, .. } | SinkKind::Gotify { url, .. } => {
+            let valid = reqwest::Url::parse(url)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+            if !valid {
+                let message = format!("{:?} is not a valid http or https URL", url);
+                problems.push(Problem::new(&format!("{}.url", key), message));
+            }
+        },
+        SinkKind::Email { host, to, .. } => {
+            check_host(problems, &format!("{}.host", key), host);
+            if to.is_empty() {
+                problems.push(Problem::new(&format!("{}.to", key), "must have an address"));
+            }
+        },
+        SinkKind::Desktop => {},
+    }
+}
+
+/// Returns `true` if the value is an IP address or a valid host name.
+fn is_host(value: &str) -> bool {
+    if value.parse::<IpAddr>().is_ok() {
+        return true;
+    }
+
+    !value.is_empty()
+        && value.len() <= 253
+        && value.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
     use std::path::PathBuf;
 
-    use crate::test_utils::TempFile;
+    use crate::test_utils::{TempDir, TempFile};
 
     #[test]
     fn test_save_load_settings() {
@@ -78,7 +610,60 @@ mod tests {
                     String::from("127.0.0.1:0001"),
                     String::from("127.0.0.1:0002"),
                 ],
-            }
+                discovery: crate::net::discovery::Settings {
+                    enabled: true,
+                    ..Default::default()
+                },
+                storage: HashMap::from([
+                    (String::from("127.0.0.1:0002"), crate::net::transfer::StorageMode::Transfer),
+                ]),
+            },
+            metadata: crate::metadata::Settings {
+                provider: Some(crate::metadata::ProviderKind::Tmdb),
+                tmdb_api_key: String::from("key"),
+                cache_days: 7,
+                ..crate::metadata::Settings::default()
+            },
+            previews: crate::library::preview::Settings {
+                preview_clip: true,
+                ..crate::library::preview::Settings::default()
+            },
+            integrity: crate::library::integrity::Settings {
+                interval_hours: 168,
+                ..crate::library::integrity::Settings::default()
+            },
+            backup: crate::db::backup::Settings {
+                retain: 3,
+                folder: Some(PathBuf::from("/backups")),
+                ..crate::db::backup::Settings::default()
+            },
+            media_server: crate::mediaserver::Settings {
+                server: Some(crate::mediaserver::ServerKind::Jellyfin),
+                url: String::from("http://localhost:8096"),
+                api_key: String::from("key"),
+                ..crate::mediaserver::Settings::default()
+            },
+            notify: crate::notify::Settings {
+                sinks: vec![
+                    crate::notify::SinkSettings {
+                        kind: crate::notify::SinkKind::Webhook {
+                            url: String::from("http://localhost:8080/artie"),
+                        },
+                        events: vec![crate::notify::EventKind::CopyCompleted],
+                    },
+                ],
+            },
+            metrics: crate::metrics::Settings {
+                enabled: true,
+                listen_port: 9100,
+                ..crate::metrics::Settings::default()
+            },
+            logging: crate::logging::Settings {
+                filter: String::from("warn,artie=trace"),
+                json: true,
+                rotation: crate::logging::Rotation::Hourly,
+                ..crate::logging::Settings::default()
+            },
         };
 
         settings.save(path.path()).unwrap();
@@ -95,5 +680,240 @@ mod tests {
         assert_eq!(2, loaded_settings.net.workers.len());
         assert_eq!(settings.net.workers[0], loaded_settings.net.workers[0]);
         assert_eq!(settings.net.workers[1], loaded_settings.net.workers[1]);
+        assert!(loaded_settings.net.discovery.enabled);
+        assert_eq!(settings.net.storage, loaded_settings.net.storage);
+        assert_eq!(settings.net.discovery.port, loaded_settings.net.discovery.port);
+
+        assert_eq!(settings.metadata.provider, loaded_settings.metadata.provider);
+        assert_eq!(settings.metadata.tmdb_api_key, loaded_settings.metadata.tmdb_api_key);
+        assert_eq!(settings.metadata.cache_days, loaded_settings.metadata.cache_days);
+
+        assert!(loaded_settings.previews.contact_sheet);
+        assert!(loaded_settings.previews.preview_clip);
+
+        assert_eq!(settings.integrity.interval_hours, loaded_settings.integrity.interval_hours);
+        assert!(loaded_settings.integrity.repair);
+
+        assert_eq!(settings.backup.interval_hours, loaded_settings.backup.interval_hours);
+        assert_eq!(settings.backup.retain, loaded_settings.backup.retain);
+        assert_eq!(settings.backup.folder, loaded_settings.backup.folder);
+
+        assert_eq!(settings.media_server.server, loaded_settings.media_server.server);
+        assert_eq!(settings.media_server.url, loaded_settings.media_server.url);
+        assert_eq!(settings.media_server.api_key, loaded_settings.media_server.api_key);
+        assert_eq!(
+            settings.media_server.verify_attempts,
+            loaded_settings.media_server.verify_attempts,
+        );
+
+        assert_eq!(1, loaded_settings.notify.sinks.len());
+        assert!(matches!(
+            &loaded_settings.notify.sinks[0].kind,
+            crate::notify::SinkKind::Webhook { url } if url == "http://localhost:8080/artie",
+        ));
+        assert_eq!(settings.notify.sinks[0].events, loaded_settings.notify.sinks[0].events);
+
+        assert!(loaded_settings.metrics.enabled);
+        assert_eq!(settings.metrics.listen_addr, loaded_settings.metrics.listen_addr);
+        assert_eq!(settings.metrics.listen_port, loaded_settings.metrics.listen_port);
+
+        assert_eq!(settings.logging.filter, loaded_settings.logging.filter);
+        assert!(loaded_settings.logging.json);
+        assert!(!loaded_settings.logging.file);
+        assert_eq!(settings.logging.rotation, loaded_settings.logging.rotation);
+        assert_eq!(settings.logging.max_files, loaded_settings.logging.max_files);
+    }
+
+    #[test]
+    fn test_resolve_config_path() {
+        let home = Path::new("/home/artie");
+        let flag = Path::new("/etc/artie.toml");
+        let env = Some(PathBuf::from("/srv/artie.toml"));
+        let xdg = Some(PathBuf::from("/xdg"));
+
+        let path = resolve_config_path(Some(flag), env.clone(), xdg.clone(), home);
+        assert_eq!(path, PathBuf::from("/etc/artie.toml"));
+
+        let path = resolve_config_path(None, env, xdg.clone(), home);
+        assert_eq!(path, PathBuf::from("/srv/artie.toml"));
+
+        let path = resolve_config_path(None, Some(PathBuf::new()), xdg, home);
+        assert_eq!(path, PathBuf::from("/xdg/artie/artie.toml"));
+
+        let path = resolve_config_path(None, None, Some(PathBuf::from("relative")), home);
+        assert_eq!(path, PathBuf::from("/home/artie/.config/artie/artie.toml"));
+    }
+
+    #[test]
+    fn test_load_or_create() {
+        let dir = TempDir::new("artie.test.settings.create");
+        let path = dir.path().join("config").join("artie.toml");
+
+        let settings = Settings::load_or_create(&path).unwrap();
+        assert!(path.exists());
+        assert!(settings.paths.inbox.is_absolute());
+        assert!(settings.paths.data.ends_with("artie"));
+        assert!(settings.validate().is_ok());
+
+        // The existing file is loaded instead of being overwritten.
+        fs::write(&path, "[paths]\ninbox = \"/in\"\nlibrary = \"/lib\"\n\
+                          archive = \"/arc\"\ndata = \"/data\"\n").unwrap();
+        let settings = Settings::load_or_create(&path).unwrap();
+        assert_eq!(settings.paths.inbox, PathBuf::from("/in"));
+    }
+
+    #[test]
+    fn test_update_file() {
+        let dir = TempDir::new("artie.test.settings.update");
+        fs::create_dir_all(dir.path()).unwrap();
+        let path = dir.path().join("artie.toml");
+        fs::write(&path, "[paths]\ninbox = \"/in\"\nlibrary = \"/lib\"\n\
+                          archive = \"/arc\"\ndata = \"/data\"\n").unwrap();
+
+        Settings::update_file(&path, |s| s.net.workers.push(String::from("10.0.0.5:7878")))
+            .unwrap();
+
+        let settings = Settings::from_file(&path).unwrap();
+        assert_eq!(settings.net.workers, vec![String::from("10.0.0.5:7878")]);
+        assert_eq!(settings.paths.inbox, PathBuf::from("/in"));
+        assert!(Settings::update_file(&dir.path().join("missing.toml"), |_| ()).is_err());
+    }
+
+    #[test]
+    fn test_save_keeps_comments() {
+        let dir = TempDir::new("artie.test.settings.comments");
+        fs::create_dir_all(dir.path()).unwrap();
+        let path = dir.path().join("artie.toml");
+        let contents = default_config(Path::new("/home/artie"), Path::new("/home/artie/.data"));
+        let contents = contents.replace("json = false", "json = false # Text is easier to read.");
+        fs::write(&path, &contents).unwrap();
+
+        Settings::update_file(&path, |s| {
+            s.net.workers.push(String::from("10.0.0.5:7878"));
+            s.logging.json = true;
+        }).unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with("# Artie configuration.\n"));
+        assert!(saved.contains("# The worker nodes used by the control node"));
+        assert!(saved.contains("workers = [\"10.0.0.5:7878\"]"));
+        assert!(saved.contains("json = true # Text is easier to read."));
+        assert!(saved.contains("# How often a new log file is started"));
+        assert!(!dir.path().join("artie.toml.tmp").exists());
+
+        let settings = Settings::from_file(&path).unwrap();
+        assert_eq!(settings.net.workers, vec![String::from("10.0.0.5:7878")]);
+        assert!(settings.logging.json);
+        assert_eq!(settings.paths.inbox, PathBuf::from("/home/artie/Artie/inbox"));
+
+        // Saving the same settings again leaves the file as it is.
+        settings.save(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), saved);
+    }
+
+    #[test]
+    fn test_default_config() {
+        let contents = default_config(Path::new("/home/artie"), Path::new("/home/artie/.data"));
+        let settings: Settings = toml::from_str(&contents).unwrap();
+
+        assert_eq!(settings.paths.inbox, PathBuf::from("/home/artie/Artie/inbox"));
+        assert_eq!(settings.paths.data, PathBuf::from("/home/artie/.data"));
+        assert_eq!(settings.net.listen_port, crate::net::Settings::default().listen_port);
+        assert_eq!(settings.logging.filter, crate::logging::Settings::default().filter);
+        assert!(!settings.net.discovery.enabled);
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let result = Settings::from_file(Path::new("/nonexistent/artie.toml"));
+        assert!(matches!(
+            result,
+            Err(Error::SettingsRead { path, .. }) if path == Path::new("/nonexistent/artie.toml"),
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut settings: Settings = toml::from_str(r#"
+            [paths]
+            inbox = "inbox"
+            library = ""
+            archive = "/archive"
+            data = "/data"
+
+            [net]
+            listen_addr = "not an address"
+            workers = [
+                "192.168.1.20:7878",
+                "worker-2.local:7878",
+                "[::1]:7878",
+                "192.168.1.21",
+                "bad host:7878",
+                "192.168.1.22:port",
+                "192.168.1.20:7878",
+            ]
+
+            [net.storage]
+            "192.168.1.20:7878" = "transfer"
+            "192.168.1.23" = "transfer"
+
+            [net.discovery]
+            interval = 0
+        "#).unwrap();
+
+        let Err(Error::InvalidSettings { problems }) = settings.validate() else {
+            panic!("expected invalid settings");
+        };
+        let keys = problems.iter().map(|problem| problem.key.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys, vec![
+            "paths.inbox",
+            "paths.library",
+            "net.listen_addr",
+            "net.workers[3]",
+            "net.workers[4]",
+            "net.workers[5]",
+            "net.workers[6]",
+            "net.storage.\"192.168.1.23\"",
+            "net.discovery.interval",
+        ]);
+        assert_eq!(
+            problems[0].to_string(),
+            "paths.inbox: must be an absolute path, not inbox",
+        );
+        assert_eq!(problems[6].message, "duplicate of net.workers[0]");
+
+        settings.paths.inbox = PathBuf::from("/inbox");
+        settings.paths.library = PathBuf::from("/library");
+        settings.net.listen_addr = String::from("0.0.0.0");
+        settings.net.workers.truncate(3);
+        settings.net.storage.remove("192.168.1.23");
+        settings.net.discovery.interval = 5;
+        settings.logging.filter = String::from("artie=loud");
+        let Err(Error::InvalidSettings { problems }) = settings.validate() else {
+            panic!("expected invalid settings");
+        };
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].key, "logging.filter");
+
+        settings.logging.filter = String::from("info");
+        assert!(settings.validate().is_ok());
+
+        settings.notify = toml::from_str(r#"
+            [[sinks]]
+            kind = "webhook"
+            url = "localhost:8080"
+
+            [[sinks]]
+            kind = "email"
+            host = "smtp.example.com"
+            from = "artie@example.com"
+            to = []
+        "#).unwrap();
+        let Err(Error::InvalidSettings { problems }) = settings.validate() else {
+            panic!("expected invalid settings");
+        };
+        let keys = problems.iter().map(|problem| problem.key.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys, vec!["notify.sinks[0].url", "notify.sinks[1].to"]);
     }
 }
@@ -10,7 +10,7 @@
 use std::sync::OnceLock;
 
 use tokio::runtime::Runtime;
-use tokio::task::JoinHandle;
+use tokio::task::{AbortHandle, JoinHandle, JoinSet};
 
 /// Spawns a new asynchronous task returning the join handle for it.
 ///
@@ -24,6 +24,18 @@ where
     runtime().spawn(future)
 }
 
+/// Spawns a new asynchronous task in a [`JoinSet`].
+///
+/// Like [`spawn`], the task is run with the tokio runtime. The tasks in the set are aborted when
+/// it is dropped, so tasks that are no longer needed (e.g. after one of them fails) are stopped.
+pub fn spawn_in<F>(set: &mut JoinSet<F::Output>, future: F) -> AbortHandle
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    set.spawn_on(future, runtime().handle())
+}
+
 /// Runs the provided closure on a thread where blocking is acceptable.
 ///
 /// This is essentially just a drop-in for the `tokio::spawn_blocking` method which can't be
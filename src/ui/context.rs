@@ -3,10 +3,11 @@
 
 //! Application context for the UI.
 //!
-//! [`ContextObject`] provides application data for the UI such as the application mode and handle
-//! for interfacing with the message bus.
+//! [`ContextObject`] provides application data for the UI such as the application mode, handle
+//! for interfacing with the message bus, and the application settings.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use gtk::gio::ListStore;
@@ -15,9 +16,10 @@ use gtk::glib::{self, Object};
 use gtk::prelude::Cast;
 use gtk::subclass::prelude::ObjectSubclassIsExt;
 
-use crate::Mode;
+use crate::{Mode, Result};
 use crate::bus::Handle;
 use crate::drive::{self, OpticalDrive};
+use crate::settings::Settings;
 use crate::ui::data::OpticalDriveObject;
 
 glib::wrapper! {
@@ -25,7 +27,18 @@ glib::wrapper! {
 }
 
 impl ContextObject {
-    pub fn new(mode: Mode, bus: Handle) -> Self {
+    /// Creates a new context.
+    ///
+    /// # Args
+    ///
+    /// `mode`:  The mode the application is running in.
+    ///
+    /// `bus`:  Handle used to send messages to the application actors.
+    ///
+    /// `settings`:  The application settings loaded during startup.
+    ///
+    /// `config_path`:  The path of the config file the settings were loaded from.
+    pub fn new(mode: Mode, bus: Handle, settings: Settings, config_path: PathBuf) -> Self {
         let obj: Self = Object::builder()
             .property("is-worker", mode == Mode::Worker)
             .build();
@@ -35,6 +48,8 @@ impl ContextObject {
         let imp = obj.imp();
         imp.bus.replace(Some(bus.clone()));
         imp.drive_store.replace(Some(drive_store.clone()));
+        imp.settings.replace(settings);
+        imp.config_path.replace(config_path);
 
         glib::spawn_future_local(glib::clone!(
             #[weak]
@@ -50,6 +65,20 @@ impl ContextObject {
         obj
     }
 
+    /// Returns the handle used to send messages to the application actors.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the message bus is `None`. This shouldn't be possible given its set when
+    /// constructed and never changed.
+    pub fn bus(&self) -> Handle {
+        self.imp().bus
+            .borrow()
+            .as_ref()
+            .expect("message bus not set")
+            .clone()
+    }
+
     /// Returns list of [`crate::ui::data::OpticalDriveObject`] instances containing the optical
     /// drive data.
     pub fn drive_store(&self) -> Option<ListStore> {
@@ -57,6 +86,39 @@ impl ContextObject {
             .borrow()
             .clone()
     }
+
+    /// Returns the path of the config file the settings are saved to.
+    pub fn config_path(&self) -> PathBuf {
+        self.imp().config_path
+            .borrow()
+            .clone()
+    }
+
+    /// Returns the current application settings.
+    pub fn settings(&self) -> Settings {
+        self.imp().settings
+            .borrow()
+            .clone()
+    }
+
+    /// Saves the settings to the config file and makes them the current settings.
+    ///
+    /// Applying the settings to the running application is up to the caller.
+    ///
+    /// # Args
+    ///
+    /// `settings`:  The settings to save.
+    ///
+    /// # Errors
+    ///
+    /// See [`Settings::save`].
+    pub fn save_settings(&self, settings: Settings) -> Result<()> {
+        let config_path = self.config_path();
+        settings.save(&config_path)?;
+        tracing::info!(?config_path, "settings saved");
+        self.imp().settings.replace(settings);
+        Ok(())
+    }
 }
 
 // TODO: The following update logic could probably be more efficent. Its good enough for now and
@@ -158,6 +220,7 @@ fn update_drive_store(bus: &Handle, store: &ListStore, drives: Vec<OpticalDrive>
 
 mod imp {
     use std::cell::{Cell, RefCell};
+    use std::path::PathBuf;
 
     use gtk::glib::{self, Properties};
     use gtk::gio::ListStore;
@@ -165,6 +228,7 @@ mod imp {
     use gtk::subclass::prelude::*;
 
     use crate::bus::Handle;
+    use crate::settings::Settings;
 
     #[derive(Default, Properties)]
     #[properties(wrapper_type = super::ContextObject)]
@@ -179,6 +243,12 @@ mod imp {
 
         /// Message bus for sending requests to the various application actors.
         pub(super) bus: RefCell<Option<Handle>>,
+
+        /// The current application settings.
+        pub(super) settings: RefCell<Settings>,
+
+        /// The path of the config file the settings are saved to.
+        pub(super) config_path: RefCell<PathBuf>,
     }
 
     #[glib::object_subclass]
@@ -9,7 +9,8 @@ use gtk::glib;
 /// GObject representation of the optical drive's state.
 ///
 /// This state combines the [`crate::drive::DiscState`] and [`crate::drive::OpticalDriveState`]
-/// rust types into a single enumeration that can be used as a GObject property.
+/// rust types into a single enumeration that can be used as a GObject property. An idle drive in a
+/// worker node whose media folders have not been verified is `StorageUnverified`.
 #[derive(Default, Debug, Eq, PartialEq, Clone, Copy, glib::Enum)]
 #[repr(u8)]
 #[enum_type(name = "ArtieOpticalDriveState")]
@@ -21,4 +22,5 @@ pub enum OpticalDriveState {
     Copying = 3,
     Success = 4,
     Failed = 5,
+    StorageUnverified = 6,
 }
@@ -3,15 +3,16 @@
 
 //! GObject representation of an optical drive.
 
-use std::time::Duration;
-
 use gtk::glib::{self, Object};
 use gtk::subclass::prelude::*;
 
+use crate::Error;
 use crate::bus::Handle;
 use crate::drive::{self, FormData, FormDataUpdate, OpticalDrive};
-use crate::models::CopyParamaters;
+use crate::metadata::{self, SearchResult};
+use crate::models::{CopyParamaters, MediaType};
 use crate::ui::data::OpticalDriveState;
+use crate::ui::helpers;
 
 glib::wrapper! {
     pub struct OpticalDriveObject(ObjectSubclass<imp::OpticalDriveObject>);
@@ -87,6 +88,34 @@ impl OpticalDriveObject {
         }
     }
 
+    /// Searches the metadata provider for movies or shows matching the form's title.
+    ///
+    /// An empty list is returned if the search fails or a metadata provider is not configured.
+    ///
+    /// # Args
+    ///
+    /// `media_type`:  The type of media to search for.
+    ///
+    /// `title`:  The title (or partial title) to search for.
+    ///
+    /// `year`:  If `Some`, limits the results to the provided release year.
+    pub async fn search_metadata(
+        &self,
+        media_type: MediaType,
+        title: &str,
+        year: Option<u16>,
+    ) -> Vec<SearchResult> {
+        let bus = self.bus();
+        match metadata::search(&bus, media_type, title, year).await {
+            Ok(results) => results,
+            Err(Error::MetadataDisabled) => Vec::new(),
+            Err(error) => {
+                tracing::error!(title, ?error, "failed to search metadata");
+                Vec::new()
+            },
+        }
+    }
+
     /// Updates the saved copy parameters for the drive..
     ///
     /// # Args
@@ -124,7 +153,13 @@ impl OpticalDriveObject {
                 self.set_drive_state(OpticalDriveState::Disconnected);
             },
             crate::drive::OpticalDriveState::Idle => {
-                self.set_drive_state(OpticalDriveState::Idle);
+                match drive.storage.filter(|check| !check.allows_copy()) {
+                    Some(check) => {
+                        self.set_drive_state(OpticalDriveState::StorageUnverified);
+                        self.set_storage_message(check.to_string());
+                    },
+                    None => self.set_drive_state(OpticalDriveState::Idle),
+                }
             },
             crate::drive::OpticalDriveState::Copying {
                 stage,
@@ -136,7 +171,7 @@ impl OpticalDriveObject {
             } => {
                 self.set_drive_state(OpticalDriveState::Copying);
                 self.set_stage(stage);
-                self.set_elapsed_time(format_elapsed_time(&elapsed_time));
+                self.set_elapsed_time(helpers::format_duration(&elapsed_time));
                 self.set_task(task);
                 self.set_task_progress(task_progress);
                 self.set_subtask(subtask);
@@ -168,15 +203,6 @@ impl OpticalDriveObject {
 
 }
 
-/// Formats the elapsed time duration into a string.
-fn format_elapsed_time(elapsed_time: &Duration) -> String {
-    let total_seconds = elapsed_time.as_secs();
-    let hours = total_seconds / 3600;
-    let minutes = (total_seconds % 3600) / 60;
-    let seconds = total_seconds % 60;
-    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
-}
-
 mod imp {
     use std::cell::{Cell, RefCell};
 
@@ -266,6 +292,12 @@ mod imp {
         #[property(name = "subtask-progress", get, set, type = f32)]
         pub(super) subtask_progress: Cell<f32>,
 
+        /// Describes why the worker node's media folders could not be verified.
+        ///
+        /// This is only valid when the state is [`OpticalDriveState::StorageUnverified`].
+        #[property(name = "storage-message", get, set, type = String)]
+        pub(super) storage_message: RefCell<String>,
+
         /// Interface for sending messages to application actors, mainly the actor associated with
         /// this optical drive.
         pub(super) bus: RefCell<Option<Handle>>,
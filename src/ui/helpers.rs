@@ -3,6 +3,8 @@
 
 //! UI utility functions.
 
+use std::time::Duration;
+
 use gtk::Entry;
 use gtk::prelude::*;
 
@@ -39,6 +41,15 @@ pub fn entry_invalid(entry: &Entry) {
     entry.add_css_class(INVALID_CSS_CLASS);
 }
 
+/// Formats a duration into a string in the form `HH:MM:SS`.
+pub fn format_duration(duration: &Duration) -> String {
+    let total_seconds = duration.as_secs();
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+}
+
 #[cfg(test)]
 mod tests {
     // TODO[TESTS]
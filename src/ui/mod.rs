@@ -8,6 +8,8 @@ mod data;
 mod helpers;
 mod widget;
 
+use std::path::PathBuf;
+
 use gtk::gdk::Display;
 use gtk::gio::prelude::*;
 use gtk::gio;
@@ -18,6 +20,7 @@ use gtk::{Application, CssProvider, IconTheme};
 use crate::{Mode, Result};
 
 use crate::bus::Handle;
+use crate::settings::Settings;
 use context::ContextObject;
 use widget::Window;
 
@@ -34,10 +37,21 @@ pub enum Message {
 ///
 /// `mode`:  The mode the application is being run in. When running in worker mode, the UI will be
 /// much more limited since it is the control that the user is expected to interact with.
-pub fn run(mode: Mode, bus: &Handle) -> Result<glib::ExitCode> {
+///
+/// `bus`:  Handle used to send messages to the application actors.
+///
+/// `settings`:  The application settings, which can be changed in the preferences.
+///
+/// `config_path`:  The path of the config file the settings were loaded from.
+pub fn run(
+    mode: Mode,
+    bus: &Handle,
+    settings: Settings,
+    config_path: PathBuf,
+) -> Result<glib::ExitCode> {
     gio::resources_register_include!("compiled.gresource")?;
 
-    let context = ContextObject::new(mode, bus.clone());
+    let context = ContextObject::new(mode, bus.clone(), settings, config_path);
 
     let app = Application::builder()
         // TODO: Comment this out for now for testing so that we can create multiple instances of
@@ -0,0 +1,612 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Defines the catalog page widget.
+//!
+//! The catalog page is used to review and classify the titles created by copy operations. For
+//! shows that have not been classified yet, the episode numbers proposed for each title (see
+//! [`crate::library::episodes`]) are displayed so that they can be corrected and confirmed.
+
+use gtk::{
+    Align,
+    Box,
+    Label,
+    Orientation,
+    PolicyType,
+    ScrolledWindow,
+    SelectionMode,
+};
+use gtk::glib::{self, Object};
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use crate::{Error, Result};
+use crate::db;
+use crate::library::episodes::{self, Proposal};
+use crate::library::nfo;
+use crate::mediaserver;
+use crate::metadata::{self, MediaDetails, ProviderId};
+use crate::models::{Classification, CopyOperation, MediaLocation, MediaType, Title};
+use crate::notify::{self, Event};
+use crate::task;
+use crate::ui::ContextObject;
+use crate::ui::widget::{IconButton, TitleEditorWidget};
+
+/// The maximum number of copy operations listed.
+const RECENT_LIMIT: u32 = 100;
+
+glib::wrapper! {
+    pub struct CatalogPageWidget(ObjectSubclass<imp::CatalogPageWidget>)
+        @extends gtk::Box,
+                 gtk::Widget,
+        @implements gtk::Accessible,
+                    gtk::Buildable,
+                    gtk::ConstraintTarget,
+                    gtk::Orientable;
+}
+
+impl CatalogPageWidget {
+    /// Creates a new catalog page instance.
+    ///
+    /// # Args
+    ///
+    /// `context`:  The application context fo the UI.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the GObject cannot be created.
+    pub fn new(context: &ContextObject) -> Self {
+        Object::builder()
+            .property("context", context)
+            .build()
+    }
+
+    /// Builds the widget.
+    ///
+    /// Called by the implementation ([`imp::CatalogPageWidget`]) when constructed.
+    fn build_ui(&self) {
+        let imp = self.imp();
+
+        let copy_list = imp.copy_list
+            .borrow()
+            .clone();
+        copy_list.set_selection_mode(SelectionMode::Single);
+        copy_list.add_css_class("catalog-copy-list");
+
+        let copy_scroll = ScrolledWindow::builder()
+            .child(&copy_list)
+            .hscrollbar_policy(PolicyType::Never)
+            .vexpand(true)
+            .vscrollbar_policy(PolicyType::Automatic)
+            .width_request(320)
+            .build();
+
+        let refresh_button = IconButton::new(
+            "fontawesome.v7.solid.rotate-left-symbolic",
+            "Refresh",
+        );
+        refresh_button.add_css_class("default");
+
+        let copy_column = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .build();
+        copy_column.append(&refresh_button);
+        copy_column.append(&copy_scroll);
+
+        let heading_label = imp.heading_label
+            .borrow()
+            .clone();
+        heading_label.add_css_class("catalog-heading");
+        heading_label.set_halign(Align::Start);
+        heading_label.set_hexpand(true);
+
+        let save_button = IconButton::new(
+            "fontawesome.v7.solid.circle-check-symbolic",
+            "Save",
+        );
+        save_button.add_css_class("success");
+        save_button.set_visible(false);
+
+        let header_row = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(4)
+            .build();
+        header_row.append(&heading_label);
+        header_row.append(&save_button);
+
+        let title_list = imp.title_list
+            .borrow()
+            .clone();
+        title_list.set_selection_mode(SelectionMode::None);
+        title_list.add_css_class("catalog-title-list");
+
+        let title_scroll = ScrolledWindow::builder()
+            .child(&title_list)
+            .hexpand(true)
+            .hscrollbar_policy(PolicyType::Never)
+            .vexpand(true)
+            .vscrollbar_policy(PolicyType::Automatic)
+            .build();
+
+        let title_column = Box::builder()
+            .hexpand(true)
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .build();
+        title_column.append(&header_row);
+        title_column.append(&title_scroll);
+
+        self.append(&copy_column);
+        self.append(&title_column);
+
+        self.set_margin_bottom(16);
+        self.set_margin_end(16);
+        self.set_margin_start(16);
+        self.set_margin_top(16);
+        self.set_orientation(Orientation::Horizontal);
+        self.set_spacing(16);
+
+        imp.refresh_button.replace(Some(refresh_button));
+        imp.save_button.replace(Some(save_button));
+    }
+
+    /// Connects the signals of the child widgets.
+    ///
+    /// Called by the implementation ([`imp::CatalogPageWidget`]) when constructed.
+    fn setup_callbacks(&self) {
+        let imp = self.imp();
+
+        self.connect_map(|page| {
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                page,
+                async move {
+                    page.refresh().await;
+                }
+            ));
+        });
+
+        imp.refresh_button
+            .borrow()
+            .as_ref()
+            .expect("refresh_button should not be None")
+            .connect_clicked(glib::clone!(
+                #[weak(rename_to = page)]
+                self,
+                move |_| {
+                    glib::spawn_future_local(glib::clone!(
+                        #[weak]
+                        page,
+                        async move {
+                            page.refresh().await;
+                        }
+                    ));
+                }
+            ));
+
+        imp.copy_list.borrow().connect_row_selected(glib::clone!(
+            #[weak(rename_to = page)]
+            self,
+            move |_, row| {
+                let selection = row.and_then(|row| {
+                    let index = usize::try_from(row.index()).ok()?;
+                    page.imp().copy_operations.borrow().get(index).map(Selection::new)
+                });
+                glib::spawn_future_local(glib::clone!(
+                    #[weak]
+                    page,
+                    async move {
+                        page.select_copy_operation(selection).await;
+                    }
+                ));
+            }
+        ));
+
+        imp.save_button
+            .borrow()
+            .as_ref()
+            .expect("save_button should not be None")
+            .connect_clicked(glib::clone!(
+                #[weak(rename_to = page)]
+                self,
+                move |_| {
+                    glib::spawn_future_local(glib::clone!(
+                        #[weak]
+                        page,
+                        async move {
+                            page.save().await;
+                        }
+                    ));
+                }
+            ));
+    }
+
+    /// Reloads the list of recent copy operations.
+    async fn refresh(&self) {
+        let context = self.context().expect("context not set");
+        let copy_operations = match recent_copy_operations(&context).await {
+            Ok(copy_operations) => copy_operations,
+            Err(error) => {
+                tracing::error!(?error, "failed to read recent copy operations");
+                return;
+            },
+        };
+
+        let imp = self.imp();
+        let copy_list = imp.copy_list
+            .borrow()
+            .clone();
+        let selected = imp.selected.borrow().as_ref().map(|s| s.id);
+        let position = copy_operations.iter().position(|c| Some(c.id) == selected);
+
+        // Replace the list before updating the rows since removing the rows will emit the row
+        // selected signal.
+        let labels: Vec<String> = copy_operations.iter().map(copy_operation_label).collect();
+        imp.copy_operations.replace(copy_operations);
+        copy_list.remove_all();
+
+        for text in labels {
+            let label = Label::builder()
+                .halign(Align::Start)
+                .label(text)
+                .build();
+            copy_list.append(&label);
+        }
+
+        if let Some(position) = position {
+            copy_list.select_row(copy_list.row_at_index(position as i32).as_ref());
+        }
+    }
+
+    /// Displays the titles of the selected copy operation.
+    ///
+    /// # Args
+    ///
+    /// `selection`:  The selected copy operation or `None` if the selection was cleared.
+    async fn select_copy_operation(&self, selection: Option<Selection>) {
+        let imp = self.imp();
+        imp.selected.replace(selection.clone());
+        self.set_titles(Vec::new(), Vec::new());
+
+        let Some(selection) = selection else {
+            imp.heading_label.borrow().set_label("");
+            return;
+        };
+
+        imp.heading_label.borrow().set_label(&selection.label);
+
+        let context = self.context().expect("context not set");
+        match load_titles(&context, &selection).await {
+            Ok((titles, proposals)) => {
+                // Ignore the titles if the selection changed while loading.
+                let current = imp.selected.borrow().as_ref().map(|s| s.id);
+                if current == Some(selection.id) {
+                    self.set_titles(titles, proposals);
+                }
+            },
+            Err(error) => {
+                tracing::error!(id=selection.id, ?error, "failed to load titles");
+            },
+        }
+    }
+
+    /// Replaces the displayed titles.
+    ///
+    /// # Args
+    ///
+    /// `titles`:  The titles to display.
+    ///
+    /// `proposals`:  The proposed episode assignments for the titles. Empty if there are none.
+    fn set_titles(&self, titles: Vec<Title>, proposals: Vec<Proposal>) {
+        let imp = self.imp();
+        let title_list = imp.title_list
+            .borrow()
+            .clone();
+        title_list.remove_all();
+
+        let editors: Vec<TitleEditorWidget> = titles.iter()
+            .map(|title| {
+                let proposal = proposals.iter().find(|p| p.title_id == title.id);
+                let editor = TitleEditorWidget::new();
+                editor.bind(title, proposal);
+                title_list.append(&editor);
+                editor
+            })
+            .collect();
+
+        if let Some(save_button) = imp.save_button.borrow().as_ref() {
+            save_button.set_visible(!titles.is_empty());
+        }
+        imp.titles.replace(titles);
+        imp.title_editors.replace(editors);
+    }
+
+    /// Saves the classification of the displayed titles.
+    ///
+    /// The Jellyfin sidecars of the titles' videos in the library are updated to match and the
+    /// media server is asked to refresh the videos. A [`Event::CatalogSaved`] notification is
+    /// published for the titles that were kept.
+    async fn save(&self) {
+        let imp = self.imp();
+        let Some(selection) = imp.selected.borrow().clone() else {
+            return;
+        };
+        let classifications: Vec<Classification> = imp.title_editors
+            .borrow()
+            .iter()
+            .map(TitleEditorWidget::classification)
+            .collect();
+
+        let context = self.context().expect("context not set");
+        let mut titles = imp.titles.take();
+        for (title, classification) in titles.iter_mut().zip(classifications) {
+            title.classify(classification);
+        }
+
+        // The titles are moved to the blocking thread and handed back so the page keeps them.
+        let result = db::run(&context.bus(), move |conn| {
+            let result = update_titles(conn, &titles);
+            Ok((titles, result))
+        }).await;
+
+        let result = match result {
+            Ok((titles, result)) => {
+                let saved = result.map(|()| {
+                    let sidecars: Vec<_> = titles.iter().flat_map(nfo::sidecars).collect();
+                    let locations: Vec<_> = titles.iter()
+                        .filter(|t| !t.discarded)
+                        .filter_map(|t| t.videos.as_ref())
+                        .flatten()
+                        .map(|v| v.location.clone())
+                        .collect();
+                    let count = titles.iter().filter(|t| !t.discarded).count();
+                    (sidecars, locations, count)
+                });
+                imp.titles.replace(titles);
+                saved
+            },
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok((sidecars, locations, count)) => {
+                tracing::info!(id=selection.id, "saved title classifications");
+                if let Err(error) = nfo::write_all(sidecars).await {
+                    tracing::error!(id=selection.id, ?error, "failed to write sidecars");
+                }
+                task::spawn(refresh_media_server(locations));
+                let event = Event::CatalogSaved { title: selection.label.clone(), count };
+                notify::publish(&context.bus(), event).await;
+                self.select_copy_operation(Some(selection)).await;
+            },
+            Err(error) => {
+                tracing::error!(id=selection.id, ?error, "failed to save titles");
+                // The titles are lost if a database connection could not be made, so they are
+                // reloaded to keep the page in sync with the editors.
+                if imp.titles.borrow().is_empty() {
+                    self.select_copy_operation(Some(selection)).await;
+                }
+            },
+        }
+    }
+}
+
+/// The copy operation selected in the copy list.
+#[derive(Clone)]
+struct Selection {
+    /// The id of the copy operation.
+    id: u32,
+
+    /// The type of media that was copied.
+    media_type: MediaType,
+
+    /// The movie or show the copy operation is associated with.
+    metadata_id: Option<ProviderId>,
+
+    /// Describes the copy operation.
+    label: String,
+}
+
+impl Selection {
+    /// Creates the selection for a copy operation.
+    fn new(copy_operation: &CopyOperation) -> Self {
+        Self {
+            id: copy_operation.id,
+            media_type: copy_operation.media_type,
+            metadata_id: copy_operation.metadata_id.clone(),
+            label: copy_operation_label(copy_operation),
+        }
+    }
+}
+
+/// Reads the most recent copy operations.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the copy operations cannot be read.
+async fn recent_copy_operations(context: &ContextObject) -> Result<Vec<CopyOperation>> {
+    db::run(&context.bus(), |conn| db::copy_operation::get_recent(conn, RECENT_LIMIT)).await
+}
+
+/// Reads the titles (and their videos) created by the selected copy operation.
+///
+/// For shows whose titles have not been classified yet, the episodes are also proposed. The
+/// metadata for the show is used if the copy operation is associated with one. Failing to fetch the
+/// metadata is not an error; the proposals will just be based on the title durations.
+///
+/// # Errors
+///
+/// [`Error::Database`] if the titles cannot be read.
+async fn load_titles(
+    context: &ContextObject,
+    selection: &Selection,
+) -> Result<(Vec<Title>, Vec<Proposal>)> {
+    let bus = context.bus();
+
+    let id = selection.id;
+    let (mut titles, mut videos) = db::run(&bus, move |conn| {
+        let titles = db::title::get_by_copy_operation(conn, id)?;
+        let videos = db::video::get_by_copy_operation(conn, id)?;
+        Ok((titles, videos))
+    }).await?;
+    for title in titles.iter_mut() {
+        let (matched, others) = videos.into_iter().partition(|v| v.title.id == title.id);
+        title.videos = Some(matched);
+        videos = others;
+    }
+
+    let unclassified = titles.iter().all(|t| t.classification() == Classification::Main);
+    if selection.media_type != MediaType::Show || !unclassified {
+        return Ok((titles, Vec::new()));
+    }
+
+    let mut details: Option<MediaDetails> = None;
+    if let Some(id) = selection.metadata_id.clone() {
+        match metadata::details(&bus, MediaType::Show, id).await {
+            Ok(value) => details = Some(value),
+            Err(Error::MetadataDisabled) => {},
+            Err(error) => tracing::warn!(?error, "failed to get show metadata"),
+        }
+    }
+
+    let proposals = db::run(&bus, move |conn| {
+        episodes::load_proposals(conn, id, details.as_ref())
+    }).await?;
+    Ok((titles, proposals))
+}
+
+/// Asks the media server to refresh the videos in the library.
+///
+/// The refresh is checked in the background since it can take a while for the media server to
+/// scan the videos. Failures are only logged.
+///
+/// # Args
+///
+/// `locations`:  The locations of the videos. Locations outside of the library are ignored.
+async fn refresh_media_server(locations: Vec<MediaLocation>) {
+    match mediaserver::library_updated(&locations).await {
+        Ok(()) | Err(Error::MediaServerDisabled) => {},
+        Err(error) => tracing::warn!(?error, "media server refresh failed"),
+    }
+}
+
+/// Writes the titles to the database.
+///
+/// # Errors
+///
+/// [`Error::Database`] if any of the titles cannot be updated. None of the titles will be updated
+/// in that case.
+fn update_titles(conn: &mut rusqlite::Connection, titles: &[Title]) -> Result<()> {
+    let transaction = db::transaction::start(conn)?;
+    for title in titles {
+        db::title::update(&transaction, title)?;
+    }
+    db::transaction::commit(transaction)
+}
+
+/// Creates the text used to describe a copy operation.
+fn copy_operation_label(copy_operation: &CopyOperation) -> String {
+    match copy_operation.media_type {
+        MediaType::Movie => {
+            format!("{} ({})", copy_operation.title, copy_operation.year)
+        },
+        MediaType::Show => {
+            format!(
+                "{} ({}) - Season {} Disc {}",
+                copy_operation.title,
+                copy_operation.year,
+                copy_operation.season,
+                copy_operation.disc,
+            )
+        },
+    }
+}
+
+mod imp {
+    //! Implemenation for the catalog page widget.
+
+    use std::cell::RefCell;
+
+    use gtk::{Box, Label, ListBox};
+
+    use gtk::glib::{self, Properties};
+    use gtk::prelude::*;
+    use gtk::subclass::prelude::*;
+
+    use crate::models::{CopyOperation, Title};
+    use crate::ui::ContextObject;
+    use crate::ui::widget::{IconButton, TitleEditorWidget};
+
+    use super::Selection;
+
+    /// Implemenation for [`super::CatalogPageWidget`].
+    #[derive(Default, Properties)]
+    #[properties(wrapper_type = super::CatalogPageWidget)]
+    pub struct CatalogPageWidget {
+        /// List of the recent copy operations.
+        pub(super) copy_list: RefCell<ListBox>,
+
+        /// The copy operations displayed in the copy list (in the same order).
+        pub(super) copy_operations: RefCell<Vec<CopyOperation>>,
+
+        /// The selected copy operation.
+        pub(super) selected: RefCell<Option<Selection>>,
+
+        /// Describes the selected copy operation.
+        pub(super) heading_label: RefCell<Label>,
+
+        /// List of the titles created by the selected copy operation.
+        pub(super) title_list: RefCell<ListBox>,
+
+        /// The titles displayed in the title list (in the same order).
+        pub(super) titles: RefCell<Vec<Title>>,
+
+        /// The editors for each of the displayed titles.
+        pub(super) title_editors: RefCell<Vec<TitleEditorWidget>>,
+
+        /// Reloads the copy list.
+        pub(super) refresh_button: RefCell<Option<IconButton>>,
+
+        /// Saves the title classifications.
+        pub(super) save_button: RefCell<Option<IconButton>>,
+
+        /// The application context.
+        #[property(get, set = Self::set_context, construct_only)]
+        pub(super) context: RefCell<Option<ContextObject>>,
+    }
+
+    impl CatalogPageWidget {
+        /// Sets the application context.
+        fn set_context(&self, context: Option<ContextObject>) {
+            self.context.replace(context);
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CatalogPageWidget {
+        const NAME: &'static str = "ArtieCatalogPageWidget";
+        type Type = super::CatalogPageWidget;
+        type ParentType = Box;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for CatalogPageWidget {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.build_ui();
+            obj.setup_callbacks();
+        }
+    }
+
+    impl WidgetImpl for CatalogPageWidget {}
+
+    impl BoxImpl for CatalogPageWidget {}
+}
+
+#[cfg(test)]
+mod tests {
+    // TODO[TESTS]
+}
@@ -11,7 +11,12 @@ use gtk::{
     DropDown,
     Entry,
     Label,
+    ListBox,
+    ListBoxRow,
     Orientation,
+    Popover,
+    PositionType,
+    SelectionMode,
     StringList
 };
 use gtk::glib::{self, Object};
@@ -19,6 +24,7 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 
 use crate::drive::FormData;
+use crate::metadata::{ProviderId, SearchResult};
 use crate::models::{CopyParamaters, MediaType};
 use crate::ui::helpers;
 
@@ -51,6 +57,60 @@ impl CopyFormWidget {
         imp.season_number_entry.borrow().set_text("");
         imp.location_entry.borrow().set_text("");
         imp.memo_entry.borrow().set_text("");
+        imp.metadata_id.replace(None);
+        self.set_suggestions(Vec::new());
+    }
+
+    /// Gets the canonical identifier of the selected metadata search result.
+    ///
+    /// This will be `None` if a search result has not been selected or if the title was edited
+    /// after selecting a search result.
+    pub fn metadata_id(&self) -> Option<ProviderId> {
+        self.imp().metadata_id.borrow().clone()
+    }
+
+    /// Sets the metadata search results displayed below the title entry.
+    ///
+    /// The suggestions are hidden if the list is empty or the title entry is not focused.
+    ///
+    /// # Args
+    ///
+    /// `results`:  The search results to display.
+    pub fn set_suggestions(&self, results: Vec<SearchResult>) {
+        let imp = self.imp();
+
+        let suggestions_list = imp.suggestions_list.borrow();
+        suggestions_list.remove_all();
+        for result in &results {
+            let text = if result.year > 0 {
+                format!("{} ({})", result.name, result.year)
+            } else {
+                result.name.clone()
+            };
+            let label = Label::builder()
+                .halign(Align::Start)
+                .label(text)
+                .tooltip_text(&result.overview)
+                .build();
+            let row = ListBoxRow::builder()
+                .child(&label)
+                .build();
+            suggestions_list.append(&row);
+        }
+
+        let title_focused = imp.title_entry
+            .borrow()
+            .delegate()
+            .is_some_and(|delegate| delegate.has_focus());
+
+        let popover = imp.suggestions_popover.borrow();
+        if !results.is_empty() && title_focused {
+            popover.popup();
+        } else {
+            popover.popdown();
+        }
+
+        imp.suggestions.replace(results);
     }
 
     /// Subscribe to changes to the media type.
@@ -257,6 +317,10 @@ impl CopyFormWidget {
             .borrow()
             .text();
 
+        let metadata_id = imp.metadata_id
+            .borrow()
+            .clone();
+
         CopyParamaters {
             media_type,
             title: title.into(),
@@ -265,6 +329,7 @@ impl CopyFormWidget {
             disc_number,
             location: location.into(),
             memo: memo.into(),
+            metadata_id,
         }
     }
 
@@ -274,7 +339,10 @@ impl CopyFormWidget {
         if let Some(media_type) = MediaType::from_string(&form_data.media_type) {
             imp.type_dropdown.borrow().set_selected(media_type.as_index());
         };
+        imp.selecting.set(true);
         imp.title_entry.borrow().set_text(&form_data.title);
+        imp.selecting.set(false);
+        imp.metadata_id.replace(ProviderId::parse(&form_data.metadata_id));
         imp.year_entry.borrow().set_text(&form_data.year);
         imp.disc_number_entry.borrow().set_text(&form_data.disc_number);
         imp.season_number_entry.borrow().set_text(&form_data.season_number);
@@ -341,6 +409,20 @@ impl CopyFormWidget {
         title_field.append(&title_entry);
         title_field.append(&title_label);
 
+        let suggestions_list = ListBox::builder()
+            .selection_mode(SelectionMode::None)
+            .build();
+
+        let suggestions_popover = Popover::builder()
+            .autohide(false)
+            .can_focus(false)
+            .child(&suggestions_list)
+            .halign(Align::Start)
+            .has_arrow(false)
+            .position(PositionType::Bottom)
+            .build();
+        suggestions_popover.set_parent(&title_entry);
+
         let year_entry = Entry::builder()
             .max_length(4)
             .max_width_chars(12)
@@ -455,6 +537,8 @@ impl CopyFormWidget {
         let imp = self.imp();
         imp.type_dropdown.replace(type_dropdown);
         imp.title_entry.replace(title_entry);
+        imp.suggestions_popover.replace(suggestions_popover);
+        imp.suggestions_list.replace(suggestions_list);
         imp.year_entry.replace(year_entry);
         imp.disc_number_entry.replace(disc_number_entry);
         imp.season_number_field.replace(season_number_field);
@@ -496,6 +580,35 @@ impl CopyFormWidget {
     fn setup_callbacks(&self) {
         let imp = self.imp();
 
+        let title_entry = imp.title_entry
+            .borrow()
+            .clone();
+        if let Some(delegate) = title_entry.delegate() {
+            // Editing the title invalidates the selected search result. This is connected before
+            // any of the `connect_*` subscribers so that they see the updated identifier.
+            delegate.connect_text_notify(glib::clone!(
+                #[weak(rename_to = form)]
+                self,
+                move |_| {
+                    if !form.imp().selecting.get() {
+                        form.imp().metadata_id.replace(None);
+                    }
+                }
+            ));
+        } else {
+            panic!("failed to get delegate for title");
+        }
+
+        imp.suggestions_list.borrow().connect_row_activated(glib::clone!(
+            #[weak(rename_to = form)]
+            self,
+            move |_, row| {
+                if let Ok(index) = usize::try_from(row.index()) {
+                    form.select_suggestion(index);
+                }
+            }
+        ));
+
         let year_entry = imp.year_entry
             .borrow()
             .clone();
@@ -522,6 +635,30 @@ impl CopyFormWidget {
         }
     }
 
+    /// Fills in the form using the metadata search result at the provided index.
+    ///
+    /// # Args
+    ///
+    /// `index`:  The index of the selected search result.
+    fn select_suggestion(&self, index: usize) {
+        let imp = self.imp();
+
+        let Some(result) = imp.suggestions.borrow().get(index).cloned() else {
+            return;
+        };
+
+        imp.suggestions_popover.borrow().popdown();
+
+        // The identifier is set before the title so that the title subscribers see the new value.
+        imp.metadata_id.replace(Some(result.id));
+        imp.selecting.set(true);
+        imp.title_entry.borrow().set_text(&result.name);
+        imp.selecting.set(false);
+        if result.year > 0 {
+            imp.year_entry.borrow().set_text(&result.year.to_string());
+        }
+    }
+
     /// Validates the title and return the result.
     ///
     /// This will update the entry's CSS to reflect is validly.
@@ -641,12 +778,15 @@ fn number_only_insert_text(entry: &gtk::Editable, text: &str, _position: &mut i3
 }
 
 mod imp {
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
 
-    use gtk::{Box, Entry, DropDown};
+    use gtk::{Box, Entry, DropDown, ListBox, Popover};
     use gtk::glib;
+    use gtk::prelude::*;
     use gtk::subclass::prelude::*;
 
+    use crate::metadata::{ProviderId, SearchResult};
+
     #[derive(Default)]
     pub struct CopyFormWidget {
         /// Dropdown used to select the type of media.
@@ -655,6 +795,22 @@ mod imp {
         /// The entry for the movie title.
         pub(super) title_entry: RefCell<Entry>,
 
+        /// Popover displaying the metadata search results below the title entry.
+        pub(super) suggestions_popover: RefCell<Popover>,
+
+        /// The list of metadata search results.
+        pub(super) suggestions_list: RefCell<ListBox>,
+
+        /// The metadata search results currently displayed.
+        pub(super) suggestions: RefCell<Vec<SearchResult>>,
+
+        /// The canonical identifier of the selected search result.
+        pub(super) metadata_id: RefCell<Option<ProviderId>>,
+
+        /// Set while the title is being changed programmatically so that the selected search
+        /// result is not cleared.
+        pub(super) selecting: Cell<bool>,
+
         /// The entry for the release year.
         pub(super) year_entry: RefCell<Entry>,
 
@@ -690,6 +846,10 @@ mod imp {
             obj.setup_bindings();
             obj.setup_callbacks();
         }
+
+        fn dispose(&self) {
+            self.suggestions_popover.borrow().unparent();
+        }
     }
 
     impl WidgetImpl for CopyFormWidget {}
@@ -5,6 +5,10 @@
 //!
 //! The optical drive widget is used to initiate, monitor, and terminate copy operations.
 
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
 use gtk::{
     Align,
     Box,
@@ -23,6 +27,9 @@ use crate::ui::data::{OpticalDriveState, OpticalDriveObject};
 use crate::ui::widget::{CopyFormWidget, IconButton};
 use crate::task;
 
+/// How long to wait after the title stops changing before searching for metadata.
+const SEARCH_DELAY: Duration = Duration::from_millis(300);
+
 glib::wrapper! {
     pub struct DriveWidget(ObjectSubclass<imp::DriveWidget>)
         @extends gtk::Box,
@@ -104,6 +111,7 @@ impl DriveWidget {
         self.build_copying_ui(&stack);
         self.build_success_ui(&stack);
         self.build_failed_ui(&stack);
+        self.build_storage_ui(&stack);
 
         let content_row = Box::builder()
             .orientation(Orientation::Horizontal)
@@ -444,6 +452,63 @@ impl DriveWidget {
         imp.error_message.replace(error_message);
     }
 
+    /// Builds the view used when the drive is in a worker node whose media folders have not been
+    /// verified as shared with the control node.
+    ///
+    /// # Args
+    ///
+    /// `stack`:  The stack widget that the view should be added to..
+    fn build_storage_ui(&self, stack: &Stack) {
+        let icon_box = self.build_icon_box(
+            Some("fontawesome.v7.solid.exclamation-triangle"),
+            "failed"
+        );
+
+        let notice = Label::builder()
+            .halign(Align::Start)
+            .hexpand(true)
+            .label("Discs cannot be copied until the worker's media folders are verified.")
+            .build();
+
+        let storage_message = Label::builder()
+            .halign(Align::Start)
+            .hexpand(true)
+            .margin_bottom(8)
+            .margin_end(8)
+            .margin_start(8)
+            .margin_top(8)
+            .build();
+
+        let storage_message_box = Box::builder()
+            .build();
+        storage_message_box.add_css_class("drive-widget-error-message");
+        storage_message_box.append(&storage_message);
+
+        let content = Box::builder()
+            .margin_bottom(8)
+            .margin_end(8)
+            .margin_start(8)
+            .margin_top(8)
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .valign(Align::Center)
+            .vexpand(true)
+            .build();
+        content.append(&notice);
+        content.append(&storage_message_box);
+
+        let storage_view = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .build();
+        storage_view.append(&icon_box);
+        storage_view.append(&content);
+
+        stack.add_named(&storage_view, Some("storage-view"));
+        let imp = self.imp();
+        imp.storage_view.replace(storage_view);
+        imp.storage_message.replace(storage_message);
+    }
+
     /// Configures the signals and callbacks.
     ///
     /// Called by the implementation ([`imp::DriveWidget`]) when constructed.
@@ -530,6 +595,7 @@ impl DriveWidget {
                     OpticalDriveState::Copying => String::from("copying-view"),
                     OpticalDriveState::Success => String::from("success-view"),
                     OpticalDriveState::Failed => String::from("failed-view"),
+                    OpticalDriveState::StorageUnverified => String::from("storage-view"),
                 };
                 Some(view_name)
             })
@@ -544,6 +610,13 @@ impl DriveWidget {
             .build();
         bindings.push(error_binding);
 
+        let storage_message = imp.storage_message.borrow();
+        let storage_binding = drive_object
+            .bind_property("storage-message", &storage_message.clone(), "label")
+            .sync_create()
+            .build();
+        bindings.push(storage_binding);
+
         let stage_label = imp.stage_label.borrow();
         let stage_binding = drive_object
             .bind_property("stage", &stage_label.clone(), "label")
@@ -693,13 +766,53 @@ impl DriveWidget {
 
         let drive = drive_object
             .clone();
+        let form = copy_form
+            .clone();
+        let search_generation = Rc::new(Cell::new(0u32));
         copy_form.connect_title_changed(move |title| {
-            let title = title.to_owned();
+            let data = FormDataUpdate {
+                title: Some(title.to_owned()),
+                metadata_id: Some(form.metadata_id().map(|id| id.to_string()).unwrap_or_default()),
+                ..FormDataUpdate::default()
+            };
             glib::spawn_future_local(glib::clone!(
                 #[weak]
                 drive,
                 async move {
-                    drive.save_form_data(FormDataUpdate::title(title)).await;
+                    drive.save_form_data(data).await;
+                }
+            ));
+
+            // Search for the title once the user stops typing. A search result was just selected
+            // if the identifier is set so there is no reason to search again.
+            let generation = search_generation.get().wrapping_add(1);
+            search_generation.set(generation);
+            if form.metadata_id().is_some() {
+                form.set_suggestions(Vec::new());
+                return;
+            }
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                drive,
+                #[weak]
+                form,
+                #[strong]
+                search_generation,
+                async move {
+                    glib::timeout_future(SEARCH_DELAY).await;
+                    if search_generation.get() != generation {
+                        return;
+                    }
+
+                    let params = form.get_copy_parameters();
+                    let year = Some(params.release_year).filter(|y| *y > 0);
+                    let results = drive.search_metadata(params.media_type, &params.title, year)
+                        .await;
+
+                    // Ignore the results if the title changed while searching.
+                    if search_generation.get() == generation {
+                        form.set_suggestions(results);
+                    }
                 }
             ));
         });
@@ -871,6 +984,12 @@ mod imp {
         /// The error message displayed in the failed view.
         pub(super) error_message: RefCell<Label>,
 
+        /// View when the drive is in a worker node whose media folders have not been verified.
+        pub(super) storage_view: RefCell<Box>,
+
+        /// Describes why the media folders have not been verified in the storage view.
+        pub(super) storage_message: RefCell<Label>,
+
         /// The widget's bindings.
         ///
         /// This is populated when the widget is bound to a optical drive object and cleared when
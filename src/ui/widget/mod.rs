@@ -3,14 +3,20 @@
 
 //! Custom UI widgets.
 
+mod catalog_page;
 mod copy_form;
 mod copy_page;
 mod drive;
 mod icon_button;
+mod preferences;
+mod title_editor;
 mod window;
 
+pub use catalog_page::CatalogPageWidget;
 pub use copy_form::CopyFormWidget;
 pub use copy_page::CopyPageWidget;
 pub use drive::DriveWidget;
 pub use icon_button::IconButton;
+pub use preferences::PreferencesWindow;
+pub use title_editor::TitleEditorWidget;
 pub use window::Window;
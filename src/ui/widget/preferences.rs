@@ -0,0 +1,1096 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Defines the preferences window.
+//!
+//! The preferences window is used to edit the paths, worker nodes, and notification sinks. The
+//! changes are validated using [`Settings::validate`] and saved to the config file. The worker
+//! nodes and the notification sinks are applied without a restart; changes to the paths take effect
+//! the next time the application is started.
+//!
+//! The status of the connection to each worker node is displayed next to the worker and refreshed
+//! while the window is open, along with the worker nodes found on the local network that can be
+//! adopted (see [`crate::net::discovery`]).
+//!
+//! There are no MakeMKV or HandBrake options to edit yet since the settings do not have any. The
+//! MakeMKV executable is set using the [`makemkv::PROGRAM_ENV`] variable and HandBrake is not run
+//! by the application.
+
+use std::time::Duration;
+
+use chrono::Local;
+use gtk::{
+    AlertDialog,
+    Align,
+    Box,
+    CheckButton,
+    Entry,
+    Label,
+    Notebook,
+    Orientation,
+    PolicyType,
+    ScrolledWindow,
+    SelectionMode,
+    StringList,
+};
+use gtk::glib::{self, Object};
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use crate::Error;
+use crate::net;
+use crate::net::client::{ConnectionState, WorkerStatus};
+use crate::net::discovery::DiscoveredWorker;
+use crate::notify::{self, EventKind, SinkKind, SinkSettings};
+use crate::settings::{Problem, Settings};
+use crate::ui::ContextObject;
+use crate::ui::helpers;
+use crate::ui::widget::IconButton;
+
+/// The kinds of notification sinks that can be added in the preferences.
+///
+/// Email sinks have too many settings for the form, so they need to be added to the config file.
+/// They are still listed and can be removed.
+const SINK_KINDS: [&str; 4] = ["Webhook", "ntfy", "Gotify", "Desktop"];
+
+/// How often the status of the worker nodes and the discovered workers are refreshed.
+const STATUS_INTERVAL: Duration = Duration::from_secs(2);
+
+glib::wrapper! {
+    pub struct PreferencesWindow(ObjectSubclass<imp::PreferencesWindow>)
+        @extends gtk::Window,
+                 gtk::Widget,
+        @implements gtk::Accessible,
+                    gtk::Buildable,
+                    gtk::ConstraintTarget,
+                    gtk::Native,
+                    gtk::Root,
+                    gtk::ShortcutManager;
+}
+
+impl PreferencesWindow {
+    /// Creates a new preferences window.
+    ///
+    /// The form is filled using the current settings from the context.
+    ///
+    /// # Args
+    ///
+    /// `parent`:  The window the preferences are opened from.
+    ///
+    /// `context`:  The application context of the UI.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the GObject cannot be created.
+    pub fn new(parent: &impl IsA<gtk::Window>, context: &ContextObject) -> Self {
+        Object::builder()
+            .property("context", context)
+            .property("modal", true)
+            .property("transient-for", parent)
+            .build()
+    }
+
+    /// Builds the widget.
+    ///
+    /// Called by the implementation ([`imp::PreferencesWindow`]) when constructed.
+    fn build_ui(&self) {
+        let notebook = Notebook::builder()
+            .vexpand(true)
+            .build();
+        notebook.append_page(&self.build_paths_page(), Some(&Label::new(Some("Paths"))));
+        notebook.append_page(&self.build_workers_page(), Some(&Label::new(Some("Workers"))));
+        notebook.append_page(&self.build_sinks_page(), Some(&Label::new(Some("Notifications"))));
+        // TODO: Add MakeMKV and HandBrake pages once the settings have options for them (follow-up
+        //       to user-041). The MakeMKV executable first needs to move from the MAKEMKVCON
+        //       variable to the settings, and HandBrake needs to be run by transcode operations.
+
+        let imp = self.imp();
+
+        let problems_label = imp.problems_label
+            .borrow()
+            .clone();
+        problems_label.add_css_class("preferences-problems");
+        problems_label.set_halign(Align::Start);
+        problems_label.set_hexpand(true);
+        problems_label.set_wrap(true);
+        problems_label.set_xalign(0.0);
+
+        let cancel_button = IconButton::new(
+            "fontawesome.v7.solid.ban-symbolic",
+            "Cancel",
+        );
+        cancel_button.add_css_class("default");
+
+        let save_button = IconButton::new(
+            "fontawesome.v7.solid.circle-check-symbolic",
+            "Save",
+        );
+        save_button.add_css_class("success");
+
+        let footer_row = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(8)
+            .build();
+        footer_row.append(&problems_label);
+        footer_row.append(&cancel_button);
+        footer_row.append(&save_button);
+
+        let layout = Box::builder()
+            .margin_bottom(16)
+            .margin_end(16)
+            .margin_start(16)
+            .margin_top(16)
+            .orientation(Orientation::Vertical)
+            .spacing(16)
+            .build();
+        layout.append(&notebook);
+        layout.append(&footer_row);
+
+        self.set_child(Some(&layout));
+        self.set_default_height(560);
+        self.set_default_width(720);
+        self.set_title(Some("Preferences"));
+
+        imp.cancel_button.replace(Some(cancel_button));
+        imp.save_button.replace(Some(save_button));
+    }
+
+    /// Builds the page for editing the paths.
+    fn build_paths_page(&self) -> Box {
+        let imp = self.imp();
+
+        let page = Box::builder()
+            .margin_bottom(16)
+            .margin_end(16)
+            .margin_start(16)
+            .margin_top(16)
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .build();
+
+        let fields = [
+            (imp.inbox_entry.borrow().clone(), "Inbox"),
+            (imp.library_entry.borrow().clone(), "Library"),
+            (imp.archive_entry.borrow().clone(), "Archive"),
+            (imp.data_entry.borrow().clone(), "Data"),
+        ];
+        for (entry, label) in fields {
+            page.append(&form_field(&entry, label));
+        }
+
+        let note_label = Label::builder()
+            .halign(Align::Start)
+            .label("Changes to the paths take effect after restarting.")
+            .margin_start(8)
+            .build();
+        page.append(&note_label);
+
+        page
+    }
+
+    /// Builds the page for editing the worker nodes.
+    fn build_workers_page(&self) -> Box {
+        let imp = self.imp();
+
+        let worker_list = imp.worker_list
+            .borrow()
+            .clone();
+        worker_list.set_selection_mode(SelectionMode::None);
+        worker_list.add_css_class("preferences-list");
+
+        let worker_scroll = ScrolledWindow::builder()
+            .child(&worker_list)
+            .hscrollbar_policy(PolicyType::Never)
+            .vexpand(true)
+            .vscrollbar_policy(PolicyType::Automatic)
+            .build();
+
+        let worker_entry = imp.worker_entry
+            .borrow()
+            .clone();
+        worker_entry.set_placeholder_text(Some("192.168.1.20:7878"));
+
+        let add_button = IconButton::new(
+            "fontawesome.v7.solid.plus-symbolic",
+            "Add",
+        );
+        add_button.add_css_class("primary");
+        add_button.set_valign(Align::Start);
+
+        let add_row = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(8)
+            .build();
+        add_row.append(&form_field(&worker_entry, "Worker Address"));
+        add_row.append(&add_button);
+
+        let discovered_label = Label::builder()
+            .halign(Align::Start)
+            .label("Discovered Workers")
+            .margin_top(8)
+            .build();
+
+        let discovered_placeholder = Label::builder()
+            .label("No workers found on the local network. Discovery is enabled in the config.")
+            .wrap(true)
+            .build();
+
+        let discovered_list = imp.discovered_list
+            .borrow()
+            .clone();
+        discovered_list.set_placeholder(Some(&discovered_placeholder));
+        discovered_list.set_selection_mode(SelectionMode::None);
+        discovered_list.add_css_class("preferences-list");
+
+        let discovered_scroll = ScrolledWindow::builder()
+            .child(&discovered_list)
+            .hscrollbar_policy(PolicyType::Never)
+            .min_content_height(96)
+            .vscrollbar_policy(PolicyType::Automatic)
+            .build();
+
+        let page = Box::builder()
+            .margin_bottom(16)
+            .margin_end(16)
+            .margin_start(16)
+            .margin_top(16)
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .build();
+        page.append(&worker_scroll);
+        page.append(&add_row);
+        page.append(&discovered_label);
+        page.append(&discovered_scroll);
+
+        imp.add_worker_button.replace(Some(add_button));
+
+        page
+    }
+
+    /// Builds the page for editing the notification sinks.
+    fn build_sinks_page(&self) -> Box {
+        let imp = self.imp();
+
+        let sink_list = imp.sink_list
+            .borrow()
+            .clone();
+        sink_list.set_selection_mode(SelectionMode::None);
+        sink_list.add_css_class("preferences-list");
+
+        let sink_scroll = ScrolledWindow::builder()
+            .child(&sink_list)
+            .hscrollbar_policy(PolicyType::Never)
+            .vexpand(true)
+            .vscrollbar_policy(PolicyType::Automatic)
+            .build();
+
+        let kind_dropdown = imp.sink_kind_dropdown
+            .borrow()
+            .clone();
+        kind_dropdown.set_model(Some(&StringList::new(&SINK_KINDS)));
+        kind_dropdown.set_width_request(116);
+
+        let kind_label = Label::builder()
+            .halign(Align::Start)
+            .label("Type")
+            .margin_start(8)
+            .build();
+
+        let kind_field = Box::builder()
+            .orientation(Orientation::Vertical)
+            .build();
+        kind_field.append(&kind_dropdown);
+        kind_field.append(&kind_label);
+
+        let url_entry = imp.sink_url_entry
+            .borrow()
+            .clone();
+        url_entry.set_placeholder_text(Some("https://ntfy.sh/my-topic"));
+
+        let token_entry = imp.sink_token_entry
+            .borrow()
+            .clone();
+        token_entry.set_visibility(false);
+
+        let add_button = IconButton::new(
+            "fontawesome.v7.solid.plus-symbolic",
+            "Add",
+        );
+        add_button.add_css_class("primary");
+        add_button.set_valign(Align::Start);
+
+        let add_row = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(8)
+            .build();
+        add_row.append(&kind_field);
+        add_row.append(&form_field(&url_entry, "URL"));
+        add_row.append(&form_field(&token_entry, "Token (optional)"));
+        add_row.append(&add_button);
+
+        let events_row = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(8)
+            .build();
+        let event_checks = EventKind::ALL.iter()
+            .map(|kind| {
+                let check = CheckButton::with_label(kind.label());
+                events_row.append(&check);
+                check
+            })
+            .collect::<Vec<_>>();
+
+        let events_label = Label::builder()
+            .halign(Align::Start)
+            .label("Events (all if none are selected)")
+            .margin_start(8)
+            .build();
+
+        let page = Box::builder()
+            .margin_bottom(16)
+            .margin_end(16)
+            .margin_start(16)
+            .margin_top(16)
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .build();
+        page.append(&sink_scroll);
+        page.append(&add_row);
+        page.append(&events_row);
+        page.append(&events_label);
+
+        imp.add_sink_button.replace(Some(add_button));
+        imp.event_checks.replace(event_checks);
+
+        page
+    }
+
+    /// Connects the signals of the child widgets.
+    ///
+    /// Called by the implementation ([`imp::PreferencesWindow`]) when constructed.
+    fn setup_callbacks(&self) {
+        let imp = self.imp();
+
+        imp.add_worker_button
+            .borrow()
+            .as_ref()
+            .expect("add_worker_button should not be None")
+            .connect_clicked(glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_| {
+                    window.add_worker();
+                }
+            ));
+
+        imp.worker_entry.borrow().connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| {
+                window.add_worker();
+            }
+        ));
+
+        imp.add_sink_button
+            .borrow()
+            .as_ref()
+            .expect("add_sink_button should not be None")
+            .connect_clicked(glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_| {
+                    window.add_sink();
+                }
+            ));
+
+        imp.cancel_button
+            .borrow()
+            .as_ref()
+            .expect("cancel_button should not be None")
+            .connect_clicked(glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_| {
+                    window.close();
+                }
+            ));
+
+        imp.save_button
+            .borrow()
+            .as_ref()
+            .expect("save_button should not be None")
+            .connect_clicked(glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_| {
+                    glib::spawn_future_local(glib::clone!(
+                        #[weak]
+                        window,
+                        async move {
+                            window.save().await;
+                        }
+                    ));
+                }
+            ));
+
+        let weak_window = self.downgrade();
+        glib::spawn_future_local(async move {
+            while let Some(window) = weak_window.upgrade() {
+                if !window.refresh_workers().await {
+                    break;
+                }
+                drop(window);
+                glib::timeout_future(STATUS_INTERVAL).await;
+            }
+        });
+    }
+
+    /// Fills the form with the current settings.
+    ///
+    /// Called by the implementation ([`imp::PreferencesWindow`]) when constructed.
+    fn load(&self) {
+        let imp = self.imp();
+        let settings = self.context().expect("context not set").settings();
+
+        imp.inbox_entry.borrow().set_text(&settings.paths.inbox.to_string_lossy());
+        imp.library_entry.borrow().set_text(&settings.paths.library.to_string_lossy());
+        imp.archive_entry.borrow().set_text(&settings.paths.archive.to_string_lossy());
+        imp.data_entry.borrow().set_text(&settings.paths.data.to_string_lossy());
+
+        imp.workers.replace(settings.net.workers);
+        imp.sinks.replace(settings.notify.sinks);
+        self.update_worker_list();
+        self.update_sink_list();
+    }
+
+    /// Returns the settings with the values from the form.
+    ///
+    /// The settings that cannot be edited in the preferences keep their current values.
+    fn settings(&self) -> Settings {
+        let imp = self.imp();
+        let mut settings = self.context().expect("context not set").settings();
+
+        settings.paths.inbox = imp.inbox_entry.borrow().text().trim().into();
+        settings.paths.library = imp.library_entry.borrow().text().trim().into();
+        settings.paths.archive = imp.archive_entry.borrow().text().trim().into();
+        settings.paths.data = imp.data_entry.borrow().text().trim().into();
+        settings.net.workers = imp.workers.borrow().clone();
+        settings.notify.sinks = imp.sinks.borrow().clone();
+
+        settings
+    }
+
+    /// Adds the worker in the worker entry to the worker list.
+    ///
+    /// The worker is not added if it is invalid or already in the list.
+    fn add_worker(&self) {
+        let entry = self.imp().worker_entry.borrow().clone();
+        let worker = entry.text().trim().to_owned();
+
+        if !self.push_worker(worker) {
+            helpers::entry_invalid(&entry);
+            return;
+        }
+
+        helpers::entry_valid(&entry);
+        entry.set_text("");
+    }
+
+    /// Adds a worker to the workers being edited.
+    ///
+    /// # Args
+    ///
+    /// `worker`:  The address of the worker.
+    ///
+    /// Returns `false` if the worker was not added because it is invalid or already in the list.
+    fn push_worker(&self, worker: String) -> bool {
+        let mut settings = self.settings();
+        settings.net.workers.push(worker);
+        let key = format!("net.workers[{}]", settings.net.workers.len() - 1);
+        if !self.check_new_item(&settings, &key) {
+            return false;
+        }
+
+        self.imp().workers.replace(settings.net.workers);
+        self.update_worker_list();
+        true
+    }
+
+    /// Adds the sink in the sink form to the sink list.
+    ///
+    /// The sink is not added if it is invalid.
+    fn add_sink(&self) {
+        let imp = self.imp();
+        let url_entry = imp.sink_url_entry.borrow().clone();
+        let token_entry = imp.sink_token_entry.borrow().clone();
+        let url = url_entry.text().trim().to_owned();
+        let token = token_entry.text().trim().to_owned();
+
+        let kind = match imp.sink_kind_dropdown.borrow().selected() {
+            0 => SinkKind::Webhook { url },
+            1 => SinkKind::Ntfy { url, token },
+            2 => SinkKind::Gotify { url, token },
+            _ => SinkKind::Desktop,
+        };
+        let events = EventKind::ALL.iter()
+            .zip(imp.event_checks.borrow().iter())
+            .filter(|(_, check)| check.is_active())
+            .map(|(kind, _)| *kind)
+            .collect();
+
+        let mut settings = self.settings();
+        settings.notify.sinks.push(SinkSettings { kind, events });
+        let key = format!("notify.sinks[{}]", settings.notify.sinks.len() - 1);
+        if !self.check_new_item(&settings, &key) {
+            helpers::entry_invalid(&url_entry);
+            return;
+        }
+
+        helpers::entry_valid(&url_entry);
+        url_entry.set_text("");
+        token_entry.set_text("");
+        for check in imp.event_checks.borrow().iter() {
+            check.set_active(false);
+        }
+        imp.sinks.replace(settings.notify.sinks);
+        self.update_sink_list();
+    }
+
+    /// Validates the settings with a new worker or sink, displaying its problems if invalid.
+    ///
+    /// Problems with the other settings are ignored, since they are reported when saving.
+    ///
+    /// # Args
+    ///
+    /// `settings`:  The settings including the new item.
+    ///
+    /// `key`:  The config key of the new item (e.g. `net.workers[2]`).
+    ///
+    /// Returns `true` if the new item is valid.
+    fn check_new_item(&self, settings: &Settings, key: &str) -> bool {
+        let problems = match settings.validate() {
+            Err(Error::InvalidSettings { problems }) => problems.into_iter()
+                .filter(|problem| problem.key.starts_with(key))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        self.show_problems(&problems);
+        problems.is_empty()
+    }
+
+    /// Displays the problems found when validating the settings.
+    ///
+    /// The path entries with problems are marked as invalid.
+    ///
+    /// # Args
+    ///
+    /// `problems`:  The problems to display. Clears the problems if empty.
+    fn show_problems(&self, problems: &[Problem]) {
+        let imp = self.imp();
+
+        let path_entries = [
+            ("paths.inbox", imp.inbox_entry.borrow().clone()),
+            ("paths.library", imp.library_entry.borrow().clone()),
+            ("paths.archive", imp.archive_entry.borrow().clone()),
+            ("paths.data", imp.data_entry.borrow().clone()),
+        ];
+        for (key, entry) in path_entries {
+            let valid = !problems.iter().any(|problem| problem.key == key);
+            helpers::update_validity_style(&entry, valid);
+        }
+
+        let text = problems.iter()
+            .map(Problem::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        imp.problems_label.borrow().set_text(&text);
+    }
+
+    /// Displays an error that occurred while saving.
+    ///
+    /// # Args
+    ///
+    /// `message`:  The message to display.
+    fn show_error(&self, message: &str) {
+        self.imp().problems_label.borrow().set_text(message);
+    }
+
+    /// Validates, applies, and saves the settings.
+    ///
+    /// The window is closed if the settings are saved. A dialog is shown if a restart is needed for
+    /// some of the changes to take effect.
+    async fn save(&self) {
+        let context = self.context().expect("context not set");
+        let previous = context.settings();
+        let settings = self.settings();
+
+        match settings.validate() {
+            Ok(()) => self.show_problems(&[]),
+            Err(Error::InvalidSettings { problems }) => {
+                self.show_problems(&problems);
+                return;
+            },
+            Err(error) => {
+                tracing::error!(?error, "failed to validate settings");
+                self.show_error("The settings could not be validated.");
+                return;
+            },
+        }
+
+        let bus = context.bus();
+
+        if let Err(error) = notify::configure(&bus, settings.notify.clone()).await {
+            tracing::error!(?error, "failed to apply notification settings");
+            self.show_error("The notification settings could not be applied. Check the email \
+                             addresses of the email sinks in the config file.");
+            return;
+        }
+
+        let added = settings.net.workers.iter()
+            .filter(|worker| !previous.net.workers.contains(worker));
+        for worker in added {
+            match net::add_worker(&bus, worker).await {
+                Ok(()) | Err(Error::WorkerExists { .. }) => {},
+                Err(error) => {
+                    tracing::error!(?error, worker, "failed to add worker");
+                    self.show_error(&format!("Worker {} could not be added.", worker));
+                    return;
+                },
+            }
+        }
+
+        let removed = previous.net.workers.iter()
+            .filter(|worker| !settings.net.workers.contains(worker));
+        for worker in removed {
+            match net::remove_worker(&bus, worker).await {
+                Ok(()) | Err(Error::WorkerNotFound { .. }) => {},
+                Err(error) => {
+                    tracing::error!(?error, worker, "failed to remove worker");
+                    self.show_error(&format!("Worker {} could not be removed.", worker));
+                    return;
+                },
+            }
+        }
+
+        let restart_needed = settings.paths != previous.paths;
+
+        if let Err(error) = context.save_settings(settings) {
+            tracing::error!(?error, "failed to save settings");
+            let path = context.config_path();
+            self.show_error(&format!("The settings could not be saved to {}.", path.display()));
+            return;
+        }
+
+        if restart_needed {
+            let dialog = AlertDialog::builder()
+                .detail("Changes to the paths take effect after restarting.")
+                .message("Restart Required")
+                .modal(true)
+                .build();
+            dialog.show(self.transient_for().as_ref());
+        }
+
+        self.close();
+    }
+
+    /// Refreshes the status of the worker nodes and the discovered workers.
+    ///
+    /// Returns `false` if the workers cannot be retrieved (e.g. running as a worker node), so they
+    /// do not need to be refreshed again.
+    async fn refresh_workers(&self) -> bool {
+        let bus = self.context().expect("context not set").bus();
+        let workers = match net::list_workers(&bus).await {
+            Ok(statuses) => net::list_discovered(&bus).await
+                .map(|discovered| (statuses, discovered)),
+            Err(error) => Err(error),
+        };
+        let (statuses, discovered) = match workers {
+            Ok(workers) => workers,
+            Err(error) => {
+                tracing::debug!(?error, "worker status not available");
+                return false;
+            },
+        };
+
+        let imp = self.imp();
+        imp.worker_statuses.replace(
+            statuses.into_iter()
+                .map(|status| (status.addr.clone(), status))
+                .collect()
+        );
+        self.update_worker_status_labels();
+
+        // Only rebuild the list when the workers change, so a button is not replaced while it is
+        // being clicked.
+        let key = |worker: &DiscoveredWorker| {
+            (worker.addr.clone(), worker.hostname.clone(), worker.version)
+        };
+        let changed = !imp.discovered.borrow().iter().map(key).eq(discovered.iter().map(key));
+        if changed {
+            imp.discovered.replace(discovered);
+            self.update_discovered_list();
+        }
+        true
+    }
+
+    /// Updates the worker list to match the workers being edited.
+    fn update_worker_list(&self) {
+        let imp = self.imp();
+        let list = imp.worker_list.borrow().clone();
+        list.remove_all();
+
+        let mut status_labels = Vec::new();
+        for (index, worker) in imp.workers.borrow().iter().enumerate() {
+            let row = removable_row(worker, glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move || {
+                    window.imp().workers.borrow_mut().remove(index);
+                    window.update_worker_list();
+                }
+            ));
+
+            let status_label = Label::new(None);
+            status_label.add_css_class("preferences-worker-status");
+            row.insert_child_after(&status_label, row.first_child().as_ref());
+            status_labels.push(status_label);
+
+            list.append(&row);
+        }
+
+        imp.worker_status_labels.replace(status_labels);
+        self.update_worker_status_labels();
+        self.update_discovered_list();
+    }
+
+    /// Updates the discovered worker list, leaving out the workers being edited.
+    fn update_discovered_list(&self) {
+        let imp = self.imp();
+        let list = imp.discovered_list.borrow().clone();
+        list.remove_all();
+
+        let workers = imp.workers.borrow();
+        let discovered = imp.discovered.borrow();
+        for worker in discovered.iter().filter(|w| !workers.contains(&w.addr)) {
+            let addr = worker.addr.clone();
+            let row = discovered_row(worker, glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move || {
+                    window.push_worker(addr.clone());
+                }
+            ));
+            list.append(&row);
+        }
+    }
+
+    /// Updates the status displayed for each worker in the worker list.
+    fn update_worker_status_labels(&self) {
+        let imp = self.imp();
+        let statuses = imp.worker_statuses.borrow();
+        let workers = imp.workers.borrow();
+        for (worker, label) in workers.iter().zip(imp.worker_status_labels.borrow().iter()) {
+            label.set_text(&status_description(statuses.get(worker)));
+        }
+    }
+
+    /// Updates the sink list to match the sinks being edited.
+    fn update_sink_list(&self) {
+        let imp = self.imp();
+        let list = imp.sink_list.borrow().clone();
+        list.remove_all();
+
+        for (index, sink) in imp.sinks.borrow().iter().enumerate() {
+            let row = removable_row(&sink_description(sink), glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move || {
+                    window.imp().sinks.borrow_mut().remove(index);
+                    window.update_sink_list();
+                }
+            ));
+            list.append(&row);
+        }
+    }
+}
+
+/// Creates a form field with a label below the entry.
+///
+/// # Args
+///
+/// `entry`:  The field's entry.
+///
+/// `label`:  The field's label.
+fn form_field(entry: &Entry, label: &str) -> Box {
+    let label = Label::builder()
+        .halign(Align::Start)
+        .label(label)
+        .margin_start(8)
+        .build();
+
+    let field = Box::builder()
+        .hexpand(true)
+        .orientation(Orientation::Vertical)
+        .build();
+    field.append(entry);
+    field.append(&label);
+    field
+}
+
+/// Creates a list row with a label and a button to remove the row.
+///
+/// # Args
+///
+/// `text`:  The text of the row.
+///
+/// `remove`:  Called when the remove button is clicked.
+fn removable_row<F>(text: &str, remove: F) -> Box
+where
+    F: Fn() + 'static,
+{
+    let label = Label::builder()
+        .halign(Align::Start)
+        .hexpand(true)
+        .label(text)
+        .build();
+
+    let remove_button = IconButton::new(
+        "fontawesome.v7.solid.xmark-circle-symbolic",
+        "Remove",
+    );
+    remove_button.add_css_class("danger");
+    remove_button.connect_clicked(move |_| remove());
+
+    let row = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(8)
+        .build();
+    row.append(&label);
+    row.append(&remove_button);
+    row
+}
+
+/// Creates a row of the discovered worker list.
+///
+/// The adopt button is disabled if the worker uses a different protocol version.
+///
+/// # Args
+///
+/// `worker`:  The discovered worker.
+///
+/// `adopt`:  Called when the adopt button is clicked.
+fn discovered_row<F>(worker: &DiscoveredWorker, adopt: F) -> Box
+where
+    F: Fn() + 'static,
+{
+    let mut text = format!("{} ({})", worker.hostname, worker.addr);
+    if !worker.is_compatible() {
+        text.push_str(&format!(", protocol version {} is not supported", worker.version));
+    }
+
+    let label = Label::builder()
+        .halign(Align::Start)
+        .hexpand(true)
+        .label(&text)
+        .build();
+
+    let adopt_button = IconButton::new(
+        "fontawesome.v7.solid.plus-symbolic",
+        "Adopt",
+    );
+    adopt_button.add_css_class("primary");
+    adopt_button.set_sensitive(worker.is_compatible());
+    adopt_button.connect_clicked(move |_| adopt());
+
+    let row = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(8)
+        .build();
+    row.append(&label);
+    row.append(&adopt_button);
+    row
+}
+
+/// Returns the description of a worker's connection status displayed in the worker list.
+///
+/// # Args
+///
+/// `status`:  The status of the worker. `None` if the worker has not been saved yet.
+fn status_description(status: Option<&WorkerStatus>) -> String {
+    let Some(status) = status else {
+        return String::from("Not saved");
+    };
+
+    let last_seen = status.last_seen
+        .map(|time| format!(", last seen {}", time.with_timezone(&Local).format("%H:%M:%S")))
+        .unwrap_or_default();
+
+    match (status.state, status.remote_addr) {
+        (ConnectionState::Connected, Some(remote_addr)) => {
+            format!("Connected to {}{}", remote_addr, last_seen)
+        },
+        (ConnectionState::Connected, None) => format!("Connected{}", last_seen),
+        (ConnectionState::Connecting, _) if status.attempt == 0 => String::from("Connecting"),
+        (ConnectionState::Connecting, _) => {
+            format!("Connecting, attempt {}{}", status.attempt + 1, last_seen)
+        },
+        (ConnectionState::Waiting, _) => {
+            format!("Disconnected, {} failed attempts{}", status.attempt, last_seen)
+        },
+    }
+}
+
+/// Returns the description of a sink displayed in the sink list.
+///
+/// # Args
+///
+/// `sink`:  The sink settings.
+fn sink_description(sink: &SinkSettings) -> String {
+    let target = match &sink.kind {
+        SinkKind::Webhook { url } => format!("Webhook {}", url),
+        SinkKind::Ntfy { url, .. } => format!("ntfy {}", url),
+        SinkKind::Gotify { url, .. } => format!("Gotify {}", url),
+        SinkKind::Email { to, .. } => format!("Email {}", to.join(", ")),
+        SinkKind::Desktop => String::from("Desktop"),
+    };
+
+    if sink.events.is_empty() {
+        format!("{} (all events)", target)
+    } else {
+        let events = sink.events.iter()
+            .map(EventKind::label)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{} ({})", target, events)
+    }
+}
+
+mod imp {
+    //! Implementation for the preferences window.
+
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use gtk::{CheckButton, DropDown, Entry, Label, ListBox};
+    use gtk::glib::{self, Properties};
+    use gtk::prelude::*;
+    use gtk::subclass::prelude::*;
+
+    use crate::net::client::WorkerStatus;
+    use crate::net::discovery::DiscoveredWorker;
+    use crate::notify::SinkSettings;
+    use crate::ui::ContextObject;
+    use crate::ui::widget::IconButton;
+
+    /// Implementation for [`super::PreferencesWindow`].
+    #[derive(Default, Properties)]
+    #[properties(wrapper_type = super::PreferencesWindow)]
+    pub struct PreferencesWindow {
+        /// Entry for the inbox path.
+        pub(super) inbox_entry: RefCell<Entry>,
+
+        /// Entry for the library path.
+        pub(super) library_entry: RefCell<Entry>,
+
+        /// Entry for the archive path.
+        pub(super) archive_entry: RefCell<Entry>,
+
+        /// Entry for the data path.
+        pub(super) data_entry: RefCell<Entry>,
+
+        /// The worker addresses being edited.
+        pub(super) workers: RefCell<Vec<String>>,
+
+        /// List displaying the workers being edited.
+        pub(super) worker_list: RefCell<ListBox>,
+
+        /// Entry for the address of a worker to add.
+        pub(super) worker_entry: RefCell<Entry>,
+
+        /// Button for adding the worker in the worker entry.
+        pub(super) add_worker_button: RefCell<Option<IconButton>>,
+
+        /// The last status retrieved for each worker node by address.
+        pub(super) worker_statuses: RefCell<HashMap<String, WorkerStatus>>,
+
+        /// Labels displaying the status of each worker, in the order of the workers being edited.
+        pub(super) worker_status_labels: RefCell<Vec<Label>>,
+
+        /// The worker nodes found on the local network.
+        pub(super) discovered: RefCell<Vec<DiscoveredWorker>>,
+
+        /// List displaying the discovered workers that can be adopted.
+        pub(super) discovered_list: RefCell<ListBox>,
+
+        /// The notification sinks being edited.
+        pub(super) sinks: RefCell<Vec<SinkSettings>>,
+
+        /// List displaying the sinks being edited.
+        pub(super) sink_list: RefCell<ListBox>,
+
+        /// Dropdown for the kind of sink to add.
+        pub(super) sink_kind_dropdown: RefCell<DropDown>,
+
+        /// Entry for the URL of a sink to add.
+        pub(super) sink_url_entry: RefCell<Entry>,
+
+        /// Entry for the token of a sink to add.
+        pub(super) sink_token_entry: RefCell<Entry>,
+
+        /// Check buttons for the events sent to a sink to add, in the order of
+        /// [`crate::notify::EventKind::ALL`].
+        pub(super) event_checks: RefCell<Vec<CheckButton>>,
+
+        /// Button for adding the sink in the sink form.
+        pub(super) add_sink_button: RefCell<Option<IconButton>>,
+
+        /// Label displaying the problems found when validating the settings.
+        pub(super) problems_label: RefCell<Label>,
+
+        /// Button for closing the window without saving.
+        pub(super) cancel_button: RefCell<Option<IconButton>>,
+
+        /// Button for saving the settings.
+        pub(super) save_button: RefCell<Option<IconButton>>,
+
+        /// The application context.
+        #[property(get, set = Self::set_context, construct_only)]
+        pub(super) context: RefCell<Option<ContextObject>>,
+    }
+
+    impl PreferencesWindow {
+        /// Sets the application context.
+        fn set_context(&self, context: Option<ContextObject>) {
+            self.context.replace(context);
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PreferencesWindow {
+        const NAME: &'static str = "ArtiePreferencesWindow";
+        type Type = super::PreferencesWindow;
+        type ParentType = gtk::Window;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for PreferencesWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.build_ui();
+            obj.setup_callbacks();
+            obj.load();
+        }
+    }
+
+    impl WidgetImpl for PreferencesWindow {}
+
+    impl WindowImpl for PreferencesWindow {}
+}
+
+#[cfg(test)]
+mod tests {
+    // TODO[TESTS]
+}
@@ -0,0 +1,457 @@
+// Copyright 2026 Kevin Fisher. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Title editor widget.
+//!
+//! The title editor displays a title copied from a disc along with its runtime, tracks, and
+//! previews (see [`crate::library::preview`]). It is used to classify the title as the main
+//! feature, episodes, a special feature, an alternate version, or discard it.
+
+use gtk::{Align, Box, ContentFit, Orientation, Popover, StringList};
+use gtk::glib::{self, Object};
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use crate::library::episodes::Proposal;
+use crate::models::{Classification, SpecialFeature, SpecialFeatureType, Title};
+use crate::path;
+use crate::ui::helpers;
+
+/// Position of the main feature option in the kind drop down.
+const MAIN_POSITION: u32 = 0;
+
+/// Position of the episodes option in the kind drop down.
+const EPISODES_POSITION: u32 = 1;
+
+/// Position of the special feature option in the kind drop down.
+const SPECIAL_POSITION: u32 = 2;
+
+/// Position of the alternate version option in the kind drop down.
+const VERSION_POSITION: u32 = 3;
+
+/// Position of the discard option in the kind drop down.
+const DISCARD_POSITION: u32 = 4;
+
+/// The kind options in the order they appear in the kind drop down.
+const KIND_NAMES: [&str; 5] = [
+    "Main Feature",
+    "Episodes",
+    "Special Feature",
+    "Alternate Version",
+    "Discard",
+];
+
+/// The names of the detail stack pages for each of the kind options.
+const DETAIL_PAGES: [&str; 5] = ["none", "episodes", "special", "version", "none"];
+
+glib::wrapper! {
+    pub struct TitleEditorWidget(ObjectSubclass<imp::TitleEditorWidget>)
+        @extends gtk::Box,
+                 gtk::Widget,
+        @implements gtk::Accessible,
+                    gtk::Buildable,
+                    gtk::ConstraintTarget,
+                    gtk::Orientable;
+}
+
+impl TitleEditorWidget {
+    /// Creates a new title editor instance.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the GObject cannot be created.
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    /// Builds the widget.
+    ///
+    /// Called by the implementation ([`imp::TitleEditorWidget`]) when constructed.
+    fn build_ui(&self) {
+        let imp = self.imp();
+
+        let thumbnail = imp.thumbnail
+            .borrow()
+            .clone();
+        thumbnail.add_css_class("title-editor-thumbnail");
+        thumbnail.set_content_fit(ContentFit::Contain);
+        thumbnail.set_size_request(320, 135);
+        thumbnail.set_valign(Align::Center);
+
+        let preview_video = imp.preview_video
+            .borrow()
+            .clone();
+        preview_video.set_autoplay(true);
+        preview_video.set_size_request(640, 360);
+
+        let preview_popover = Popover::builder()
+            .child(&preview_video)
+            .build();
+
+        let preview_button = imp.preview_button
+            .borrow()
+            .clone();
+        preview_button.set_label("Preview");
+        preview_button.set_popover(Some(&preview_popover));
+        preview_button.set_halign(Align::Start);
+        preview_button.set_visible(false);
+
+        let name_label = imp.name_label
+            .borrow()
+            .clone();
+        name_label.add_css_class("title-editor-label");
+        name_label.set_halign(Align::Start);
+
+        let summary_label = imp.summary_label
+            .borrow()
+            .clone();
+        summary_label.add_css_class("title-editor-summary");
+        summary_label.set_halign(Align::Start);
+        summary_label.set_wrap(true);
+
+        let warning_icon = imp.warning_icon
+            .borrow()
+            .clone();
+        warning_icon.set_icon_name(Some("fontawesome.v7.solid.exclamation-triangle-symbolic"));
+        warning_icon.add_css_class("title-editor-warning");
+        warning_icon.set_visible(false);
+
+        let name_row = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(8)
+            .build();
+        name_row.append(&name_label);
+        name_row.append(&warning_icon);
+
+        let info_column = Box::builder()
+            .hexpand(true)
+            .orientation(Orientation::Vertical)
+            .spacing(4)
+            .valign(Align::Center)
+            .build();
+        info_column.append(&name_row);
+        info_column.append(&summary_label);
+        info_column.append(&preview_button);
+
+        let kind_dropdown = imp.kind_dropdown
+            .borrow()
+            .clone();
+        kind_dropdown.set_model(Some(&StringList::new(&KIND_NAMES)));
+
+        let episode_number = imp.episode_number
+            .borrow()
+            .clone();
+        episode_number.set_range(1.0, 999.0);
+        episode_number.set_increments(1.0, 10.0);
+        episode_number.set_tooltip_text(Some("First episode"));
+
+        let episode_count = imp.episode_count
+            .borrow()
+            .clone();
+        episode_count.set_range(1.0, 9.0);
+        episode_count.set_increments(1.0, 1.0);
+        episode_count.set_tooltip_text(Some("Number of episodes"));
+
+        let episodes_box = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(4)
+            .build();
+        episodes_box.append(&episode_number);
+        episodes_box.append(&episode_count);
+
+        let special_names: Vec<&str> = SpecialFeatureType::ALL.iter()
+            .map(|k| k.as_str())
+            .collect();
+        let special_kind = imp.special_kind
+            .borrow()
+            .clone();
+        special_kind.set_model(Some(&StringList::new(&special_names)));
+
+        let special_name = imp.special_name
+            .borrow()
+            .clone();
+        special_name.set_placeholder_text(Some("Name"));
+
+        let special_box = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(4)
+            .build();
+        special_box.append(&special_kind);
+        special_box.append(&special_name);
+
+        let version_entry = imp.version_entry
+            .borrow()
+            .clone();
+        version_entry.set_placeholder_text(Some("Version (e.g. Directors Cut)"));
+
+        let detail_stack = imp.detail_stack
+            .borrow()
+            .clone();
+        detail_stack.add_named(&Box::default(), Some("none"));
+        detail_stack.add_named(&episodes_box, Some("episodes"));
+        detail_stack.add_named(&special_box, Some("special"));
+        detail_stack.add_named(&version_entry, Some("version"));
+        detail_stack.set_width_request(320);
+
+        let controls = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(8)
+            .valign(Align::Center)
+            .build();
+        controls.append(&kind_dropdown);
+        controls.append(&detail_stack);
+
+        self.append(&thumbnail);
+        self.append(&info_column);
+        self.append(&controls);
+
+        self.add_css_class("title-editor");
+        self.set_margin_bottom(4);
+        self.set_margin_top(4);
+        self.set_orientation(Orientation::Horizontal);
+        self.set_spacing(16);
+    }
+
+    /// Connects the signals of the child widgets.
+    ///
+    /// Called by the implementation ([`imp::TitleEditorWidget`]) when constructed.
+    fn setup_callbacks(&self) {
+        let imp = self.imp();
+
+        imp.kind_dropdown.borrow().connect_selected_notify(glib::clone!(
+            #[weak(rename_to = editor)]
+            self,
+            move |dropdown| {
+                let page = DETAIL_PAGES.get(dropdown.selected() as usize).unwrap_or(&"none");
+                editor.imp().detail_stack.borrow().set_visible_child_name(page);
+            }
+        ));
+
+        if let Some(popover) = imp.preview_button.borrow().popover() {
+            popover.connect_closed(glib::clone!(
+                #[weak(rename_to = editor)]
+                self,
+                move |_| {
+                    if let Some(stream) = editor.imp().preview_video.borrow().media_stream() {
+                        stream.pause();
+                    }
+                }
+            ));
+        }
+    }
+
+    /// Displays a title.
+    ///
+    /// # Args
+    ///
+    /// `title`:  The title to display. The title's videos should be loaded so that the runtime and
+    /// tracks can be displayed.
+    ///
+    /// `proposal`:  The proposed episode assignment for the title. When `Some`, it is used instead
+    /// of the title's current classification.
+    pub fn bind(&self, title: &Title, proposal: Option<&Proposal>) {
+        let imp = self.imp();
+
+        let video = title.videos.as_ref().and_then(|v| v.first());
+        let name = match video {
+            Some(video) => {
+                format!("Title {}  {}", title.index, helpers::format_duration(&video.duration))
+            },
+            None => format!("Title {}", title.index),
+        };
+        imp.name_label.borrow().set_label(&name);
+
+        let summary = video.map(|v| v.track_summary()).unwrap_or_default();
+        imp.summary_label.borrow().set_label(&summary);
+
+        let contact_sheet = video
+            .and_then(|v| v.contact_sheet.as_ref())
+            .and_then(path::location_path)
+            .filter(|p| p.is_file());
+        imp.thumbnail.borrow().set_filename(contact_sheet.as_ref());
+
+        let preview_clip = video
+            .and_then(|v| v.preview_clip.as_ref())
+            .and_then(path::location_path)
+            .filter(|p| p.is_file());
+        imp.preview_video.borrow().set_filename(preview_clip.as_ref());
+        imp.preview_button.borrow().set_visible(preview_clip.is_some());
+
+        let warning_icon = imp.warning_icon.borrow();
+        match proposal {
+            Some(proposal) if !proposal.confident => {
+                let tooltip = match proposal.expected {
+                    Some(expected) => {
+                        format!("Expected {}", helpers::format_duration(&expected))
+                    },
+                    None => String::from("Duration does not match the other episodes"),
+                };
+                warning_icon.set_tooltip_text(Some(&tooltip));
+                warning_icon.set_visible(true);
+            },
+            _ => warning_icon.set_visible(false),
+        }
+
+        let classification = match proposal {
+            Some(proposal) => proposal.classification(),
+            None => title.classification(),
+        };
+        self.set_classification(&classification);
+    }
+
+    /// Updates the controls to display a classification.
+    ///
+    /// # Args
+    ///
+    /// `classification`:  The classification to display.
+    fn set_classification(&self, classification: &Classification) {
+        let imp = self.imp();
+
+        imp.episode_number.borrow().set_value(1.0);
+        imp.episode_count.borrow().set_value(1.0);
+        imp.special_kind.borrow().set_selected(0);
+        imp.special_name.borrow().set_text("");
+        imp.version_entry.borrow().set_text("");
+
+        let position = match classification {
+            Classification::Main => MAIN_POSITION,
+            Classification::Episodes { number, count } => {
+                imp.episode_number.borrow().set_value(f64::from(*number));
+                imp.episode_count.borrow().set_value(f64::from(*count));
+                EPISODES_POSITION
+            },
+            Classification::Special(special_feature) => {
+                let kind = SpecialFeatureType::ALL.iter()
+                    .position(|k| *k == special_feature.kind)
+                    .unwrap_or_default();
+                imp.special_kind.borrow().set_selected(kind as u32);
+                imp.special_name.borrow().set_text(&special_feature.name);
+                SPECIAL_POSITION
+            },
+            Classification::Version { name } => {
+                imp.version_entry.borrow().set_text(name);
+                VERSION_POSITION
+            },
+            Classification::Discard => DISCARD_POSITION,
+        };
+
+        let kind_dropdown = imp.kind_dropdown.borrow();
+        kind_dropdown.set_selected(position);
+        imp.detail_stack.borrow().set_visible_child_name(DETAIL_PAGES[position as usize]);
+    }
+
+    /// Gets the classification selected by the user.
+    pub fn classification(&self) -> Classification {
+        let imp = self.imp();
+
+        match imp.kind_dropdown.borrow().selected() {
+            EPISODES_POSITION => Classification::Episodes {
+                number: imp.episode_number.borrow().value_as_int() as u16,
+                count: imp.episode_count.borrow().value_as_int() as u16,
+            },
+            SPECIAL_POSITION => {
+                let kind = SpecialFeatureType::ALL
+                    .get(imp.special_kind.borrow().selected() as usize)
+                    .copied()
+                    .unwrap_or(SpecialFeatureType::Extras);
+                let name = imp.special_name.borrow().text().trim().to_owned();
+                Classification::Special(SpecialFeature { kind, name })
+            },
+            VERSION_POSITION => {
+                let name = imp.version_entry.borrow().text().trim().to_owned();
+                if name.is_empty() {
+                    Classification::Main
+                } else {
+                    Classification::Version { name }
+                }
+            },
+            DISCARD_POSITION => Classification::Discard,
+            _ => Classification::Main,
+        }
+    }
+}
+
+impl Default for TitleEditorWidget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod imp {
+    //! Implemenation for the title editor widget.
+
+    use std::cell::RefCell;
+
+    use gtk::{Box, DropDown, Entry, Image, Label, MenuButton, Picture, SpinButton, Stack, Video};
+    use gtk::glib;
+    use gtk::subclass::prelude::*;
+
+    /// Implemenation for [`super::TitleEditorWidget`].
+    #[derive(Default)]
+    pub struct TitleEditorWidget {
+        /// Displays the title's contact sheet.
+        pub(super) thumbnail: RefCell<Picture>,
+
+        /// Opens a popover to play the title's preview clip.
+        pub(super) preview_button: RefCell<MenuButton>,
+
+        /// Plays the title's preview clip.
+        pub(super) preview_video: RefCell<Video>,
+
+        /// Displays the title index and runtime.
+        pub(super) name_label: RefCell<Label>,
+
+        /// Displays a summary of the title's tracks.
+        pub(super) summary_label: RefCell<Label>,
+
+        /// Displayed when the proposed episode assignment is uncertain.
+        pub(super) warning_icon: RefCell<Image>,
+
+        /// Selects what the title contains.
+        pub(super) kind_dropdown: RefCell<DropDown>,
+
+        /// Contains the additional fields for the selected kind.
+        pub(super) detail_stack: RefCell<Stack>,
+
+        /// The (first) episode number.
+        pub(super) episode_number: RefCell<SpinButton>,
+
+        /// The number of episodes.
+        pub(super) episode_count: RefCell<SpinButton>,
+
+        /// The type of special feature.
+        pub(super) special_kind: RefCell<DropDown>,
+
+        /// The name of the special feature.
+        pub(super) special_name: RefCell<Entry>,
+
+        /// The name of the alternate version.
+        pub(super) version_entry: RefCell<Entry>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TitleEditorWidget {
+        const NAME: &'static str = "ArtieTitleEditorWidget";
+        type Type = super::TitleEditorWidget;
+        type ParentType = Box;
+    }
+
+    impl ObjectImpl for TitleEditorWidget {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.build_ui();
+            obj.setup_callbacks();
+        }
+    }
+
+    impl WidgetImpl for TitleEditorWidget {}
+
+    impl BoxImpl for TitleEditorWidget {}
+}
+
+#[cfg(test)]
+mod tests {
+    // TODO[TESTS]
+}
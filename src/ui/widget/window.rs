@@ -5,13 +5,13 @@
 
 use glib::Object;
 
-use gtk::{Application, HeaderBar, Stack, StackSwitcher};
+use gtk::{Application, HeaderBar, MenuButton, Stack, StackSwitcher};
 use gtk::gio;
 use gtk::glib;
 use gtk::prelude::*;
 
 use crate::ui::context::ContextObject;
-use crate::ui::widget::CopyPageWidget;
+use crate::ui::widget::{CatalogPageWidget, CopyPageWidget, PreferencesWindow};
 
 glib::wrapper! {
     pub struct Window(ObjectSubclass<imp::Window>)
@@ -69,9 +69,7 @@ impl Window {
             .label("Transcode Page")
             .build();
 
-        let catalog_page = gtk::Label::builder()
-            .label("Catalog Page")
-            .build();
+        let catalog_page = CatalogPageWidget::new(&context);
 
         let stack = Stack::builder()
             .build();
@@ -93,15 +91,26 @@ impl Window {
         self.set_default_height(920);
         self.set_child(Some(&stack));
 
-        //--] let menu_popover = PopoverMenu::builder()
-        //--]     .build();
+        let menu = gio::Menu::new();
+        menu.append(Some("Preferences"), Some("win.preferences"));
+
+        let menu_button = MenuButton::builder()
+            .icon_name("open-menu-symbolic")
+            .menu_model(&menu)
+            .build();
 
-        //--] let menu_button = MenuButton::builder()
-        //--]     .icon_name("open-menu-symbolic")
-        //--]     .popover(&menu_popover)
-        //--]     .build();
+        header_bar.pack_end(&menu_button);
 
-        //--] header_bar.pack_end(&menu_button);
+        let preferences_action = gio::ActionEntry::builder("preferences")
+            .activate(|window: &Self, _, _| window.show_preferences())
+            .build();
+        self.add_action_entries([preferences_action]);
+    }
+
+    /// Opens the preferences window.
+    fn show_preferences(&self) {
+        let context = self.context().expect("context not set");
+        PreferencesWindow::new(self, &context).present();
     }
 
     /// Builds the widget when running as a worker node.